@@ -0,0 +1,432 @@
+//! Comparison bundles for sharing setups.
+//!
+//! A bundle is a zip file containing the original HTML setup exports for a comparison, along with
+//! a TOML manifest that describes the track, car, and the order in which setups were selected. The
+//! first setup in the selection is the baseline that all other setups are compared against. User
+//! notes and favorites for the setups are included in the manifest when they exist.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use toml_edit::{Document, TomlError};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Name of the manifest file within a bundle.
+const MANIFEST_NAME: &str = "manifest.toml";
+
+/// Directory within a bundle that contains the setup exports.
+const SETUPS_DIR: &str = "setups";
+
+/// Manifest format version.
+const VERSION: i64 = 1;
+
+/// Exporting and importing bundles can fail.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Zip archive error.
+    #[error("Zip archive error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    /// Manifest parse error.
+    #[error("Manifest parse error: {0}")]
+    Parse(#[from] TomlError),
+
+    /// Manifest is not valid.
+    #[error("Invalid manifest: {0}")]
+    Manifest(String),
+
+    /// Setups listed in the manifest are missing from the bundle.
+    #[error("Setups missing from bundle: {}", .0.join(", "))]
+    MissingSetups(Vec<String>),
+}
+
+/// Describes the comparison contained in a bundle.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Manifest {
+    /// Track name, as shown by the exporting application.
+    pub(crate) track_name: String,
+
+    /// Car name, as shown by the exporting application.
+    pub(crate) car_name: String,
+
+    /// File names of the setups, in selection order. The first one is the baseline.
+    pub(crate) setups: Vec<String>,
+
+    /// Map setup file names to user notes.
+    pub(crate) notes: HashMap<String, String>,
+
+    /// File names of the setups starred by the user, in selection order.
+    pub(crate) favorites: Vec<String>,
+}
+
+/// The result of importing a bundle.
+pub(crate) struct Import {
+    /// The bundle manifest.
+    pub(crate) manifest: Manifest,
+
+    /// Paths to all extracted setups, in selection order.
+    pub(crate) paths: Vec<PathBuf>,
+
+    /// File names listed in the manifest that could not be found in the bundle.
+    pub(crate) missing: Vec<String>,
+}
+
+impl Manifest {
+    /// Parse a manifest from TOML.
+    fn from_toml(toml: &str) -> Result<Self, Error> {
+        let doc: Document = toml.parse()?;
+
+        let version = doc
+            .get("version")
+            .and_then(|t| t.as_integer())
+            .ok_or_else(|| Error::Manifest("expected `version` to be an integer".to_string()))?;
+        if version != VERSION {
+            return Err(Error::Manifest(format!("unsupported version {}", version)));
+        }
+
+        let get_str = |key: &str| {
+            doc.get(key)
+                .and_then(|t| t.as_str())
+                .map(|s| s.to_string())
+                .ok_or_else(|| Error::Manifest(format!("expected `{}` to be a string", key)))
+        };
+        let track_name = get_str("track")?;
+        let car_name = get_str("car")?;
+
+        let setups = doc
+            .get("setups")
+            .and_then(|t| t.as_array())
+            .ok_or_else(|| Error::Manifest("expected `setups` to be an array".to_string()))?;
+        let mut setups = setups
+            .iter()
+            .map(|name| {
+                name.as_str()
+                    .filter(|name| is_file_name(name))
+                    .map(|name| name.to_string())
+                    .ok_or_else(|| {
+                        Error::Manifest(format!("expected {} to be an HTML file name", name))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if setups.is_empty() {
+            return Err(Error::Manifest("no setups listed".to_string()));
+        }
+
+        // The baseline is optional, but it must be one of the listed setups
+        if let Some(baseline) = doc.get("baseline") {
+            let index = baseline
+                .as_str()
+                .and_then(|baseline| setups.iter().position(|name| name == baseline))
+                .ok_or_else(|| {
+                    Error::Manifest("expected `baseline` to be one of the setups".to_string())
+                })?;
+            let baseline = setups.remove(index);
+            setups.insert(0, baseline);
+        }
+
+        // Notes are optional
+        let mut notes = HashMap::new();
+        if let Some(table) = doc.get("notes") {
+            let table = table
+                .as_table()
+                .ok_or_else(|| Error::Manifest("expected `notes` to be a table".to_string()))?;
+
+            for (name, note) in table.iter() {
+                let note = note
+                    .as_str()
+                    .filter(|_| setups.iter().any(|setup| setup == name))
+                    .ok_or_else(|| {
+                        Error::Manifest(format!("expected note for listed setup {}", name))
+                    })?;
+
+                notes.insert(name.to_string(), note.to_string());
+            }
+        }
+
+        // Favorites are optional
+        let mut favorites = Vec::new();
+        if let Some(array) = doc.get("favorites") {
+            let array = array.as_array().ok_or_else(|| {
+                Error::Manifest("expected `favorites` to be an array".to_string())
+            })?;
+
+            for name in array.iter() {
+                let name = name
+                    .as_str()
+                    .filter(|name| setups.iter().any(|setup| setup == name))
+                    .ok_or_else(|| {
+                        Error::Manifest(format!("expected favorite {} to be a listed setup", name))
+                    })?;
+
+                favorites.push(name.to_string());
+            }
+        }
+
+        Ok(Self {
+            track_name,
+            car_name,
+            setups,
+            notes,
+            favorites,
+        })
+    }
+
+    /// Create a TOML string from this manifest.
+    fn to_toml(&self) -> String {
+        let mut doc = Document::new();
+        let mut setups = toml_edit::Array::default();
+        for name in &self.setups {
+            setups.push(name.as_str());
+        }
+
+        doc["version"] = toml_edit::value(VERSION);
+        doc["track"] = toml_edit::value(self.track_name.as_str());
+        doc["car"] = toml_edit::value(self.car_name.as_str());
+        doc["baseline"] = toml_edit::value(self.setups[0].as_str());
+        doc["setups"] = toml_edit::value(setups);
+
+        if !self.favorites.is_empty() {
+            let favorites: toml_edit::Array =
+                self.favorites.iter().map(|name| name.as_str()).collect();
+            doc["favorites"] = toml_edit::value(favorites);
+        }
+
+        if !self.notes.is_empty() {
+            let mut notes = toml_edit::Table::new();
+            for name in &self.setups {
+                if let Some(note) = self.notes.get(name) {
+                    notes[name.as_str()] = toml_edit::value(note.as_str());
+                }
+            }
+            doc["notes"] = toml_edit::Item::Table(notes);
+        }
+
+        doc.to_string()
+    }
+}
+
+/// Export setups to a bundle.
+///
+/// The `setups` paths must be in selection order, with the baseline first. Each path is paired
+/// with the user's note for that setup, if any, and whether the user starred it.
+pub(crate) fn export<P: AsRef<Path>>(
+    path: P,
+    track_name: &str,
+    car_name: &str,
+    setups: &[(&Path, Option<&str>, bool)],
+) -> Result<(), Error> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut manifest = Manifest {
+        track_name: track_name.to_string(),
+        car_name: car_name.to_string(),
+        setups: Vec::with_capacity(setups.len()),
+        notes: HashMap::new(),
+        favorites: Vec::new(),
+    };
+
+    for (i, &(setup_path, note, favorite)) in setups.iter().enumerate() {
+        // Setups in different directories may share a file name
+        let file_name = setup_path
+            .file_name()
+            .map_or_else(|| format!("{}.htm", i), |s| s.to_string_lossy().to_string());
+        let file_name = if manifest.setups.contains(&file_name) {
+            format!("{}-{}", i, file_name)
+        } else {
+            file_name
+        };
+
+        zip.start_file(format!("{}/{}", SETUPS_DIR, file_name), options)?;
+        zip.write_all(&fs::read(setup_path)?)?;
+        if let Some(note) = note {
+            manifest.notes.insert(file_name.clone(), note.to_string());
+        }
+        if favorite {
+            manifest.favorites.push(file_name.clone());
+        }
+        manifest.setups.push(file_name);
+    }
+
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(manifest.to_toml().as_bytes())?;
+    zip.finish()?;
+
+    Ok(())
+}
+
+/// Import a bundle, extracting all setups into the `dest` directory.
+///
+/// Setups that are missing from the bundle are reported in [`Import::missing`].
+pub(crate) fn import<P: AsRef<Path>>(path: P, dest: &Path) -> Result<Import, Error> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+
+    let mut toml = String::new();
+    zip.by_name(MANIFEST_NAME)?.read_to_string(&mut toml)?;
+    let manifest = Manifest::from_toml(&toml)?;
+
+    fs::create_dir_all(dest)?;
+
+    let mut paths = Vec::with_capacity(manifest.setups.len());
+    let mut missing = Vec::new();
+    for file_name in &manifest.setups {
+        let mut file = match zip.by_name(&format!("{}/{}", SETUPS_DIR, file_name)) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => {
+                missing.push(file_name.to_string());
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let setup_path = dest.join(file_name);
+        fs::write(&setup_path, bytes)?;
+        paths.push(setup_path);
+    }
+
+    Ok(Import {
+        manifest,
+        paths,
+        missing,
+    })
+}
+
+/// Check that a manifest entry is a plain HTML file name without any directory components.
+fn is_file_name(name: &str) -> bool {
+    let path = Path::new(name);
+
+    path.file_name().and_then(|s| s.to_str()) == Some(name)
+        && (name.ends_with(".htm") || name.ends_with(".html"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_import() {
+        let dir = tempfile::tempdir().expect("Unable to create temp dir");
+        let bundle = dir.path().join("bundle.zip");
+        let setups = [
+            (Path::new("./fixtures/baseline.htm"), None, true),
+            (
+                Path::new("./fixtures/skip_barber_centripetal.htm"),
+                Some("Good in the rain"),
+                false,
+            ),
+        ];
+
+        export(&bundle, "Centripetal Circuit", "Skip Barber", &setups).unwrap();
+
+        let dest = dir.path().join("imported");
+        let result = import(&bundle, &dest).unwrap();
+        assert_eq!(
+            result.manifest,
+            Manifest {
+                track_name: "Centripetal Circuit".to_string(),
+                car_name: "Skip Barber".to_string(),
+                setups: vec![
+                    "baseline.htm".to_string(),
+                    "skip_barber_centripetal.htm".to_string(),
+                ],
+                notes: HashMap::from([(
+                    "skip_barber_centripetal.htm".to_string(),
+                    "Good in the rain".to_string()
+                )]),
+                favorites: vec!["baseline.htm".to_string()],
+            }
+        );
+        assert_eq!(
+            result.paths,
+            vec![
+                dest.join("baseline.htm"),
+                dest.join("skip_barber_centripetal.htm"),
+            ]
+        );
+        assert!(result.missing.is_empty());
+        assert_eq!(
+            fs::read(&result.paths[0]).unwrap(),
+            fs::read(setups[0].0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_manifest_validation() {
+        let valid = r#"
+            version = 1
+            track = "Centripetal Circuit"
+            car = "Skip Barber"
+            setups = ["a.htm", "b.html"]
+        "#;
+        assert!(Manifest::from_toml(valid).is_ok());
+
+        let traversal = valid.replace("a.htm", "../a.htm");
+        assert!(matches!(
+            Manifest::from_toml(&traversal),
+            Err(Error::Manifest(_))
+        ));
+
+        let not_html = valid.replace("a.htm", "a.exe");
+        assert!(matches!(
+            Manifest::from_toml(&not_html),
+            Err(Error::Manifest(_))
+        ));
+
+        let version = valid.replace("version = 1", "version = 2");
+        assert!(matches!(
+            Manifest::from_toml(&version),
+            Err(Error::Manifest(_))
+        ));
+
+        let baseline = format!("{}baseline = \"b.html\"", valid);
+        assert_eq!(
+            Manifest::from_toml(&baseline).unwrap().setups,
+            vec!["b.html".to_string(), "a.htm".to_string()]
+        );
+
+        let bad_baseline = format!("{}baseline = \"c.htm\"", valid);
+        assert!(matches!(
+            Manifest::from_toml(&bad_baseline),
+            Err(Error::Manifest(_))
+        ));
+
+        let notes = format!("{}[notes]\n\"a.htm\" = \"Good in the rain\"", valid);
+        assert_eq!(
+            Manifest::from_toml(&notes).unwrap().notes["a.htm"],
+            "Good in the rain"
+        );
+
+        let bad_notes = format!("{}[notes]\n\"c.htm\" = \"Good in the rain\"", valid);
+        assert!(matches!(
+            Manifest::from_toml(&bad_notes),
+            Err(Error::Manifest(_))
+        ));
+
+        let favorites = format!("{}favorites = [\"b.html\"]", valid);
+        assert_eq!(
+            Manifest::from_toml(&favorites).unwrap().favorites,
+            vec!["b.html".to_string()]
+        );
+
+        let bad_favorites = format!("{}favorites = [\"c.htm\"]", valid);
+        assert!(matches!(
+            Manifest::from_toml(&bad_favorites),
+            Err(Error::Manifest(_))
+        ));
+
+        let empty = valid.replace(r#"["a.htm", "b.html"]"#, "[]");
+        assert!(matches!(
+            Manifest::from_toml(&empty),
+            Err(Error::Manifest(_))
+        ));
+    }
+}
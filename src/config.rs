@@ -1,11 +1,18 @@
 //! Application configuration parsing and validation.
 
+use crate::formatter::Formatter;
+use crate::gpu::{GpuBackend, PresentMode};
+use crate::locale::Locale;
+use crate::lockfile;
+use crate::opacity::MIN_OPACITY;
 use crate::updates::UpdateFrequency;
+use crate::value::{PerchOffsetUnit, Value};
 use directories::UserDirs;
 use patricia_tree::PatriciaSet;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 use toml_edit::{Document, Item, TomlError};
 use winit::dpi::{PhysicalPosition, PhysicalSize};
@@ -14,6 +21,21 @@ use winit::window::Theme;
 #[cfg(target_os = "windows")]
 use winit::platform::windows::WindowExtWindows;
 
+/// Smallest grid zoom percentage allowed.
+pub(crate) const MIN_GRID_ZOOM: u16 = 50;
+
+/// Largest grid zoom percentage allowed.
+pub(crate) const MAX_GRID_ZOOM: u16 = 300;
+
+/// Fewest setups selected by "Compare Newest".
+pub(crate) const MIN_NEWEST_COUNT: usize = 2;
+
+/// Most setups selected by "Compare Newest".
+pub(crate) const MAX_NEWEST_COUNT: usize = 9;
+
+/// Narrowest locked column width in the comparison grid, in points.
+pub(crate) const MIN_COLUMN_WIDTH: f32 = 24.0;
+
 /// Parsing and writing configurations can fail.
 #[derive(Debug, Error)]
 pub(crate) enum Error {
@@ -32,13 +54,40 @@ pub(crate) enum Error {
     /// Color format.
     #[error("Expected {0:?} to be a hex color in `#rrggbb` format")]
     Color(String),
+
+    /// Empty color palette.
+    #[error(
+        "Expected \"config.colors\" to contain at least one color; using a single default color"
+    )]
+    EmptyColors,
+
+    /// Several car IDs have the same name.
+    #[error("Cars {ids:?} are all named {name:?}; showing their IDs to keep them apart")]
+    DuplicateCarName { name: String, ids: Vec<String> },
+
+    /// The setup exports path is empty or relative.
+    #[error(
+        "Expected \"config.setups_path\" to be an absolute path, found {0:?}; using the default path"
+    )]
+    SetupsPath(String),
+
+    /// A formatter pattern is not a valid regular expression.
+    #[error("Expected {path:?} to be a regular expression; ignoring the formatter: {error}")]
+    Formatter { path: String, error: regex::Error },
 }
 
+/// Maximum number of recently used setup exports paths to remember.
+const MAX_RECENT_SETUPS_PATHS: usize = 5;
+
+/// Column color used when the user's palette is empty.
+const DEFAULT_COLOR: egui::Color32 = egui::Color32::from_rgb(0x91, 0x16, 0x91);
+
 /// Application configuration backed by TOML.
 ///
 /// This struct retains the original parsed TOML and allows runtime changes while preserving
 /// comments and original document structure. It is also strongly typed, so error handling only
 /// needs to be done when reading and writing TOML.
+#[derive(Clone)]
 pub(crate) struct Config {
     /// Original path to TOML file.
     doc_path: PathBuf,
@@ -46,15 +95,95 @@ pub(crate) struct Config {
     /// Original parsed TOML.
     doc: Document,
 
+    /// TOML as it was last read from or written to the file. `None` when the file has not been
+    /// read or written.
+    saved_toml: Option<String>,
+
     /// Setup exports path.
     setups_path: PathBuf,
 
+    /// Subfolder of the setup exports path that the library is restricted to. Empty for the full
+    /// library.
+    setups_scope: PathBuf,
+
+    /// Setup exports and folders excluded from the library, relative to the setup exports path.
+    excluded_paths: Vec<PathBuf>,
+
+    /// Recently used setup exports paths, most recent first.
+    recent_setups_paths: Vec<PathBuf>,
+
     /// Window minimum inner size.
     min_size: PhysicalSize<u32>,
 
     /// User's theme choice.
     theme: UserTheme,
 
+    /// User's sort order choice for tracks and cars.
+    sort_order: SortOrder,
+
+    /// Number keys toggle setups in the comparison.
+    number_key_toggles: bool,
+
+    /// Setups exported for the selected car are added to the comparison.
+    auto_select_new_setups: bool,
+
+    /// Setup exports are loaded once their size stops changing.
+    wait_for_writes: bool,
+
+    /// Save settings when the window is closed.
+    auto_save: bool,
+
+    /// Keep the window above other windows.
+    always_on_top: bool,
+
+    /// Window opacity percentage.
+    opacity: u8,
+
+    /// Hide settings, file operations, and editing controls.
+    presentation_mode: bool,
+
+    /// User's choice for browsing setups by track or by car.
+    browse: Browse,
+
+    /// User's property grouping choice for the grid.
+    grouping: Grouping,
+
+    /// User's diff reference choice for the grid.
+    diff_reference: DiffReference,
+
+    /// How diff colors are blended into the background of values in the grid.
+    diff_blend: DiffBlend,
+
+    /// Diff multi-value properties position by position, instead of as a whole.
+    positional_diffs: bool,
+
+    /// Show fractions with Unicode fraction glyphs in the grid.
+    fraction_glyphs: bool,
+
+    /// Dim values in the grid that are the same in every setup.
+    dim_unchanged: bool,
+
+    /// Show setup names cleaned up from their file names.
+    clean_setup_names: bool,
+
+    /// Text size in the comparison grid, as a percentage of the normal size.
+    grid_zoom: u16,
+
+    /// Number of the most recently modified setups selected by "Compare Newest".
+    newest_count: usize,
+
+    /// Keep column widths in the comparison grid fixed, instead of fitting them to their contents.
+    lock_column_widths: bool,
+
+    /// Map car names to their locked column widths, starting with the property names column.
+    column_widths: HashMap<String, Vec<f32>>,
+
+    /// File name patterns for finding baseline setups.
+    baseline_patterns: Vec<String>,
+
+    /// Log diagnostics for every parsed setup export.
+    parse_log: bool,
+
     /// User's color-coding choices.
     colors: Vec<egui::Color32>,
 
@@ -64,6 +193,24 @@ pub(crate) struct Config {
     /// User's update check frequency choice.
     update_check: UpdateFrequency,
 
+    /// User's unit choice for spring perch offsets.
+    perch_offset_unit: PerchOffsetUnit,
+
+    /// User's locale choice for displaying numbers and dates.
+    locale: Locale,
+
+    /// Locale used for display, with the system locale resolved.
+    display_locale: Locale,
+
+    /// User's choices for table exports.
+    table_options: TableOptions,
+
+    /// User's graphics backend choice.
+    gpu_backend: GpuBackend,
+
+    /// User's surface presentation mode choice.
+    present_mode: PresentMode,
+
     /// Map raw track IDs to unique track IDs.
     track_ids: PatriciaSet,
 
@@ -72,6 +219,51 @@ pub(crate) struct Config {
 
     /// Map car IDs to car names.
     cars: HashMap<String, String>,
+
+    /// Map property names to display names for all cars.
+    aliases: HashMap<String, String>,
+
+    /// Map car names to property display names that only apply to that car.
+    car_aliases: HashMap<String, HashMap<String, String>>,
+
+    /// Property groups hidden from the grid for all cars.
+    hidden_groups: Vec<String>,
+
+    /// Map car names to property groups hidden from the grid for that car only.
+    car_hidden_groups: HashMap<String, Vec<String>>,
+
+    /// Property groups that start collapsed in the grid, e.g. groups of computed values.
+    collapsed_groups: Vec<String>,
+
+    /// Properties with swapped diff colors, because lower values are better.
+    swapped_diffs: Vec<String>,
+
+    /// Map property names to their safe ranges.
+    safe_ranges: HashMap<String, SafeRange>,
+
+    /// Map car names to formatters for their property values.
+    formatters: HashMap<String, Vec<Formatter>>,
+
+    /// Localized text between the car identifier and setup name in export headers.
+    setup_markers: Vec<String>,
+
+    /// Localized property group names that end the setup, in lowercase.
+    stop_groups: Vec<String>,
+
+    /// Map track names to the time they were last selected, in seconds since the Unix epoch.
+    track_usage: HashMap<String, i64>,
+
+    /// Map car names to the time they were last selected, in seconds since the Unix epoch.
+    car_usage: HashMap<String, i64>,
+
+    /// Map setup export paths to user notes.
+    notes: HashMap<PathBuf, String>,
+
+    /// Setup export paths starred by the user.
+    favorites: HashSet<PathBuf>,
+
+    /// Problems in the TOML that were worked around while loading.
+    warnings: Vec<Error>,
 }
 
 /// Window settings.
@@ -83,6 +275,150 @@ pub(crate) struct Window {
     pub(crate) size: PhysicalSize<u32>,
 }
 
+/// User's choices for resetting all settings to defaults.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct ResetOptions {
+    /// Retain the setup exports path.
+    pub(crate) keep_setups_path: bool,
+
+    /// Retain the `[tracks]` and `[cars]` tables.
+    pub(crate) keep_names: bool,
+}
+
+/// User's choices for table exports.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct TableOptions {
+    /// Field delimiter for TSV exports.
+    pub(crate) delimiter: char,
+
+    /// Quoting style for TSV exports.
+    pub(crate) quoting: Quoting,
+
+    /// Write property groups in a separate column instead of section rows.
+    pub(crate) group_column: bool,
+
+    /// Include property groups that are hidden from the grid.
+    pub(crate) include_hidden_groups: bool,
+}
+
+/// User's choices for displaying and diffing values in the grid.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct GridOptions {
+    /// Spring perch offsets are converted to this unit before they are compared.
+    pub(crate) perch_offset_unit: PerchOffsetUnit,
+
+    /// Diff multi-value properties position by position, instead of as a whole.
+    pub(crate) positional_diffs: bool,
+
+    /// Show fractions with Unicode fraction glyphs. Values are always compared as-is.
+    pub(crate) fraction_glyphs: bool,
+
+    /// Dim values that are the same in every setup.
+    pub(crate) dim_unchanged: bool,
+
+    /// The column that each setup is diffed against.
+    pub(crate) diff_reference: DiffReference,
+
+    /// How diff colors are blended into the background of values.
+    pub(crate) diff_blend: DiffBlend,
+
+    /// Properties with swapped diff colors, see [`GridOptions::is_diff_swapped`].
+    pub(crate) swapped_diffs: Vec<String>,
+
+    /// Map property names to their safe ranges. Values outside the range are flagged.
+    pub(crate) safe_ranges: HashMap<String, SafeRange>,
+
+    /// Property groups that start collapsed. They can still be expanded.
+    pub(crate) collapsed_groups: Vec<String>,
+
+    /// Numbers are shown in this locale. Values are always compared as they appear in the export.
+    pub(crate) locale: Locale,
+
+    /// Formatters for the values of the compared car, see [`Config::formatters`]. Values are
+    /// always compared as they appear in the export.
+    pub(crate) formatters: Vec<Formatter>,
+}
+
+/// A safe operating range for the values of a property, e.g. tire pressures from 18 to 30 psi.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct SafeRange {
+    /// Smallest safe value, if any.
+    pub(crate) min: Option<f64>,
+
+    /// Largest safe value, if any.
+    pub(crate) max: Option<f64>,
+
+    /// Only numbers with this unit are checked, ignoring case. Numbers with any unit are checked
+    /// when it is empty.
+    pub(crate) unit: String,
+}
+
+/// Quoting style for delimited text exports.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Quoting {
+    /// Quote cells only when they contain the delimiter, a quote, or a line break.
+    Minimal,
+
+    /// Quote every cell.
+    Always,
+
+    /// Never quote cells. Delimiters and line breaks within cells are replaced with spaces.
+    Never,
+}
+
+/// User's sort order choice for tracks and cars.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum SortOrder {
+    /// Sort by name.
+    Name,
+
+    /// Sort by most recently used, then by name.
+    Recent,
+}
+
+/// User's choice for browsing setups.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Browse {
+    /// Choose a track, then a car at that track.
+    Track,
+
+    /// Choose a car, then compare its setups at every track.
+    Car,
+}
+
+/// User's property grouping choice for the grid.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Grouping {
+    /// Property groups as they appear in the export.
+    Export,
+
+    /// Merge property groups for each corner of the car.
+    Corner,
+}
+
+/// User's choice of the column that each setup is diffed against in the grid.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum DiffReference {
+    /// Diff every column against the first column, e.g. the baseline.
+    First,
+
+    /// Diff every column against the column immediately to its left.
+    Previous,
+}
+
+/// User's choice of how diff colors are blended into the background of values in the grid.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum DiffBlend {
+    /// Multiply the diff color with the code background. (default)
+    Multiply,
+
+    /// Overlay the diff color on the code background, for subtle highlights.
+    Overlay,
+
+    /// Paint the diff color over the code background at half opacity, for crisp highlights.
+    Solid,
+}
+
 /// User's theme choice.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub(crate) enum UserTheme {
@@ -114,17 +450,93 @@ impl Config {
         let mut config = Self {
             doc_path: doc_path.as_ref().to_path_buf(),
             doc: include_str!("default.toml").parse().unwrap(),
+            saved_toml: None,
             setups_path: PathBuf::new(),
+            setups_scope: PathBuf::new(),
+            excluded_paths: Vec::new(),
+            recent_setups_paths: Vec::new(),
             min_size,
             theme: UserTheme::Auto,
+            sort_order: SortOrder::Name,
+            number_key_toggles: true,
+            auto_select_new_setups: false,
+            wait_for_writes: true,
+            auto_save: true,
+            always_on_top: false,
+            opacity: 100,
+            presentation_mode: false,
+            browse: Browse::Track,
+            grouping: Grouping::Export,
+            diff_reference: DiffReference::First,
+            diff_blend: DiffBlend::Multiply,
+            positional_diffs: false,
+            fraction_glyphs: false,
+            dim_unchanged: false,
+            clean_setup_names: false,
+            grid_zoom: 100,
+            newest_count: 2,
+            lock_column_widths: false,
+            column_widths: HashMap::new(),
+            baseline_patterns: Vec::new(),
+            parse_log: false,
             colors: Vec::new(),
             diff_colors: (egui::Color32::TRANSPARENT, egui::Color32::TRANSPARENT),
             update_check: UpdateFrequency::default(),
+            perch_offset_unit: PerchOffsetUnit::default(),
+            locale: Locale::default(),
+            display_locale: Locale::default().resolve(),
+            table_options: TableOptions {
+                delimiter: '\t',
+                quoting: Quoting::Never,
+                group_column: true,
+                include_hidden_groups: true,
+            },
+            gpu_backend: GpuBackend::default(),
+            present_mode: PresentMode::default(),
             track_ids: PatriciaSet::new(),
             tracks: HashMap::new(),
             cars: HashMap::new(),
+            aliases: HashMap::new(),
+            car_aliases: HashMap::new(),
+            hidden_groups: Vec::new(),
+            car_hidden_groups: HashMap::new(),
+            collapsed_groups: Vec::new(),
+            swapped_diffs: Vec::new(),
+            safe_ranges: HashMap::new(),
+            formatters: HashMap::new(),
+            setup_markers: Vec::new(),
+            stop_groups: Vec::new(),
+            track_usage: HashMap::new(),
+            car_usage: HashMap::new(),
+            notes: HashMap::new(),
+            favorites: HashSet::new(),
+            warnings: Vec::new(),
         };
 
+        config.update_setups_path(Self::default_setups_path());
+        config.load_excluded_paths().unwrap();
+        config.load_recent_setups_paths().unwrap();
+        config.load_baseline_patterns().unwrap();
+        config.load_tracks_and_cars().unwrap();
+        config.load_colors().unwrap();
+        config.load_aliases().unwrap();
+        config.load_hidden_groups().unwrap();
+        config.load_collapsed_groups().unwrap();
+        config.load_swapped_diffs().unwrap();
+        config.load_safe_ranges().unwrap();
+        config.load_formatters().unwrap();
+        config.load_localization().unwrap();
+        config.load_usage().unwrap();
+        config.load_notes().unwrap();
+        config.load_favorites().unwrap();
+        config.load_column_widths().unwrap();
+        config.load_table_options().unwrap();
+
+        config
+    }
+
+    /// Get the default setup exports path.
+    pub(crate) fn default_setups_path() -> PathBuf {
         // Default setup exports path is selected with the following precedence:
         // 1. `$HOME/Documents/iRacing`
         // 2. `$HOME/iRacing`
@@ -137,16 +549,25 @@ impl Config {
         });
         setups_path.push("iRacing");
 
-        config.update_setups_path(setups_path);
-        config.load_tracks_and_cars().unwrap();
-        config.load_colors().unwrap();
+        setups_path
+    }
 
-        config
+    /// Find folders where iRacing may keep setup exports, in order of preference.
+    ///
+    /// Only folders that exist are returned. They may not contain any setup exports.
+    pub(crate) fn find_setups_paths() -> Vec<PathBuf> {
+        UserDirs::new().map_or_else(Vec::new, |dirs| {
+            setups_path_candidates(dirs.document_dir(), dirs.home_dir())
+                .into_iter()
+                .filter(|path| path.is_dir())
+                .collect()
+        })
     }
 
     /// Parse TOML into a Config.
     ///
-    /// The path is allowed to be nonexistent. It isn't an error, but there will be no config.
+    /// The path is allowed to be nonexistent or empty. It isn't an error, but there will be no
+    /// config.
     pub(crate) fn from_toml<P: AsRef<Path>>(
         doc_path: P,
         min_size: PhysicalSize<u32>,
@@ -156,14 +577,32 @@ impl Config {
             return Ok(None);
         }
 
-        let doc: Document = fs::read_to_string(&doc_path)?.parse()?;
+        // An empty file is treated like a missing file, e.g. the sentinel for portable mode
+        let toml = fs::read_to_string(&doc_path)?;
+        if toml.trim().is_empty() {
+            return Ok(None);
+        }
+        // Editors on Windows may save the file with a UTF-8 byte order mark
+        let toml = toml.strip_prefix('\u{feff}').unwrap_or(&toml);
+        let doc: Document = toml.parse()?;
 
         let setups_path = PathBuf::from(
             doc.get("config")
                 .and_then(|t| t.get("setups_path"))
                 .and_then(|t| t.as_str())
-                .ok_or_else(|| Error::type_error("config.setups_path", "string"))?,
+                .ok_or_else(|| Error::type_error("config.setups_path", "string"))?
+                .trim(),
+        );
+
+        let setups_scope = PathBuf::from(
+            doc.get("config")
+                .and_then(|t| t.get("setups_scope"))
+                .and_then(|t| t.as_str())
+                .unwrap_or_default(),
         );
+        if !is_relative_subfolder(&setups_scope) {
+            return Err(Error::type_error("config.setups_scope", "relative path"));
+        }
 
         let theme = doc
             .get("config")
@@ -172,6 +611,137 @@ impl Config {
             .unwrap_or("auto");
         let theme = UserTheme::from_str(theme);
 
+        let sort_order = doc
+            .get("config")
+            .and_then(|t| t.get("sort_order"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("name");
+        let sort_order = SortOrder::from_str(sort_order);
+
+        let number_key_toggles = doc
+            .get("config")
+            .and_then(|t| t.get("number_key_toggles"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(true);
+
+        let auto_select_new_setups = doc
+            .get("config")
+            .and_then(|t| t.get("auto_select_new_setups"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
+        let wait_for_writes = doc
+            .get("config")
+            .and_then(|t| t.get("wait_for_writes"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(true);
+
+        let auto_save = doc
+            .get("config")
+            .and_then(|t| t.get("auto_save"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(true);
+
+        let always_on_top = doc
+            .get("config")
+            .and_then(|t| t.get("always_on_top"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
+        let opacity = doc
+            .get("config")
+            .and_then(|t| t.get("opacity"))
+            .and_then(|t| t.as_integer())
+            .map_or(100, |opacity| {
+                opacity.clamp(i64::from(MIN_OPACITY), 100) as u8
+            });
+
+        let presentation_mode = doc
+            .get("config")
+            .and_then(|t| t.get("presentation_mode"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
+        let browse = doc
+            .get("config")
+            .and_then(|t| t.get("browse"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("track");
+        let browse = Browse::from_str(browse);
+
+        let grouping = doc
+            .get("config")
+            .and_then(|t| t.get("grouping"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("export");
+        let grouping = Grouping::from_str(grouping);
+
+        let diff_reference = doc
+            .get("config")
+            .and_then(|t| t.get("diff_reference"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("first");
+        let diff_reference = DiffReference::from_str(diff_reference);
+
+        let diff_blend = doc
+            .get("config")
+            .and_then(|t| t.get("diff_blend"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("multiply");
+        let diff_blend = DiffBlend::from_str(diff_blend);
+
+        let positional_diffs = doc
+            .get("config")
+            .and_then(|t| t.get("positional_diffs"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
+        let fraction_glyphs = doc
+            .get("config")
+            .and_then(|t| t.get("fraction_glyphs"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
+        let dim_unchanged = doc
+            .get("config")
+            .and_then(|t| t.get("dim_unchanged"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
+        let clean_setup_names = doc
+            .get("config")
+            .and_then(|t| t.get("clean_setup_names"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
+        let grid_zoom = doc
+            .get("config")
+            .and_then(|t| t.get("grid_zoom"))
+            .and_then(|t| t.as_integer())
+            .map_or(100, |zoom| {
+                zoom.clamp(i64::from(MIN_GRID_ZOOM), i64::from(MAX_GRID_ZOOM)) as u16
+            });
+
+        let newest_count = doc
+            .get("config")
+            .and_then(|t| t.get("newest_count"))
+            .and_then(|t| t.as_integer())
+            .map_or(2, |count| {
+                count.clamp(MIN_NEWEST_COUNT as i64, MAX_NEWEST_COUNT as i64) as usize
+            });
+
+        let lock_column_widths = doc
+            .get("config")
+            .and_then(|t| t.get("lock_column_widths"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
+        let parse_log = doc
+            .get("config")
+            .and_then(|t| t.get("parse_log"))
+            .and_then(|t| t.as_bool())
+            .unwrap_or(false);
+
         let update_check = doc
             .get("config")
             .and_then(|t| t.get("update_check"))
@@ -179,28 +749,181 @@ impl Config {
             .map(UpdateFrequency::from)
             .unwrap_or_default();
 
+        let perch_offset_unit = doc
+            .get("config")
+            .and_then(|t| t.get("perch_offset_unit"))
+            .and_then(|t| t.as_str())
+            .map(PerchOffsetUnit::from)
+            .unwrap_or_default();
+
+        let locale = doc
+            .get("config")
+            .and_then(|t| t.get("locale"))
+            .and_then(|t| t.as_str())
+            .map(Locale::from)
+            .unwrap_or_default();
+
+        let gpu_backend = doc
+            .get("config")
+            .and_then(|t| t.get("gpu_backend"))
+            .and_then(|t| t.as_str())
+            .map(GpuBackend::from)
+            .unwrap_or_default();
+
+        let present_mode = doc
+            .get("config")
+            .and_then(|t| t.get("present_mode"))
+            .and_then(|t| t.as_str())
+            .map(PresentMode::from)
+            .unwrap_or_default();
+
         let mut config = Self::new(doc_path, min_size);
         config.doc = doc;
-        config.update_setups_path(setups_path);
+        // An empty or relative path would search the current directory
+        if setups_path.is_absolute() || setups_path.exists() {
+            config.update_setups_path(setups_path);
+        } else {
+            let found = setups_path.to_string_lossy().to_string();
+            config.update_setups_path(Self::default_setups_path());
+            config.warnings.push(Error::SetupsPath(found));
+        }
+        config.update_setups_scope(setups_scope);
         config.update_theme(theme);
+        config.update_sort_order(sort_order);
+        config.set_number_key_toggles(number_key_toggles);
+        config.set_auto_select_new_setups(auto_select_new_setups);
+        config.set_wait_for_writes(wait_for_writes);
+        config.set_auto_save(auto_save);
+        config.set_always_on_top(always_on_top);
+        config.set_opacity(opacity);
+        config.set_presentation_mode(presentation_mode);
+        config.update_browse(browse);
+        config.update_grouping(grouping);
+        config.update_diff_reference(diff_reference);
+        config.update_diff_blend(diff_blend);
+        config.set_positional_diffs(positional_diffs);
+        config.set_fraction_glyphs(fraction_glyphs);
+        config.set_dim_unchanged(dim_unchanged);
+        config.set_clean_setup_names(clean_setup_names);
+        config.set_grid_zoom(grid_zoom);
+        config.set_newest_count(newest_count);
+        config.set_lock_column_widths(lock_column_widths);
+        config.set_parse_log(parse_log);
         config.set_update_check(update_check);
+        config.set_perch_offset_unit(perch_offset_unit);
+        config.set_locale(locale);
+        config.set_gpu_backend(gpu_backend);
+        config.set_present_mode(present_mode);
+        config.load_excluded_paths()?;
+        config.load_recent_setups_paths()?;
+        config.load_baseline_patterns()?;
         config.load_tracks_and_cars()?;
         config.load_colors()?;
+        config.load_aliases()?;
+        config.load_hidden_groups()?;
+        config.load_collapsed_groups()?;
+        config.load_swapped_diffs()?;
+        config.load_safe_ranges()?;
+        config.load_formatters()?;
+        config.load_localization()?;
+        config.load_usage()?;
+        config.load_notes()?;
+        config.load_favorites()?;
+        config.load_column_widths()?;
+        config.load_table_options()?;
+        config.saved_toml = Some(config.doc.to_string());
 
         Ok(Some(config))
     }
 
+    /// Get the directory containing the config file, where logs are also written.
+    pub(crate) fn dir(&self) -> &Path {
+        self.doc_path.parent().unwrap_or_else(|| Path::new("."))
+    }
+
     /// Create TOML file from this Config.
     ///
     /// The Config remembers the original TOML path, and this method rewrites that file. The config
     /// file is created if it does not exist, along with all intermediate directories in the path.
-    pub(crate) fn write_toml(&self) -> Result<(), Error> {
+    ///
+    /// Writes hold a lock file, so concurrent writes from this or another instance cannot clobber
+    /// each other.
+    pub(crate) fn write_toml(&mut self) -> Result<(), Error> {
         let toml = self.doc.to_string();
         if let Some(parent) = self.doc_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        Ok(fs::write(&self.doc_path, toml)?)
+        lockfile::write(&self.doc_path, &toml)?;
+        self.saved_toml = Some(toml);
+
+        Ok(())
+    }
+
+    /// Write only the auto save preference to the config file.
+    ///
+    /// The file is read again and only this preference is changed, so hand edits and other unsaved
+    /// settings are left alone. The whole config is written when the file does not exist yet.
+    pub(crate) fn write_auto_save(&mut self) -> Result<(), Error> {
+        let toml = match fs::read_to_string(&self.doc_path) {
+            Ok(toml) => toml,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return self.write_toml(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut doc = toml.parse::<Document>()?;
+        doc["config"]["auto_save"] = toml_edit::value(self.auto_save);
+        lockfile::write(&self.doc_path, &doc.to_string())?;
+
+        Ok(())
+    }
+
+    /// Check if the TOML has changed since it was last read from or written to the file.
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.saved_toml.as_deref() != Some(&self.doc.to_string())
+    }
+
+    /// Reset all settings to defaults.
+    ///
+    /// The setup exports path is always retained; use [`Self::update_setups_path`] to change it.
+    /// The `[tracks]` and `[cars]` tables are retained when `keep_names` is true.
+    pub(crate) fn reset(&mut self, keep_names: bool) -> Result<(), Error> {
+        let mut config = Self::new(&self.doc_path, self.min_size);
+        config.update_setups_path(&self.setups_path);
+
+        if keep_names {
+            for key in ["tracks", "cars"] {
+                if let Some(item) = self.doc.get(key) {
+                    config.doc[key] = item.clone();
+                }
+            }
+            config.load_tracks_and_cars()?;
+        }
+
+        // Notes and favorites are user data, not settings
+        for key in ["notes", "favorites"] {
+            if let Some(item) = self.doc.get(key) {
+                config.doc[key] = item.clone();
+            }
+        }
+        config.notes = std::mem::take(&mut self.notes);
+        config.favorites = std::mem::take(&mut self.favorites);
+
+        *self = config;
+
+        Ok(())
+    }
+
+    /// Reset the settings that are ignored in safe mode to defaults.
+    ///
+    /// These are the GPU preferences and the window geometry, position, and opacity. They take
+    /// effect on the next launch.
+    pub(crate) fn reset_startup_settings(&mut self) {
+        self.set_gpu_backend(GpuBackend::default());
+        self.set_present_mode(PresentMode::default());
+        self.set_always_on_top(false);
+        self.set_opacity(100);
+        self.doc.as_table_mut().remove("window");
     }
 
     /// Get window configuration if it's valid.
@@ -226,6 +949,23 @@ impl Config {
         self.doc["window"] = Window::from_winit(window).to_table();
     }
 
+    /// Get the size of the error and warning windows, if it's valid.
+    pub(crate) fn get_message_size(&self) -> Option<egui::Vec2> {
+        let messages = &self.doc.get("messages")?;
+
+        let width = messages.get("width").and_then(|t| t.as_integer())?;
+        let height = messages.get("height").and_then(|t| t.as_integer())?;
+
+        Some(egui::Vec2::new(width as f32, height as f32))
+    }
+
+    /// Update the size of the error and warning windows.
+    pub(crate) fn update_message_size(&mut self, size: egui::Vec2) {
+        let messages = table_mut(self.doc.as_table_mut(), "messages");
+        messages["width"] = toml_edit::value(size.x.round() as i64);
+        messages["height"] = toml_edit::value(size.y.round() as i64);
+    }
+
     /// Get a reference to the setup exports path.
     pub(crate) fn get_setups_path(&self) -> &Path {
         &self.setups_path
@@ -247,52 +987,560 @@ impl Config {
         self.doc["config"]["setups_path"] = toml_edit::value(setups_path.as_ref());
     }
 
-    /// Get a reference to the theme preference.
-    pub(crate) fn theme(&self) -> &UserTheme {
-        &self.theme
+    /// Get the recently used setup exports paths, most recent first.
+    pub(crate) fn recent_setups_paths(&self) -> &[PathBuf] {
+        &self.recent_setups_paths
     }
 
-    /// Update the theme preference.
-    pub(crate) fn update_theme(&mut self, theme: UserTheme) {
-        self.theme = theme;
-        self.doc["config"]["theme"] = toml_edit::value(theme.as_str());
+    /// Move the current setup exports path to the front of the recently used paths.
+    ///
+    /// Paths are deduplicated by their canonical form, and only the most recent
+    /// [`MAX_RECENT_SETUPS_PATHS`] are kept.
+    pub(crate) fn remember_setups_path(&mut self) {
+        let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let current = canonical(&self.setups_path);
+
+        self.recent_setups_paths
+            .retain(|path| canonical(path) != current);
+        self.recent_setups_paths.insert(0, current);
+        self.recent_setups_paths.truncate(MAX_RECENT_SETUPS_PATHS);
+
+        let paths: toml_edit::Array = self
+            .recent_setups_paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        self.doc["config"]["recent_setups_paths"] = toml_edit::value(paths);
     }
 
-    /// Get a reference for mapping raw track IDs to unique track IDs.
-    pub(crate) fn track_ids(&self) -> &PatriciaSet {
-        &self.track_ids
+    /// Get a reference to the library subfolder, relative to the setup exports path.
+    ///
+    /// The path is empty when the full library is shown.
+    pub(crate) fn get_setups_scope(&self) -> &Path {
+        &self.setups_scope
     }
 
-    /// Get a reference for mapping track IDs to track names.
-    pub(crate) fn tracks(&self) -> &HashMap<String, String> {
-        &self.tracks
+    /// Get the path that setups are loaded from; the library subfolder within the setup exports
+    /// path.
+    pub(crate) fn get_scoped_setups_path(&self) -> PathBuf {
+        self.setups_path.join(&self.setups_scope)
     }
 
-    /// Get a reference for mapping car IDs to car names.
-    pub(crate) fn cars(&self) -> &HashMap<String, String> {
-        &self.cars
+    /// Restrict the library to a subfolder, relative to the setup exports path.
+    ///
+    /// An empty path restores the full library.
+    pub(crate) fn update_setups_scope<P: AsRef<Path>>(&mut self, setups_scope: P) {
+        self.setups_scope = setups_scope.as_ref().to_path_buf();
+        self.doc["config"]["setups_scope"] = toml_edit::value(portable_path(&self.setups_scope));
     }
 
-    /// Get user's color-coding choices.
-    pub(crate) fn colors(&self) -> Vec<egui::Color32> {
-        self.colors.clone()
+    /// Check if a path is within the library subfolder.
+    ///
+    /// Paths outside of the setup exports path are always in scope; these are setups opened
+    /// individually.
+    pub(crate) fn is_in_setups_scope(&self, path: &Path) -> bool {
+        !path.starts_with(&self.setups_path) || path.starts_with(self.get_scoped_setups_path())
     }
 
-    /// Modify user's color-coding choices.
-    pub(crate) fn colors_mut(&mut self) -> &mut Vec<egui::Color32> {
-        &mut self.colors
+    /// Get the setup exports and folders excluded from the library, relative to the setup exports
+    /// path.
+    pub(crate) fn excluded_paths(&self) -> &[PathBuf] {
+        &self.excluded_paths
     }
 
-    /// Update colors in TOML document.
-    pub(crate) fn update_colors(&mut self) {
-        let mut colors = toml_edit::Array::default();
+    /// Exclude a setup export or folder from the library.
+    ///
+    /// Exclusions within the path are replaced by it. Returns `false` when the path is not within
+    /// the setup exports path.
+    pub(crate) fn exclude_path(&mut self, path: &Path) -> bool {
+        let path = match path.strip_prefix(&self.setups_path) {
+            Ok(path) if !path.as_os_str().is_empty() => path.to_path_buf(),
+            _ => return false,
+        };
 
-        for color in &self.colors {
-            let color = format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b());
-            colors.push(color);
+        if !self
+            .excluded_paths
+            .iter()
+            .any(|excluded| path.starts_with(excluded))
+        {
+            self.excluded_paths
+                .retain(|excluded| !excluded.starts_with(&path));
+            self.excluded_paths.push(path);
+            self.excluded_paths.sort();
+            self.write_excluded_paths();
         }
 
-        self.doc["config"]["colors"] = toml_edit::value(colors);
+        true
+    }
+
+    /// Include a setup export or folder in the library again.
+    ///
+    /// The path is relative to the setup exports path, as returned by [`Config::excluded_paths`].
+    pub(crate) fn include_path(&mut self, path: &Path) {
+        self.excluded_paths.retain(|excluded| excluded != path);
+        self.write_excluded_paths();
+    }
+
+    /// Check if a setup export path is excluded from the library, directly or by a parent folder.
+    pub(crate) fn is_excluded(&self, path: &Path) -> bool {
+        path.strip_prefix(&self.setups_path).map_or(false, |path| {
+            self.excluded_paths
+                .iter()
+                .any(|excluded| path.starts_with(excluded))
+        })
+    }
+
+    /// Write the excluded paths to the TOML document.
+    fn write_excluded_paths(&mut self) {
+        let paths: toml_edit::Array = self
+            .excluded_paths
+            .iter()
+            .map(|path| portable_path(path))
+            .collect();
+        self.doc["config"]["excluded_paths"] = toml_edit::value(paths);
+    }
+
+    /// Get a reference to the theme preference.
+    pub(crate) fn theme(&self) -> &UserTheme {
+        &self.theme
+    }
+
+    /// Update the theme preference.
+    pub(crate) fn update_theme(&mut self, theme: UserTheme) {
+        self.theme = theme;
+        self.doc["config"]["theme"] = toml_edit::value(theme.as_str());
+    }
+
+    /// Get the sort order preference for tracks and cars.
+    pub(crate) fn sort_order(&self) -> SortOrder {
+        self.sort_order
+    }
+
+    /// Update the sort order preference for tracks and cars.
+    pub(crate) fn update_sort_order(&mut self, sort_order: SortOrder) {
+        self.sort_order = sort_order;
+        self.doc["config"]["sort_order"] = toml_edit::value(sort_order.as_str());
+    }
+
+    /// Check if number keys toggle setups in the comparison.
+    pub(crate) fn number_key_toggles(&self) -> bool {
+        self.number_key_toggles
+    }
+
+    /// Update the number key preference for toggling setups.
+    pub(crate) fn set_number_key_toggles(&mut self, enabled: bool) {
+        self.number_key_toggles = enabled;
+        self.doc["config"]["number_key_toggles"] = toml_edit::value(enabled);
+    }
+
+    /// Check if setups exported for the selected car are added to the comparison.
+    pub(crate) fn auto_select_new_setups(&self) -> bool {
+        self.auto_select_new_setups
+    }
+
+    /// Update the preference for adding newly exported setups to the comparison.
+    pub(crate) fn set_auto_select_new_setups(&mut self, enabled: bool) {
+        self.auto_select_new_setups = enabled;
+        self.doc["config"]["auto_select_new_setups"] = toml_edit::value(enabled);
+    }
+
+    /// Check if setup exports are only loaded once their size stops changing.
+    pub(crate) fn wait_for_writes(&self) -> bool {
+        self.wait_for_writes
+    }
+
+    /// Update the preference for waiting until setup exports stop changing size before loading.
+    pub(crate) fn set_wait_for_writes(&mut self, enabled: bool) {
+        self.wait_for_writes = enabled;
+        self.doc["config"]["wait_for_writes"] = toml_edit::value(enabled);
+    }
+
+    /// Check if settings are saved when the window is closed.
+    pub(crate) fn auto_save(&self) -> bool {
+        self.auto_save
+    }
+
+    /// Update the preference for saving settings when the window is closed.
+    ///
+    /// Use [`Self::write_auto_save`] to save the preference itself, since it may not be saved on
+    /// close.
+    pub(crate) fn set_auto_save(&mut self, enabled: bool) {
+        self.auto_save = enabled;
+        self.doc["config"]["auto_save"] = toml_edit::value(enabled);
+    }
+
+    /// Check if the window is kept above other windows.
+    pub(crate) fn always_on_top(&self) -> bool {
+        self.always_on_top
+    }
+
+    /// Update the preference for keeping the window above other windows.
+    pub(crate) fn set_always_on_top(&mut self, enabled: bool) {
+        self.always_on_top = enabled;
+        self.doc["config"]["always_on_top"] = toml_edit::value(enabled);
+    }
+
+    /// Get the window opacity percentage.
+    pub(crate) fn opacity(&self) -> u8 {
+        self.opacity
+    }
+
+    /// Update the window opacity percentage.
+    pub(crate) fn set_opacity(&mut self, opacity: u8) {
+        self.opacity = opacity;
+        self.doc["config"]["opacity"] = toml_edit::value(i64::from(opacity));
+    }
+
+    /// Check if presentation mode hides settings, file operations, and editing controls.
+    pub(crate) fn presentation_mode(&self) -> bool {
+        self.presentation_mode
+    }
+
+    /// Update the presentation mode preference.
+    pub(crate) fn set_presentation_mode(&mut self, enabled: bool) {
+        self.presentation_mode = enabled;
+        self.doc["config"]["presentation_mode"] = toml_edit::value(enabled);
+    }
+
+    /// Check if multi-value properties are diffed position by position.
+    pub(crate) fn positional_diffs(&self) -> bool {
+        self.positional_diffs
+    }
+
+    /// Update the preference for diffing multi-value properties position by position.
+    pub(crate) fn set_positional_diffs(&mut self, enabled: bool) {
+        self.positional_diffs = enabled;
+        self.doc["config"]["positional_diffs"] = toml_edit::value(enabled);
+    }
+
+    /// Check if fractions are shown with Unicode fraction glyphs in the grid.
+    pub(crate) fn fraction_glyphs(&self) -> bool {
+        self.fraction_glyphs
+    }
+
+    /// Update the preference for showing fractions with Unicode fraction glyphs.
+    pub(crate) fn set_fraction_glyphs(&mut self, enabled: bool) {
+        self.fraction_glyphs = enabled;
+        self.doc["config"]["fraction_glyphs"] = toml_edit::value(enabled);
+    }
+
+    /// Check if values that are the same in every setup are dimmed in the grid.
+    pub(crate) fn dim_unchanged(&self) -> bool {
+        self.dim_unchanged
+    }
+
+    /// Update the preference for dimming values that are the same in every setup.
+    pub(crate) fn set_dim_unchanged(&mut self, enabled: bool) {
+        self.dim_unchanged = enabled;
+        self.doc["config"]["dim_unchanged"] = toml_edit::value(enabled);
+    }
+
+    /// Check if setup names are cleaned up from their file names.
+    pub(crate) fn clean_setup_names(&self) -> bool {
+        self.clean_setup_names
+    }
+
+    /// Update the preference for cleaning up setup names from their file names.
+    pub(crate) fn set_clean_setup_names(&mut self, enabled: bool) {
+        self.clean_setup_names = enabled;
+        self.doc["config"]["clean_setup_names"] = toml_edit::value(enabled);
+    }
+
+    /// Get the text size in the comparison grid, as a percentage of the normal size.
+    pub(crate) fn grid_zoom(&self) -> u16 {
+        self.grid_zoom
+    }
+
+    /// Update the text size in the comparison grid.
+    ///
+    /// The percentage is clamped between [`MIN_GRID_ZOOM`] and [`MAX_GRID_ZOOM`].
+    pub(crate) fn set_grid_zoom(&mut self, zoom: u16) {
+        self.grid_zoom = zoom.clamp(MIN_GRID_ZOOM, MAX_GRID_ZOOM);
+        self.doc["config"]["grid_zoom"] = toml_edit::value(i64::from(self.grid_zoom));
+    }
+
+    /// Get the number of the most recently modified setups selected by "Compare Newest".
+    pub(crate) fn newest_count(&self) -> usize {
+        self.newest_count
+    }
+
+    /// Update the number of setups selected by "Compare Newest".
+    ///
+    /// The count is clamped between [`MIN_NEWEST_COUNT`] and [`MAX_NEWEST_COUNT`].
+    pub(crate) fn set_newest_count(&mut self, count: usize) {
+        self.newest_count = count.clamp(MIN_NEWEST_COUNT, MAX_NEWEST_COUNT);
+        self.doc["config"]["newest_count"] = toml_edit::value(self.newest_count as i64);
+    }
+
+    /// Check if column widths in the comparison grid are locked.
+    pub(crate) fn lock_column_widths(&self) -> bool {
+        self.lock_column_widths
+    }
+
+    /// Update the preference for locking column widths in the comparison grid.
+    pub(crate) fn set_lock_column_widths(&mut self, enabled: bool) {
+        self.lock_column_widths = enabled;
+        self.doc["config"]["lock_column_widths"] = toml_edit::value(enabled);
+    }
+
+    /// Get the locked column widths for a car, starting with the property names column.
+    pub(crate) fn column_widths(&self, car_name: &str) -> Option<&[f32]> {
+        self.column_widths.get(car_name).map(Vec::as_slice)
+    }
+
+    /// Update the locked column widths for a car.
+    ///
+    /// Widths are rounded to whole points, and are never narrower than [`MIN_COLUMN_WIDTH`].
+    pub(crate) fn set_column_widths(&mut self, car_name: &str, widths: &[f32]) {
+        let widths: Vec<_> = widths
+            .iter()
+            .map(|width| width.round().max(MIN_COLUMN_WIDTH))
+            .collect();
+        let array: toml_edit::Array = widths.iter().map(|&width| f64::from(width)).collect();
+
+        table_mut(self.doc.as_table_mut(), "column_widths")[car_name] = toml_edit::value(array);
+        self.column_widths.insert(car_name.to_string(), widths);
+    }
+
+    /// Forget the locked column widths for a car, so they fit their contents again.
+    pub(crate) fn reset_column_widths(&mut self, car_name: &str) {
+        self.column_widths.remove(car_name);
+        table_mut(self.doc.as_table_mut(), "column_widths").remove(car_name);
+    }
+
+    /// Get the user's choices for displaying and diffing values in the grid.
+    pub(crate) fn grid_options(&self) -> GridOptions {
+        GridOptions {
+            perch_offset_unit: self.perch_offset_unit,
+            positional_diffs: self.positional_diffs,
+            fraction_glyphs: self.fraction_glyphs,
+            dim_unchanged: self.dim_unchanged,
+            diff_reference: self.diff_reference,
+            diff_blend: self.diff_blend,
+            swapped_diffs: self.swapped_diffs.clone(),
+            safe_ranges: self.safe_ranges.clone(),
+            collapsed_groups: self.collapsed_groups.clone(),
+            locale: self.display_locale,
+            formatters: Vec::new(),
+        }
+    }
+
+    /// Get the path to the diagnostic log for parsed setup exports, if logging is enabled.
+    pub(crate) fn parse_log_path(&self) -> Option<PathBuf> {
+        self.parse_log
+            .then(|| self.doc_path.with_file_name("parse.log"))
+    }
+
+    /// Check if diagnostics are logged for every parsed setup export.
+    pub(crate) fn parse_log(&self) -> bool {
+        self.parse_log
+    }
+
+    /// Update the preference for logging diagnostics for parsed setup exports.
+    pub(crate) fn set_parse_log(&mut self, enabled: bool) {
+        self.parse_log = enabled;
+        self.doc["config"]["parse_log"] = toml_edit::value(enabled);
+    }
+
+    /// Get the file name patterns for finding baseline setups.
+    pub(crate) fn baseline_patterns(&self) -> &[String] {
+        &self.baseline_patterns
+    }
+
+    /// Get the preference for browsing setups by track or by car.
+    pub(crate) fn browse(&self) -> Browse {
+        self.browse
+    }
+
+    /// Update the preference for browsing setups by track or by car.
+    pub(crate) fn update_browse(&mut self, browse: Browse) {
+        self.browse = browse;
+        self.doc["config"]["browse"] = toml_edit::value(browse.as_str());
+    }
+
+    /// Get the property grouping preference for the grid.
+    pub(crate) fn grouping(&self) -> Grouping {
+        self.grouping
+    }
+
+    /// Update the property grouping preference for the grid.
+    pub(crate) fn update_grouping(&mut self, grouping: Grouping) {
+        self.grouping = grouping;
+        self.doc["config"]["grouping"] = toml_edit::value(grouping.as_str());
+    }
+
+    /// Get the diff reference preference for the grid.
+    pub(crate) fn diff_reference(&self) -> DiffReference {
+        self.diff_reference
+    }
+
+    /// Update the diff reference preference for the grid.
+    pub(crate) fn update_diff_reference(&mut self, diff_reference: DiffReference) {
+        self.diff_reference = diff_reference;
+        self.doc["config"]["diff_reference"] = toml_edit::value(diff_reference.as_str());
+    }
+
+    /// Get the diff background blend mode preference for the grid.
+    pub(crate) fn diff_blend(&self) -> DiffBlend {
+        self.diff_blend
+    }
+
+    /// Update the diff background blend mode preference for the grid.
+    pub(crate) fn update_diff_blend(&mut self, diff_blend: DiffBlend) {
+        self.diff_blend = diff_blend;
+        self.doc["config"]["diff_blend"] = toml_edit::value(diff_blend.as_str());
+    }
+
+    /// Get the time a track was last selected, in seconds since the Unix epoch.
+    pub(crate) fn track_last_used(&self, track_name: &str) -> Option<i64> {
+        self.track_usage.get(track_name).copied()
+    }
+
+    /// Get the time a car was last selected, in seconds since the Unix epoch.
+    pub(crate) fn car_last_used(&self, car_name: &str) -> Option<i64> {
+        self.car_usage.get(car_name).copied()
+    }
+
+    /// Record that a track was selected.
+    pub(crate) fn touch_track(&mut self, track_name: &str) {
+        let now = unix_time();
+        self.track_usage.insert(track_name.to_string(), now);
+
+        let usage = table_mut(self.doc.as_table_mut(), "usage");
+        usage.set_implicit(true);
+        table_mut(usage, "tracks")[track_name] = toml_edit::value(now);
+    }
+
+    /// Record that a car was selected.
+    pub(crate) fn touch_car(&mut self, car_name: &str) {
+        let now = unix_time();
+        self.car_usage.insert(car_name.to_string(), now);
+
+        let usage = table_mut(self.doc.as_table_mut(), "usage");
+        usage.set_implicit(true);
+        table_mut(usage, "cars")[car_name] = toml_edit::value(now);
+    }
+
+    /// Get the user's note for a setup export.
+    pub(crate) fn get_note(&self, path: &Path) -> Option<&str> {
+        self.notes.get(path).map(|note| note.as_str())
+    }
+
+    /// Update the user's note for a setup export. Empty notes are removed.
+    pub(crate) fn set_note(&mut self, path: &Path, note: &str) {
+        let key = path.to_string_lossy();
+        let notes = table_mut(self.doc.as_table_mut(), "notes");
+
+        if note.is_empty() {
+            self.notes.remove(path);
+            notes.remove(&*key);
+        } else {
+            self.notes.insert(path.to_path_buf(), note.to_string());
+            notes[&*key] = toml_edit::value(note);
+        }
+    }
+
+    /// Move the user's note for a setup export that has been renamed.
+    pub(crate) fn rename_note(&mut self, from: &Path, to: &Path) {
+        if let Some(note) = self.notes.get(from).cloned() {
+            self.set_note(from, "");
+            self.set_note(to, &note);
+        }
+    }
+
+    /// Check if the user has starred a setup export.
+    pub(crate) fn is_favorite(&self, path: &Path) -> bool {
+        self.favorites.contains(path)
+    }
+
+    /// Star or unstar a setup export.
+    pub(crate) fn set_favorite(&mut self, path: &Path, favorite: bool) {
+        let changed = if favorite {
+            self.favorites.insert(path.to_path_buf())
+        } else {
+            self.favorites.remove(path)
+        };
+
+        if changed {
+            self.write_favorites();
+        }
+    }
+
+    /// Move the star for a setup export that has been renamed.
+    pub(crate) fn rename_favorite(&mut self, from: &Path, to: &Path) {
+        if self.is_favorite(from) {
+            self.set_favorite(from, false);
+            self.set_favorite(to, true);
+        }
+    }
+
+    /// Write the favorites to the TOML document, sorted so the file does not churn.
+    fn write_favorites(&mut self) {
+        let mut paths: Vec<_> = self
+            .favorites
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        paths.sort();
+
+        if paths.is_empty() {
+            self.doc.as_table_mut().remove("favorites");
+        } else {
+            let paths: toml_edit::Array = paths.into_iter().collect();
+            self.doc["favorites"] = toml_edit::value(paths);
+        }
+    }
+
+    /// Get a reference for mapping raw track IDs to unique track IDs.
+    pub(crate) fn track_ids(&self) -> &PatriciaSet {
+        &self.track_ids
+    }
+
+    /// Get a reference for mapping track IDs to track names.
+    pub(crate) fn tracks(&self) -> &HashMap<String, String> {
+        &self.tracks
+    }
+
+    /// Get a reference for mapping car IDs to car names.
+    pub(crate) fn cars(&self) -> &HashMap<String, String> {
+        &self.cars
+    }
+
+    /// Get localized text that separates the car identifier from the setup name in export headers.
+    pub(crate) fn setup_markers(&self) -> &[String] {
+        &self.setup_markers
+    }
+
+    /// Get localized property group names that end the setup, in lowercase.
+    pub(crate) fn stop_groups(&self) -> &[String] {
+        &self.stop_groups
+    }
+
+    /// Take all problems in the TOML that were worked around while loading.
+    pub(crate) fn take_warnings(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Get user's color-coding choices.
+    pub(crate) fn colors(&self) -> Vec<egui::Color32> {
+        self.colors.clone()
+    }
+
+    /// Modify user's color-coding choices.
+    pub(crate) fn colors_mut(&mut self) -> &mut Vec<egui::Color32> {
+        &mut self.colors
+    }
+
+    /// Update colors in TOML document.
+    pub(crate) fn update_colors(&mut self) {
+        let mut colors = toml_edit::Array::default();
+
+        for color in &self.colors {
+            let color = format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b());
+            colors.push(color);
+        }
+
+        self.doc["config"]["colors"] = toml_edit::value(colors);
 
         self.doc["config"]["background_decrease"] = toml_edit::value(format!(
             "#{:02x}{:02x}{:02x}",
@@ -318,7 +1566,8 @@ impl Config {
         &mut self.diff_colors
     }
 
-    /// Update the frequency for update checks.
+    /// Get the frequency for update checks.
+    #[cfg(feature = "update-check")]
     pub(crate) fn get_update_check(&self) -> UpdateFrequency {
         self.update_check
     }
@@ -329,119 +1578,1100 @@ impl Config {
         self.doc["config"]["update_check"] = toml_edit::value(self.update_check.as_str());
     }
 
-    /// Load track and car info from config.
-    fn load_tracks_and_cars(&mut self) -> Result<(), Error> {
-        let table = &self.doc.get("tracks").and_then(|t| t.as_table());
-        if let Some(tracks) = table {
-            for (id, name) in tracks.iter() {
-                let name = name
-                    .as_str()
-                    .ok_or_else(|| Error::type_error(&format!("tracks.{}", id), "string"))?;
+    /// Get the unit preference for spring perch offsets.
+    pub(crate) fn get_perch_offset_unit(&self) -> PerchOffsetUnit {
+        self.perch_offset_unit
+    }
 
-                self.track_ids.insert(id);
-                self.tracks.insert(id.to_string(), name.to_string());
-            }
-        } else if !table.is_none() {
-            return Err(Error::type_error("tracks", "table"));
-        }
+    /// Update the unit preference for spring perch offsets.
+    pub(crate) fn set_perch_offset_unit(&mut self, perch_offset_unit: PerchOffsetUnit) {
+        self.perch_offset_unit = perch_offset_unit;
+        self.doc["config"]["perch_offset_unit"] = toml_edit::value(self.perch_offset_unit.as_str());
+    }
 
-        let cars = &self.doc.get("cars").and_then(|t| t.as_table());
-        if let Some(cars) = cars {
-            for (id, name) in cars.iter() {
-                let name = name
-                    .as_str()
-                    .ok_or_else(|| Error::type_error(&format!("cars.{}", id), "string"))?;
+    /// Get the locale preference for displaying numbers and dates.
+    pub(crate) fn get_locale(&self) -> Locale {
+        self.locale
+    }
 
-                self.cars.insert(id.to_string(), name.to_string());
-            }
-        } else if !cars.is_none() {
-            return Err(Error::type_error("cars", "table"));
-        }
+    /// Get the locale that numbers and dates are displayed in.
+    ///
+    /// This is the operating system's locale when the preference is [`Locale::System`].
+    pub(crate) fn display_locale(&self) -> Locale {
+        self.display_locale
+    }
 
-        Ok(())
+    /// Update the locale preference for displaying numbers and dates.
+    pub(crate) fn set_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+        self.display_locale = locale.resolve();
+        self.doc["config"]["locale"] = toml_edit::value(self.locale.as_str());
     }
 
-    /// Load column colors and background colors from config.
-    fn load_colors(&mut self) -> Result<(), Error> {
-        let mut parsed = Vec::new();
-        let colors = &self
-            .doc
-            .get("config")
-            .and_then(|t| t.get("colors"))
-            .and_then(|t| t.as_array());
+    /// Get the user's choices for table exports.
+    pub(crate) fn table_options(&self) -> TableOptions {
+        self.table_options
+    }
 
-        if let Some(colors) = colors {
-            for (i, color) in colors.iter().enumerate() {
-                let color = color
-                    .as_str()
-                    .ok_or_else(|| Error::type_error(&format!("config.colors[{}]", i), "string"))?;
-                let color = color_from_str(color)
-                    .map_err(|_| Error::Color(format!("config.colors[{}]", i)))?;
+    /// Update the user's choices for table exports.
+    pub(crate) fn set_table_options(&mut self, table_options: TableOptions) {
+        self.table_options = table_options;
 
-                parsed.push(color);
-            }
-        } else if !colors.is_none() {
-            return Err(Error::type_error("config.colors", "array"));
-        }
+        let export = table_mut(self.doc.as_table_mut(), "export");
+        export["delimiter"] = toml_edit::value(table_options.delimiter.to_string());
+        export["quoting"] = toml_edit::value(table_options.quoting.as_str());
+        export["group_column"] = toml_edit::value(table_options.group_column);
+        export["include_hidden_groups"] = toml_edit::value(table_options.include_hidden_groups);
+    }
 
-        // Parse background colors
-        let mut background = Vec::new();
-        for name in &["background_decrease", "background_increase"] {
-            let color = self
-                .doc
-                .get("config")
-                .and_then(|t| t.get(name))
-                .and_then(|t| t.as_str());
+    /// Get the graphics backend preference.
+    pub(crate) fn get_gpu_backend(&self) -> GpuBackend {
+        self.gpu_backend
+    }
 
-            if let Some(color) = color {
-                let color =
-                    color_from_str(color).map_err(|_| Error::Color(format!("config.{}", name)))?;
+    /// Update the graphics backend preference.
+    ///
+    /// The change takes effect on the next application start.
+    pub(crate) fn set_gpu_backend(&mut self, gpu_backend: GpuBackend) {
+        self.gpu_backend = gpu_backend;
+        self.doc["config"]["gpu_backend"] = toml_edit::value(self.gpu_backend.as_str());
+    }
 
-                background.push(color);
-            }
-        }
+    /// Get the surface presentation mode preference.
+    pub(crate) fn get_present_mode(&self) -> PresentMode {
+        self.present_mode
+    }
 
-        // If all colors are parsed successfully, replace the entire config
-        if !parsed.is_empty() {
-            self.colors = parsed;
-        }
-        if background.len() == 2 {
+    /// Update the surface presentation mode preference.
+    ///
+    /// The change takes effect on the next application start.
+    pub(crate) fn set_present_mode(&mut self, present_mode: PresentMode) {
+        self.present_mode = present_mode;
+        self.doc["config"]["present_mode"] = toml_edit::value(self.present_mode.as_str());
+    }
+
+    /// Get all property display names that apply to the given car.
+    ///
+    /// Aliases for the car take precedence over aliases for all cars.
+    pub(crate) fn aliases(&self, car_name: &str) -> HashMap<String, String> {
+        let mut aliases = self.aliases.clone();
+        if let Some(car_aliases) = self.car_aliases.get(car_name) {
+            aliases.extend(car_aliases.clone());
+        }
+
+        aliases
+    }
+
+    /// Get the display name for a property.
+    ///
+    /// When `car_name` is `None`, only aliases for all cars are considered.
+    pub(crate) fn get_alias(&self, car_name: Option<&str>, prop_name: &str) -> Option<&str> {
+        let aliases = match car_name {
+            Some(car_name) => self.car_aliases.get(car_name)?,
+            None => &self.aliases,
+        };
+
+        aliases.get(prop_name).map(|alias| alias.as_str())
+    }
+
+    /// Update the display name for a property.
+    ///
+    /// When `car_name` is `None`, the alias applies to all cars. An empty alias removes it.
+    pub(crate) fn set_alias(&mut self, car_name: Option<&str>, prop_name: &str, alias: &str) {
+        let (aliases, table) = match car_name {
+            Some(car_name) => {
+                let cars = table_mut(self.doc.as_table_mut(), "car_aliases");
+                cars.set_implicit(true);
+
+                let aliases = self.car_aliases.entry(car_name.to_string()).or_default();
+                (aliases, table_mut(cars, car_name))
+            }
+            None => (
+                &mut self.aliases,
+                table_mut(self.doc.as_table_mut(), "aliases"),
+            ),
+        };
+
+        if alias.is_empty() {
+            aliases.remove(prop_name);
+            table.remove(prop_name);
+        } else {
+            aliases.insert(prop_name.to_string(), alias.to_string());
+            table[prop_name] = toml_edit::value(alias);
+        }
+
+        // Remove empty per-car tables
+        if let Some(car_name) = car_name {
+            if aliases.is_empty() {
+                self.car_aliases.remove(car_name);
+                if let Some(cars) = self
+                    .doc
+                    .get_mut("car_aliases")
+                    .and_then(|t| t.as_table_mut())
+                {
+                    cars.remove(car_name);
+                }
+            }
+        }
+    }
+
+    /// Get all property groups hidden from the grid for the given car.
+    ///
+    /// Groups hidden for all cars are included.
+    pub(crate) fn hidden_groups(&self, car_name: &str) -> HashSet<String> {
+        self.hidden_groups
+            .iter()
+            .chain(self.car_hidden_groups.get(car_name).into_iter().flatten())
+            .cloned()
+            .collect()
+    }
+
+    /// Get the formatters for property values of the given car, in the order they are applied.
+    pub(crate) fn formatters(&self, car_name: &str) -> Vec<Formatter> {
+        self.formatters.get(car_name).cloned().unwrap_or_default()
+    }
+
+    /// Check if a property group is hidden from the grid.
+    ///
+    /// When `car_name` is `None`, only groups hidden for all cars are considered.
+    pub(crate) fn is_group_hidden(&self, car_name: Option<&str>, group_name: &str) -> bool {
+        let hidden_groups = match car_name {
+            Some(car_name) => match self.car_hidden_groups.get(car_name) {
+                Some(hidden_groups) => hidden_groups,
+                None => return false,
+            },
+            None => &self.hidden_groups,
+        };
+
+        hidden_groups.iter().any(|name| name == group_name)
+    }
+
+    /// Check if a property group starts collapsed in the grid.
+    pub(crate) fn is_group_collapsed(&self, group_name: &str) -> bool {
+        self.collapsed_groups.iter().any(|name| name == group_name)
+    }
+
+    /// Choose whether a property group starts collapsed in the grid, for all cars.
+    pub(crate) fn set_group_collapsed(&mut self, group_name: &str, collapsed: bool) {
+        self.collapsed_groups.retain(|name| name != group_name);
+        if collapsed {
+            self.collapsed_groups.push(group_name.to_string());
+        }
+
+        let array: toml_edit::Array = self.collapsed_groups.iter().collect();
+        self.doc["config"]["collapsed_groups"] = toml_edit::value(array);
+    }
+
+    /// Get the properties with swapped diff colors.
+    pub(crate) fn swapped_diffs(&self) -> &[String] {
+        &self.swapped_diffs
+    }
+
+    /// Swap the diff colors for properties matching `pattern`, or restore them.
+    ///
+    /// See [`GridOptions::is_diff_swapped`] for how patterns are matched.
+    pub(crate) fn set_diff_swapped(&mut self, pattern: &str, swapped: bool) {
+        self.swapped_diffs
+            .retain(|name| !name.eq_ignore_ascii_case(pattern));
+        if swapped {
+            self.swapped_diffs.push(pattern.to_string());
+        }
+
+        let array: toml_edit::Array = self.swapped_diffs.iter().collect();
+        self.doc["config"]["swapped_diffs"] = toml_edit::value(array);
+    }
+
+    /// Get the safe ranges for property values, by property name.
+    pub(crate) fn safe_ranges(&self) -> &HashMap<String, SafeRange> {
+        &self.safe_ranges
+    }
+
+    /// Set the safe range for a property, or remove it with `None`.
+    pub(crate) fn set_safe_range(&mut self, prop_name: &str, range: Option<SafeRange>) {
+        let table = table_mut(self.doc.as_table_mut(), "safe_ranges");
+
+        match range {
+            Some(range) => {
+                table[prop_name] = toml_edit::value(range.to_inline_table());
+                self.safe_ranges.insert(prop_name.to_string(), range);
+            }
+            None => {
+                table.remove(prop_name);
+                self.safe_ranges.remove(prop_name);
+            }
+        }
+    }
+
+    /// Hide or show a property group in the grid.
+    ///
+    /// When `car_name` is `None`, the choice applies to all cars.
+    pub(crate) fn set_group_hidden(
+        &mut self,
+        car_name: Option<&str>,
+        group_name: &str,
+        hidden: bool,
+    ) {
+        let hidden_groups = match car_name {
+            Some(car_name) => self
+                .car_hidden_groups
+                .entry(car_name.to_string())
+                .or_default(),
+            None => &mut self.hidden_groups,
+        };
+
+        hidden_groups.retain(|name| name != group_name);
+        if hidden {
+            hidden_groups.push(group_name.to_string());
+        }
+        let array: toml_edit::Array = hidden_groups.iter().collect();
+
+        match car_name {
+            None => self.doc["config"]["hidden_groups"] = toml_edit::value(array),
+            Some(car_name) => {
+                let cars = table_mut(self.doc.as_table_mut(), "car_hidden_groups");
+                if array.is_empty() {
+                    self.car_hidden_groups.remove(car_name);
+                    cars.remove(car_name);
+                } else {
+                    cars[car_name] = toml_edit::value(array);
+                }
+            }
+        }
+    }
+
+    /// Load track and car info from config.
+    fn load_tracks_and_cars(&mut self) -> Result<(), Error> {
+        let table = &self.doc.get("tracks").and_then(|t| t.as_table());
+        if let Some(tracks) = table {
+            for (id, name) in tracks.iter() {
+                let name = name
+                    .as_str()
+                    .ok_or_else(|| Error::type_error(&format!("tracks.{}", id), "string"))?;
+
+                self.track_ids.insert(id);
+                self.tracks.insert(id.to_string(), name.to_string());
+            }
+        } else if !table.is_none() {
+            return Err(Error::type_error("tracks", "table"));
+        }
+
+        let cars = &self.doc.get("cars").and_then(|t| t.as_table());
+        if let Some(cars) = cars {
+            for (id, name) in cars.iter() {
+                let name = name
+                    .as_str()
+                    .ok_or_else(|| Error::type_error(&format!("cars.{}", id), "string"))?;
+
+                self.cars.insert(id.to_string(), name.to_string());
+            }
+        } else if !cars.is_none() {
+            return Err(Error::type_error("cars", "table"));
+        }
+
+        self.disambiguate_car_names();
+
+        Ok(())
+    }
+
+    /// Add the car ID to car names that are shared by more than one car ID.
+    ///
+    /// Setups are grouped by car name, so a shared name would silently merge setups for unrelated
+    /// cars.
+    fn disambiguate_car_names(&mut self) {
+        let mut ids_by_name: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (id, name) in self.cars.iter() {
+            ids_by_name.entry(name).or_default().push(id);
+        }
+
+        let mut duplicates: Vec<_> = ids_by_name
+            .into_iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .map(|(name, mut ids)| {
+                ids.sort_unstable();
+                let ids: Vec<_> = ids.into_iter().map(|id| id.to_string()).collect();
+                (name.to_string(), ids)
+            })
+            .collect();
+        duplicates.sort();
+
+        for (name, ids) in duplicates {
+            for id in &ids {
+                self.cars
+                    .insert(id.to_string(), format!("{} ({})", name, id));
+            }
+            self.warnings.push(Error::DuplicateCarName { name, ids });
+        }
+    }
+
+    /// Load column colors and background colors from config.
+    fn load_colors(&mut self) -> Result<(), Error> {
+        let mut parsed = None;
+        let colors = self.doc.get("config").and_then(|t| t.get("colors"));
+
+        // Colors are kept as-is when the key is absent
+        if let Some(colors) = colors {
+            let colors = colors
+                .as_array()
+                .ok_or_else(|| Error::type_error("config.colors", "array"))?;
+            let mut output = Vec::with_capacity(colors.len());
+
+            for (i, color) in colors.iter().enumerate() {
+                let color = color
+                    .as_str()
+                    .ok_or_else(|| Error::type_error(&format!("config.colors[{}]", i), "string"))?;
+                let color = color_from_str(color)
+                    .map_err(|_| Error::Color(format!("config.colors[{}]", i)))?;
+
+                output.push(color);
+            }
+
+            parsed = Some(output);
+        }
+
+        // Parse background colors
+        let mut background = Vec::new();
+        for name in &["background_decrease", "background_increase"] {
+            let color = self
+                .doc
+                .get("config")
+                .and_then(|t| t.get(name))
+                .and_then(|t| t.as_str());
+
+            if let Some(color) = color {
+                let color =
+                    color_from_str(color).map_err(|_| Error::Color(format!("config.{}", name)))?;
+
+                background.push(color);
+            }
+        }
+
+        // If all colors are parsed successfully, replace the entire config
+        match parsed {
+            Some(parsed) if parsed.is_empty() => {
+                self.colors = vec![DEFAULT_COLOR];
+                self.warnings.push(Error::EmptyColors);
+            }
+            Some(parsed) => self.colors = parsed,
+            None => (),
+        }
+        if background.len() == 2 {
             self.diff_colors = (background[0], background[1]);
         }
 
         Ok(())
     }
+
+    /// Load localized text for parsing setup exports from config.
+    ///
+    /// Entries are merged with the existing lists, so the defaults are always available.
+    fn load_localization(&mut self) -> Result<(), Error> {
+        let localization = self.doc.get("localization");
+        let localization = match localization {
+            Some(localization) => localization
+                .as_table()
+                .ok_or_else(|| Error::type_error("localization", "table"))?,
+            None => return Ok(()),
+        };
+
+        for (name, list) in [
+            ("setup_markers", &mut self.setup_markers),
+            ("stop_groups", &mut self.stop_groups),
+        ] {
+            let path = format!("localization.{}", name);
+            let array = match localization.get(name) {
+                Some(array) => array
+                    .as_array()
+                    .ok_or_else(|| Error::type_error(&path, "array"))?,
+                None => continue,
+            };
+
+            for (i, text) in array.iter().enumerate() {
+                let text = text
+                    .as_str()
+                    .ok_or_else(|| Error::type_error(&format!("{}[{}]", path, i), "string"))?;
+                let text = if name == "stop_groups" {
+                    text.to_lowercase()
+                } else {
+                    text.to_string()
+                };
+
+                if !text.trim().is_empty() && !list.contains(&text) {
+                    list.push(text);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load hidden property groups from config.
+    fn load_hidden_groups(&mut self) -> Result<(), Error> {
+        if let Some(hidden_groups) = self.doc.get("config").and_then(|t| t.get("hidden_groups")) {
+            self.hidden_groups = strings_from_array(hidden_groups, "config.hidden_groups")?;
+        }
+
+        if let Some(cars) = self.doc.get("car_hidden_groups") {
+            let cars = cars
+                .as_table()
+                .ok_or_else(|| Error::type_error("car_hidden_groups", "table"))?;
+
+            for (car_name, hidden_groups) in cars.iter() {
+                let path = format!("car_hidden_groups.{}", car_name);
+                let hidden_groups = strings_from_array(hidden_groups, &path)?;
+                self.car_hidden_groups
+                    .insert(car_name.to_string(), hidden_groups);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load property groups that start collapsed from config.
+    ///
+    /// The defaults are kept when the config file does not have this list.
+    fn load_collapsed_groups(&mut self) -> Result<(), Error> {
+        if let Some(groups) = self
+            .doc
+            .get("config")
+            .and_then(|t| t.get("collapsed_groups"))
+        {
+            self.collapsed_groups = strings_from_array(groups, "config.collapsed_groups")?;
+        }
+
+        Ok(())
+    }
+
+    /// Load properties with swapped diff colors from config.
+    fn load_swapped_diffs(&mut self) -> Result<(), Error> {
+        if let Some(swapped_diffs) = self.doc.get("config").and_then(|t| t.get("swapped_diffs")) {
+            self.swapped_diffs = strings_from_array(swapped_diffs, "config.swapped_diffs")?;
+        }
+
+        Ok(())
+    }
+
+    /// Load safe ranges for property values from config.
+    fn load_safe_ranges(&mut self) -> Result<(), Error> {
+        if let Some(ranges) = self.doc.get("safe_ranges") {
+            let ranges = ranges
+                .as_table()
+                .ok_or_else(|| Error::type_error("safe_ranges", "table"))?;
+
+            for (prop_name, range) in ranges.iter() {
+                let path = format!("safe_ranges.{}", prop_name);
+                let range = SafeRange::from_item(range, &path)?;
+                self.safe_ranges.insert(prop_name.to_string(), range);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load formatters for property values from config.
+    ///
+    /// Formatters with an invalid pattern are left out with a warning.
+    fn load_formatters(&mut self) -> Result<(), Error> {
+        if let Some(cars) = self.doc.get("formatters") {
+            let cars = cars
+                .as_table()
+                .ok_or_else(|| Error::type_error("formatters", "table"))?;
+
+            for (car_name, formatters) in cars.iter() {
+                let path = format!("formatters.{}", car_name);
+                let formatters = formatters
+                    .as_array()
+                    .ok_or_else(|| Error::type_error(&path, "array"))?;
+
+                let mut output = Vec::with_capacity(formatters.len());
+                for (i, formatter) in formatters.iter().enumerate() {
+                    let path = format!("{}[{}]", path, i);
+                    let table = formatter
+                        .as_inline_table()
+                        .ok_or_else(|| Error::type_error(&path, "table"))?;
+                    let string = |key: &str| -> Result<&str, Error> {
+                        table
+                            .get(key)
+                            .and_then(|value| value.as_str())
+                            .ok_or_else(|| {
+                                Error::type_error(&format!("{}.{}", path, key), "string")
+                            })
+                    };
+
+                    match Formatter::new(
+                        string("property")?,
+                        string("pattern")?,
+                        string("replacement")?,
+                    ) {
+                        Ok(formatter) => output.push(formatter),
+                        Err(error) => {
+                            let path = format!("{}.pattern", path);
+                            self.warnings.push(Error::Formatter { path, error });
+                        }
+                    }
+                }
+                self.formatters.insert(car_name.to_string(), output);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load table export choices from config.
+    fn load_table_options(&mut self) -> Result<(), Error> {
+        let export = match self.doc.get("export") {
+            Some(export) => export
+                .as_table()
+                .ok_or_else(|| Error::type_error("export", "table"))?,
+            None => return Ok(()),
+        };
+
+        if let Some(delimiter) = export.get("delimiter") {
+            let mut chars = delimiter.as_str().unwrap_or_default().chars();
+            self.table_options.delimiter = match (chars.next(), chars.next()) {
+                (Some(ch), None) if ch != '"' && ch != '\n' && ch != '\r' => ch,
+                _ => return Err(Error::type_error("export.delimiter", "single character")),
+            };
+        }
+
+        if let Some(quoting) = export.get("quoting") {
+            let quoting = quoting
+                .as_str()
+                .ok_or_else(|| Error::type_error("export.quoting", "string"))?;
+
+            self.table_options.quoting = Quoting::from_str(quoting);
+        }
+
+        if let Some(group_column) = export.get("group_column") {
+            self.table_options.group_column = group_column
+                .as_bool()
+                .ok_or_else(|| Error::type_error("export.group_column", "boolean"))?;
+        }
+
+        if let Some(include_hidden_groups) = export.get("include_hidden_groups") {
+            self.table_options.include_hidden_groups = include_hidden_groups
+                .as_bool()
+                .ok_or_else(|| Error::type_error("export.include_hidden_groups", "boolean"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Load track and car usage times from config.
+    fn load_usage(&mut self) -> Result<(), Error> {
+        let usage = match self.doc.get("usage") {
+            Some(usage) => usage
+                .as_table()
+                .ok_or_else(|| Error::type_error("usage", "table"))?,
+            None => return Ok(()),
+        };
+
+        for (name, map) in [
+            ("tracks", &mut self.track_usage),
+            ("cars", &mut self.car_usage),
+        ] {
+            let path = format!("usage.{}", name);
+            let table = match usage.get(name) {
+                Some(table) => table
+                    .as_table()
+                    .ok_or_else(|| Error::type_error(&path, "table"))?,
+                None => continue,
+            };
+
+            for (key, time) in table.iter() {
+                let time = time
+                    .as_integer()
+                    .ok_or_else(|| Error::type_error(&format!("{}.{}", path, key), "integer"))?;
+
+                map.insert(key.to_string(), time);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load user notes for setup exports from config.
+    fn load_notes(&mut self) -> Result<(), Error> {
+        let notes = match self.doc.get("notes") {
+            Some(notes) => notes
+                .as_table()
+                .ok_or_else(|| Error::type_error("notes", "table"))?,
+            None => return Ok(()),
+        };
+
+        for (path, note) in notes.iter() {
+            let note = note
+                .as_str()
+                .ok_or_else(|| Error::type_error(&format!("notes.{}", path), "string"))?;
+
+            self.notes.insert(PathBuf::from(path), note.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Load recently used setup exports paths from config.
+    fn load_recent_setups_paths(&mut self) -> Result<(), Error> {
+        let paths = match self
+            .doc
+            .get("config")
+            .and_then(|t| t.get("recent_setups_paths"))
+        {
+            Some(paths) => paths
+                .as_array()
+                .ok_or_else(|| Error::type_error("config.recent_setups_paths", "array"))?,
+            None => return Ok(()),
+        };
+
+        self.recent_setups_paths = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                path.as_str().map(PathBuf::from).ok_or_else(|| {
+                    Error::type_error(&format!("config.recent_setups_paths[{}]", i), "string")
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(())
+    }
+
+    /// Load locked column widths for each car from config.
+    fn load_column_widths(&mut self) -> Result<(), Error> {
+        let cars = match self.doc.get("column_widths") {
+            Some(cars) => cars
+                .as_table()
+                .ok_or_else(|| Error::type_error("column_widths", "table"))?,
+            None => return Ok(()),
+        };
+
+        for (car_name, widths) in cars.iter() {
+            let path = format!("column_widths.{}", car_name);
+            let widths = widths
+                .as_array()
+                .ok_or_else(|| Error::type_error(&path, "array"))?
+                .iter()
+                .enumerate()
+                .map(|(i, width)| {
+                    width
+                        .as_float()
+                        .or_else(|| width.as_integer().map(|width| width as f64))
+                        .map(|width| (width as f32).max(MIN_COLUMN_WIDTH))
+                        .ok_or_else(|| Error::type_error(&format!("{}[{}]", path, i), "number"))
+                })
+                .collect::<Result<_, _>>()?;
+
+            self.column_widths.insert(car_name.to_string(), widths);
+        }
+
+        Ok(())
+    }
+
+    /// Load starred setup exports from config.
+    fn load_favorites(&mut self) -> Result<(), Error> {
+        let paths = match self.doc.get("favorites") {
+            Some(paths) => paths
+                .as_array()
+                .ok_or_else(|| Error::type_error("favorites", "array"))?,
+            None => return Ok(()),
+        };
+
+        self.favorites = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                path.as_str()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| Error::type_error(&format!("favorites[{}]", i), "string"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(())
+    }
+
+    /// Load setup exports and folders excluded from the library from config.
+    fn load_excluded_paths(&mut self) -> Result<(), Error> {
+        let paths = match self.doc.get("config").and_then(|t| t.get("excluded_paths")) {
+            Some(paths) => paths
+                .as_array()
+                .ok_or_else(|| Error::type_error("config.excluded_paths", "array"))?,
+            None => return Ok(()),
+        };
+
+        self.excluded_paths = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                path.as_str()
+                    .map(PathBuf::from)
+                    .filter(|path| !path.as_os_str().is_empty() && is_relative_subfolder(path))
+                    .ok_or_else(|| {
+                        Error::type_error(&format!("config.excluded_paths[{}]", i), "relative path")
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(())
+    }
+
+    /// Load file name patterns for finding baseline setups from config.
+    fn load_baseline_patterns(&mut self) -> Result<(), Error> {
+        let patterns = match self
+            .doc
+            .get("config")
+            .and_then(|t| t.get("baseline_patterns"))
+        {
+            Some(patterns) => patterns
+                .as_array()
+                .ok_or_else(|| Error::type_error("config.baseline_patterns", "array"))?,
+            None => return Ok(()),
+        };
+
+        self.baseline_patterns = patterns
+            .iter()
+            .enumerate()
+            .map(|(i, pattern)| {
+                pattern.as_str().map(str::to_string).ok_or_else(|| {
+                    Error::type_error(&format!("config.baseline_patterns[{}]", i), "string")
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(())
+    }
+
+    /// Load property display names from config.
+    fn load_aliases(&mut self) -> Result<(), Error> {
+        if let Some(aliases) = self.doc.get("aliases") {
+            let aliases = aliases
+                .as_table()
+                .ok_or_else(|| Error::type_error("aliases", "table"))?;
+
+            self.aliases = aliases_from_table(aliases, "aliases")?;
+        }
+
+        if let Some(cars) = self.doc.get("car_aliases") {
+            let cars = cars
+                .as_table()
+                .ok_or_else(|| Error::type_error("car_aliases", "table"))?;
+
+            for (car_name, aliases) in cars.iter() {
+                let path = format!("car_aliases.{}", car_name);
+                let aliases = aliases
+                    .as_table()
+                    .ok_or_else(|| Error::type_error(&path, "table"))?;
+
+                let aliases = aliases_from_table(aliases, &path)?;
+                self.car_aliases.insert(car_name.to_string(), aliases);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Window {
+    /// Create a Window configuration.
+    fn new(position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> Self {
+        Self { position, size }
+    }
+
+    /// Create a Window from a `winit` window.
+    fn from_winit(window: &winit::window::Window) -> Self {
+        #[cfg(target_os = "macos")]
+        let position = window.inner_position();
+        #[cfg(not(target_os = "macos"))]
+        let position = window.outer_position();
+
+        let position = position.unwrap_or_else(|_| PhysicalPosition::default());
+        let size = window.inner_size();
+
+        Self { position, size }
+    }
+
+    /// Create a TOML table from this Window.
+    fn to_table(&self) -> Item {
+        let mut output = toml_edit::table();
+
+        output["x"] = toml_edit::value(self.position.x as i64);
+        output["y"] = toml_edit::value(self.position.y as i64);
+        output["width"] = toml_edit::value(self.size.width as i64);
+        output["height"] = toml_edit::value(self.size.height as i64);
+
+        output
+    }
+}
+
+impl GridOptions {
+    /// Check if the diff colors are swapped for a property, because lower values are better.
+    ///
+    /// Properties are matched when their name contains any of the `swapped_diffs` patterns,
+    /// ignoring case. E.g. the pattern `temp` matches all tire temperatures.
+    pub(crate) fn is_diff_swapped(&self, prop_name: &str) -> bool {
+        let prop_name = prop_name.to_lowercase();
+
+        self.swapped_diffs
+            .iter()
+            .filter(|pattern| !pattern.is_empty())
+            .any(|pattern| prop_name.contains(&pattern.to_lowercase()))
+    }
+}
+
+impl SafeRange {
+    /// Check if a value is outside of this range.
+    ///
+    /// Text and numbers with a different unit are never outside of the range.
+    pub(crate) fn is_outside(&self, value: &str) -> bool {
+        match Value::parse(value) {
+            Value::Number(number)
+                if self.unit.is_empty() || number.unit.eq_ignore_ascii_case(&self.unit) =>
+            {
+                let value = number.as_f64();
+
+                self.min.map_or(false, |min| value < min)
+                    || self.max.map_or(false, |max| value > max)
+            }
+            _ => false,
+        }
+    }
+
+    /// Parse a safe range from a table like `{ min = 18.0, max = 30.0, unit = "psi" }`.
+    ///
+    /// All keys are optional. Integers are accepted for `min` and `max`.
+    fn from_item(item: &Item, path: &str) -> Result<Self, Error> {
+        let table = item
+            .as_table_like()
+            .ok_or_else(|| Error::type_error(path, "table"))?;
+
+        let number = |key: &str| -> Result<Option<f64>, Error> {
+            match table.get(key) {
+                Some(item) => item
+                    .as_float()
+                    .or_else(|| item.as_integer().map(|value| value as f64))
+                    .map(Some)
+                    .ok_or_else(|| Error::type_error(&format!("{}.{}", path, key), "number")),
+                None => Ok(None),
+            }
+        };
+        let unit = match table.get("unit") {
+            Some(item) => item
+                .as_str()
+                .ok_or_else(|| Error::type_error(&format!("{}.unit", path), "string"))?,
+            None => "",
+        };
+
+        Ok(Self {
+            min: number("min")?,
+            max: number("max")?,
+            unit: unit.to_string(),
+        })
+    }
+
+    /// Create an inline TOML table from this range, leaving out missing limits and an empty unit.
+    fn to_inline_table(&self) -> toml_edit::InlineTable {
+        let mut table = toml_edit::InlineTable::default();
+
+        if let Some(min) = self.min {
+            table.get_or_insert("min", min);
+        }
+        if let Some(max) = self.max {
+            table.get_or_insert("max", max);
+        }
+        if !self.unit.is_empty() {
+            table.get_or_insert("unit", self.unit.as_str());
+        }
+
+        table
+    }
+}
+
+impl std::fmt::Display for SafeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = if self.unit.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", self.unit)
+        };
+
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => write!(f, "{} to {}{}", min, max, unit),
+            (Some(min), None) => write!(f, "at least {}{}", min, unit),
+            (None, Some(max)) => write!(f, "at most {}{}", max, unit),
+            (None, None) => write!(f, "any value"),
+        }
+    }
+}
+
+impl Quoting {
+    /// Create a `Quoting` from a string slice.
+    fn from_str(value: &str) -> Self {
+        match value {
+            "always" => Self::Always,
+            "never" => Self::Never,
+            _ => Self::Minimal,
+        }
+    }
+
+    /// Get a string slice that is TOML-compatible for this `Quoting`.
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Minimal => "minimal",
+            Self::Always => "always",
+            Self::Never => "never",
+        }
+    }
+}
+
+impl std::fmt::Display for Quoting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Minimal => "When Needed",
+            Self::Always => "Always",
+            Self::Never => "Never",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl SortOrder {
+    /// Create a `SortOrder` from a string slice.
+    fn from_str(value: &str) -> Self {
+        match value {
+            "recent" => Self::Recent,
+            _ => Self::Name,
+        }
+    }
+
+    /// Get a string slice that is TOML-compatible for this `SortOrder`.
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Name => "name",
+            Self::Recent => "recent",
+        }
+    }
+}
+
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Name => "By Name",
+            Self::Recent => "Most Recently Used",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl Browse {
+    /// Create a `Browse` from a string slice.
+    fn from_str(value: &str) -> Self {
+        match value {
+            "car" => Self::Car,
+            _ => Self::Track,
+        }
+    }
+
+    /// Get a string slice that is TOML-compatible for this `Browse`.
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Track => "track",
+            Self::Car => "car",
+        }
+    }
+}
+
+impl std::fmt::Display for Browse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Track => "By Track",
+            Self::Car => "By Car",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl Grouping {
+    /// Create a `Grouping` from a string slice.
+    fn from_str(value: &str) -> Self {
+        match value {
+            "corner" => Self::Corner,
+            _ => Self::Export,
+        }
+    }
+
+    /// Get a string slice that is TOML-compatible for this `Grouping`.
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Export => "export",
+            Self::Corner => "corner",
+        }
+    }
+}
+
+impl std::fmt::Display for Grouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Export => "As Exported",
+            Self::Corner => "By Corner",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl DiffReference {
+    /// Create a `DiffReference` from a string slice.
+    fn from_str(value: &str) -> Self {
+        match value {
+            "previous" => Self::Previous,
+            _ => Self::First,
+        }
+    }
+
+    /// Get a string slice that is TOML-compatible for this `DiffReference`.
+    fn as_str(&self) -> &str {
+        match self {
+            Self::First => "first",
+            Self::Previous => "previous",
+        }
+    }
+}
+
+impl std::fmt::Display for DiffReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::First => "First Column",
+            Self::Previous => "Previous Column",
+        };
+        write!(f, "{}", text)
+    }
 }
 
-impl Window {
-    /// Create a Window configuration.
-    fn new(position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> Self {
-        Self { position, size }
+impl DiffBlend {
+    /// Create a `DiffBlend` from a string slice.
+    fn from_str(value: &str) -> Self {
+        match value {
+            "overlay" => Self::Overlay,
+            "solid" => Self::Solid,
+            _ => Self::Multiply,
+        }
     }
 
-    /// Create a Window from a `winit` window.
-    fn from_winit(window: &winit::window::Window) -> Self {
-        #[cfg(target_os = "macos")]
-        let position = window.inner_position();
-        #[cfg(not(target_os = "macos"))]
-        let position = window.outer_position();
-
-        let position = position.unwrap_or_else(|_| PhysicalPosition::default());
-        let size = window.inner_size();
-
-        Self { position, size }
+    /// Get a string slice that is TOML-compatible for this `DiffBlend`.
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Multiply => "multiply",
+            Self::Overlay => "overlay",
+            Self::Solid => "solid",
+        }
     }
+}
 
-    /// Create a TOML table from this Window.
-    fn to_table(&self) -> Item {
-        let mut output = toml_edit::table();
-
-        output["x"] = toml_edit::value(self.position.x as i64);
-        output["y"] = toml_edit::value(self.position.y as i64);
-        output["width"] = toml_edit::value(self.size.width as i64);
-        output["height"] = toml_edit::value(self.size.height as i64);
-
-        output
+impl std::fmt::Display for DiffBlend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Multiply => "Multiply",
+            Self::Overlay => "Overlay",
+            Self::Solid => "Solid",
+        };
+        write!(f, "{}", text)
     }
 }
 
@@ -496,6 +2726,109 @@ impl std::fmt::Display for UserTheme {
     }
 }
 
+/// Get the current time in seconds since the Unix epoch.
+fn unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+/// Parse a table of property display names.
+fn aliases_from_table(
+    table: &toml_edit::Table,
+    path: &str,
+) -> Result<HashMap<String, String>, Error> {
+    let mut aliases = HashMap::new();
+
+    for (prop_name, alias) in table.iter() {
+        let alias = alias
+            .as_str()
+            .ok_or_else(|| Error::type_error(&format!("{}.{}", path, prop_name), "string"))?;
+
+        aliases.insert(prop_name.to_string(), alias.to_string());
+    }
+
+    Ok(aliases)
+}
+
+/// Parse an array of strings.
+fn strings_from_array(item: &Item, path: &str) -> Result<Vec<String>, Error> {
+    let array = item
+        .as_array()
+        .ok_or_else(|| Error::type_error(path, "array"))?;
+
+    array
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            value
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| Error::type_error(&format!("{}[{}]", path, i), "string"))
+        })
+        .collect()
+}
+
+/// Get a mutable reference to a child table, creating it if it does not exist.
+fn table_mut<'a>(table: &'a mut toml_edit::Table, key: &str) -> &'a mut toml_edit::Table {
+    let item = table.entry(key).or_insert_with(toml_edit::table);
+    if !item.is_table() {
+        *item = toml_edit::table();
+    }
+
+    item.as_table_mut().unwrap()
+}
+
+/// Get the folders where iRacing may keep setup exports, in order of preference.
+///
+/// Besides the documents directory, this includes documents synced by OneDrive, and the Wine and
+/// Proton prefixes used to run iRacing on Linux.
+fn setups_path_candidates(document_dir: Option<&Path>, home_dir: &Path) -> Vec<PathBuf> {
+    // Steam app ID for iRacing, which names its Proton prefix
+    const STEAM_APP_ID: &str = "266410";
+
+    let user_name = home_dir.file_name().unwrap_or_default();
+    let mut candidates: Vec<PathBuf> = document_dir.into_iter().map(Path::to_path_buf).collect();
+    candidates.extend([
+        home_dir.join("Documents"),
+        home_dir.join("OneDrive").join("Documents"),
+        home_dir.to_path_buf(),
+        home_dir
+            .join(".steam/steam/steamapps/compatdata")
+            .join(STEAM_APP_ID)
+            .join("pfx/drive_c/users/steamuser/Documents"),
+        home_dir
+            .join(".wine/drive_c/users")
+            .join(user_name)
+            .join("Documents"),
+    ]);
+
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let path = candidate.join("iRacing");
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    paths
+}
+
+/// Format a relative path with forward slashes, which work on all platforms and keep the config file
+/// portable.
+fn portable_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Check that a path only descends into subfolders, so it cannot escape the setup exports path.
+fn is_relative_subfolder(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
 fn color_from_str(color: &str) -> Result<egui::Color32, ()> {
     // Validate color format. Require HTML hex `#rrggbb` for convenience
     let mut validator = color.chars();
@@ -552,4 +2885,824 @@ mod tests {
             Some(&"Skip Barber Formula 2000".to_string())
         )
     }
+
+    /// Test absent, empty, and malformed color arrays.
+    #[test]
+    fn test_load_colors() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        let defaults = config.colors();
+        assert_eq!(defaults.len(), 6);
+        assert!(config.take_warnings().is_empty());
+
+        // Absent colors keep the defaults
+        config.doc["config"]
+            .as_table_mut()
+            .unwrap()
+            .remove("colors");
+        config.load_colors().unwrap();
+        assert_eq!(config.colors(), defaults);
+        assert!(config.take_warnings().is_empty());
+
+        // An empty palette uses a single default color, with a warning
+        config.doc["config"]["colors"] = toml_edit::value(toml_edit::Array::default());
+        config.load_colors().unwrap();
+        assert_eq!(config.colors(), [DEFAULT_COLOR]);
+        let warnings = config.take_warnings();
+        assert!(matches!(warnings.as_slice(), [Error::EmptyColors]));
+
+        // Malformed colors are errors
+        config.doc["config"]["colors"] = toml_edit::value("#ff0000");
+        assert!(matches!(config.load_colors(), Err(Error::Type { .. })));
+
+        let mut colors = toml_edit::Array::default();
+        colors.push(42_i64);
+        config.doc["config"]["colors"] = toml_edit::value(colors);
+        assert!(matches!(config.load_colors(), Err(Error::Type { .. })));
+
+        let mut colors = toml_edit::Array::default();
+        colors.push("red");
+        config.doc["config"]["colors"] = toml_edit::value(colors);
+        assert!(matches!(config.load_colors(), Err(Error::Color(_))));
+    }
+
+    /// Test property aliases for all cars and for a single car.
+    #[test]
+    fn test_aliases() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+
+        config.set_alias(None, "LS rbd damping", "Low-speed rebound");
+        config.set_alias(None, "Camber", "Camber angle");
+        config.set_alias(Some("Test Car"), "Camber", "Static camber");
+
+        let aliases = config.aliases("Test Car");
+        assert_eq!(aliases["LS rbd damping"], "Low-speed rebound");
+        assert_eq!(aliases["Camber"], "Static camber");
+        assert_eq!(config.aliases("VW Beetle")["Camber"], "Camber angle");
+        assert_eq!(config.get_alias(None, "Camber"), Some("Camber angle"));
+
+        // Aliases must survive a round trip through TOML.
+        let doc = config.doc.to_string();
+        assert!(doc.contains(r#"[car_aliases."Test Car"]"#));
+        config.doc = doc.parse().unwrap();
+        config.aliases.clear();
+        config.car_aliases.clear();
+        assert!(config.load_aliases().is_ok());
+        assert_eq!(config.aliases("Test Car")["Camber"], "Static camber");
+
+        // Empty aliases are removed.
+        config.set_alias(Some("Test Car"), "Camber", "");
+        assert_eq!(config.get_alias(Some("Test Car"), "Camber"), None);
+        assert!(!config.doc.to_string().contains("Test Car"));
+    }
+
+    /// Test hiding property groups for all cars and for a single car.
+    #[test]
+    fn test_hidden_groups() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        assert!(config.hidden_groups("Test Car").is_empty());
+
+        config.set_group_hidden(None, "Lighting", true);
+        config.set_group_hidden(Some("Test Car"), "Aero Calculator", true);
+        assert!(config.is_group_hidden(None, "Lighting"));
+        assert!(!config.is_group_hidden(Some("Test Car"), "Lighting"));
+        assert!(config.is_group_hidden(Some("Test Car"), "Aero Calculator"));
+        assert_eq!(
+            config.hidden_groups("Test Car"),
+            HashSet::from(["Lighting".to_string(), "Aero Calculator".to_string()]),
+        );
+        assert_eq!(
+            config.hidden_groups("VW Beetle"),
+            HashSet::from(["Lighting".to_string()]),
+        );
+
+        // Hidden groups must survive a round trip through TOML.
+        config.doc = config.doc.to_string().parse().unwrap();
+        config.hidden_groups.clear();
+        config.car_hidden_groups.clear();
+        config.load_hidden_groups().unwrap();
+        assert!(config.is_group_hidden(Some("Test Car"), "Aero Calculator"));
+        assert!(config.is_group_hidden(None, "Lighting"));
+
+        // Empty per-car lists are removed.
+        config.set_group_hidden(Some("Test Car"), "Aero Calculator", false);
+        assert!(!config.doc.to_string().contains("Test Car"));
+        assert!(!config.is_group_hidden(Some("Test Car"), "Aero Calculator"));
+
+        config.doc["config"]["hidden_groups"] = toml_edit::value("Lighting");
+        assert!(matches!(
+            config.load_hidden_groups(),
+            Err(Error::Type { .. })
+        ));
+    }
+
+    /// Test resetting the config to defaults.
+    #[test]
+    fn test_reset() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        config.update_setups_path("/tmp/setups");
+        config.update_theme(UserTheme::Light);
+        config.set_alias(None, "Camber", "Camber angle");
+        config.doc["cars"]["newcar"] = toml_edit::value("New Car");
+        config.load_tracks_and_cars().unwrap();
+        config.set_note(Path::new("/tmp/setups/wet.htm"), "Good in the rain");
+
+        config.reset(true).unwrap();
+        assert_eq!(config.theme(), &UserTheme::Auto);
+        assert_eq!(config.get_alias(None, "Camber"), None);
+        assert_eq!(config.get_setups_path(), Path::new("/tmp/setups"));
+        assert_eq!(config.cars().get("newcar"), Some(&"New Car".to_string()));
+
+        config.reset(false).unwrap();
+        assert_eq!(
+            config.get_note(Path::new("/tmp/setups/wet.htm")),
+            Some("Good in the rain")
+        );
+        assert_eq!(config.cars().get("newcar"), None);
+        assert_eq!(
+            config.cars().get("rt2000"),
+            Some(&"Skip Barber Formula 2000".to_string())
+        );
+    }
+
+    #[test]
+    fn test_notes() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        let from = Path::new("/tmp/setups/baseline.htm");
+        let to = Path::new("/tmp/setups/wet.htm");
+
+        config.set_note(from, "Good in the rain");
+        assert_eq!(config.get_note(from), Some("Good in the rain"));
+
+        config.rename_note(from, to);
+        assert_eq!(config.get_note(from), None);
+        assert_eq!(config.get_note(to), Some("Good in the rain"));
+
+        // Notes survive a round trip through TOML
+        let mut reloaded = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        reloaded.doc = config.doc.to_string().parse().unwrap();
+        reloaded.load_notes().unwrap();
+        assert_eq!(reloaded.get_note(to), Some("Good in the rain"));
+
+        config.set_note(to, "");
+        assert_eq!(config.get_note(to), None);
+        assert!(config.doc["notes"].as_table().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_favorites() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        let from = Path::new("/tmp/setups/baseline.htm");
+        let to = Path::new("/tmp/setups/wet.htm");
+        let other = Path::new("/tmp/setups/dry.htm");
+        assert!(config.doc.get("favorites").is_none());
+
+        config.set_favorite(from, true);
+        config.set_favorite(other, true);
+        assert!(config.is_favorite(from));
+
+        config.rename_favorite(from, to);
+        assert!(!config.is_favorite(from));
+        assert!(config.is_favorite(to));
+
+        // Favorites survive a round trip through TOML, and a reset
+        let mut reloaded = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        reloaded.doc = config.doc.to_string().parse().unwrap();
+        reloaded.load_favorites().unwrap();
+        assert!(reloaded.is_favorite(to));
+        assert!(reloaded.is_favorite(other));
+
+        reloaded.reset(false).unwrap();
+        assert!(reloaded.is_favorite(to));
+
+        config.set_favorite(to, false);
+        config.set_favorite(other, false);
+        assert!(!config.is_favorite(to));
+        assert!(config.doc.get("favorites").is_none());
+    }
+
+    #[test]
+    fn test_column_widths() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        let car_name = "Porsche 911 GT3 R";
+        assert!(!config.lock_column_widths());
+        assert_eq!(config.column_widths(car_name), None);
+
+        config.set_lock_column_widths(true);
+        config.set_column_widths(car_name, &[180.4, 96.6, 3.0]);
+        assert_eq!(
+            config.column_widths(car_name),
+            Some([180.0, 97.0, MIN_COLUMN_WIDTH].as_slice())
+        );
+
+        // Widths survive a round trip through TOML
+        let mut reloaded = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        reloaded.doc = config.doc.to_string().parse().unwrap();
+        reloaded.load_column_widths().unwrap();
+        assert_eq!(
+            reloaded.column_widths(car_name),
+            config.column_widths(car_name)
+        );
+
+        config.reset_column_widths(car_name);
+        assert_eq!(config.column_widths(car_name), None);
+        assert!(config.doc["column_widths"].as_table().unwrap().is_empty());
+
+        // Integers are accepted, and other types are errors
+        reloaded.doc = "[column_widths]\n\"Dallara P217\" = [200, 80.5]\n"
+            .parse()
+            .unwrap();
+        reloaded.load_column_widths().unwrap();
+        assert_eq!(
+            reloaded.column_widths("Dallara P217"),
+            Some([200.0, 80.5].as_slice())
+        );
+        reloaded.doc = "[column_widths]\n\"Dallara P217\" = [\"wide\"]\n"
+            .parse()
+            .unwrap();
+        assert!(reloaded.load_column_widths().is_err());
+    }
+
+    #[test]
+    fn test_setups_scope() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        config.update_setups_path("/tmp/setups");
+        assert_eq!(config.get_setups_scope(), Path::new(""));
+        assert!(config.is_in_setups_scope(Path::new("/tmp/setups/league/baseline.htm")));
+
+        config.update_setups_scope(Path::new("league").join("week1"));
+        assert_eq!(
+            config.doc["config"]["setups_scope"].as_str(),
+            Some("league/week1")
+        );
+        assert_eq!(
+            config.get_scoped_setups_path(),
+            Path::new("/tmp/setups/league/week1")
+        );
+        assert!(config.is_in_setups_scope(Path::new("/tmp/setups/league/week1/baseline.htm")));
+        assert!(!config.is_in_setups_scope(Path::new("/tmp/setups/league/baseline.htm")));
+        assert!(config.is_in_setups_scope(Path::new("/tmp/opened/baseline.htm")));
+
+        assert!(is_relative_subfolder(Path::new("league/week1")));
+        assert!(!is_relative_subfolder(Path::new("../league")));
+        assert!(!is_relative_subfolder(Path::new("/tmp/setups")));
+    }
+
+    #[test]
+    fn test_setups_path_candidates() {
+        let home = Path::new("/home/racer");
+        let candidates = setups_path_candidates(Some(&home.join("Documents")), home);
+        let candidates: Vec<_> = candidates
+            .iter()
+            .map(|path| {
+                path.strip_prefix(home)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+
+        // The documents directory is not repeated
+        assert_eq!(
+            candidates,
+            [
+                "Documents/iRacing",
+                "OneDrive/Documents/iRacing",
+                "iRacing",
+                ".steam/steam/steamapps/compatdata/266410/pfx/drive_c/users/steamuser/Documents/iRacing",
+                ".wine/drive_c/users/racer/Documents/iRacing",
+            ]
+        );
+
+        let candidates = setups_path_candidates(None, home);
+        assert_eq!(candidates[0], home.join("Documents").join("iRacing"));
+    }
+
+    #[test]
+    fn test_excluded_paths() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        config.update_setups_path("/tmp/setups");
+        assert!(config.excluded_paths().is_empty());
+
+        let file = Path::new("/tmp/setups/league/week1/baseline.htm");
+        assert!(config.exclude_path(file));
+        assert!(config.is_excluded(file));
+        assert!(!config.is_excluded(Path::new("/tmp/setups/league/week1/race.htm")));
+
+        // Folders replace the exclusions within them
+        let folder = Path::new("/tmp/setups").join("league").join("week1");
+        assert!(config.exclude_path(&folder));
+        assert_eq!(config.excluded_paths(), [Path::new("league/week1")]);
+        assert!(config.is_excluded(Path::new("/tmp/setups/league/week1/race.htm")));
+        assert!(!config.is_excluded(Path::new("/tmp/setups/league/week2/race.htm")));
+        assert!(!config.is_excluded(Path::new("/tmp/opened/league/week1/race.htm")));
+        assert_eq!(
+            config.doc["config"]["excluded_paths"]
+                .as_array()
+                .and_then(|paths| paths.get(0))
+                .and_then(|path| path.as_str()),
+            Some("league/week1")
+        );
+
+        // Paths outside of the setup exports path cannot be excluded
+        assert!(!config.exclude_path(Path::new("/tmp/opened/baseline.htm")));
+        assert!(!config.exclude_path(Path::new("/tmp/setups")));
+
+        config.include_path(Path::new("league/week1"));
+        assert!(config.excluded_paths().is_empty());
+        assert!(!config.is_excluded(file));
+    }
+
+    #[test]
+    fn test_table_options() {
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        assert_eq!(config.table_options().delimiter, '\t');
+        assert_eq!(config.table_options().quoting, Quoting::Never);
+
+        config.set_table_options(TableOptions {
+            delimiter: ';',
+            quoting: Quoting::Always,
+            group_column: false,
+            include_hidden_groups: false,
+        });
+        let table_options = config.table_options();
+        config.load_table_options().unwrap();
+        assert_eq!(config.table_options(), table_options);
+
+        config.doc["export"]["delimiter"] = toml_edit::value(";;");
+        assert!(matches!(
+            config.load_table_options(),
+            Err(Error::Type { .. })
+        ));
+    }
+
+    #[test]
+    fn test_recent_setups_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(100, 100));
+        assert!(config.recent_setups_paths().is_empty());
+
+        let paths: Vec<_> = (0..=MAX_RECENT_SETUPS_PATHS)
+            .map(|i| {
+                let path = dir.path().join(i.to_string());
+                std::fs::create_dir(&path).unwrap();
+                path.canonicalize().unwrap()
+            })
+            .collect();
+        for path in &paths {
+            config.update_setups_path(path);
+            config.remember_setups_path();
+        }
+
+        // The oldest path is forgotten
+        let expected: Vec<_> = paths.iter().rev().take(MAX_RECENT_SETUPS_PATHS).collect();
+        assert!(config.recent_setups_paths().iter().eq(expected));
+
+        // Paths are deduplicated by their canonical form
+        config.update_setups_path(paths[3].join("..").join("3"));
+        config.remember_setups_path();
+        assert_eq!(config.recent_setups_paths()[0], paths[3]);
+        assert_eq!(config.recent_setups_paths()[1], paths[5]);
+        assert_eq!(config.recent_setups_paths().len(), MAX_RECENT_SETUPS_PATHS);
+
+        // The list round-trips through TOML
+        let recent = config.recent_setups_paths().to_vec();
+        config.load_recent_setups_paths().unwrap();
+        assert_eq!(config.recent_setups_paths(), recent);
+    }
+
+    #[test]
+    fn test_empty_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("portable.toml");
+        let min_size = PhysicalSize::new(100, 100);
+
+        assert!(Config::from_toml(&path, min_size).unwrap().is_none());
+
+        std::fs::write(&path, "\n").unwrap();
+        assert!(Config::from_toml(&path, min_size).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_byte_order_mark() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "\u{feff}[config]\nsetups_path = '/tmp/setups'\n").unwrap();
+
+        let config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.get_setups_path(), Path::new("/tmp/setups"));
+    }
+
+    #[test]
+    fn test_opacity() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\nopacity = 5\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.opacity(), MIN_OPACITY);
+
+        config.set_opacity(80);
+        assert_eq!(config.doc["config"]["opacity"].as_integer(), Some(80));
+    }
+
+    #[test]
+    fn test_grid_zoom() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\ngrid_zoom = 1000\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.grid_zoom(), MAX_GRID_ZOOM);
+
+        config.set_grid_zoom(10);
+        assert_eq!(config.grid_zoom(), MIN_GRID_ZOOM);
+        assert_eq!(
+            config.doc["config"]["grid_zoom"].as_integer(),
+            Some(i64::from(MIN_GRID_ZOOM)),
+        );
+    }
+
+    #[test]
+    fn test_newest_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\nnewest_count = -3\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.newest_count(), MIN_NEWEST_COUNT);
+
+        config.set_newest_count(3);
+        assert_eq!(config.newest_count(), 3);
+        config.set_newest_count(100);
+        assert_eq!(config.newest_count(), MAX_NEWEST_COUNT);
+        assert_eq!(
+            config.doc["config"]["newest_count"].as_integer(),
+            Some(MAX_NEWEST_COUNT as i64),
+        );
+    }
+
+    #[test]
+    fn test_locale() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\nlocale = 'de-DE'\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.get_locale(), Locale::German);
+        assert_eq!(config.grid_options().locale, Locale::German);
+
+        // The system locale is resolved for display
+        config.set_locale(Locale::System);
+        assert_eq!(config.get_locale(), Locale::System);
+        assert_ne!(config.display_locale(), Locale::System);
+        assert_eq!(config.doc["config"]["locale"].as_str(), Some("system"));
+    }
+
+    #[test]
+    fn test_collapsed_groups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[config]\nsetups_path = '/tmp/setups'\n").unwrap();
+
+        // Computed groups are collapsed by default
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert!(config.is_group_collapsed("Aero Calculator"));
+        assert!(config.is_group_collapsed("Aero Balance Calc"));
+        assert!(!config.is_group_collapsed("Left Front"));
+
+        config.set_group_collapsed("Aero Calculator", false);
+        config.set_group_collapsed("Lighting", true);
+        assert!(!config.is_group_collapsed("Aero Calculator"));
+        assert_eq!(
+            config.grid_options().collapsed_groups,
+            ["Aero Balance Calc", "Lighting"],
+        );
+        let array = config.doc["config"]["collapsed_groups"].as_array().unwrap();
+        let groups: Vec<_> = array.iter().filter_map(|value| value.as_str()).collect();
+        assert_eq!(groups, ["Aero Balance Calc", "Lighting"]);
+
+        // An empty list collapses nothing
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\ncollapsed_groups = []\n",
+        )
+        .unwrap();
+        let config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert!(!config.is_group_collapsed("Aero Calculator"));
+    }
+
+    #[test]
+    fn test_swapped_diffs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\nswapped_diffs = ['Temp', '']\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        let options = config.grid_options();
+        assert!(options.is_diff_swapped("Last temps O M I"));
+        assert!(!options.is_diff_swapped("Cold pressure"));
+
+        config.set_diff_swapped("Fuel level", true);
+        config.set_diff_swapped("temp", false);
+        assert_eq!(config.swapped_diffs(), ["", "Fuel level"]);
+        assert!(!config.grid_options().is_diff_swapped("Last temps O M I"));
+        assert!(config.grid_options().is_diff_swapped("Fuel level"));
+        let array = config.doc["config"]["swapped_diffs"].as_array().unwrap();
+        let patterns: Vec<_> = array.iter().filter_map(|value| value.as_str()).collect();
+        assert_eq!(patterns, ["", "Fuel level"]);
+
+        // Swapped diffs must be an array of strings
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\nswapped_diffs = 'temp'\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            Config::from_toml(&path, PhysicalSize::new(100, 100)),
+            Err(Error::Type { .. })
+        ));
+    }
+
+    #[test]
+    fn test_safe_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\n\n[safe_ranges]\n\
+            'Cold pressure' = { min = 18, max = 30.5, unit = 'PSI' }\n\
+            'Camber' = { max = -1.0 }\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        let pressure = &config.safe_ranges()["Cold pressure"];
+        assert_eq!(pressure.to_string(), "18 to 30.5 PSI");
+        assert!(!pressure.is_outside("25.0 psi"));
+        assert!(pressure.is_outside("17.5 psi"));
+        assert!(pressure.is_outside("31.0 psi"));
+        assert!(!pressure.is_outside("250.0 kPa"));
+        assert!(!pressure.is_outside("Soft"));
+
+        let camber = &config.safe_ranges()["Camber"];
+        assert_eq!(camber.to_string(), "at most -1");
+        assert!(!camber.is_outside("-2.4 deg"));
+        assert!(camber.is_outside("+0.5 deg"));
+
+        let range = SafeRange {
+            min: Some(1.5),
+            max: None,
+            unit: String::new(),
+        };
+        config.set_safe_range("Wing angle", Some(range.clone()));
+        config.set_safe_range("Camber", None);
+        assert_eq!(config.safe_ranges().get("Wing angle"), Some(&range));
+        assert!(config.safe_ranges().get("Camber").is_none());
+        let table = config.doc["safe_ranges"].as_table().unwrap();
+        assert!(table.get("Camber").is_none());
+        let wing_angle = table["Wing angle"].as_inline_table().unwrap();
+        assert_eq!(wing_angle.get("min").and_then(|v| v.as_float()), Some(1.5));
+        assert!(wing_angle.get("max").is_none());
+
+        // Limits must be numbers
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\n\n[safe_ranges]\nCamber = { max = 'low' }\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            Config::from_toml(&path, PhysicalSize::new(100, 100)),
+            Err(Error::Type { .. })
+        ));
+    }
+
+    #[test]
+    fn test_formatters() {
+        use crate::formatter::format_value;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\n\n[formatters]\n\
+            'Dallara P217' = [\n\
+                { property = 'LS comp damping', pattern = '^(\\d+) clicks$', replacement = '$1 N' },\n\
+                { property = 'Camber', pattern = '(deg', replacement = '°' },\n\
+                { property = 'Camber', pattern = ' deg$', replacement = '°' },\n\
+            ]\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        let formatters = config.formatters("Dallara P217");
+        assert_eq!(formatters.len(), 2);
+        assert_eq!(
+            format_value(&formatters, "LS comp damping", "12 clicks"),
+            "12 N"
+        );
+        assert_eq!(format_value(&formatters, "Camber", "-2.4 deg"), "-2.4°");
+        assert!(config.formatters("VW Beetle").is_empty());
+
+        // Invalid patterns are skipped with a warning
+        let warnings = config.take_warnings();
+        assert!(matches!(
+            warnings.as_slice(),
+            [Error::Formatter { path, .. }] if path == "formatters.Dallara P217[1].pattern"
+        ));
+
+        // Formatters need a replacement
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\n\n[formatters]\n\
+            'Dallara P217' = [{ property = 'Camber', pattern = 'deg' }]\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            Config::from_toml(&path, PhysicalSize::new(100, 100)),
+            Err(Error::Type { path, .. }) if path == "formatters.Dallara P217[0].replacement"
+        ));
+    }
+
+    #[test]
+    fn test_write_auto_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let toml = "# Hand edited\n[config]\nsetups_path = '/tmp/setups'\n";
+        std::fs::write(&path, toml).unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert!(config.auto_save());
+
+        // Other unsaved settings are not written with the preference
+        config.set_number_key_toggles(false);
+        config.set_auto_save(false);
+        config.write_auto_save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{}auto_save = false\n", toml),
+        );
+        assert!(config.is_dirty());
+
+        let config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert!(!config.auto_save());
+        assert!(config.number_key_toggles());
+
+        // The whole config is written when the file does not exist
+        let path = dir.path().join("new.toml");
+        let mut config = Config::new(&path, PhysicalSize::new(100, 100));
+        config.set_auto_save(false);
+        config.write_auto_save().unwrap();
+        assert!(!config.is_dirty());
+    }
+
+    #[test]
+    fn test_reset_startup_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[config]\nsetups_path = '/tmp/setups'\ngpu_backend = 'vulkan'\n\
+            always_on_top = true\nopacity = 50\n\
+            [window]\nx = 10\ny = 20\nwidth = 800\nheight = 600\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        assert!(config.get_window().is_some());
+
+        config.reset_startup_settings();
+        assert_eq!(config.get_gpu_backend(), GpuBackend::default());
+        assert_eq!(config.get_present_mode(), PresentMode::default());
+        assert!(!config.always_on_top());
+        assert_eq!(config.opacity(), 100);
+        assert!(config.get_window().is_none());
+
+        // Other settings are kept
+        assert_eq!(config.get_setups_path(), Path::new("/tmp/setups"));
+    }
+
+    #[test]
+    fn test_empty_setups_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[config]\nsetups_path = ''\n").unwrap();
+
+        let mut config = Config::from_toml(&path, PhysicalSize::new(100, 100))
+            .unwrap()
+            .unwrap();
+        let expected = Config::new(&path, PhysicalSize::new(100, 100));
+        assert_eq!(config.get_setups_path(), expected.get_setups_path());
+        assert!(!config.get_setups_path().as_os_str().is_empty());
+
+        let warnings = config.take_warnings();
+        assert!(matches!(warnings.as_slice(), [Error::SetupsPath(path)] if path.is_empty()));
+    }
+
+    #[test]
+    fn test_diff_reference() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let min_size = PhysicalSize::new(100, 100);
+
+        std::fs::write(&path, "[config]\ndiff_reference = 'previous'\n").unwrap();
+        let config = Config::from_toml(&path, min_size).unwrap().unwrap();
+        assert_eq!(
+            config.grid_options().diff_reference,
+            DiffReference::Previous
+        );
+
+        // Unknown values fall back to the first column
+        std::fs::write(&path, "[config]\ndiff_reference = 'sideways'\n").unwrap();
+        let config = Config::from_toml(&path, min_size).unwrap().unwrap();
+        assert_eq!(config.diff_reference(), DiffReference::First);
+    }
+
+    #[test]
+    fn test_diff_blend() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let min_size = PhysicalSize::new(100, 100);
+
+        std::fs::write(&path, "[config]\ndiff_blend = 'overlay'\n").unwrap();
+        let mut config = Config::from_toml(&path, min_size).unwrap().unwrap();
+        assert_eq!(config.grid_options().diff_blend, DiffBlend::Overlay);
+
+        config.update_diff_blend(DiffBlend::Solid);
+        assert_eq!(config.doc["config"]["diff_blend"].as_str(), Some("solid"));
+
+        // Unknown values fall back to multiplying
+        std::fs::write(&path, "[config]\ndiff_blend = 'screen'\n").unwrap();
+        let config = Config::from_toml(&path, min_size).unwrap().unwrap();
+        assert_eq!(config.diff_blend(), DiffBlend::Multiply);
+    }
+
+    #[test]
+    fn test_is_dirty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let min_size = PhysicalSize::new(100, 100);
+
+        // A new config has never been written
+        let mut config = Config::new(&path, min_size);
+        assert!(config.is_dirty());
+
+        config.write_toml().unwrap();
+        assert!(!config.is_dirty());
+
+        // A loaded config is clean until it is changed
+        let mut config = Config::from_toml(&path, min_size).unwrap().unwrap();
+        assert!(!config.is_dirty());
+
+        config.set_note(Path::new("setup.htm"), "Good in the rain");
+        assert!(config.is_dirty());
+
+        config.write_toml().unwrap();
+        assert!(!config.is_dirty());
+    }
 }
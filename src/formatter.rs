@@ -0,0 +1,134 @@
+//! User-defined formatters for property values.
+//!
+//! Formatters are rules in the config file that rewrite values before they are shown in the grid,
+//! e.g. converting damper clicks to a force for a specific car. Each rule replaces the matches of a
+//! regular expression. Values are still compared as they appear in the export.
+
+use crate::str_ext::WildcardMatch;
+use regex::{Regex, RegexBuilder};
+use std::borrow::Cow;
+
+/// Compiled patterns are limited to this many bytes, so a pattern from the config file cannot use
+/// unbounded memory. Matching time is always linear in the length of the value.
+const SIZE_LIMIT: usize = 1 << 20;
+
+/// A rule for rewriting the values of a property.
+#[derive(Clone, Debug)]
+pub(crate) struct Formatter {
+    /// Property names that this rule applies to. Matching is case insensitive, and `*` matches any
+    /// text.
+    property: String,
+
+    /// Pattern matched against each value.
+    pattern: Regex,
+
+    /// Replacement for each match. `$1` inserts the first capture group, and `$name` inserts a
+    /// named group.
+    replacement: String,
+}
+
+impl PartialEq for Formatter {
+    fn eq(&self, other: &Self) -> bool {
+        self.property == other.property
+            && self.pattern.as_str() == other.pattern.as_str()
+            && self.replacement == other.replacement
+    }
+}
+
+impl Formatter {
+    /// Create a formatter for values of the `property`.
+    ///
+    /// Returns an error when the `pattern` is not a valid regular expression.
+    pub(crate) fn new(
+        property: &str,
+        pattern: &str,
+        replacement: &str,
+    ) -> Result<Self, regex::Error> {
+        let pattern = RegexBuilder::new(pattern).size_limit(SIZE_LIMIT).build()?;
+
+        Ok(Self {
+            property: property.to_string(),
+            pattern,
+            replacement: replacement.to_string(),
+        })
+    }
+
+    /// Check if this formatter applies to values of the property.
+    fn applies_to(&self, prop_name: &str) -> bool {
+        prop_name.wildcard_match(&self.property)
+    }
+}
+
+/// Format a property value with every formatter that applies to the property, in order.
+///
+/// Each formatter sees the value as rewritten by the formatters before it.
+pub(crate) fn format_value<'a>(
+    formatters: &[Formatter],
+    prop_name: &str,
+    value: &'a str,
+) -> Cow<'a, str> {
+    let mut value = Cow::Borrowed(value);
+
+    for formatter in formatters.iter().filter(|f| f.applies_to(prop_name)) {
+        let replaced = match formatter
+            .pattern
+            .replace_all(&value, formatter.replacement.as_str())
+        {
+            Cow::Owned(replaced) => Some(replaced),
+            Cow::Borrowed(_) => None,
+        };
+        if let Some(replaced) = replaced {
+            value = Cow::Owned(replaced);
+        }
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_value() {
+        let formatters = [
+            Formatter::new("LS comp damping", r"^(\d+) clicks$", "${1}0 N/(m/s)").unwrap(),
+            Formatter::new("*pressure", r"(\d+\.\d) (?P<unit>psi)", "$1 PSI").unwrap(),
+            Formatter::new("*pressure", r"PSI$", "lbf/in²").unwrap(),
+        ];
+
+        assert_eq!(
+            format_value(&formatters, "LS comp damping", "12 clicks"),
+            "120 N/(m/s)"
+        );
+        assert_eq!(
+            format_value(&formatters, "Cold pressure", "25.5 psi"),
+            "25.5 lbf/in²"
+        );
+        assert_eq!(
+            format_value(&formatters, "cold PRESSURE", "25.5 psi"),
+            "25.5 lbf/in²"
+        );
+
+        // Values that don't match, and other properties, are kept
+        assert!(matches!(
+            format_value(&formatters, "LS comp damping", "-12 clicks"),
+            Cow::Borrowed("-12 clicks")
+        ));
+        assert!(matches!(
+            format_value(&formatters, "HS comp damping", "12 clicks"),
+            Cow::Borrowed("12 clicks")
+        ));
+        assert_eq!(format_value(&[], "Cold pressure", "25.5 psi"), "25.5 psi");
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(Formatter::new("Camber", r"(\d+", "$1").is_err());
+        assert!(Formatter::new("Camber", r"\d{1000}{1000}", "").is_err());
+
+        let a = Formatter::new("Camber", r"\d+", "$0").unwrap();
+        assert_eq!(a, a.clone());
+        assert_ne!(a, Formatter::new("Camber", r"\d*", "$0").unwrap());
+    }
+}
@@ -1,20 +1,45 @@
 //! Platform-neutral framework for processing events and handling app configuration.
 
-use crate::config::{Config, Error as ConfigError, UserTheme};
+use crate::config::{Config, Error as ConfigError, ResetOptions, UserTheme};
 use crate::gpu::Gpu;
 use crate::gui::{ErrorButton, Gui, ShowError, ShowWarning};
+use crate::setup::{Error as SetupError, Setups};
+use crate::snapshot;
+#[cfg(feature = "update-check")]
 use crate::updates::{UpdateChecker, UpdateNotification};
 use directories::ProjectDirs;
 use egui::{ClippedMesh, CtxRef};
 use egui_wgpu_backend::{BackendError, RenderPass, ScreenDescriptor};
 use font_loader::system_fonts::{self, FontPropertyBuilder};
+use log::warn;
 use std::collections::VecDeque;
+use std::fs;
 use std::path::PathBuf;
 use thiserror::Error;
 use winit::dpi::PhysicalSize;
-use winit::event_loop::EventLoopProxy;
+use winit::event_loop::{ControlFlow, EventLoopProxy};
 use winit::window::{Theme, Window};
 
+/// Command line argument that enables portable mode.
+pub(crate) const PORTABLE_FLAG: &str = "--portable";
+
+/// Command line argument that enables safe mode.
+///
+/// Safe mode ignores the settings that may prevent the app from starting: the GPU preferences, the
+/// window geometry, position, and opacity. egui's built-in fonts are used instead of system fonts.
+pub(crate) const SAFE_MODE_FLAG: &str = "--safe-mode";
+
+/// Command line argument that enables presentation mode for this session.
+///
+/// Presentation mode hides settings, file operations, and editing controls.
+pub(crate) const PRESENTATION_FLAG: &str = "--presentation";
+
+/// File name of the marker that exists while CarTunes is running. It is next to the config file.
+const RUNNING_MARKER: &str = "running";
+
+/// Config file name in portable mode. Its presence next to the executable enables portable mode.
+const PORTABLE_CONFIG: &str = "portable.toml";
+
 /// Manages all state required for rendering egui.
 pub(crate) struct Framework {
     // State for egui.
@@ -25,8 +50,8 @@ pub(crate) struct Framework {
     paint_jobs: Vec<ClippedMesh>,
     theme: Option<Theme>,
     gui: Gui,
+    #[cfg(feature = "update-check")]
     update_checker: Option<UpdateChecker>,
-    event_loop_proxy: EventLoopProxy<UserEvent>,
 }
 
 /// Framework errors.
@@ -48,16 +73,55 @@ pub(crate) enum UserEvent {
     /// Change the path for setup export files.
     SetupPath(Option<PathBuf>),
 
+    /// Restrict the library to a subfolder of the setup exports path.
+    SetupScope(Option<PathBuf>),
+
+    /// Export the selected setups to a bundle at the given path.
+    ExportBundle(Option<PathBuf>),
+
+    /// Import a bundle from the given path.
+    ImportBundle(Option<PathBuf>),
+
+    /// Export the whole setups library as JSON to the given path.
+    ExportLibrary(Option<PathBuf>),
+
+    /// Export a comparison table of the selected setups to the given path.
+    ExportTable(Option<PathBuf>),
+
+    /// Export the comparison grid as it is shown to a CSV file at the given path.
+    ExportComparison(Option<PathBuf>),
+
+    /// Export an image of the comparison grid to the given path.
+    ExportImage(Option<PathBuf>),
+
+    /// Export a checklist of the changes between the two selected setups to the given path.
+    ExportChecklist(Option<PathBuf>),
+
+    /// Reset all settings to defaults.
+    ResetConfig(ResetOptions),
+
     /// File system event for the setup export path.
     FsChange(hotwatch::Event),
 
+    /// Number of files processed by a setup loader, identified by its generation, and the total
+    /// number of files.
+    SetupsProgress(u64, usize, usize),
+
+    /// A setup loader has finished, with any errors that it encountered.
+    SetupsLoaded(u64, Setups, Vec<(SetupError, String)>),
+
+    /// Setup exports have been counted in the folders offered as the setup exports path.
+    SetupsCounted(Vec<(PathBuf, usize)>),
+
     /// Change the theme preference.
     Theme(UserTheme),
 
     /// Change the update check preference.
+    #[cfg(feature = "update-check")]
     UpdateCheck,
 
     /// Show update message.
+    #[cfg(feature = "update-check")]
     UpdateAvailable(UpdateNotification),
 }
 
@@ -72,6 +136,33 @@ pub(crate) enum ConfigHandler {
     Keep,
 }
 
+/// How the previous run of CarTunes ended, according to its running marker.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum PreviousRun {
+    /// The previous run exited cleanly.
+    Exited,
+
+    /// The previous run did not exit cleanly.
+    Crashed,
+
+    /// The previous run is still running, e.g. CarTunes was started twice.
+    Running,
+
+    /// The previous run did not exit cleanly, or is still running. The process could not be
+    /// checked to tell them apart.
+    Unknown,
+}
+
+/// What to do when the user asks to close the window.
+#[derive(Debug, Eq, PartialEq)]
+enum CloseAction {
+    /// Exit without writing the config file.
+    Exit,
+
+    /// Write the config file, and exit if writing succeeds.
+    Save,
+}
+
 impl Framework {
     /// Create a framework for egui.
     pub(crate) fn new(
@@ -80,11 +171,10 @@ impl Framework {
         theme: Theme,
         gui: Gui,
         gpu: &Gpu,
-        event_loop_proxy: EventLoopProxy<UserEvent>,
     ) -> Self {
         let width = size.width;
         let height = size.height;
-        let font_definitions = create_fonts();
+        let font_definitions = create_fonts(gui.is_safe_mode());
         let style = create_style(theme);
 
         let egui_state = egui_winit::State::from_pixels_per_point(scale_factor);
@@ -99,6 +189,7 @@ impl Framework {
         egui_ctx.set_fonts(font_definitions);
         egui_ctx.set_style(style);
 
+        #[cfg_attr(not(feature = "update-check"), allow(unused_mut))]
         let mut result = Self {
             egui_ctx,
             egui_state,
@@ -107,9 +198,10 @@ impl Framework {
             paint_jobs: Vec::new(),
             theme: None,
             gui,
+            #[cfg(feature = "update-check")]
             update_checker: None,
-            event_loop_proxy,
         };
+        #[cfg(feature = "update-check")]
         result.create_update_checker();
 
         result
@@ -125,6 +217,44 @@ impl Framework {
         self.gui.handle_fs_change(event);
     }
 
+    /// Toggle a setup in the comparison with the keyboard.
+    ///
+    /// Key presses are ignored while a text field has keyboard focus.
+    pub(crate) fn toggle_setup(&mut self, index: usize) {
+        if !self.egui_ctx.wants_keyboard_input() {
+            self.gui.toggle_setup(index);
+        }
+    }
+
+    /// Compare the newest setups of the selected car with the keyboard.
+    ///
+    /// Key presses are ignored while a text field has keyboard focus.
+    pub(crate) fn compare_newest(&mut self) {
+        if !self.egui_ctx.wants_keyboard_input() {
+            self.gui.compare_newest();
+        }
+    }
+
+    /// Update the progress of the setup loader.
+    pub(crate) fn setups_progress(&mut self, generation: u64, count: usize, total: usize) {
+        self.gui.setups_progress(generation, count, total);
+    }
+
+    /// Replace the setups tree with the results from the setup loader.
+    pub(crate) fn setups_loaded(
+        &mut self,
+        generation: u64,
+        setups: Setups,
+        errors: Vec<(SetupError, String)>,
+    ) {
+        self.gui.setups_loaded(generation, setups, errors);
+    }
+
+    /// Show the number of setup exports in each folder offered as the setup exports path.
+    pub(crate) fn setups_counted(&mut self, counts: Vec<(PathBuf, usize)>) {
+        self.gui.setups_counted(counts);
+    }
+
     /// Resize egui.
     pub(crate) fn resize(&mut self, size: PhysicalSize<u32>) {
         self.screen_descriptor.physical_width = size.width;
@@ -137,7 +267,10 @@ impl Framework {
     }
 
     /// Prepare egui.
-    pub(crate) fn prepare(&mut self, window: &Window) {
+    ///
+    /// Returns the control flow that the event loop should use while waiting for the next frame. A
+    /// redraw is requested immediately when egui needs to be repainted, e.g. for animations.
+    pub(crate) fn prepare(&mut self, window: &Window) -> ControlFlow {
         update_theme(&mut self.theme, &self.egui_ctx);
 
         // Run the egui frame and create all paint jobs to prepare for rendering.
@@ -147,9 +280,17 @@ impl Framework {
             self.gui.ui(egui_ctx, window);
         });
 
+        if output.needs_repaint {
+            window.request_redraw();
+        }
+
         self.egui_state
             .handle_output(window, &self.egui_ctx, output);
         self.paint_jobs = self.egui_ctx.tessellate(paint_commands);
+
+        self.gui
+            .repaint_deadline()
+            .map_or(ControlFlow::Wait, ControlFlow::WaitUntil)
     }
 
     /// Render egui.
@@ -180,6 +321,13 @@ impl Framework {
         )
     }
 
+    /// Recreate the render pass after the GPU device has been recreated.
+    ///
+    /// All textures will be uploaded again on the next render.
+    pub(crate) fn recreate_render_pass(&mut self, gpu: &Gpu) {
+        self.rpass = RenderPass::new(&gpu.device, wgpu::TextureFormat::Bgra8UnormSrgb, 1);
+    }
+
     /// Call this when the system theme changes.
     ///
     /// `force` will ignore the user's configuration preference.
@@ -249,9 +397,10 @@ impl Framework {
     /// Create an update checker.
     ///
     /// Adds a warning window if the checker cannot be created.
+    #[cfg(feature = "update-check")]
     fn create_update_checker(&mut self) {
         self.update_checker = match UpdateChecker::new(
-            self.event_loop_proxy.clone(),
+            self.gui.event_loop_proxy(),
             self.gui.config.get_update_check(),
         ) {
             Ok(update_checker) => update_checker,
@@ -265,6 +414,7 @@ impl Framework {
     }
 
     /// Set the update check frequency.
+    #[cfg(feature = "update-check")]
     pub(crate) fn recreate_update_check(&mut self) {
         // Stop the old update checker
         if let Some(update_check) = self.update_checker.take() {
@@ -278,12 +428,43 @@ impl Framework {
         self.create_update_checker();
     }
 
-    /// Try to save the configuration with the current window geometry.
+    /// Stop background work before the app exits.
+    ///
+    /// Returns promptly, even when an update check is waiting on the network.
+    pub(crate) fn shutdown(&mut self) {
+        #[cfg(feature = "update-check")]
+        if let Some(update_check) = self.update_checker.take() {
+            if let Err(err) = update_check.stop(true) {
+                warn!("Error while stopping update checker: {}", err);
+            }
+        }
+    }
+
+    /// Handle the user's request to close the window.
+    ///
+    /// Returns true when the app can exit now. Otherwise the user has been asked to confirm, and
+    /// [`UserEvent::Exit`] will be sent if they choose to exit anyway.
+    pub(crate) fn close_requested(
+        &mut self,
+        window: &winit::window::Window,
+        keep_config: &ConfigHandler,
+    ) -> bool {
+        let auto_save = self.gui.config.auto_save();
+        if auto_save {
+            self.gui.config.update_window(window);
+        }
+
+        match close_action(keep_config, auto_save, self.gui.has_unsaved_changes()) {
+            CloseAction::Exit => true,
+            CloseAction::Save => self.save_config(),
+        }
+    }
+
+    /// Try to save the configuration.
     ///
     /// Returns true on success. When saving fails, the error is shown to the user and `false` is
     /// returned.
-    pub(crate) fn save_config(&mut self, window: &winit::window::Window) -> bool {
-        self.gui.config.update_window(window);
+    fn save_config(&mut self) -> bool {
         match self.gui.config.write_toml() {
             Ok(()) => true,
             Err(err) => {
@@ -315,6 +496,68 @@ impl Framework {
         self.gui.update_setups_path(setups_path);
     }
 
+    /// Restrict the library to a subfolder of the setup exports path.
+    pub(crate) fn update_setups_scope(&mut self, path: PathBuf) {
+        self.gui.update_setups_scope(path);
+    }
+
+    /// Reset all settings to defaults.
+    pub(crate) fn reset_config(&mut self, options: ResetOptions) {
+        self.gui.reset_config(options);
+    }
+
+    /// Export the selected setups to a bundle.
+    pub(crate) fn export_bundle(&mut self, path: PathBuf) {
+        self.gui.export_bundle(path);
+    }
+
+    /// Export the whole setups library as JSON.
+    pub(crate) fn export_library(&mut self, path: PathBuf) {
+        self.gui.export_library(path);
+    }
+
+    /// Import a bundle and select its setups.
+    pub(crate) fn import_bundle(&mut self, path: PathBuf) {
+        self.gui.import_bundle(path);
+    }
+
+    /// Export a comparison table of the selected setups.
+    pub(crate) fn export_table(&mut self, path: PathBuf) {
+        self.gui.export_table(path);
+    }
+
+    /// Export the comparison grid as it is shown to a CSV file.
+    pub(crate) fn export_comparison(&mut self, path: PathBuf) {
+        self.gui.export_comparison(path);
+    }
+
+    /// Export a checklist of the changes between the two selected setups.
+    pub(crate) fn export_checklist(&mut self, path: PathBuf) {
+        self.gui.export_checklist(path);
+    }
+
+    /// Export an image of the comparison grid.
+    ///
+    /// The grid is rendered offscreen with the current theme and scale factor.
+    pub(crate) fn export_image(&mut self, path: PathBuf, gpu: &Gpu) {
+        let gui = &self.gui;
+        let result = snapshot::render(
+            gpu,
+            self.egui_ctx.style(),
+            create_fonts(self.gui.is_safe_mode()),
+            self.screen_descriptor.scale_factor,
+            |ui| gui.snapshot(ui),
+        )
+        .and_then(|image| image.save(&path));
+
+        if let Err(err) = result {
+            self.gui.add_warning(ShowWarning::new(
+                err,
+                format!("Unable to export image `{}`.", path.to_string_lossy()),
+            ));
+        }
+    }
+
     /// Add an error message window to the GUI.
     ///
     /// The [`ShowError`] type allows asynchronous user feedback for error handling.
@@ -323,11 +566,29 @@ impl Framework {
     }
 
     /// Add an update notification to the GUI.
+    #[cfg(feature = "update-check")]
     pub(crate) fn add_update_notification(&mut self, notification: UpdateNotification) {
         self.gui.add_update_notification(notification);
     }
 }
 
+/// Decide what to do when the user asks to close the window.
+///
+/// The config file is never written when the user chose to keep a config file that could not be
+/// read, or opted out of saving settings automatically. It is not rewritten when nothing has
+/// changed.
+fn close_action(
+    keep_config: &ConfigHandler,
+    auto_save: bool,
+    unsaved_changes: bool,
+) -> CloseAction {
+    match keep_config {
+        ConfigHandler::Keep => CloseAction::Exit,
+        ConfigHandler::Replace if auto_save && unsaved_changes => CloseAction::Save,
+        ConfigHandler::Replace => CloseAction::Exit,
+    }
+}
+
 /// Configure the theme based on system settings.
 fn update_theme(theme: &mut Option<Theme>, ctx: &egui::CtxRef) {
     if let Some(theme) = theme.take() {
@@ -336,13 +597,27 @@ fn update_theme(theme: &mut Option<Theme>, ctx: &egui::CtxRef) {
     }
 }
 
+/// Get the application cache path.
+///
+/// In portable mode, this is the `cache` directory next to the executable.
+#[cfg(feature = "update-check")]
 pub(crate) fn cache_path() -> PathBuf {
+    if let Some(dir) = portable_dir() {
+        return dir.join("cache");
+    }
+
     ProjectDirs::from("org", "KodeWerx", "CarTunes")
         .map_or_else(|| PathBuf::from("."), |dir| dir.cache_dir().to_path_buf())
 }
 
 /// Get the application configuration path.
+///
+/// In portable mode, this is the [`PORTABLE_CONFIG`] file next to the executable.
 fn config_path() -> PathBuf {
+    if let Some(dir) = portable_dir() {
+        return dir.join(PORTABLE_CONFIG);
+    }
+
     // If a project directory cannot be found, use the current working directory.
     let mut config_path = ProjectDirs::from("org", "KodeWerx", "CarTunes")
         .map_or_else(|| PathBuf::from("."), |dir| dir.config_dir().to_path_buf());
@@ -351,38 +626,169 @@ fn config_path() -> PathBuf {
     config_path
 }
 
-/// Create fonts for egui from the embedded TTFs.
-fn create_fonts() -> egui::FontDefinitions {
-    let mut fonts = egui::FontDefinitions::default();
+/// Create the marker file that exists while CarTunes is running.
+///
+/// The marker holds the process ID. When the marker already exists, the previous run either did
+/// not exit cleanly or is still running in another window. A marker that belongs to a running
+/// window is kept, so that window can still remove it when it exits. Failing to create the marker
+/// only disables crash detection.
+pub(crate) fn create_running_marker() -> PreviousRun {
+    let path = config_path().with_file_name(RUNNING_MARKER);
+    let previous_run = match fs::read_to_string(&path) {
+        Ok(marker) => previous_run(&marker, is_process_running),
+        Err(_) => PreviousRun::Exited,
+    };
+    if previous_run == PreviousRun::Running {
+        return previous_run;
+    }
 
-    // Add font data
-    let props = FontPropertyBuilder::new().monospace().build();
-    let font = system_fonts::get(&props)
-        .expect("Unable to find a monospace font")
-        .0;
-    fonts
-        .font_data
-        .insert("MonoSpace".to_owned(), egui::FontData::from_owned(font));
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, std::process::id().to_string()));
+    if let Err(err) = result {
+        warn!("Unable to create the running marker: {}", err);
+    }
 
-    let props = FontPropertyBuilder::new().family("sans-serif").build();
-    let font = system_fonts::get(&props)
-        .or_else(|| {
-            let props = FontPropertyBuilder::new().family("Helvetica Neue").build();
-            system_fonts::get(&props)
-        })
-        .expect("Unable to find a sans-serif font")
-        .0;
-    fonts
-        .font_data
-        .insert("SansSerif".to_owned(), egui::FontData::from_owned(font));
+    previous_run
+}
 
-    // Set font families
-    fonts
-        .fonts_for_family
-        .insert(egui::FontFamily::Monospace, vec!["MonoSpace".to_owned()]);
-    fonts
-        .fonts_for_family
-        .insert(egui::FontFamily::Proportional, vec!["SansSerif".to_owned()]);
+/// Decide how the previous run ended from the process ID in its running marker.
+///
+/// The outcome is unknown when the process cannot be checked, e.g. markers written by older
+/// versions are empty.
+fn previous_run(marker: &str, is_running: impl Fn(u32) -> Option<bool>) -> PreviousRun {
+    match marker.trim().parse().ok().and_then(is_running) {
+        Some(true) => PreviousRun::Running,
+        Some(false) => PreviousRun::Crashed,
+        None => PreviousRun::Unknown,
+    }
+}
+
+/// Check if a process is running. Returns `None` when the process cannot be checked.
+///
+/// A process that exists but cannot be opened, e.g. one owned by another user, is not reported
+/// either way.
+#[cfg(target_os = "windows")]
+fn is_process_running(pid: u32) -> Option<bool> {
+    use winapi::shared::minwindef::{DWORD, FALSE};
+    use winapi::shared::winerror::ERROR_ACCESS_DENIED;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::minwinbase::STILL_ACTIVE;
+    use winapi::um::processthreadsapi::{GetExitCodeProcess, OpenProcess};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+        if handle.is_null() {
+            return match GetLastError() {
+                ERROR_ACCESS_DENIED => None,
+                _ => Some(false),
+            };
+        }
+
+        let mut exit_code: DWORD = 0;
+        let result = GetExitCodeProcess(handle, &mut exit_code);
+        CloseHandle(handle);
+
+        (result != 0).then(|| exit_code == STILL_ACTIVE)
+    }
+}
+
+/// Check if a process is running. Returns `None` when the process cannot be checked.
+///
+/// Signal 0 only checks that the process exists. A process that cannot be signaled, e.g. one
+/// owned by another user, is not reported either way.
+#[cfg(unix)]
+fn is_process_running(pid: u32) -> Option<bool> {
+    let pid = libc::pid_t::try_from(pid).ok()?;
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(true);
+    }
+
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::ESRCH) => Some(false),
+        _ => None,
+    }
+}
+
+/// Check if a process is running. Returns `None` when the process cannot be checked.
+#[cfg(not(any(target_os = "windows", unix)))]
+fn is_process_running(_pid: u32) -> Option<bool> {
+    None
+}
+
+/// Remove the marker file when CarTunes exits cleanly.
+///
+/// Only the window that created the marker removes it, so other windows keep it while they run.
+pub(crate) fn remove_running_marker() {
+    let path = config_path().with_file_name(RUNNING_MARKER);
+    let marker = fs::read_to_string(&path).unwrap_or_default();
+    if is_own_marker(&marker, std::process::id()) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Check if a running marker holds the process ID `pid`.
+fn is_own_marker(marker: &str, pid: u32) -> bool {
+    marker.trim().parse() == Ok(pid)
+}
+
+/// Get a URL for reporting a crash on the issue tracker.
+///
+/// The issue is pre-filled with the CarTunes version and the OS and CPU architecture. Nothing else
+/// about the user or their system is included, and nothing is sent until the user submits it.
+pub(crate) fn crash_report_url() -> String {
+    let body = format!(
+        "CarTunes version: {}\nOS: {} ({})\n\nWhat were you doing when CarTunes crashed?\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    );
+    let base = concat!(env!("CARGO_PKG_REPOSITORY"), "/issues/new");
+
+    url::Url::parse_with_params(base, &[("title", "Crash report"), ("body", &body)])
+        .map_or_else(|_| base.to_string(), |url| url.to_string())
+}
+
+/// Check if safe mode is enabled by the [`SAFE_MODE_FLAG`] command line argument.
+pub(crate) fn is_safe_mode() -> bool {
+    std::env::args_os().skip(1).any(|arg| arg == SAFE_MODE_FLAG)
+}
+
+/// Check if presentation mode is enabled by the [`PRESENTATION_FLAG`] command line argument.
+pub(crate) fn is_presentation_mode() -> bool {
+    std::env::args_os()
+        .skip(1)
+        .any(|arg| arg == PRESENTATION_FLAG)
+}
+
+/// Get the executable directory when running in portable mode.
+///
+/// Portable mode is enabled by the [`PORTABLE_FLAG`] command line argument, or when the
+/// [`PORTABLE_CONFIG`] file exists next to the executable. All state is then kept next to the
+/// executable instead of the OS config and cache directories. When that directory is read-only,
+/// the usual errors for writing the config file and update cache are shown.
+fn portable_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    let flag = std::env::args_os().skip(1).any(|arg| arg == PORTABLE_FLAG);
+
+    (flag || dir.join(PORTABLE_CONFIG).is_file()).then(|| dir.to_path_buf())
+}
+
+/// Create fonts for egui from the system fonts.
+///
+/// egui's built-in fonts are used for any font family that cannot be found on the system, so the
+/// app remains usable on systems with few fonts installed (e.g. minimal Linux containers). System
+/// fonts are not loaded at all when `builtin` is true.
+fn create_fonts(builtin: bool) -> egui::FontDefinitions {
+    let mut fonts = egui::FontDefinitions::default();
+
+    if !builtin {
+        add_system_fonts(&mut fonts);
+    }
 
     if let Some(mut monospace) = fonts.family_and_size.get_mut(&egui::TextStyle::Monospace) {
         // The default monospace size is too small.
@@ -397,6 +803,39 @@ fn create_fonts() -> egui::FontDefinitions {
     fonts
 }
 
+/// Add the system monospace and sans-serif fonts to the font definitions.
+fn add_system_fonts(fonts: &mut egui::FontDefinitions) {
+    let props = FontPropertyBuilder::new().monospace().build();
+    match system_fonts::get(&props) {
+        Some((font, _)) => {
+            fonts
+                .font_data
+                .insert("MonoSpace".to_owned(), egui::FontData::from_owned(font));
+            fonts
+                .fonts_for_family
+                .insert(egui::FontFamily::Monospace, vec!["MonoSpace".to_owned()]);
+        }
+        None => warn!("Unable to find a monospace font, using the built-in font"),
+    }
+
+    let props = FontPropertyBuilder::new().family("sans-serif").build();
+    let font = system_fonts::get(&props).or_else(|| {
+        let props = FontPropertyBuilder::new().family("Helvetica Neue").build();
+        system_fonts::get(&props)
+    });
+    match font {
+        Some((font, _)) => {
+            fonts
+                .font_data
+                .insert("SansSerif".to_owned(), egui::FontData::from_owned(font));
+            fonts
+                .fonts_for_family
+                .insert(egui::FontFamily::Proportional, vec!["SansSerif".to_owned()]);
+        }
+        None => warn!("Unable to find a sans-serif font, using the built-in font"),
+    }
+}
+
 /// Create the default style for egui based on system settings.
 fn create_style(theme: Theme) -> egui::Style {
     let mut visuals = match theme {
@@ -420,3 +859,81 @@ fn create_style(theme: Theme) -> egui::Style {
         ..egui::Style::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_previous_run() {
+        assert_eq!(previous_run("", |_| Some(true)), PreviousRun::Unknown);
+        assert_eq!(previous_run("1234\n", |_| Some(true)), PreviousRun::Running);
+        assert_eq!(previous_run("1234", |_| Some(false)), PreviousRun::Crashed);
+        assert_eq!(previous_run("1234", |_| None), PreviousRun::Unknown);
+    }
+
+    #[test]
+    fn test_is_own_marker() {
+        assert!(is_own_marker("1234", 1234));
+        assert!(is_own_marker("1234\n", 1234));
+        assert!(!is_own_marker("4321", 1234));
+        assert!(!is_own_marker("", 1234));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_is_process_running() {
+        assert_eq!(is_process_running(std::process::id()), Some(true));
+
+        // A child process that has exited and been reaped no longer exists
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert_eq!(is_process_running(pid), Some(false));
+    }
+
+    #[test]
+    fn test_close_action() {
+        assert_eq!(
+            close_action(&ConfigHandler::Replace, true, true),
+            CloseAction::Save
+        );
+        assert_eq!(
+            close_action(&ConfigHandler::Replace, true, false),
+            CloseAction::Exit
+        );
+
+        // Keeping an unreadable config file must never overwrite it
+        assert_eq!(
+            close_action(&ConfigHandler::Keep, true, true),
+            CloseAction::Exit
+        );
+        assert_eq!(
+            close_action(&ConfigHandler::Keep, true, false),
+            CloseAction::Exit
+        );
+
+        // Settings are not saved on close when the user opted out
+        assert_eq!(
+            close_action(&ConfigHandler::Replace, false, true),
+            CloseAction::Exit
+        );
+    }
+
+    #[test]
+    fn test_crash_report_url() {
+        let url = url::Url::parse(&crash_report_url()).unwrap();
+        assert_eq!(url.path(), "/parasyte/cartunes/issues/new");
+
+        let body = url
+            .query_pairs()
+            .find_map(|(key, value)| (key == "body").then(|| value.to_string()))
+            .unwrap();
+        assert!(body.starts_with(concat!(
+            "CarTunes version: ",
+            env!("CARGO_PKG_VERSION"),
+            "\n",
+        )));
+        assert!(body.contains(std::env::consts::OS));
+    }
+}
@@ -1,5 +1,6 @@
 //! Platform-neutral GPU state management and rendering.
 
+use log::{error, info, warn};
 use raw_window_handle::HasRawWindowHandle;
 use thiserror::Error;
 
@@ -16,42 +17,215 @@ pub(crate) enum Error {
     Surface(wgpu::SurfaceError),
 }
 
+/// User's graphics backend choice.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum GpuBackend {
+    /// Let `wgpu` pick from the primary backends. (default)
+    Auto,
+
+    /// Vulkan.
+    Vulkan,
+
+    /// Metal.
+    Metal,
+
+    /// DirectX 12.
+    Dx12,
+
+    /// DirectX 11.
+    Dx11,
+
+    /// OpenGL.
+    Gl,
+}
+
+/// User's surface presentation mode choice.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum PresentMode {
+    /// Wait for vertical blanking. (default)
+    Vsync,
+
+    /// Low-latency without tearing.
+    Mailbox,
+
+    /// Uncapped; tearing may be visible.
+    Immediate,
+}
+
+/// Describes which GPU resources were recreated by [`Gpu::recover`].
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum Recovery {
+    /// Only the surface was recreated.
+    Surface,
+
+    /// The device and surface were both recreated. All GPU resources must be recreated.
+    Device,
+}
+
+/// Details about the GPU adapter in use, shown in the About window for bug reports.
+#[derive(Clone, Debug)]
+pub(crate) struct GpuInfo {
+    /// Adapter name, e.g. `NVIDIA GeForce RTX 3060`.
+    name: String,
+
+    /// Graphics backend that the adapter is used with.
+    backend: wgpu::Backend,
+
+    /// Kind of adapter, e.g. an integrated GPU or a software renderer.
+    device_type: wgpu::DeviceType,
+
+    /// PCI vendor ID.
+    vendor: usize,
+
+    /// PCI device ID.
+    device: usize,
+}
+
 pub(crate) struct Gpu {
     pub(crate) device: wgpu::Device,
     pub(crate) queue: wgpu::Queue,
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
     surface: wgpu::Surface,
     window_size: winit::dpi::PhysicalSize<u32>,
+    present_mode: PresentMode,
+
+    /// Set by [`Gpu::recover`] and cleared when a frame is acquired successfully.
+    recovering: bool,
 }
 
 impl Gpu {
     pub(crate) fn new<W: HasRawWindowHandle>(
         window: &W,
         window_size: winit::dpi::PhysicalSize<u32>,
+        backend: GpuBackend,
+        present_mode: PresentMode,
     ) -> Result<Self, Error> {
-        let instance = wgpu::Instance::new(wgpu::Backends::PRIMARY);
-        let surface = unsafe { instance.create_surface(window) };
-        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-            power_preference: wgpu::PowerPreference::HighPerformance,
-        });
-        let adapter = pollster::block_on(adapter).ok_or(Error::AdapterNotFound)?;
-        let (device, queue) =
-            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
-                .map_err(Error::DeviceNotFound)?;
+        let result = Self::request_adapter(window, backend.as_backends());
+        let (instance, surface, adapter) = match result {
+            Some(result) => result,
+            None if backend != GpuBackend::Auto => {
+                // Fall back to the primary backends when the requested backend is unavailable.
+                warn!(
+                    "No adapter found for backend `{}`, falling back to `{}`",
+                    backend.as_str(),
+                    GpuBackend::Auto.as_str(),
+                );
+                Self::request_adapter(window, GpuBackend::Auto.as_backends())
+                    .ok_or(Error::AdapterNotFound)?
+            }
+            None => return Err(Error::AdapterNotFound),
+        };
+
+        let info = adapter.get_info();
+        info!(
+            "Using GPU adapter: {} ({:?}, {:?})",
+            info.name, info.backend, info.device_type,
+        );
+        if info.device_type == wgpu::DeviceType::Cpu {
+            warn!("Using a software adapter, rendering performance may be poor");
+        }
+
+        let (device, queue) = Self::request_device(&adapter)?;
 
         let gpu = Self {
             device,
             queue,
+            instance,
+            adapter,
             surface,
             window_size,
+            present_mode,
+            recovering: false,
         };
         gpu.reconfigure_surface();
 
         Ok(gpu)
     }
 
+    /// Get details about the GPU adapter in use.
+    pub(crate) fn info(&self) -> GpuInfo {
+        GpuInfo::from(self.adapter.get_info())
+    }
+
+    /// Recreate the surface after it has been lost.
+    ///
+    /// When the surface is lost again before a frame could be acquired, the device is assumed to
+    /// be lost as well and it is recreated from the adapter. The caller is responsible for
+    /// recreating any resources that were created with the old device.
+    pub(crate) fn recover<W: HasRawWindowHandle>(&mut self, window: &W) -> Result<Recovery, Error> {
+        let recovery = if self.recovering {
+            warn!("GPU surface lost again, recreating device");
+            let (device, queue) = Self::request_device(&self.adapter)?;
+            self.device = device;
+            self.queue = queue;
+
+            Recovery::Device
+        } else {
+            warn!("GPU surface lost, recreating surface");
+
+            Recovery::Surface
+        };
+
+        self.surface = unsafe { self.instance.create_surface(window) };
+        self.reconfigure_surface();
+        self.recovering = true;
+
+        Ok(recovery)
+    }
+
+    /// Create a surface and request an adapter that can present to it.
+    ///
+    /// Adapters are requested with decreasing requirements: first a high-performance adapter, then
+    /// a low-power adapter, and finally a software adapter. Every adapter must be able to present
+    /// to the surface.
+    fn request_adapter<W: HasRawWindowHandle>(
+        window: &W,
+        backends: wgpu::Backends,
+    ) -> Option<(wgpu::Instance, wgpu::Surface, wgpu::Adapter)> {
+        let instance = wgpu::Instance::new(backends);
+        let surface = unsafe { instance.create_surface(window) };
+        let attempts = [
+            (wgpu::PowerPreference::HighPerformance, false),
+            (wgpu::PowerPreference::LowPower, false),
+            (wgpu::PowerPreference::LowPower, true),
+        ];
+
+        for (power_preference, force_fallback_adapter) in attempts {
+            let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+                compatible_surface: Some(&surface),
+                force_fallback_adapter,
+                power_preference,
+            });
+
+            if let Some(adapter) = pollster::block_on(adapter) {
+                return Some((instance, surface, adapter));
+            }
+
+            warn!(
+                "No adapter found with {:?} (software fallback: {})",
+                power_preference, force_fallback_adapter,
+            );
+        }
+
+        None
+    }
+
+    /// Request a device and queue from the adapter.
+    ///
+    /// Uncaptured device errors are logged instead of panicking.
+    fn request_device(adapter: &wgpu::Adapter) -> Result<(wgpu::Device, wgpu::Queue), Error> {
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+                .map_err(Error::DeviceNotFound)?;
+
+        device.on_uncaptured_error(|err| error!("Uncaptured GPU error: {}", err));
+
+        Ok((device, queue))
+    }
+
     fn reconfigure_surface(&self) {
+        // Unsupported present modes are downgraded to `Fifo` by `wgpu`.
         self.surface.configure(
             &self.device,
             &wgpu::SurfaceConfiguration {
@@ -59,7 +233,7 @@ impl Gpu {
                 format: wgpu::TextureFormat::Bgra8UnormSrgb,
                 width: self.window_size.width,
                 height: self.window_size.height,
-                present_mode: wgpu::PresentMode::Fifo,
+                present_mode: self.present_mode.as_wgpu(),
             },
         )
     }
@@ -84,6 +258,8 @@ impl Gpu {
                 err => Err(err),
             })
             .map_err(Error::Surface)?;
+        self.recovering = false;
+
         let encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -93,3 +269,125 @@ impl Gpu {
         Ok((encoder, frame))
     }
 }
+
+impl From<wgpu::AdapterInfo> for GpuInfo {
+    fn from(info: wgpu::AdapterInfo) -> Self {
+        Self {
+            name: info.name,
+            backend: info.backend,
+            device_type: info.device_type,
+            vendor: info.vendor,
+            device: info.device,
+        }
+    }
+}
+
+impl std::fmt::Display for GpuInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let backend = match self.backend {
+            wgpu::Backend::Empty => "None",
+            wgpu::Backend::Vulkan => "Vulkan",
+            wgpu::Backend::Metal => "Metal",
+            wgpu::Backend::Dx12 => "DirectX 12",
+            wgpu::Backend::Dx11 => "DirectX 11",
+            wgpu::Backend::Gl => "OpenGL",
+            wgpu::Backend::BrowserWebGpu => "WebGPU",
+        };
+        let device_type = match self.device_type {
+            wgpu::DeviceType::Other => "Other",
+            wgpu::DeviceType::IntegratedGpu => "Integrated GPU",
+            wgpu::DeviceType::DiscreteGpu => "Discrete GPU",
+            wgpu::DeviceType::VirtualGpu => "Virtual GPU",
+            wgpu::DeviceType::Cpu => "Software",
+        };
+
+        writeln!(f, "Graphics: {}", self.name)?;
+        writeln!(f, "Backend: {} ({})", backend, device_type)?;
+        write!(
+            f,
+            "Vendor ID: {:#06x}, Device ID: {:#06x}",
+            self.vendor, self.device
+        )
+    }
+}
+
+impl Default for GpuBackend {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+impl From<&str> for GpuBackend {
+    fn from(value: &str) -> Self {
+        match value {
+            "vulkan" => Self::Vulkan,
+            "metal" => Self::Metal,
+            "dx12" => Self::Dx12,
+            "dx11" => Self::Dx11,
+            "gl" => Self::Gl,
+            _ => Self::Auto,
+        }
+    }
+}
+
+impl GpuBackend {
+    /// Get a string slice that is TOML-compatible for this `GpuBackend`.
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::Auto => "auto",
+            Self::Vulkan => "vulkan",
+            Self::Metal => "metal",
+            Self::Dx12 => "dx12",
+            Self::Dx11 => "dx11",
+            Self::Gl => "gl",
+        }
+    }
+
+    /// Convert this backend into the set of [`wgpu::Backends`] to search for adapters.
+    fn as_backends(&self) -> wgpu::Backends {
+        match self {
+            Self::Auto => wgpu::Backends::PRIMARY,
+            Self::Vulkan => wgpu::Backends::VULKAN,
+            Self::Metal => wgpu::Backends::METAL,
+            Self::Dx12 => wgpu::Backends::DX12,
+            Self::Dx11 => wgpu::Backends::DX11,
+            Self::Gl => wgpu::Backends::GL,
+        }
+    }
+}
+
+impl Default for PresentMode {
+    fn default() -> Self {
+        Self::Vsync
+    }
+}
+
+impl From<&str> for PresentMode {
+    fn from(value: &str) -> Self {
+        match value {
+            "mailbox" => Self::Mailbox,
+            "immediate" => Self::Immediate,
+            _ => Self::Vsync,
+        }
+    }
+}
+
+impl PresentMode {
+    /// Get a string slice that is TOML-compatible for this `PresentMode`.
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::Vsync => "vsync",
+            Self::Mailbox => "mailbox",
+            Self::Immediate => "immediate",
+        }
+    }
+
+    /// Convert this present mode into a [`wgpu::PresentMode`].
+    fn as_wgpu(&self) -> wgpu::PresentMode {
+        match self {
+            Self::Vsync => wgpu::PresentMode::Fifo,
+            Self::Mailbox => wgpu::PresentMode::Mailbox,
+            Self::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+}
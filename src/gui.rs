@@ -1,22 +1,64 @@
 //! User interface structure, rendering, and state management.
 
-use self::grid::SetupGrid;
-use crate::config::{Config, UserTheme};
+use self::grid::{contrast_ratio, diff_checklist, diff_summary, SetupGrid, MIN_CONTRAST};
+use self::selection::TrackOffsets;
+use self::table::{Delimited, Markdown, Table};
+use crate::bundle;
+use crate::config::{
+    Browse, Config, DiffBlend, DiffReference, GridOptions, Grouping, Quoting, ResetOptions,
+    SafeRange, SortOrder, TableOptions, UserTheme, MAX_GRID_ZOOM, MAX_NEWEST_COUNT, MIN_GRID_ZOOM,
+    MIN_NEWEST_COUNT,
+};
 use crate::framework::UserEvent;
-use crate::setup::{Setup, Setups};
-use crate::str_ext::{Ellipsis, HumanCompare};
+use crate::gpu::GpuInfo;
+use crate::locale::Locale;
+use crate::opacity::{self, MIN_OPACITY};
+use crate::setup::{
+    align_gears, count_exports, distribution, format_number, group_by_corner, settle_events,
+    setup_from_clipboard, with_unit, Error as SetupError, Setup, SetupInfo, Setups, UpdateKind,
+};
+use crate::str_ext::{Ellipsis, HumanCompare, WildcardMatch};
+#[cfg(feature = "update-check")]
 use crate::updates::{UpdateFrequency, UpdateNotification};
+use crate::value::PerchOffsetUnit;
 use copypasta::{ClipboardContext, ClipboardProvider};
 use egui::widgets::color_picker::{color_edit_button_srgba, Alpha};
 use egui::{CtxRef, Widget};
 use hotwatch::Hotwatch;
-use std::collections::{HashMap, VecDeque};
-use std::path::Path;
+use log::warn;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use thiserror::Error;
 use winit::event_loop::EventLoopProxy;
 
 mod grid;
+mod selection;
+mod table;
+
+/// File system events are applied in batches after this delay.
+const FS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Incomplete setup exports are loaded again after this delay, since they may still be written.
+const FS_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Incomplete setup exports are reported after this many attempts to load them again.
+const FS_MAX_RETRIES: usize = 5;
+
+/// Minimum time between progress reports while loading setups.
+const LOAD_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Common properties where lower values are better, with the pattern that matches them.
+const COMMON_SWAPPED_DIFFS: [(&str, &str); 2] =
+    [("Tire temperatures", "temp"), ("Fuel level", "fuel level")];
+
+/// Default content size for the error and warning windows.
+const MESSAGE_SIZE: egui::Vec2 = egui::Vec2::new(550.0, 185.0);
+
+/// Minimum content size for the error and warning windows.
+const MESSAGE_MIN_SIZE: egui::Vec2 = egui::Vec2::new(350.0, 120.0);
 
 /// Manages all state required for rendering the GUI.
 pub(crate) struct Gui {
@@ -29,6 +71,32 @@ pub(crate) struct Gui {
     /// Filesystem watcher for changes to any setup exports.
     hotwatch: Hotwatch,
 
+    /// File system events waiting to be applied to the setups tree.
+    fs_events: Vec<hotwatch::Event>,
+
+    /// Queued file system events are applied at this time.
+    fs_deadline: Option<Instant>,
+
+    /// Number of attempts to load incomplete setup exports again, by path.
+    fs_retries: HashMap<PathBuf, usize>,
+
+    /// Sizes of setup exports that may still be written, by path.
+    fs_sizes: HashMap<PathBuf, u64>,
+
+    /// Opened setup exports outside of the setup exports path, which are watched individually
+    /// while they are in the setups tree.
+    watched_files: HashSet<PathBuf>,
+
+    /// Number of files processed so far while setups are loading on a worker thread, and the total
+    /// number of files. The total is zero until all files have been found.
+    loading: Option<(usize, usize)>,
+
+    /// Identifies the most recent setup loader. Results from older loaders are discarded.
+    load_generation: u64,
+
+    /// Setup exports to open when loading finishes.
+    pending_open: Vec<PathBuf>,
+
     /// Selected track name.
     selected_track_name: Option<String>,
 
@@ -38,19 +106,87 @@ pub(crate) struct Gui {
     /// Selected setup indices.
     selected_setups: Vec<usize>,
 
+    /// Paths of selected setups with hidden columns. They stay selected and keep their colors.
+    hidden_setups: HashSet<PathBuf>,
+
     /// An event loop proxy for sending user events.
     event_loop_proxy: EventLoopProxy<UserEvent>,
 
     /// Show the "About..." window.
     about: bool,
 
+    /// GPU adapter details shown in the "About..." window.
+    gpu_info: Option<GpuInfo>,
+
+    /// Show the "Keyboard Shortcuts" window.
+    shortcuts: bool,
+
     /// Show the "Preferences..." window.
     preferences: bool,
 
+    /// Show the "iRacing Folders" window with these folders and their number of setup exports,
+    /// once they have been counted.
+    setups_path_candidates: Option<Vec<(PathBuf, Option<usize>)>>,
+
+    /// Show the "Property Aliases..." window.
+    property_aliases: bool,
+
+    /// Property aliases are edited for all cars instead of the selected car.
+    aliases_all_cars: bool,
+
+    /// Show the "Property Groups" window.
+    property_groups: bool,
+
+    /// Property groups are hidden for all cars instead of the selected car.
+    groups_all_cars: bool,
+
+    /// Show the "Property Distribution" window.
+    distribution: bool,
+
+    /// Group name and property name shown in the "Property Distribution" window.
+    distribution_prop: (Option<String>, Option<String>),
+
+    /// Show the "Setup Notes" window for this setup export path.
+    edit_note: Option<PathBuf>,
+
+    /// A setup pasted from the clipboard, compared in an extra column.
+    clipboard_setup: Option<ClipboardSetup>,
+
+    /// Remove the setup pasted from the clipboard.
+    clear_clipboard_setup: bool,
+
+    /// Copy this setup export path to the clipboard.
+    copy_path: Option<PathBuf>,
+
+    /// Exclude this setup export or folder from the library.
+    exclude_path: Option<PathBuf>,
+
+    /// Star or unstar this setup export.
+    toggle_favorite: Option<PathBuf>,
+
+    /// Only show starred setups in the setup selection, along with any that are selected.
+    favorites_only: bool,
+
+    /// Copy this text from a grid cell to the clipboard.
+    copy_cell: Option<String>,
+
+    /// User's choices for resetting all settings to defaults.
+    reset_options: ResetOptions,
+
+    /// Text field for adding a property with swapped diff colors in the "Preferences" window.
+    swapped_diff_pattern: String,
+
+    /// Text fields for adding a safe range in the "Preferences" window.
+    safe_range_input: SafeRangeInput,
+
     /// Show the "Warning" window.
     warning: bool,
 
+    /// Show the window for reporting a crash in the previous run.
+    crash_report: bool,
+
     /// Show the "Update Notification" window.
+    #[cfg(feature = "update-check")]
     update_notification: bool,
 
     /// Show an error message.
@@ -60,10 +196,49 @@ pub(crate) struct Gui {
     show_warnings: VecDeque<ShowWarning>,
 
     /// Show an update notification message.
+    #[cfg(feature = "update-check")]
     show_update_notification: Option<UpdateNotification>,
 
+    /// The user hid the update footer for this session.
+    #[cfg(feature = "update-check")]
+    update_footer_dismissed: bool,
+
+    /// Safe mode is active, see [`crate::framework::SAFE_MODE_FLAG`].
+    safe_mode: bool,
+
+    /// The user hid the safe mode banner for this session.
+    safe_mode_dismissed: bool,
+
+    /// Presentation mode hides settings, file operations, and editing controls, leaving only
+    /// browsing and comparison.
+    presentation: bool,
+
+    /// The settings ignored in safe mode were reset to defaults.
+    startup_settings_reset: bool,
+
     /// Show a tooltip.
     show_tooltips: HashMap<egui::Id, (String, Instant)>,
+
+    /// The GUI must be redrawn at this time, even without any input.
+    repaint_deadline: Option<Instant>,
+
+    /// The scale factor used for the previous frame.
+    pixels_per_point: Option<f32>,
+
+    /// Grid scroll offsets for each comparison, keyed by track and car name.
+    scroll_offsets: HashMap<String, egui::Vec2>,
+
+    /// The scroll offsets key for the grid shown in the previous frame.
+    grid_key: Option<String>,
+
+    /// Scale factor for the grid text size.
+    ///
+    /// The config only keeps whole percentages, which would swallow small zoom steps from
+    /// trackpads.
+    grid_zoom: f32,
+
+    /// The grid drawn in the previous frame, reused until the comparison or its options change.
+    grid_cache: Option<GridCache>,
 }
 
 /// Holds state for an error message to show to the user, and provides a feedback mechanism for the
@@ -97,6 +272,82 @@ pub(crate) struct ShowWarning {
     context: String,
 }
 
+/// A setup pasted from the clipboard. It is compared without being written to disk.
+struct ClipboardSetup {
+    /// Track name from the pasted export.
+    track_name: String,
+
+    /// Car name from the pasted export. The setup is only compared with setups for this car.
+    car_name: String,
+
+    /// The setup, named "Clipboard" and without a file path.
+    info: SetupInfo,
+}
+
+/// A comparison grid that is kept between frames.
+struct GridCache {
+    /// Everything the grid was built from.
+    key: GridKey,
+
+    /// The grid, with all of its text laid out.
+    grid: SetupGrid,
+}
+
+/// Everything that changes how the comparison grid is laid out.
+///
+/// Setups are identified by their path. Their contents only change when setups are loaded or
+/// updated, or when a setup is pasted from the clipboard, which drops the cached grid.
+#[derive(PartialEq)]
+struct GridKey {
+    /// Path of the setup in each column.
+    paths: Vec<PathBuf>,
+
+    /// Color of each column.
+    colors: Vec<egui::Color32>,
+
+    /// Diff colors for lower and higher values.
+    diff_colors: (egui::Color32, egui::Color32),
+
+    /// Property name aliases for the selected car.
+    aliases: HashMap<String, String>,
+
+    /// Property groups hidden for the selected car.
+    hidden_groups: HashSet<String>,
+
+    /// Property grouping choice.
+    grouping: Grouping,
+
+    /// Display and diff options.
+    options: GridOptions,
+
+    /// Label above each column.
+    header: Vec<String>,
+
+    /// Setup name for each column, used to copy cell references.
+    setup_names: Vec<String>,
+
+    /// Theme colors and spacing.
+    style: Arc<egui::Style>,
+
+    /// Scale factor that text was laid out for.
+    pixels_per_point: f32,
+
+    /// Grid text size.
+    grid_zoom: f32,
+
+    /// Column widths for the selected car, when they are locked.
+    column_widths: Option<Vec<f32>>,
+}
+
+/// Text fields for adding a safe range in the "Preferences" window.
+#[derive(Default)]
+struct SafeRangeInput {
+    prop_name: String,
+    min: String,
+    max: String,
+    unit: String,
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum Error {
     #[error("File system watch error: {0}")]
@@ -105,39 +356,97 @@ pub(crate) enum Error {
 
 impl Gui {
     /// Create a GUI.
+    ///
+    /// The setups tree starts empty; use [`Self::reload_setups`] to load it.
     pub(crate) fn new(
-        config: Config,
-        setups: Setups,
+        mut config: Config,
         event_loop_proxy: EventLoopProxy<UserEvent>,
         show_errors: VecDeque<ShowError>,
-        show_warnings: VecDeque<ShowWarning>,
+        mut show_warnings: VecDeque<ShowWarning>,
     ) -> Result<Self, Error> {
+        for warning in config.take_warnings() {
+            show_warnings.push_front(ShowWarning::new(warning, "Problem in the config file."));
+        }
+
         let mut hotwatch = Hotwatch::new()?;
         let watcher = Self::watch_setups_path(event_loop_proxy.clone());
 
         hotwatch.watch(config.get_setups_path(), watcher)?;
+        let grid_zoom = f32::from(config.grid_zoom()) / 100.0;
+        let presentation = config.presentation_mode();
 
         Ok(Self {
             config,
-            setups,
+            setups: Setups::default(),
             hotwatch,
+            fs_events: Vec::new(),
+            fs_deadline: None,
+            fs_retries: HashMap::new(),
+            fs_sizes: HashMap::new(),
+            watched_files: HashSet::new(),
+            loading: None,
+            load_generation: 0,
+            pending_open: Vec::new(),
             selected_track_name: None,
             selected_car_name: None,
             selected_setups: Vec::new(),
+            hidden_setups: HashSet::new(),
             event_loop_proxy,
             about: false,
+            gpu_info: None,
+            shortcuts: false,
             preferences: false,
+            setups_path_candidates: None,
+            property_aliases: false,
+            aliases_all_cars: false,
+            property_groups: false,
+            groups_all_cars: false,
+            distribution: false,
+            distribution_prop: (None, None),
+            edit_note: None,
+            clipboard_setup: None,
+            clear_clipboard_setup: false,
+            copy_path: None,
+            exclude_path: None,
+            toggle_favorite: None,
+            favorites_only: false,
+            copy_cell: None,
+            reset_options: ResetOptions {
+                keep_setups_path: true,
+                keep_names: true,
+            },
+            swapped_diff_pattern: String::new(),
+            safe_range_input: SafeRangeInput::default(),
             warning: false,
+            crash_report: false,
+            #[cfg(feature = "update-check")]
             update_notification: false,
             show_errors,
             show_warnings,
+            #[cfg(feature = "update-check")]
             show_update_notification: None,
+            #[cfg(feature = "update-check")]
+            update_footer_dismissed: false,
+            safe_mode: false,
+            safe_mode_dismissed: false,
+            presentation,
+            startup_settings_reset: false,
             show_tooltips: HashMap::new(),
+            repaint_deadline: None,
+            pixels_per_point: None,
+            scroll_offsets: HashMap::new(),
+            grid_key: None,
+            grid_cache: None,
+            grid_zoom,
         })
     }
 
     /// Draw the UI using egui.
     pub(crate) fn ui(&mut self, ctx: &egui::CtxRef, window: &winit::window::Window) {
+        self.repaint_deadline = None;
+        self.check_scale_factor(ctx);
+        self.flush_fs_changes();
+
         // Show an error message (if any) in a modal window by disabling the rest of the UI.
         let enabled = self.error_window(ctx);
 
@@ -145,64 +454,201 @@ impl Gui {
         egui::TopBottomPanel::top("menubar-container").show(ctx, |ui| {
             ui.set_enabled(enabled);
             egui::menu::bar(ui, |ui| {
-                ui.menu_button("File", |ui| {
+                // Settings and file operations are hidden in presentation mode
+                if !self.presentation {
+                    ui.menu_button("File", |ui| {
+                        ui.set_min_width(200.0);
+                        if ui.button("Preferences").clicked() {
+                            ui.close_menu();
+                            self.preferences = true;
+                        }
+                        if ui.button("Property Aliases").clicked() {
+                            ui.close_menu();
+                            self.property_aliases = true;
+                        }
+                        if ui.button("Property Groups").clicked() {
+                            ui.close_menu();
+                            self.property_groups = true;
+                        }
+                        if ui
+                            .button("Save Settings Now")
+                            .on_hover_text("Write all settings to the config file.")
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            self.save_settings(window);
+                        }
+                        ui.separator();
+                        let can_export = !self.selected_setups.is_empty();
+                        if ui
+                            .add_enabled(can_export, egui::Button::new("Export Bundle..."))
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            self.choose_bundle_export(window);
+                        }
+                        if ui.button("Import Bundle...").clicked() {
+                            ui.close_menu();
+                            let f = rfd::AsyncFileDialog::new()
+                                .set_parent(window)
+                                .add_filter("Zip archive", &["zip"])
+                                .pick_file();
+                            self.send_file_choice(f, UserEvent::ImportBundle);
+                        }
+                        if ui
+                            .button("Export Library as JSON...")
+                            .on_hover_text("Save every loaded setup, for use with other tools.")
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            self.choose_library_export(window);
+                        }
+                        ui.separator();
+                        if ui
+                            .add_enabled(can_export, egui::Button::new("Export Table..."))
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            self.choose_table_export(window);
+                        }
+                        if ui
+                            .add_enabled(
+                                can_export,
+                                egui::Button::new("Export Comparison as CSV..."),
+                            )
+                            .on_hover_text(
+                                "Save the grid as it is shown, without hidden groups or columns.",
+                            )
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            self.choose_comparison_export(window);
+                        }
+                        if ui
+                            .add_enabled(can_export, egui::Button::new("Export Image..."))
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            self.choose_image_export(window);
+                        }
+                        let can_export_checklist = self.selected_setups.len() == 2;
+                        if ui
+                            .add_enabled(
+                                can_export_checklist,
+                                egui::Button::new("Export Change Checklist..."),
+                            )
+                            .on_disabled_hover_text(
+                                "Select exactly two setups to list the changes.",
+                            )
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            self.choose_checklist_export(window);
+                        }
+                    });
+                }
+                ui.menu_button("View", |ui| {
                     ui.set_min_width(200.0);
-                    if ui.button("Preferences").clicked() {
+                    let mut presentation = self.presentation;
+                    if ui
+                        .checkbox(&mut presentation, "Presentation Mode")
+                        .on_hover_text("Hide settings, file operations, and editing controls.")
+                        .changed()
+                    {
+                        ui.close_menu();
+                        self.config.set_presentation_mode(presentation);
+                        self.set_presentation(presentation);
+                    }
+                    if ui
+                        .button("Property Distribution...")
+                        .on_hover_text("Chart the values of a property in every setup for a car.")
+                        .clicked()
+                    {
                         ui.close_menu();
-                        self.preferences = true;
+                        self.distribution = true;
                     }
                 });
                 ui.menu_button("Help", |ui| {
                     ui.set_min_width(200.0);
-                    if ui.button("About CarTunes...").clicked() {
+                    if ui.button("Keyboard Shortcuts...").clicked() {
                         ui.close_menu();
-                        self.about = true;
+                        self.shortcuts = true;
                     }
-                    if ui.button("Support CarTunes on Patreon").clicked() {
+                    if ui.button("About CarTunes...").clicked() {
                         ui.close_menu();
-                        if let Err(err) = webbrowser::open("https://www.patreon.com/blipjoy") {
-                            let warning = ShowWarning::new(err, "Unable to open web browser.");
-                            self.show_warnings.push_front(warning);
-                        }
+                        self.about = true;
                     }
+                    #[cfg(feature = "web-links")]
+                    self.patreon_menu_item(ui);
                 });
             });
         });
 
+        // Draw the safe mode banner
+        if self.safe_mode && !self.safe_mode_dismissed {
+            egui::TopBottomPanel::top("safe-mode-container").show(ctx, |ui| {
+                ui.set_enabled(enabled);
+                self.safe_mode_banner(ui);
+            });
+        }
+
         // Draw the footer
-        if self.show_update_notification.is_some() || !self.show_warnings.is_empty() {
+        #[cfg(feature = "update-check")]
+        let update_available =
+            self.show_update_notification.is_some() && !self.update_footer_dismissed;
+        #[cfg(not(feature = "update-check"))]
+        let update_available = false;
+
+        if update_available || !self.show_warnings.is_empty() {
             egui::TopBottomPanel::bottom("footer-container").show(ctx, |ui| {
-                if self.show_update_notification.is_some() {
-                    let rect = ui
-                        .horizontal(|ui| {
-                            let rect = ui.available_rect_before_wrap();
-                            let size = ui.spacing().interact_size.y;
-                            let center = egui::Vec2::splat(size / 2.0);
-                            let green = egui::Color32::from_rgb(40, 210, 40);
+                #[cfg(feature = "update-check")]
+                if update_available {
+                    ui.horizontal(|ui| {
+                        let rect = ui
+                            .horizontal(|ui| {
+                                let rect = ui.available_rect_before_wrap();
+                                let size = ui.spacing().interact_size.y;
+                                let center = egui::Vec2::splat(size / 2.0);
+                                let green = egui::Color32::from_rgb(40, 210, 40);
 
-                            ui.spacing_mut().item_spacing.x /= 2.0;
-                            ui.painter()
-                                .circle_filled(rect.min + center, center.x - 3.0, green);
-                            ui.add_space(size);
-                            ui.label("Update available");
-                            ui.add_space(0.0);
-                        })
-                        .response
-                        .rect;
-                    let response = ui.interact(
-                        rect,
-                        egui::Id::new("update-notification-button"),
-                        egui::Sense::click(),
-                    );
+                                ui.spacing_mut().item_spacing.x /= 2.0;
+                                ui.painter().circle_filled(
+                                    rect.min + center,
+                                    center.x - 3.0,
+                                    green,
+                                );
+                                ui.add_space(size);
+                                ui.label("Update available");
+                                ui.add_space(0.0);
+                            })
+                            .response
+                            .rect;
+                        let response = ui.interact(
+                            rect,
+                            egui::Id::new("update-notification-button"),
+                            egui::Sense::click(),
+                        );
 
-                    if response.hovered() {
-                        let hovered = ui.visuals().widgets.hovered;
-                        ui.painter()
-                            .rect_stroke(rect, hovered.corner_radius, hovered.bg_stroke);
-                    }
-                    if response.clicked() {
-                        self.update_notification = true;
-                    }
+                        if response.hovered() {
+                            let hovered = ui.visuals().widgets.hovered;
+                            ui.painter().rect_stroke(
+                                rect,
+                                hovered.corner_radius,
+                                hovered.bg_stroke,
+                            );
+                        }
+                        if response.clicked() {
+                            self.update_notification = true;
+                        }
+
+                        // Hide the footer until the next launch, or until a newer version is found
+                        let response = ui
+                            .small_button("\u{d7}")
+                            .on_hover_text("Hide until CarTunes is restarted");
+                        if response.clicked() {
+                            self.update_footer_dismissed = true;
+                        }
+                    });
                 }
                 if !self.show_warnings.is_empty() {
                     let rect = ui
@@ -239,139 +685,1827 @@ impl Gui {
 
         // Draw the main content area
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.set_enabled(enabled);
+            // The setups tree is stale until loading finishes
+            ui.set_enabled(enabled && self.loading.is_none());
 
             // Draw car filters
             egui::containers::ScrollArea::horizontal().show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    self.track_selection(ui);
+                    self.browse_selection(ui);
+                    if self.config.browse() == Browse::Track {
+                        self.track_selection(ui);
+                    }
                     self.car_selection(ui);
+                    ui.checkbox(&mut self.favorites_only, "Favorites only")
+                        .on_hover_text(
+                            "Only show starred setups, and the setups that are selected.",
+                        );
                 });
             });
 
+            // Draw the toolbar
+            self.toolbar(ctx, ui, window);
+
             // Draw setup filters
             let colors = self.config.colors();
             let diff_colors = self.config.diff_colors();
-            let (track_name, car_name, setups) = self.setup_selection(ui, &colors);
+            let mut grid_options = self.config.grid_options();
+            if let Some(car_name) = self.selected_car_name.as_ref() {
+                grid_options.formatters = self.config.formatters(car_name);
+            }
+            let grouping = self.config.grouping();
+            let browse = self.config.browse();
+            let aliases = self
+                .selected_car_name
+                .as_ref()
+                .map(|car_name| self.config.aliases(car_name))
+                .unwrap_or_default();
+            let hidden_groups = self
+                .selected_car_name
+                .as_ref()
+                .map(|car_name| self.config.hidden_groups(car_name))
+                .unwrap_or_default();
+            // Restore the scroll position when returning to a comparison
+            let scroll_key = match browse {
+                Browse::Track => self
+                    .selected_track_name
+                    .as_ref()
+                    .zip(self.selected_car_name.as_ref())
+                    .map(|(track_name, car_name)| format!("{}{}", track_name, car_name)),
+                Browse::Car => self.selected_car_name.clone(),
+            };
+            let restore_offset = scroll_key
+                .as_ref()
+                .filter(|&key| self.grid_key.as_ref() != Some(key))
+                .and_then(|key| self.scroll_offsets.get(key).copied());
+            let mut scroll_offset = None;
+            let locale = self.config.display_locale();
+            let clean_names = self.config.clean_setup_names();
+            let grid_zoom = self.grid_zoom;
+            let column_widths = self.config.lock_column_widths().then(|| {
+                self.selected_car_name
+                    .as_ref()
+                    .and_then(|car_name| self.config.column_widths(car_name))
+                    .unwrap_or_default()
+                    .to_vec()
+            });
+            let grid_cache = self.grid_cache.take();
+
+            let (track_name, car_name, setups, column_colors) = self.setup_selection(ui, &colors);
+            let header = column_header(browse, &setups, locale);
+            let setup_names: Vec<_> = setups
+                .iter()
+                .map(|&(track_name, info)| {
+                    setup_label(browse, track_name, info, clean_names).into_owned()
+                })
+                .collect();
+            let grid_key = GridKey {
+                paths: setups
+                    .iter()
+                    .map(|(_, info)| info.path().to_path_buf())
+                    .collect(),
+                colors: column_colors,
+                diff_colors,
+                aliases,
+                hidden_groups,
+                grouping,
+                options: grid_options,
+                header,
+                setup_names,
+                style: ui.style().clone(),
+                pixels_per_point: ctx.pixels_per_point(),
+                grid_zoom,
+                column_widths,
+            };
+            let mut grid_cache = grid_cache.filter(|cache| cache.key == grid_key);
+            let setups: Vec<_> = setups.into_iter().map(|(_, info)| info.setup()).collect();
+            let mut copy_cell = None;
+            let mut resized_columns = None;
+            let mut zoom_delta = 1.0;
             if !setups.is_empty() {
                 // Draw setup properties grid
                 egui::containers::ScrollArea::both()
                     .id_source(format!("{}{}", track_name, car_name))
-                    .show(ui, |ui| {
-                        SetupGrid::new(ui, &setups, &colors, diff_colors).show(ui, car_name);
+                    .show_viewport(ui, |ui, viewport| {
+                        if let Some(offset) = restore_offset {
+                            scroll_to_offset(ui, offset);
+                        }
+                        scroll_offset = Some(viewport.min.to_vec2());
+
+                        // The grid is only laid out again when its inputs change
+                        let cache = grid_cache.get_or_insert_with(|| {
+                            let corner_setups: Vec<Setup>;
+                            let setups = match grouping {
+                                Grouping::Export => setups,
+                                Grouping::Corner => {
+                                    corner_setups =
+                                        setups.into_iter().map(group_by_corner).collect();
+                                    corner_setups.iter().collect()
+                                }
+                            };
+                            // Line up gears by gear number, in case the setups have different gear
+                            // counts
+                            let aligned_setups = align_gears(&setups);
+                            let setups: Vec<_> =
+                                aligned_setups.iter().map(|setup| setup.as_ref()).collect();
+                            let header: Vec<_> =
+                                grid_key.header.iter().map(String::as_str).collect();
+                            let grid = SetupGrid::new(
+                                ui,
+                                &setups,
+                                &grid_key.colors,
+                                grid_key.diff_colors,
+                                &grid_key.aliases,
+                                &grid_key.hidden_groups,
+                                grid_key.options.clone(),
+                            )
+                            .with_zoom(grid_key.grid_zoom)
+                            .with_header(ui, &header)
+                            .with_setup_names(grid_key.setup_names.clone());
+                            let grid = match &grid_key.column_widths {
+                                Some(widths) => grid.with_column_widths(ui, widths),
+                                None => grid,
+                            };
+
+                            GridCache {
+                                key: grid_key,
+                                grid,
+                            }
+                        });
+                        let response = cache.grid.show(ui, car_name);
+                        copy_cell = response.copy;
+                        resized_columns = response.column_widths;
+
+                        // Ctrl + scroll and pinch gestures zoom the grid text
+                        if ui.rect_contains_pointer(ui.clip_rect()) {
+                            zoom_delta = ui.input().zoom_delta();
+                        }
                     });
             }
+            self.grid_cache = grid_cache;
+            if copy_cell.is_some() {
+                self.copy_cell = copy_cell;
+                ctx.request_repaint();
+            }
+            if let (Some(widths), Some(car_name)) = (resized_columns, &self.selected_car_name) {
+                self.config.set_column_widths(car_name, &widths);
+            }
+            if (zoom_delta - 1.0).abs() > f32::EPSILON {
+                self.zoom_grid(zoom_delta);
+                ctx.request_repaint();
+            }
+
+            if let (Some(key), Some(offset)) = (scroll_key.as_ref(), scroll_offset) {
+                self.scroll_offsets.insert(key.to_string(), offset);
+            }
+            self.grid_key = scroll_key.filter(|_| scroll_offset.is_some());
         });
 
+        // The setup context menu requests excluding a path from the library
+        if let Some(path) = self.exclude_path.take() {
+            self.exclude_from_library(&path);
+        }
+
+        // The setup context menu requests starring or unstarring a setup
+        if let Some(path) = self.toggle_favorite.take() {
+            let favorite = self.config.is_favorite(&path);
+            self.config.set_favorite(&path, !favorite);
+        }
+
+        // Show loading progress over the main content area
+        if let Some((count, total)) = self.loading {
+            self.busy_indicator(ctx, count, total);
+        }
+
         // Draw the windows (if requested by the user)
         self.about_window(ctx, enabled);
+        self.shortcuts_window(ctx, enabled);
         self.prefs_window(ctx, enabled, window);
+        self.setups_path_window(ctx, enabled, window);
+        self.aliases_window(ctx, enabled);
+        self.groups_window(ctx, enabled);
+        self.distribution_window(ctx, enabled);
+        self.notes_window(ctx, enabled);
         if self.warning {
             self.warning_window(ctx, enabled);
         }
+        #[cfg(feature = "update-check")]
         self.show_update_notification(ctx, enabled);
+        self.crash_report_window(ctx, enabled);
     }
 
-    /// Create a file system watcher.
-    fn watch_setups_path(event_loop_proxy: EventLoopProxy<UserEvent>) -> impl Fn(hotwatch::Event) {
-        move |event| {
-            event_loop_proxy
-                .send_event(UserEvent::FsChange(event))
-                .expect("Event loop must exist");
-        }
-    }
-
-    /// Handle file system change events.
+    /// Draw the toolbar above the grid.
     ///
-    /// Called by the closure from `Self::watch_setups_path`.
-    pub(crate) fn handle_fs_change(&mut self, event: hotwatch::Event) {
-        use crate::setup::UpdateKind::*;
+    /// Controls are grouped by comparison actions, display options, and exports. The toolbar wraps
+    /// on narrow windows.
+    fn toolbar(&mut self, ctx: &egui::CtxRef, ui: &mut egui::Ui, window: &winit::window::Window) {
+        ui.horizontal_wrapped(|ui| {
+            // Comparison actions
+            let tooltip_id = egui::Id::new("diff-summary-copypasta");
+            let can_copy = self.selected_setups.len() == 2;
+            let button = egui::Button::new("Copy Diff Summary");
+            let response = ui
+                .add_enabled(can_copy, button)
+                .on_disabled_hover_text("Select exactly two setups to summarize changes.");
+            if response.clicked() {
+                self.copy_diff_summary(tooltip_id);
+            }
 
-        // Update the setups tree.
-        let updates = self.setups.update(&event, &self.config);
-        for update in updates {
-            match update {
-                AddedSetup(track_name, car_name, index) => {
-                    if self.selected_track_name.as_ref() == Some(&track_name)
-                        && self.selected_car_name.as_ref() == Some(&car_name)
-                    {
-                        // Update selected setups when a new one is added
-                        for i in self.selected_setups.iter_mut() {
-                            if *i >= index {
-                                *i += 1;
-                            }
-                        }
-                    }
-                }
-                RemovedSetup(track_name, car_name, index) => {
-                    if self.selected_track_name.as_ref() == Some(&track_name)
-                        && self.selected_car_name.as_ref() == Some(&car_name)
-                    {
-                        // Update selected setups when an old one is removed
-                        self.selected_setups.retain(|i| *i != index);
-                        for i in self.selected_setups.iter_mut() {
-                            if *i >= index {
-                                *i -= 1;
-                            }
-                        }
-                    }
-                }
-                RemovedCar(track_name, car_name) => {
-                    if self.selected_track_name.as_ref() == Some(&track_name)
-                        && self.selected_car_name.as_ref() == Some(&car_name)
-                    {
-                        self.selected_car_name = None;
-                        self.selected_setups.clear();
+            // Show the copy button tooltip for 3 seconds
+            self.tooltip(ctx, ui, tooltip_id, Duration::from_secs(3));
+
+            let tooltip_id = egui::Id::new("checklist-copypasta");
+            let button = egui::Button::new("Copy Change Checklist");
+            let response = ui
+                .add_enabled(can_copy, button)
+                .on_hover_text("Steps for turning the first setup into the second.")
+                .on_disabled_hover_text("Select exactly two setups to list the changes.");
+            if response.clicked() {
+                self.copy_checklist(tooltip_id);
+            }
+            self.tooltip(ctx, ui, tooltip_id, Duration::from_secs(3));
+
+            let tooltip_id = egui::Id::new("baseline-hint");
+            let has_car = self.selected_car_name.is_some();
+            let button = egui::Button::new("Compare with Baseline");
+            let response = ui
+                .add_enabled(has_car, button)
+                .on_hover_text("Use the baseline setup for this car as the diff reference.");
+            if response.clicked() {
+                self.compare_with_baseline(tooltip_id);
+            }
+            self.tooltip(ctx, ui, tooltip_id, Duration::from_secs(5));
+
+            let button = egui::Button::new("Compare Newest");
+            let hover_text = format!(
+                "Compare the {} most recently modified setups for this car.",
+                self.config.newest_count(),
+            );
+            if ui
+                .add_enabled(has_car, button)
+                .on_hover_text(hover_text)
+                .clicked()
+            {
+                self.compare_newest();
+            }
+
+            let tooltip_id = egui::Id::new("clipboard-setup-copypasta");
+            let button = egui::Button::new("Compare with Clipboard Setup");
+            let response = ui
+                .add_enabled(has_car, button)
+                .on_hover_text("Paste a setup export for this car as an extra column.");
+            if response.clicked() {
+                self.compare_with_clipboard(tooltip_id);
+            }
+            self.tooltip(ctx, ui, tooltip_id, Duration::from_secs(3));
+
+            // The setup context menu requests copying its path
+            let tooltip_id = egui::Id::new("setup-path-copypasta");
+            if let Some(path) = self.copy_path.take() {
+                self.copy_setup_path(&path, tooltip_id);
+            }
+            self.tooltip(ctx, ui, tooltip_id, Duration::from_secs(3));
+
+            // The grid cell context menu requests copying a value or cell reference
+            let tooltip_id = egui::Id::new("grid-cell-copypasta");
+            if let Some(text) = self.copy_cell.take() {
+                self.copy_text(text, tooltip_id);
+            }
+            self.tooltip(ctx, ui, tooltip_id, Duration::from_secs(3));
+
+            // Copy the whole comparison with Ctrl+C, unless a text field has keyboard focus
+            let tooltip_id = egui::Id::new("grid-copypasta");
+            let copy = ctx
+                .input()
+                .events
+                .iter()
+                .any(|event| matches!(event, egui::Event::Copy));
+            if copy && ui.is_enabled() && !ctx.wants_keyboard_input() {
+                self.copy_grid(tooltip_id);
+            }
+            self.tooltip(ctx, ui, tooltip_id, Duration::from_secs(3));
+
+            ui.separator();
+
+            // Display options
+            ui.label("Compare with:");
+            self.diff_reference_choice(ui, "diff-reference-toolbar");
+            ui.label("Groups:");
+            self.grouping_choice(ui, "grouping-toolbar");
+            ui.label("Perch offsets:");
+            self.perch_offset_choice(ui, "perch-offset-toolbar");
+
+            let positional_diffs = self.config.positional_diffs();
+            if ui
+                .selectable_label(positional_diffs, "Positional diffs")
+                .on_hover_text("Compare tire temperatures position by position.")
+                .clicked()
+            {
+                self.config.set_positional_diffs(!positional_diffs);
+            }
+            let fraction_glyphs = self.config.fraction_glyphs();
+            if ui
+                .selectable_label(fraction_glyphs, "Fractions")
+                .on_hover_text("Show fractions with fraction glyphs, e.g. ⅜ and ¹⁄₁₆.")
+                .clicked()
+            {
+                self.config.set_fraction_glyphs(!fraction_glyphs);
+            }
+            let dim_unchanged = self.config.dim_unchanged();
+            if ui
+                .selectable_label(dim_unchanged, "Dim unchanged")
+                .on_hover_text("Dim values that are the same in every setup.")
+                .clicked()
+            {
+                self.config.set_dim_unchanged(!dim_unchanged);
+            }
+            let lock_widths = self.config.lock_column_widths();
+            if ui
+                .selectable_label(lock_widths, "Lock widths")
+                .on_hover_text(
+                    "Keep the column widths for each car. Drag the column edges in the header to \
+                    resize them.",
+                )
+                .clicked()
+            {
+                self.config.set_lock_column_widths(!lock_widths);
+
+                // Locking keeps the widths that are shown
+                if let (false, Some(car_name), Some(cache)) =
+                    (lock_widths, &self.selected_car_name, &self.grid_cache)
+                {
+                    if self.config.column_widths(car_name).is_none() {
+                        self.config
+                            .set_column_widths(car_name, cache.grid.column_widths());
                     }
                 }
-                RemovedTrack(track_name) => {
-                    if self.selected_track_name.as_ref() == Some(&track_name) {
-                        self.selected_track_name = None;
-                        self.selected_car_name = None;
-                        self.selected_setups.clear();
+            }
+            if lock_widths {
+                let button = egui::Button::new("Reset Widths");
+                let response = ui
+                    .add_enabled(has_car, button)
+                    .on_hover_text("Fit the columns for this car to their contents again.");
+                if response.clicked() {
+                    if let Some(car_name) = &self.selected_car_name {
+                        self.config.reset_column_widths(car_name);
                     }
                 }
             }
-        }
-
-        // Show warning window if necessary.
-        if let hotwatch::Event::Error(error, path) = event {
-            let msg = path.map_or("Error while watching file system".to_string(), |path| {
-                format!("Error while watching path: `{:?}`", path)
-            });
 
-            self.show_warnings.push_front(ShowWarning::new(error, msg));
-        }
-    }
+            // Exports are file operations, which are hidden in presentation mode
+            if self.presentation {
+                return;
+            }
+            ui.separator();
+
+            let can_export = !self.selected_setups.is_empty();
+            ui.add_enabled_ui(can_export, |ui| {
+                ui.menu_button("Export", |ui| {
+                    if ui.button("Table...").clicked() {
+                        ui.close_menu();
+                        self.choose_table_export(window);
+                    }
+                    if ui.button("Image...").clicked() {
+                        ui.close_menu();
+                        self.choose_image_export(window);
+                    }
+                    if ui.button("Bundle...").clicked() {
+                        ui.close_menu();
+                        self.choose_bundle_export(window);
+                    }
+                    let can_export_checklist = self.selected_setups.len() == 2;
+                    if ui
+                        .add_enabled(
+                            can_export_checklist,
+                            egui::Button::new("Change Checklist..."),
+                        )
+                        .on_disabled_hover_text("Select exactly two setups to list the changes.")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.choose_checklist_export(window);
+                    }
+                });
+            });
+        });
+    }
+
+    /// Draw a combo box for choosing the column that each setup is diffed against.
+    fn diff_reference_choice(&mut self, ui: &mut egui::Ui, id_source: &str) {
+        let diff_reference = self.config.diff_reference();
+
+        egui::ComboBox::from_id_source(id_source)
+            .selected_text(diff_reference.to_string())
+            .show_ui(ui, |ui| {
+                let choices = [DiffReference::First, DiffReference::Previous];
+                for choice in &choices {
+                    let checked = diff_reference == *choice;
+                    let response = ui.selectable_label(checked, choice.to_string());
+                    if response.clicked() {
+                        self.config.update_diff_reference(*choice);
+                    }
+                }
+            });
+    }
+
+    /// Draw a combo box for choosing how properties are grouped.
+    fn grouping_choice(&mut self, ui: &mut egui::Ui, id_source: &str) {
+        let grouping = self.config.grouping();
+
+        egui::ComboBox::from_id_source(id_source)
+            .selected_text(grouping.to_string())
+            .show_ui(ui, |ui| {
+                let choices = [Grouping::Export, Grouping::Corner];
+                for choice in &choices {
+                    let checked = grouping == *choice;
+                    let response = ui.selectable_label(checked, choice.to_string());
+                    if response.clicked() {
+                        self.config.update_grouping(*choice);
+                    }
+                }
+            });
+    }
+
+    /// Draw a combo box for choosing the unit for spring perch offsets.
+    fn perch_offset_choice(&mut self, ui: &mut egui::Ui, id_source: &str) {
+        let perch_offset_unit = self.config.get_perch_offset_unit();
+
+        egui::ComboBox::from_id_source(id_source)
+            .selected_text(perch_offset_unit.to_string())
+            .show_ui(ui, |ui| {
+                let choices = [
+                    PerchOffsetUnit::Original,
+                    PerchOffsetUnit::Inches,
+                    PerchOffsetUnit::Millimeters,
+                ];
+                for choice in &choices {
+                    let checked = perch_offset_unit == *choice;
+                    let response = ui.selectable_label(checked, choice.to_string());
+                    if response.clicked() {
+                        self.config.set_perch_offset_unit(*choice);
+                    }
+                }
+            });
+    }
+
+    /// Ask the user to choose the setup exports path with a folder picker.
+    fn choose_setups_path(&self, window: &winit::window::Window) {
+        let event_loop_proxy = self.event_loop_proxy.clone();
+        let f = rfd::AsyncFileDialog::new()
+            .set_parent(window)
+            .set_directory(self.config.get_setups_path())
+            .pick_folder();
+
+        std::thread::spawn(move || {
+            let choice = pollster::block_on(f).map(|selected| selected.path().to_path_buf());
+
+            event_loop_proxy
+                .send_event(UserEvent::SetupPath(choice))
+                .expect("Event loop must exist");
+        });
+    }
+
+    /// Look for the folders where iRacing usually keeps setup exports, and offer them as the setup
+    /// exports path.
+    ///
+    /// The folder picker is shown instead when no folders are found. Setup exports in the folders
+    /// are counted on a worker thread, which sends [`UserEvent::SetupsCounted`] when it is done.
+    fn find_setups_paths(&mut self, window: &winit::window::Window) {
+        let paths = Config::find_setups_paths();
+        if paths.is_empty() {
+            self.choose_setups_path(window);
+            return;
+        }
+
+        self.setups_path_candidates = Some(paths.iter().map(|path| (path.clone(), None)).collect());
+
+        let event_loop_proxy = self.event_loop_proxy.clone();
+        std::thread::spawn(move || {
+            let counts = paths
+                .into_iter()
+                .map(|path| {
+                    let count = count_exports(&path);
+                    (path, count)
+                })
+                .collect();
+
+            // Counts are only informational, so it's fine if the event loop is gone
+            let _ = event_loop_proxy.send_event(UserEvent::SetupsCounted(counts));
+        });
+    }
+
+    /// Show the number of setup exports in each folder offered as the setup exports path.
+    ///
+    /// Counts are ignored when the "iRacing Folders" window has been closed.
+    pub(crate) fn setups_counted(&mut self, counts: Vec<(PathBuf, usize)>) {
+        let candidates = self.setups_path_candidates.iter_mut().flatten();
+        for (path, count) in candidates {
+            if let Some((_, counted)) = counts.iter().find(|(other, _)| other == path) {
+                *count = Some(*counted);
+            }
+        }
+    }
+
+    /// Ask the user where to save a bundle of the selected setups.
+    fn choose_bundle_export(&self, window: &winit::window::Window) {
+        let f = rfd::AsyncFileDialog::new()
+            .set_parent(window)
+            .add_filter("Zip archive", &["zip"])
+            .set_file_name("cartunes-bundle.zip")
+            .save_file();
+        self.send_file_choice(f, UserEvent::ExportBundle);
+    }
+
+    /// Ask the user where to save the whole setups library as JSON.
+    fn choose_library_export(&self, window: &winit::window::Window) {
+        let f = rfd::AsyncFileDialog::new()
+            .set_parent(window)
+            .add_filter("JSON", &["json"])
+            .set_file_name("cartunes-library.json")
+            .save_file();
+        self.send_file_choice(f, UserEvent::ExportLibrary);
+    }
+
+    /// Ask the user where to save a comparison table of the selected setups.
+    fn choose_table_export(&self, window: &winit::window::Window) {
+        let f = rfd::AsyncFileDialog::new()
+            .set_parent(window)
+            .add_filter("Comma separated values", &["csv"])
+            .add_filter("Tab separated values", &["tsv"])
+            .add_filter("Markdown", &["md"])
+            .set_file_name("cartunes-comparison.csv")
+            .save_file();
+        self.send_file_choice(f, UserEvent::ExportTable);
+    }
+
+    /// Ask the user where to save the comparison grid as CSV.
+    fn choose_comparison_export(&self, window: &winit::window::Window) {
+        let f = rfd::AsyncFileDialog::new()
+            .set_parent(window)
+            .add_filter("Comma separated values", &["csv"])
+            .set_file_name("cartunes-comparison.csv")
+            .save_file();
+        self.send_file_choice(f, UserEvent::ExportComparison);
+    }
+
+    /// Ask the user where to save an image of the comparison grid.
+    fn choose_image_export(&self, window: &winit::window::Window) {
+        let f = rfd::AsyncFileDialog::new()
+            .set_parent(window)
+            .add_filter("PNG image", &["png"])
+            .set_file_name("cartunes-comparison.png")
+            .save_file();
+        self.send_file_choice(f, UserEvent::ExportImage);
+    }
+
+    /// Ask the user where to save a checklist of the changes between the two selected setups.
+    fn choose_checklist_export(&self, window: &winit::window::Window) {
+        let f = rfd::AsyncFileDialog::new()
+            .set_parent(window)
+            .add_filter("Text file", &["txt"])
+            .set_file_name("cartunes-checklist.txt")
+            .save_file();
+        self.send_file_choice(f, UserEvent::ExportChecklist);
+    }
+
+    /// Show a busy indicator with a progress bar for the files processed so far.
+    fn busy_indicator(&mut self, ctx: &egui::CtxRef, count: usize, total: usize) {
+        egui::Area::new("busy-indicator")
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    // Animate the ellipsis so the GUI doesn't look frozen between progress reports
+                    let dots = (ui.input().time * 3.0) as usize % 3 + 1;
+                    ui.label(format!("Loading setups{:<3}", ".".repeat(dots)));
+
+                    let progress = if total == 0 {
+                        0.0
+                    } else {
+                        count as f32 / total as f32
+                    };
+                    let text = if total == 0 {
+                        "Looking for setup exports".to_string()
+                    } else {
+                        format!("{} of {} files processed", count, total)
+                    };
+                    ui.add(
+                        egui::ProgressBar::new(progress)
+                            .desired_width(250.0)
+                            .text(text),
+                    );
+                });
+            });
+
+        self.repaint_at(Instant::now() + Duration::from_millis(333));
+    }
+
+    /// Show the Patreon menu item.
+    #[cfg(feature = "web-links")]
+    fn patreon_menu_item(&mut self, ui: &mut egui::Ui) {
+        if ui.button("Support CarTunes on Patreon").clicked() {
+            ui.close_menu();
+            if let Err(err) = webbrowser::open("https://www.patreon.com/blipjoy") {
+                let warning = ShowWarning::new(err, "Unable to open web browser.");
+                self.show_warnings.push_front(warning);
+            }
+        }
+    }
+
+    /// Create a file system watcher.
+    fn watch_setups_path(event_loop_proxy: EventLoopProxy<UserEvent>) -> impl Fn(hotwatch::Event) {
+        move |event| {
+            event_loop_proxy
+                .send_event(UserEvent::FsChange(event))
+                .expect("Event loop must exist");
+        }
+    }
+
+    /// Handle file system change events.
+    ///
+    /// Called by the closure from `Self::watch_setups_path`. Events are queued and applied together
+    /// after a short delay, so copying many setups at once only updates the setups tree once.
+    pub(crate) fn handle_fs_change(&mut self, event: hotwatch::Event) {
+        self.fs_events.push(event);
+        if self.fs_deadline.is_none() {
+            self.fs_deadline = Some(Instant::now() + FS_DEBOUNCE);
+        }
+    }
+
+    /// Apply queued file system events if the deadline has passed.
+    ///
+    /// Events are held while setups are loading, and applied to the new setups tree.
+    fn flush_fs_changes(&mut self) {
+        if self.loading.is_some() {
+            return;
+        }
+
+        match self.fs_deadline {
+            Some(deadline) if deadline <= Instant::now() => {
+                let events = std::mem::take(&mut self.fs_events);
+                self.fs_deadline = None;
+                self.apply_fs_changes(events);
+            }
+            Some(deadline) => self.repaint_at(deadline),
+            None => (),
+        }
+    }
+
+    /// Apply a batch of file system events to the setups tree.
+    fn apply_fs_changes(&mut self, events: Vec<hotwatch::Event>) {
+        // Exports that are still growing are checked again after another delay
+        let events = if self.config.wait_for_writes() {
+            let (events, unsettled) = settle_events(events, &mut self.fs_sizes);
+            if !unsettled.is_empty() {
+                self.fs_events
+                    .extend(unsettled.into_iter().map(hotwatch::Event::Write));
+                let deadline = Instant::now() + FS_DEBOUNCE;
+                self.fs_deadline = Some(deadline);
+                self.repaint_at(deadline);
+            }
+
+            events
+        } else {
+            events
+        };
+
+        // Update the setups tree.
+        let updates = self.setups.update_batch(&events, &mut self.config);
+        let mut retries = HashMap::new();
+        for path in self.apply_updates(updates) {
+            let attempts = self.fs_retries.get(&path).copied().unwrap_or(0) + 1;
+            if attempts > FS_MAX_RETRIES {
+                let msg = format!(
+                    "Setup export is still incomplete after {} attempts to load it: `{}`",
+                    FS_MAX_RETRIES,
+                    path.to_string_lossy(),
+                );
+                let warning = SetupError::EmptyOrTruncated {
+                    path,
+                    complete: false,
+                };
+                self.show_warnings
+                    .push_front(ShowWarning::new(warning, msg));
+            } else {
+                retries.insert(path, attempts);
+            }
+        }
+
+        // Try loading incomplete setup exports again after a short delay
+        for path in retries.keys() {
+            self.fs_events.push(hotwatch::Event::Write(path.clone()));
+        }
+        if !retries.is_empty() {
+            let deadline = Instant::now() + FS_RETRY_DELAY;
+            self.fs_deadline = Some(deadline);
+            self.repaint_at(deadline);
+        }
+        self.fs_retries = retries;
+
+        // Show warning window if necessary.
+        for event in events {
+            if let hotwatch::Event::Error(error, path) = event {
+                let msg = path.map_or("Error while watching file system".to_string(), |path| {
+                    format!("Error while watching path: `{:?}`", path)
+                });
+
+                self.show_warnings.push_front(ShowWarning::new(error, msg));
+            }
+        }
+    }
+
+    /// Update the selection for changes to the setups tree.
+    ///
+    /// Returns the paths of setup exports that were incomplete.
+    fn apply_updates(&mut self, updates: Vec<UpdateKind>) -> Vec<PathBuf> {
+        use crate::setup::UpdateKind::*;
+
+        // Updated setups keep their paths, so the grid is laid out again
+        if !updates.is_empty() {
+            self.grid_cache = None;
+        }
+
+        // Positions in the updates are relative to the setups tree during the batch, not after it
+        let mut offsets = self.track_offsets(&updates);
+        let mut incomplete = Vec::new();
+        for update in updates {
+            match update {
+                AddedSetup(track_name, car_name, index) => {
+                    let index = self.available_index(&offsets, &track_name, &car_name, index);
+                    if let Some(index) = index {
+                        offsets.insert(&track_name);
+
+                        // Update selected setups when a new one is added
+                        selection::insert(&mut self.selected_setups, index);
+
+                        // New setups get their own column color, or stay unselected
+                        if self.config.auto_select_new_setups()
+                            && self.selected_setups.len() < self.config.colors().len()
+                        {
+                            self.selected_setups.push(index);
+                        }
+                    }
+                }
+                RemovedSetup(track_name, car_name, index) => {
+                    let index = self.available_index(&offsets, &track_name, &car_name, index);
+                    if let Some(index) = index {
+                        offsets.remove(&track_name);
+
+                        // Update selected setups when an old one is removed
+                        selection::remove(&mut self.selected_setups, index);
+                    }
+                }
+                RemovedCar(track_name, car_name) => {
+                    self.scroll_offsets
+                        .remove(&format!("{}{}", track_name, car_name));
+
+                    if self.selected_track_name.as_ref() == Some(&track_name)
+                        && self.selected_car_name.as_ref() == Some(&car_name)
+                    {
+                        self.selected_car_name = None;
+                        self.selected_setups.clear();
+                    }
+                }
+                RemovedTrack(track_name) => {
+                    if self.selected_track_name.as_ref() == Some(&track_name) {
+                        self.selected_track_name = None;
+                        self.selected_car_name = None;
+                        self.selected_setups.clear();
+                    }
+                }
+                Incomplete(path) => incomplete.push(path),
+            }
+        }
+
+        // When browsing by car, the car stays selected while it has setups at any track
+        if let Some(car_name) = self.selected_car_name.as_ref() {
+            if self.config.browse() == Browse::Car && self.setups.car_setups(car_name).is_empty() {
+                self.selected_car_name = None;
+                self.selected_setups.clear();
+            }
+        }
+
+        self.validate_selection();
+        self.unwatch_removed_files();
+
+        incomplete
+    }
+
+    /// Drop selected setups that are no longer available, instead of panicking when they are used.
+    ///
+    /// Returns the number of selected setups that were dropped.
+    fn validate_selection(&mut self) -> usize {
+        let len = self.available_setups().len();
+        let dropped = selection::retain_valid(&mut self.selected_setups, len);
+        if dropped > 0 {
+            warn!("Dropped {} stale setup selections", dropped);
+        }
+
+        dropped
+    }
+
+    /// Stop watching opened setup exports that are no longer in the setups tree.
+    fn unwatch_removed_files(&mut self) {
+        let removed: Vec<_> = self
+            .watched_files
+            .iter()
+            .filter(|path| self.setups.find(path).is_none())
+            .cloned()
+            .collect();
+
+        for path in removed {
+            self.watched_files.remove(&path);
+            if let Err(error) = self.hotwatch.unwatch(&path) {
+                warn!(
+                    "Unable to stop watching setup export `{:?}`: {}",
+                    path, error
+                );
+            }
+        }
+    }
+
+    /// Exclude a setup export or folder from the library.
+    ///
+    /// Setups in the path are removed immediately, and are skipped when loading and watching
+    /// setups until the path is included again in preferences.
+    fn exclude_from_library(&mut self, path: &Path) {
+        if !self.config.exclude_path(path) {
+            self.show_warnings.push_front(ShowWarning::new(
+                format!("`{}`", path.to_string_lossy()),
+                "Only paths within the setup exports path can be excluded from the library.",
+            ));
+            return;
+        }
+
+        let updates = self.setups.remove_excluded(&self.config);
+        self.apply_updates(updates);
+    }
+
+    /// Include a setup export or folder in the library again, and reload setups.
+    fn include_in_library(&mut self, path: &Path) {
+        self.config.include_path(path);
+        self.reload_setups();
+    }
 
     /// Update setups export path.
     pub(crate) fn update_setups_path<P: AsRef<Path>>(&mut self, setups_path: P) {
         if let Err(error) = self.hotwatch.unwatch(self.config.get_setups_path()) {
             self.show_warnings.push_front(ShowWarning::new(
-                error,
-                format!(
-                    "Unable to stop watching setup exports path for changes: `{:?}`",
-                    self.config.get_setups_path()
-                ),
+                error,
+                format!(
+                    "Unable to stop watching setup exports path for changes: `{:?}`",
+                    self.config.get_setups_path()
+                ),
+            ));
+        }
+
+        // Both paths are remembered, so the user can switch back with one click
+        self.config.remember_setups_path();
+        self.config.update_setups_path(setups_path);
+        self.config.remember_setups_path();
+
+        // The library subfolder is relative to the old path
+        self.config.update_setups_scope("");
+        self.reload_setups();
+
+        let watcher = Self::watch_setups_path(self.event_loop_proxy.clone());
+        if let Err(error) = self.hotwatch.watch(self.config.get_setups_path(), watcher) {
+            self.show_warnings.push_front(ShowWarning::new(
+                error,
+                format!(
+                    "Unable to watch setup exports path for changes: `{:?}`",
+                    self.config.get_setups_path()
+                ),
+            ));
+        }
+    }
+
+    /// Restrict the library to a subfolder of the setup exports path.
+    ///
+    /// An empty path restores the full library. The whole setup exports path remains watched, so
+    /// setups that are moved into or out of the subfolder are tracked.
+    pub(crate) fn update_setups_scope<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref();
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let scope = if path.as_os_str().is_empty() {
+            Path::new("")
+        } else if let Ok(scope) = path.strip_prefix(self.config.get_setups_path()) {
+            scope
+        } else {
+            self.show_warnings.push_front(ShowWarning::new(
+                format!("`{}`", path.to_string_lossy()),
+                "The library subfolder must be within the setup exports path.",
+            ));
+            return;
+        };
+
+        self.config.update_setups_scope(scope);
+        self.reload_setups();
+    }
+
+    /// Load all setups on a worker thread.
+    ///
+    /// The GUI stays responsive while loading, and the setups tree is replaced when the loader
+    /// sends [`UserEvent::SetupsLoaded`]. Starting a new load discards the results of any load that
+    /// is still running.
+    pub(crate) fn reload_setups(&mut self) {
+        self.load_generation += 1;
+        self.loading = Some((0, 0));
+
+        let generation = self.load_generation;
+        let config = self.config.clone();
+        let event_loop_proxy = self.event_loop_proxy.clone();
+
+        std::thread::spawn(move || {
+            let mut last_progress = Instant::now();
+            let (setups, errors) = Setups::load(&config, |count, total| {
+                // The total is always reported as soon as it is known
+                if count == 0 || last_progress.elapsed() >= LOAD_PROGRESS_INTERVAL {
+                    last_progress = Instant::now();
+
+                    // Progress is only informational, so it's fine if the event loop is gone
+                    let event = UserEvent::SetupsProgress(generation, count, total);
+                    let _ = event_loop_proxy.send_event(event);
+                }
+            });
+
+            event_loop_proxy
+                .send_event(UserEvent::SetupsLoaded(generation, setups, errors))
+                .expect("Event loop must exist");
+        });
+    }
+
+    /// Update the number of files processed by the setup loader.
+    pub(crate) fn setups_progress(&mut self, generation: u64, count: usize, total: usize) {
+        if generation == self.load_generation && self.loading.is_some() {
+            self.loading = Some((count, total));
+        }
+    }
+
+    /// Replace the setups tree with the results from the setup loader.
+    pub(crate) fn setups_loaded(
+        &mut self,
+        generation: u64,
+        setups: Setups,
+        errors: Vec<(SetupError, String)>,
+    ) {
+        if generation != self.load_generation {
+            return;
+        }
+
+        self.setups = setups;
+        self.grid_cache = None;
+        self.loading = None;
+
+        // Paths may have been excluded from the library while loading
+        self.setups.remove_excluded(&self.config);
+        self.unwatch_removed_files();
+        self.clear_filters();
+        self.scroll_offsets.clear();
+
+        for (err, context) in errors {
+            self.show_warnings
+                .push_front(ShowWarning::new(err, context));
+        }
+
+        let paths = std::mem::take(&mut self.pending_open);
+        if !paths.is_empty() {
+            self.open_files(paths);
+        }
+    }
+
+    /// Open setup exports and select them for comparison.
+    ///
+    /// Files outside of the setup exports path are loaded and watched until they leave the setups
+    /// tree, e.g. when setups are reloaded. While setups are loading, the files are opened when
+    /// loading finishes. Every file that cannot be opened is reported.
+    ///
+    /// Only setups for the same car as the first file are selected. When they are from different
+    /// tracks, setups are browsed by car so they can be compared.
+    pub(crate) fn open_files(&mut self, paths: Vec<PathBuf>) {
+        if self.loading.is_some() {
+            self.pending_open = paths;
+            return;
+        }
+
+        let mut opened = Vec::new();
+        for path in paths {
+            let path = path.canonicalize().unwrap_or(path);
+
+            if self.setups.find(&path).is_none() {
+                if let Err(err) = self.setups.open(&path, &self.config) {
+                    self.show_warnings.push_front(ShowWarning::new(
+                        err,
+                        format!("Unable to open setup export `{}`.", path.to_string_lossy()),
+                    ));
+                    continue;
+                }
+
+                if !path.starts_with(self.config.get_setups_path())
+                    && !self.watched_files.contains(&path)
+                {
+                    let watcher = Self::watch_setups_path(self.event_loop_proxy.clone());
+                    match self.hotwatch.watch(&path, watcher) {
+                        Ok(()) => {
+                            self.watched_files.insert(path.clone());
+                        }
+                        Err(error) => {
+                            self.show_warnings.push_front(ShowWarning::new(
+                                error,
+                                format!(
+                                    "Unable to watch setup export for changes: `{}`",
+                                    path.to_string_lossy()
+                                ),
+                            ));
+                        }
+                    }
+                }
+            }
+
+            opened.push(path);
+        }
+
+        // Find the setups after all are opened, since opening a setup may move the others
+        let found: Vec<_> = opened
+            .iter()
+            .filter_map(|path| self.setups.find(path))
+            .map(|(track_name, car_name, index)| {
+                (track_name.to_string(), car_name.to_string(), index)
+            })
+            .collect();
+        let car_name = match found.first() {
+            Some((_, car_name, _)) => car_name.clone(),
+            None => return,
+        };
+
+        let (found, other_cars): (Vec<_>, Vec<_>) = found
+            .into_iter()
+            .partition(|(_, other, _)| *other == car_name);
+        if !other_cars.is_empty() {
+            let names: Vec<_> = other_cars
+                .iter()
+                .map(|(_, name, _)| name.as_str())
+                .collect();
+            self.show_warnings.push_front(ShowWarning::new(
+                format!("Only setups for `{}` are selected.", car_name),
+                format!(
+                    "Setups for different cars cannot be compared: `{}`",
+                    names.join("`, `"),
+                ),
+            ));
+        }
+
+        if found
+            .iter()
+            .any(|(track_name, ..)| *track_name != found[0].0)
+        {
+            self.config.update_browse(Browse::Car);
+        }
+        let setups: Vec<_> = found
+            .iter()
+            .map(|(track_name, _, index)| (track_name.as_str(), *index))
+            .collect();
+        self.select_setups(&car_name, &setups);
+    }
+
+    /// Send the file chosen in a file dialog to the event loop.
+    fn send_file_choice<F>(&self, f: F, event: fn(Option<PathBuf>) -> UserEvent)
+    where
+        F: std::future::Future<Output = Option<rfd::FileHandle>> + Send + 'static,
+    {
+        let event_loop_proxy = self.event_loop_proxy.clone();
+
+        std::thread::spawn(move || {
+            let choice = pollster::block_on(f).map(|selected| selected.path().to_path_buf());
+
+            event_loop_proxy
+                .send_event(event(choice))
+                .expect("Event loop must exist");
+        });
+    }
+
+    /// Export the selected setups to a bundle.
+    ///
+    /// The bundle is named for the track of the first selected setup, when browsing by car.
+    pub(crate) fn export_bundle(&mut self, path: PathBuf) {
+        let setups = self.available_setups();
+        let (track_name, car_name) = match (
+            self.selected_setups.first(),
+            self.selected_car_name.as_ref(),
+        ) {
+            (Some(&first), Some(car_name)) => (setups[first].0, car_name),
+            _ => return,
+        };
+        let paths: Vec<_> = self
+            .selected_setups
+            .iter()
+            .map(|&i| {
+                let setup_path = setups[i].1.path();
+                (
+                    setup_path,
+                    self.config.get_note(setup_path),
+                    self.config.is_favorite(setup_path),
+                )
+            })
+            .collect();
+
+        if let Err(err) = bundle::export(&path, track_name, car_name, &paths) {
+            self.show_warnings.push_front(ShowWarning::new(
+                err,
+                format!("Unable to export bundle `{}`.", path.to_string_lossy()),
+            ));
+        }
+    }
+
+    /// Create a comparison table of the selected setups.
+    ///
+    /// Returns `None` when no setups are selected. Properties are regrouped by corner when
+    /// `grouping` asks for it, and gears are lined up like the grid. Hidden property groups are
+    /// left out when `include_hidden` is false. Hidden setup columns are left out when
+    /// `include_hidden_setups` is false.
+    fn comparison_table(
+        &self,
+        grouping: Grouping,
+        include_hidden: bool,
+        include_hidden_setups: bool,
+    ) -> Option<Table> {
+        let car_name = self.selected_car_name.as_ref()?;
+        if self.selected_setups.is_empty() {
+            return None;
+        }
+
+        let browse = self.config.browse();
+        let clean_names = self.config.clean_setup_names();
+        let setups = self.available_setups();
+        let setups: Vec<_> = self
+            .selected_setups
+            .iter()
+            .map(|&i| setups[i])
+            .filter(|(_, info)| include_hidden_setups || !self.hidden_setups.contains(info.path()))
+            .map(|(track_name, info)| {
+                let setup = match grouping {
+                    Grouping::Export => Cow::Borrowed(info.setup()),
+                    Grouping::Corner => Cow::Owned(group_by_corner(info.setup())),
+                };
+
+                (setup_label(browse, track_name, info, clean_names), setup)
+            })
+            .collect();
+        let (names, setups): (Vec<_>, Vec<_>) = setups
+            .iter()
+            .map(|(name, setup)| (name.as_ref(), setup.as_ref()))
+            .unzip();
+        let aligned_setups = align_gears(&setups);
+        let setups: Vec<_> = names
+            .into_iter()
+            .zip(aligned_setups.iter().map(|setup| setup.as_ref()))
+            .collect();
+
+        let hidden_groups = if include_hidden {
+            HashSet::new()
+        } else {
+            self.config.hidden_groups(car_name)
+        };
+
+        Some(Table::new(
+            &setups,
+            &self.config.aliases(car_name),
+            &hidden_groups,
+            self.config.get_perch_offset_unit(),
+        ))
+    }
+
+    /// Copy the selected setups to the clipboard as tab separated values.
+    ///
+    /// The table matches the grid: perch offsets are converted, properties are grouped the same
+    /// way, and hidden groups and setup columns are left out. Spreadsheets accept the copied text
+    /// as cells when pasted.
+    fn copy_grid(&mut self, tooltip_id: egui::Id) {
+        let table = match self.comparison_table(self.config.grouping(), false, false) {
+            Some(table) => table,
+            None => return,
+        };
+        let formatter = Delimited {
+            delimiter: '\t',
+            quoting: Quoting::Minimal,
+        };
+        let output = table.write(&formatter, self.config.table_options().group_column);
+
+        self.copy_text(output, tooltip_id);
+    }
+
+    /// Export a comparison table of the selected setups.
+    ///
+    /// The format is chosen by the file extension: `.tsv` uses the configured delimiter and quoting,
+    /// `.md` writes a Markdown table, and anything else is written as CSV.
+    pub(crate) fn export_table(&mut self, path: PathBuf) {
+        let options = self.config.table_options();
+        let table =
+            match self.comparison_table(Grouping::Export, options.include_hidden_groups, true) {
+                Some(table) => table,
+                None => return,
+            };
+
+        let extension = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let output = match extension.to_lowercase().as_str() {
+            "tsv" => {
+                let formatter = Delimited {
+                    delimiter: options.delimiter,
+                    quoting: options.quoting,
+                };
+                table.write(&formatter, options.group_column)
+            }
+            "md" => table.write(&Markdown, options.group_column),
+            _ => table.write(&Delimited::csv(), options.group_column),
+        };
+
+        if let Err(err) = std::fs::write(&path, output) {
+            self.show_warnings.push_front(ShowWarning::new(
+                err,
+                format!("Unable to export table `{}`.", path.to_string_lossy()),
             ));
         }
+    }
 
-        self.config.update_setups_path(setups_path);
-        self.setups = Setups::new(&mut self.show_warnings, &self.config);
-        self.clear_filters();
+    /// Export the whole setups library as pretty-printed JSON.
+    pub(crate) fn export_library(&mut self, path: PathBuf) {
+        let output = format!("{:#}", self.setups.to_json());
 
-        let watcher = Self::watch_setups_path(self.event_loop_proxy.clone());
-        if let Err(error) = self.hotwatch.watch(self.config.get_setups_path(), watcher) {
+        if let Err(err) = std::fs::write(&path, output) {
             self.show_warnings.push_front(ShowWarning::new(
-                error,
+                err,
+                format!("Unable to export library `{}`.", path.to_string_lossy()),
+            ));
+        }
+    }
+
+    /// Export the comparison grid as it is shown to a CSV file.
+    ///
+    /// The table is made from the grid that was last drawn, so values keep their formatters,
+    /// locale, and fraction glyphs, and the clipboard setup is included. Hidden groups and setup
+    /// columns are left out. Every row starts with its property group and name, regardless of the
+    /// table export options.
+    pub(crate) fn export_comparison(&mut self, path: PathBuf) {
+        let table = match self.grid_cache.as_ref() {
+            Some(cache) => cache.grid.table(),
+            None => return,
+        };
+        let output = table.write(&Delimited::csv(), true);
+
+        if let Err(err) = std::fs::write(&path, output) {
+            self.show_warnings.push_front(ShowWarning::new(
+                err,
+                format!("Unable to export comparison `{}`.", path.to_string_lossy()),
+            ));
+        }
+    }
+
+    /// Draw the comparison grid for the selected setups with a header for exporting as an image.
+    ///
+    /// The header names the track and car, and lists the setups in their column colors. When
+    /// browsing by car, the header only names the car, and each setup is named with its track.
+    pub(crate) fn snapshot(&self, ui: &mut egui::Ui) {
+        let car_name = match self.selected_car_name.as_ref() {
+            Some(car_name) => car_name,
+            None => return,
+        };
+        let browse = self.config.browse();
+        let clean_names = self.config.clean_setup_names();
+        let setups = self.available_setups();
+        let colors = self.config.colors();
+
+        match self.selected_track_name.as_ref() {
+            Some(track_name) if browse == Browse::Track => {
+                ui.heading(format!("{} \u{2013} {}", track_name, car_name));
+            }
+            _ => {
+                ui.heading(car_name.as_str());
+            }
+        }
+
+        // Hidden columns are left out, and the others keep their colors
+        let (selected, colors): (Vec<_>, Vec<_>) = self
+            .selected_setups
+            .iter()
+            .map(|&i| setups[i])
+            .zip(colors.iter().cycle().cloned())
+            .filter(|((_, info), _)| !self.hidden_setups.contains(info.path()))
+            .unzip();
+
+        ui.horizontal_wrapped(|ui| {
+            for (&(track_name, info), color) in selected.iter().zip(&colors) {
+                let name = setup_label(browse, track_name, info, clean_names);
+                ui.label(egui::RichText::new(name).color(*color));
+            }
+        });
+        ui.separator();
+
+        let header = column_header(browse, &selected, self.config.display_locale());
+        let header: Vec<_> = header.iter().map(String::as_str).collect();
+        let setups: Vec<_> = selected.into_iter().map(|(_, info)| info.setup()).collect();
+        let corner_setups: Vec<Setup>;
+        let setups = match self.config.grouping() {
+            Grouping::Export => setups,
+            Grouping::Corner => {
+                corner_setups = setups.into_iter().map(group_by_corner).collect();
+                corner_setups.iter().collect()
+            }
+        };
+        let aligned_setups = align_gears(&setups);
+        let setups: Vec<_> = aligned_setups.iter().map(|setup| setup.as_ref()).collect();
+        let hidden_groups = if self.config.table_options().include_hidden_groups {
+            HashSet::new()
+        } else {
+            self.config.hidden_groups(car_name)
+        };
+        // Images show every group expanded
+        let mut grid_options = self.config.grid_options();
+        grid_options.collapsed_groups.clear();
+        grid_options.formatters = self.config.formatters(car_name);
+        SetupGrid::new(
+            ui,
+            &setups,
+            &colors,
+            self.config.diff_colors(),
+            &self.config.aliases(car_name),
+            &hidden_groups,
+            grid_options,
+        )
+        .with_header(ui, &header)
+        .show(ui, car_name);
+
+        ui.separator();
+        ui.label(
+            egui::RichText::new(concat!("CarTunes version ", env!("CARGO_PKG_VERSION"))).small(),
+        );
+    }
+
+    /// Import a bundle and select its setups.
+    ///
+    /// Setups are extracted into a `bundles` directory in the setup exports path.
+    pub(crate) fn import_bundle(&mut self, path: PathBuf) {
+        let name = path
+            .file_stem()
+            .map_or_else(|| "bundle".to_string(), |s| s.to_string_lossy().to_string());
+        let dest = self.config.get_setups_path().join("bundles").join(name);
+
+        let result = match bundle::import(&path, &dest) {
+            Ok(result) => result,
+            Err(err) => {
+                self.show_warnings.push_front(ShowWarning::new(
+                    err,
+                    format!("Unable to import bundle `{}`.", path.to_string_lossy()),
+                ));
+                return;
+            }
+        };
+
+        for (file_name, note) in &result.manifest.notes {
+            self.config.set_note(&dest.join(file_name), note);
+        }
+        for file_name in &result.manifest.favorites {
+            self.config.set_favorite(&dest.join(file_name), true);
+        }
+
+        // Load the setups now, so the selection can be restored without waiting for the watcher
+        let events: Vec<_> = result
+            .paths
+            .iter()
+            .map(|setup_path| hotwatch::Event::Create(setup_path.clone()))
+            .collect();
+        self.apply_fs_changes(events);
+
+        // All setups are selected for the track and car where the first setup was found
+        let mut missing = result.missing;
+        let mut selection = None;
+        let mut selected_setups = Vec::new();
+        for setup_path in &result.paths {
+            let found = self
+                .setups
+                .find(setup_path)
+                .filter(|&(track_name, car_name, _)| {
+                    let (t, c) = selection
+                        .get_or_insert_with(|| (track_name.to_string(), car_name.to_string()));
+
+                    t == track_name && c == car_name
+                });
+
+            match found {
+                Some((_, _, index)) => selected_setups.push(index),
+                None => {
+                    let file_name = setup_path.file_name().unwrap_or_default();
+                    missing.push(file_name.to_string_lossy().to_string());
+                }
+            }
+        }
+
+        if let Some((track_name, car_name)) = selection {
+            let setups: Vec<_> = selected_setups
+                .iter()
+                .map(|&index| (track_name.as_str(), index))
+                .collect();
+            self.select_setups(&car_name, &setups);
+        }
+
+        if !missing.is_empty() {
+            let manifest = &result.manifest;
+            self.show_warnings.push_front(ShowWarning::new(
+                bundle::Error::MissingSetups(missing),
                 format!(
-                    "Unable to watch setup exports path for changes: `{:?}`",
-                    self.config.get_setups_path()
+                    "Some setups for {} at {} could not be imported.",
+                    manifest.car_name, manifest.track_name,
                 ),
             ));
         }
     }
 
+    /// Copy a summary of the changes between the two selected setups to the clipboard.
+    ///
+    /// The first selected setup is the baseline.
+    fn copy_diff_summary(&mut self, tooltip_id: egui::Id) {
+        let car_name = match self.selected_car_name.as_ref() {
+            Some(car_name) => car_name,
+            None => return,
+        };
+        let (baseline, other) = match self.selected_setups.as_slice() {
+            &[baseline, other] => (baseline, other),
+            _ => return,
+        };
+        let setups = self.available_setups();
+        let summary = diff_summary(
+            setups[baseline].1.setup(),
+            setups[other].1.setup(),
+            &self.config.aliases(car_name),
+            self.config.get_perch_offset_unit(),
+        );
+        let summary = if summary.is_empty() {
+            "No differences.".to_string()
+        } else {
+            summary
+        };
+
+        self.copy_text(summary, tooltip_id);
+    }
+
+    /// Create a checklist of the changes that turn the first selected setup into the second.
+    ///
+    /// The checklist names both setups and the car, and steps are grouped the same way as the grid.
+    /// Returns `None` unless exactly two setups are selected.
+    fn change_checklist(&self) -> Option<String> {
+        let car_name = self.selected_car_name.as_ref()?;
+        let (baseline, target) = match self.selected_setups.as_slice() {
+            &[baseline, target] => (baseline, target),
+            _ => return None,
+        };
+        let browse = self.config.browse();
+        let clean_names = self.config.clean_setup_names();
+        let setups = self.available_setups();
+        let (baseline, target) = (setups[baseline], setups[target]);
+
+        let grouped = |info: &SetupInfo| match self.config.grouping() {
+            Grouping::Export => Cow::Borrowed(info.setup()),
+            Grouping::Corner => Cow::Owned(group_by_corner(info.setup())),
+        };
+        let checklist = diff_checklist(
+            &grouped(baseline.1),
+            &grouped(target.1),
+            &self.config.aliases(car_name),
+            self.config.get_perch_offset_unit(),
+        );
+        let checklist = if checklist.is_empty() {
+            "No changes needed.\n".to_string()
+        } else {
+            checklist
+        };
+
+        Some(format!(
+            "Changes from {} to {} ({})\n\n{}",
+            setup_label(browse, baseline.0, baseline.1, clean_names),
+            setup_label(browse, target.0, target.1, clean_names),
+            car_name,
+            checklist,
+        ))
+    }
+
+    /// Copy a checklist of the changes between the two selected setups to the clipboard.
+    fn copy_checklist(&mut self, tooltip_id: egui::Id) {
+        let checklist = match self.change_checklist() {
+            Some(checklist) => checklist,
+            None => return,
+        };
+
+        self.copy_text(checklist, tooltip_id);
+    }
+
+    /// Save a checklist of the changes between the two selected setups to a text file.
+    pub(crate) fn export_checklist(&mut self, path: PathBuf) {
+        let checklist = match self.change_checklist() {
+            Some(checklist) => checklist,
+            None => return,
+        };
+
+        if let Err(err) = std::fs::write(&path, checklist) {
+            self.show_warnings.push_front(ShowWarning::new(
+                err,
+                format!("Unable to export checklist `{}`.", path.to_string_lossy()),
+            ));
+        }
+    }
+
+    /// Copy text to the clipboard, and confirm it with a tooltip.
+    fn copy_text(&mut self, text: String, tooltip_id: egui::Id) {
+        let mut copied = false;
+        if let Ok(mut clipboard) = ClipboardContext::new() {
+            copied = clipboard.set_contents(text).is_ok();
+        }
+
+        let label = if copied {
+            "Copied!"
+        } else {
+            "Sorry, but the clipboard isn't working..."
+        };
+
+        self.add_tooltip(tooltip_id, label);
+    }
+
+    /// Copy a setup export path to the clipboard.
+    fn copy_setup_path(&mut self, path: &Path, tooltip_id: egui::Id) {
+        self.copy_text(display_path(path).into_owned(), tooltip_id);
+    }
+
+    /// Select the baseline setup for the selected car as the first column, which every other column
+    /// is diffed against.
+    ///
+    /// The baseline is the first setup with a file name matching one of the configured patterns.
+    /// A hint is shown when there is no match.
+    fn compare_with_baseline(&mut self, tooltip_id: egui::Id) {
+        let setups = self.available_setups();
+        let patterns = self.config.baseline_patterns();
+        let baseline = setups.iter().position(|(_, info)| {
+            patterns
+                .iter()
+                .any(|pattern| info.name().wildcard_match(pattern))
+        });
+
+        match baseline {
+            Some(index) => {
+                self.selected_setups.retain(|&i| i != index);
+                self.selected_setups.insert(0, index);
+            }
+            None => self.add_tooltip(
+                tooltip_id,
+                "No baseline setup found for this car. \
+                File name patterns can be changed with `baseline_patterns` in the config file.",
+            ),
+        }
+    }
+
+    /// Select the most recently modified setups for the selected car, oldest first.
+    ///
+    /// The number of setups is chosen in preferences, but only as many as there are column colors
+    /// are selected. Setups modified at the same time are always chosen in the same order.
+    pub(crate) fn compare_newest(&mut self) {
+        if self.selected_car_name.is_none()
+            || !self.show_errors.is_empty()
+            || self.loading.is_some()
+        {
+            return;
+        }
+
+        let setups = self.available_setups();
+        let keys: Vec<_> = setups
+            .iter()
+            .map(|(_, info)| (info.modified(), info.path()))
+            .collect();
+        let count = self.config.newest_count().min(self.config.colors().len());
+        let newest = selection::newest(&keys, count);
+
+        self.selected_setups = newest;
+    }
+
+    /// Parse a setup export from the clipboard and compare it in an extra column.
+    ///
+    /// The setup is not written to disk. It must be for the selected car.
+    fn compare_with_clipboard(&mut self, tooltip_id: egui::Id) {
+        let html = match ClipboardContext::new().and_then(|mut clipboard| clipboard.get_contents())
+        {
+            Ok(html) => html,
+            Err(_) => {
+                self.add_tooltip(tooltip_id, "Sorry, but the clipboard isn't working...");
+                return;
+            }
+        };
+
+        match setup_from_clipboard(&html, &self.config) {
+            Ok((track_name, car_name, setup)) => {
+                if self.selected_car_name.as_ref() == Some(&car_name) {
+                    self.clipboard_setup = Some(ClipboardSetup {
+                        track_name,
+                        car_name,
+                        info: SetupInfo::new(setup, "Clipboard".to_string(), PathBuf::new()),
+                    });
+                    self.grid_cache = None;
+                } else {
+                    self.show_warnings.push_front(ShowWarning::new(
+                        format!("The clipboard setup is for `{}`.", car_name),
+                        "Only setups for the same car can be compared.",
+                    ));
+                }
+            }
+            Err(err) => {
+                self.show_warnings.push_front(ShowWarning::new(
+                    err,
+                    "Unable to read a setup export from the clipboard.",
+                ));
+            }
+        }
+    }
+
+    /// Toggle the setup shown at `index` for the selected car in or out of the comparison.
+    ///
+    /// Ignored when number keys are disabled, or while a modal error or loading is shown.
+    pub(crate) fn toggle_setup(&mut self, index: usize) {
+        if !self.config.number_key_toggles()
+            || !self.show_errors.is_empty()
+            || self.loading.is_some()
+        {
+            return;
+        }
+
+        // Number keys follow the order that setups are shown in
+        let index = match self.display_order().get(index) {
+            Some(&index) => index,
+            None => return,
+        };
+
+        match self.selected_setups.iter().position(|&i| i == index) {
+            Some(position) => {
+                self.selected_setups.remove(position);
+            }
+            None => self.selected_setups.push(index),
+        }
+    }
+
+    /// Write all settings to the config file, including the window geometry.
+    ///
+    /// This is how settings are saved when they are not saved automatically on close.
+    fn save_settings(&mut self, window: &winit::window::Window) {
+        self.config.update_window(window);
+
+        if let Err(err) = self.config.write_toml() {
+            let warning = ShowWarning::new(err, "Unable to write the config file.");
+            self.show_warnings.push_front(warning);
+        }
+    }
+
+    /// Reset all settings to defaults and write the config file.
+    ///
+    /// Setups are reloaded afterward.
+    pub(crate) fn reset_config(&mut self, options: ResetOptions) {
+        if let Err(err) = self.config.reset(options.keep_names) {
+            let warning = ShowWarning::new(err, "Unable to reset settings to defaults.");
+            self.show_warnings.push_front(warning);
+            return;
+        }
+
+        let setups_path = if options.keep_setups_path {
+            self.config.get_setups_path().to_path_buf()
+        } else {
+            Config::default_setups_path()
+        };
+        self.update_setups_path(setups_path);
+
+        if let Err(err) = self.config.write_toml() {
+            let warning = ShowWarning::new(err, "Unable to write the config file.");
+            self.show_warnings.push_front(warning);
+        }
+
+        // Apply the default theme and update check frequency
+        self.event_loop_proxy
+            .send_event(UserEvent::Theme(*self.config.theme()))
+            .expect("Event loop must exist");
+        #[cfg(feature = "update-check")]
+        self.event_loop_proxy
+            .send_event(UserEvent::UpdateCheck)
+            .expect("Event loop must exist");
+    }
+
+    /// Get the setups that can be selected for comparison, with the track name for each setup.
+    fn available_setups(&self) -> Vec<(&str, &SetupInfo)> {
+        available_setups(
+            &self.setups,
+            self.config.browse(),
+            self.selected_track_name.as_ref(),
+            self.selected_car_name.as_ref(),
+        )
+    }
+
+    /// Count the selected car's setups at each track, as they were before a batch of updates.
+    fn track_offsets(&self, updates: &[UpdateKind]) -> TrackOffsets {
+        use crate::setup::UpdateKind::*;
+
+        let car_name = self.selected_car_name.as_deref().unwrap_or_default();
+        let track_names = self
+            .setups
+            .car_setups(car_name)
+            .into_iter()
+            .map(|(track_name, _)| track_name);
+        let changes = updates.iter().filter_map(|update| match update {
+            AddedSetup(track_name, other, _) if other == car_name => {
+                Some((track_name.as_str(), true))
+            }
+            RemovedSetup(track_name, other, _) if other == car_name => {
+                Some((track_name.as_str(), false))
+            }
+            _ => None,
+        });
+
+        TrackOffsets::new(track_names, changes)
+    }
+
+    /// Get the positions of the available setups in the order they are shown for selection.
+    ///
+    /// Selection indices refer to the available setups, so only the display order changes.
+    fn display_order(&self) -> Vec<usize> {
+        let favorites: Vec<_> = self
+            .available_setups()
+            .iter()
+            .map(|(_, info)| self.config.is_favorite(info.path()))
+            .collect();
+
+        selection::display_order(&favorites, &self.selected_setups, self.favorites_only)
+    }
+
+    /// Get the position of a setup in the available setups, if it is available.
+    ///
+    /// `index` is the position of the setup among the setups for the car at the track, and
+    /// `offsets` counts the setups at each track when browsing by car.
+    fn available_index(
+        &self,
+        offsets: &TrackOffsets,
+        track_name: &str,
+        car_name: &str,
+        index: usize,
+    ) -> Option<usize> {
+        if self.selected_car_name.as_deref() != Some(car_name) {
+            return None;
+        }
+
+        match self.config.browse() {
+            Browse::Track => {
+                Some(index).filter(|_| self.selected_track_name.as_deref() == Some(track_name))
+            }
+            Browse::Car => Some(offsets.position(track_name, index)),
+        }
+    }
+
+    /// Select setups for comparison by the car and the tracks where they were found.
+    ///
+    /// `setups` has the track name for each setup, and its position among the setups for the car at
+    /// that track. The track of the first setup is selected when browsing by track.
+    fn select_setups(&mut self, car_name: &str, setups: &[(&str, usize)]) {
+        self.selected_track_name = setups
+            .first()
+            .map(|(track_name, _)| track_name.to_string())
+            .filter(|_| self.config.browse() == Browse::Track);
+        self.selected_car_name = Some(car_name.to_string());
+        let offsets = self.track_offsets(&[]);
+        self.selected_setups = setups
+            .iter()
+            .filter_map(|&(track_name, index)| {
+                self.available_index(&offsets, track_name, car_name, index)
+            })
+            .collect();
+        self.validate_selection();
+    }
+
+    /// Show the drop-down for browsing setups by track or by car.
+    fn browse_selection(&mut self, ui: &mut egui::Ui) {
+        let browse = self.config.browse();
+
+        ui.label("Browse:");
+        egui::ComboBox::from_id_source("browse-selection")
+            .selected_text(browse.to_string())
+            .show_ui(ui, |ui| {
+                for choice in [Browse::Track, Browse::Car] {
+                    let response = ui.selectable_label(browse == choice, choice.to_string());
+                    if response.clicked() && browse != choice {
+                        self.config.update_browse(choice);
+
+                        // Setup positions differ between modes, but the car can stay selected
+                        self.selected_track_name = None;
+                        self.selected_setups.clear();
+                        if choice == Browse::Track {
+                            self.selected_car_name = None;
+                        }
+                    }
+                }
+            });
+    }
+
     /// Clear track, car, and setup filters.
     fn clear_filters(&mut self) {
         self.selected_track_name = None;
@@ -389,12 +2523,15 @@ impl Gui {
             None => track_selection,
         };
         track_selection.show_ui(ui, |ui| {
-            let mut track_names: Vec<_> = self.setups.tracks().keys().collect();
-            track_names.sort_unstable_by(|a, b| a.human_compare(b));
+            let mut track_names = self.setups.track_names();
+            sort_names(&mut track_names, self.config.sort_order(), |name| {
+                self.config.track_last_used(name)
+            });
 
             for track_name in track_names {
                 let checked = self.selected_track_name.as_ref() == Some(track_name);
                 if ui.selectable_label(checked, track_name).clicked() {
+                    self.config.touch_track(track_name);
                     self.selected_track_name = Some(track_name.to_string());
                     self.selected_car_name = None;
                     self.selected_setups.clear();
@@ -407,22 +2544,23 @@ impl Gui {
     fn car_selection(&mut self, ui: &mut egui::Ui) {
         ui.label("Car:");
 
+        // Cars at the selected track, or at every track when browsing by car
+        let car_names: Option<Vec<_>> = match self.config.browse() {
+            Browse::Track => self
+                .selected_track_name
+                .as_ref()
+                .map(|track_name| self.setups.track_car_names(track_name)),
+            Browse::Car => Some(self.setups.car_names()),
+        };
+
         // Create a child Ui that can be temporarily disabled
         ui.scope(|ui| {
-            ui.set_enabled(self.selected_track_name.is_some());
+            ui.set_enabled(car_names.is_some());
 
             let car_selection = egui::ComboBox::from_id_source("car-selection");
-            let car_selection = match self.selected_track_name.as_ref() {
-                Some(track_name) => {
-                    // TODO: DRY
-                    let car_names = self
-                        .setups
-                        .tracks()
-                        .get(track_name)
-                        .expect("Invalid track name")
-                        .keys();
-
-                    car_selection.width(get_combo_box_width(ui, car_names))
+            let car_selection = match car_names.as_ref() {
+                Some(car_names) => {
+                    car_selection.width(get_combo_box_width(ui, car_names.iter().copied()))
                 }
                 None => car_selection,
             };
@@ -431,19 +2569,15 @@ impl Gui {
                 None => car_selection,
             };
             car_selection.show_ui(ui, |ui| {
-                if let Some(track_name) = self.selected_track_name.as_ref() {
-                    let mut car_names: Vec<_> = self
-                        .setups
-                        .tracks()
-                        .get(track_name)
-                        .expect("Invalid track name")
-                        .keys()
-                        .collect();
-                    car_names.sort_unstable_by(|a, b| a.human_compare(b));
+                if let Some(mut car_names) = car_names {
+                    sort_names(&mut car_names, self.config.sort_order(), |name| {
+                        self.config.car_last_used(name)
+                    });
 
                     for car_name in car_names {
                         let checked = self.selected_car_name.as_ref() == Some(car_name);
                         if ui.selectable_label(checked, car_name).clicked() {
+                            self.config.touch_car(car_name);
                             self.selected_car_name = Some(car_name.to_string());
                             self.selected_setups.clear();
                         }
@@ -454,66 +2588,206 @@ impl Gui {
     }
 
     /// Show setup selection check boxes.
+    ///
+    /// Returns the selected track and car names, and the selected setups with the track name for
+    /// each setup. The track name is empty when browsing by car. A setup pasted from the clipboard
+    /// is selected last, until it is unchecked.
+    ///
+    /// Starred setups are shown first. Only those, and the selected setups, are shown when the
+    /// "Favorites only" filter is enabled.
+    ///
+    /// Setups with hidden columns are left out, and the color of each remaining column is returned.
+    /// Columns keep the color of their position in the selection, so hiding one does not change
+    /// the colors of the others.
     fn setup_selection(
         &mut self,
         ui: &mut egui::Ui,
         colors: &[egui::Color32],
-    ) -> (&str, &str, Vec<&Setup>) {
+    ) -> (&str, &str, Vec<(&str, &SetupInfo)>, Vec<egui::Color32>) {
         let mut output = Vec::new();
-        let mut output_track_name = "";
-        let mut output_car_name = "";
 
+        // The clipboard setup is removed when unchecked, or when the selected car changes
+        let clear_clipboard_setup = std::mem::take(&mut self.clear_clipboard_setup);
+        let car_changed = self.clipboard_setup.as_ref().map_or(false, |clipboard| {
+            self.selected_car_name.as_ref() != Some(&clipboard.car_name)
+        });
+        if clear_clipboard_setup || car_changed {
+            self.clipboard_setup = None;
+        }
+
+        // Never index the available setups with a stale selection
+        self.validate_selection();
+        let order = self.display_order();
+
+        let browse = self.config.browse();
         let selected_track_name = self.selected_track_name.as_ref();
         let selected_car_name = self.selected_car_name.as_ref();
         let selected_setups = &mut self.selected_setups;
-        let tracks = self.setups.tracks();
+        let hidden_setups = &mut self.hidden_setups;
+        let edit_note = &mut self.edit_note;
+        let presentation = self.presentation;
+        let copy_path = &mut self.copy_path;
+        let exclude_path = &mut self.exclude_path;
+        let toggle_favorite = &mut self.toggle_favorite;
+        let clipboard_setup = self.clipboard_setup.as_ref();
+        let clear_clipboard_setup = &mut self.clear_clipboard_setup;
+        let config = &self.config;
+        let setups = available_setups(&self.setups, browse, selected_track_name, selected_car_name);
 
         ui.horizontal_wrapped(|ui| {
-            if let Some(track_name) = selected_track_name {
-                if let Some(car_name) = selected_car_name {
-                    output_track_name = track_name.as_str();
-                    output_car_name = car_name.as_str();
-
-                    let setups = tracks
-                        .get(track_name)
-                        .expect("Invalid track name")
-                        .get(car_name)
-                        .expect("Invalid car name");
-
-                    for (i, info) in setups.iter().enumerate() {
-                        let position = selected_setups.iter().position(|&v| v == i);
-                        let mut checked = position.is_some();
-                        let color = position
-                            .and_then(|i| colors.iter().cycle().nth(i))
-                            .cloned()
-                            .unwrap_or_else(|| ui.visuals().text_color());
-
-                        let checkbox = egui::Checkbox::new(
-                            &mut checked,
-                            egui::RichText::new(info.name()).color(color),
-                        )
+            for i in order {
+                let (track_name, info) = setups[i];
+                let position = selected_setups.iter().position(|&v| v == i);
+                let favorite = config.is_favorite(info.path());
+
+                let mut checked = position.is_some();
+                let color = position
+                    .and_then(|i| colors.iter().cycle().nth(i))
+                    .cloned()
+                    .unwrap_or_else(|| ui.visuals().text_color());
+
+                let label = setup_label(browse, track_name, info, config.clean_setup_names());
+                let label = if favorite {
+                    Cow::Owned(format!("\u{2b50} {}", label))
+                } else {
+                    label
+                };
+                let checkbox =
+                    egui::Checkbox::new(&mut checked, egui::RichText::new(label).color(color))
                         .ui(ui);
-                        if checkbox.clicked() {
-                            if checked {
-                                selected_setups.push(i);
-                            } else if let Some(i) = position {
-                                selected_setups.remove(i);
-                            }
+                let hover_text = config
+                    .get_note(info.path())
+                    .into_iter()
+                    .map(str::to_string)
+                    .chain(
+                        info.date()
+                            .map(|date| date.describe(config.display_locale())),
+                    )
+                    .collect::<Vec<_>>();
+                let checkbox = if hover_text.is_empty() {
+                    checkbox
+                } else {
+                    checkbox.on_hover_text(hover_text.join("\n\n"))
+                };
+                let checkbox = checkbox.context_menu(|ui| {
+                    if !presentation && ui.button("Edit Notes...").clicked() {
+                        ui.close_menu();
+                        *edit_note = Some(info.path().to_path_buf());
+                    }
+                    let favorite_label = if favorite { "Unfavorite" } else { "Favorite" };
+                    if !presentation && ui.button(favorite_label).clicked() {
+                        ui.close_menu();
+                        *toggle_favorite = Some(info.path().to_path_buf());
+                    }
+                    if ui.button("Copy Path").clicked() {
+                        ui.close_menu();
+                        ui.ctx().request_repaint();
+                        *copy_path = Some(info.path().to_path_buf());
+                    }
+
+                    // Setups opened individually are not part of the library
+                    let setups_path = config.get_setups_path();
+                    if presentation || !info.path().starts_with(setups_path) {
+                        return;
+                    }
+                    ui.separator();
+                    if ui.button("Exclude from Library").clicked() {
+                        ui.close_menu();
+                        *exclude_path = Some(info.path().to_path_buf());
+                    }
+                    let folder = info.path().parent().filter(|folder| *folder != setups_path);
+                    if let Some(folder) = folder {
+                        if ui
+                            .button("Exclude Folder from Library")
+                            .on_hover_text(folder.to_string_lossy())
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            *exclude_path = Some(folder.to_path_buf());
                         }
                     }
+                });
+                if checkbox.clicked() {
+                    if checked {
+                        selected_setups.push(i);
+                    } else if let Some(i) = position {
+                        selected_setups.remove(i);
+                    }
+                }
 
-                    for i in selected_setups {
-                        output.push(setups[*i].setup());
+                // Columns can be hidden without deselecting their setup
+                if checked {
+                    let hidden = hidden_setups.contains(info.path());
+                    let hover_text = if hidden {
+                        "Show this column."
+                    } else {
+                        "Hide this column. The setup stays selected."
+                    };
+                    if ui
+                        .selectable_label(!hidden, "\u{1f441}")
+                        .on_hover_text(hover_text)
+                        .clicked()
+                    {
+                        if hidden {
+                            hidden_setups.remove(info.path());
+                        } else {
+                            hidden_setups.insert(info.path().to_path_buf());
+                        }
                     }
                 }
             }
+
+            for i in selected_setups.iter() {
+                output.push(setups[*i]);
+            }
+
+            if let Some(clipboard) = clipboard_setup {
+                let color = colors
+                    .iter()
+                    .cycle()
+                    .nth(output.len())
+                    .cloned()
+                    .unwrap_or_else(|| ui.visuals().text_color());
+                let mut checked = true;
+                let label = egui::RichText::new("Clipboard").italics().color(color);
+                if ui
+                    .checkbox(&mut checked, label)
+                    .on_hover_text("Uncheck to remove the setup pasted from the clipboard.")
+                    .clicked()
+                {
+                    ui.ctx().request_repaint();
+                    *clear_clipboard_setup = true;
+                } else {
+                    output.push((clipboard.track_name.as_str(), &clipboard.info));
+                }
+            }
         });
 
-        (output_track_name, output_car_name, output)
+        // Forget hidden columns when their setup is deselected
+        hidden_setups.retain(|path| output.iter().any(|(_, info)| info.path() == path));
+
+        let text_color = ui.visuals().text_color();
+        let (output, output_colors) = output
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (_, info))| !hidden_setups.contains(info.path()))
+            .map(|(i, column)| {
+                let color = colors.iter().cycle().nth(i).cloned().unwrap_or(text_color);
+
+                (column, color)
+            })
+            .unzip();
+
+        let track_name = selected_track_name.map_or("", |name| name.as_str());
+        let car_name = selected_car_name.map_or("", |name| name.as_str());
+
+        (track_name, car_name, output, output_colors)
     }
 
     /// Show "About" window.
     fn about_window(&mut self, ctx: &egui::CtxRef, enabled: bool) {
+        let gpu_info = self.gpu_info.as_ref().map(GpuInfo::to_string);
+
         egui::Window::new("About CarTunes")
             .open(&mut self.about)
             .enabled(enabled)
@@ -529,8 +2803,61 @@ impl Gui {
                 ui.add_space(10.0);
                 ui.horizontal(|ui| {
                     ui.label("Website:");
+                    #[cfg(feature = "web-links")]
                     ui.hyperlink(env!("CARGO_PKG_HOMEPAGE"));
+                    #[cfg(not(feature = "web-links"))]
+                    ui.label(env!("CARGO_PKG_HOMEPAGE"));
                 });
+
+                // GPU details can be selected and copied for bug reports
+                if let Some(gpu_info) = gpu_info {
+                    ui.add_space(10.0);
+                    ui.add(
+                        egui::TextEdit::multiline(&mut gpu_info.as_str())
+                            .text_style(egui::TextStyle::Small)
+                            .desired_width(ui.available_width())
+                            .desired_rows(3),
+                    );
+                }
+            });
+    }
+
+    /// Show "Keyboard Shortcuts" window.
+    fn shortcuts_window(&mut self, ctx: &egui::CtxRef, enabled: bool) {
+        let number_key_toggles = self.config.number_key_toggles();
+
+        egui::Window::new("Keyboard Shortcuts")
+            .open(&mut self.shortcuts)
+            .enabled(enabled)
+            .collapsible(false)
+            .default_pos((175.0, 175.0))
+            .fixed_size((350.0, 100.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcuts-grid")
+                    .num_columns(2)
+                    .spacing((20.0, 5.0))
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("1 - 9").code());
+                        ui.label("Toggle the first nine setups of the selected car");
+                        ui.end_row();
+
+                        ui.label(egui::RichText::new("N").code());
+                        ui.label("Compare the newest setups of the selected car");
+                        ui.end_row();
+
+                        ui.label(egui::RichText::new("Ctrl + C").code());
+                        ui.label("Copy the comparison for pasting into a spreadsheet");
+                        ui.end_row();
+
+                        ui.label(egui::RichText::new("Ctrl + Scroll").code());
+                        ui.label("Zoom the grid text");
+                        ui.end_row();
+                    });
+
+                if !number_key_toggles {
+                    ui.add_space(5.0);
+                    ui.label("Number keys are disabled in Preferences.");
+                }
             });
     }
 
@@ -567,7 +2894,299 @@ impl Gui {
                         });
                 });
 
+                // Track and car sort order
+                ui.horizontal(|ui| {
+                    let sort_order = self.config.sort_order();
+
+                    ui.label("Sort tracks and cars:");
+                    egui::ComboBox::from_id_source("sort-order-preference")
+                        .selected_text(sort_order.to_string())
+                        .show_ui(ui, |ui| {
+                            let choices = [SortOrder::Name, SortOrder::Recent];
+                            for choice in &choices {
+                                let checked = sort_order == *choice;
+                                let response = ui.selectable_label(checked, choice.to_string());
+                                if response.clicked() {
+                                    self.config.update_sort_order(*choice);
+                                }
+                            }
+                        });
+                });
+
+                // Property grouping
+                ui.horizontal(|ui| {
+                    ui.label("Property groups:");
+                    self.grouping_choice(ui, "grouping-preference");
+                });
+
+                // Diff reference column
+                ui.horizontal(|ui| {
+                    ui.label("Compare each setup with:");
+                    self.diff_reference_choice(ui, "diff-reference-preference");
+                })
+                .response
+                .on_hover_text(
+                    "Comparing with the previous column is useful for reading a progression of \
+                    setups.",
+                );
+
+                // Keyboard toggles
+                let mut number_key_toggles = self.config.number_key_toggles();
+                if ui
+                    .checkbox(
+                        &mut number_key_toggles,
+                        "Toggle setups with number keys 1 - 9",
+                    )
+                    .changed()
+                {
+                    self.config.set_number_key_toggles(number_key_toggles);
+                }
+
+                // Selecting new setups
+                let mut auto_select = self.config.auto_select_new_setups();
+                if ui
+                    .checkbox(&mut auto_select, "Compare new setups as they are exported")
+                    .on_hover_text(
+                        "Setups exported for the selected car are added to the comparison, \
+                        while there are unused column colors.",
+                    )
+                    .changed()
+                {
+                    self.config.set_auto_select_new_setups(auto_select);
+                }
+
+                // Loading exports that are still being saved
+                let mut wait_for_writes = self.config.wait_for_writes();
+                if ui
+                    .checkbox(&mut wait_for_writes, "Wait for exports to finish saving")
+                    .on_hover_text(
+                        "New and changed setup exports are loaded once their size stops \
+                        changing, instead of every time the sim writes to them.",
+                    )
+                    .changed()
+                {
+                    self.config.set_wait_for_writes(wait_for_writes);
+                }
+
+                // Window stacking
+                let mut always_on_top = self.config.always_on_top();
+                let checkbox = egui::Checkbox::new(&mut always_on_top, "Keep window on top");
+                if ui
+                    .add_enabled(supports_always_on_top(window), checkbox)
+                    .on_hover_text("Stay visible while iRacing runs in a borderless window.")
+                    .on_disabled_hover_text("Wayland does not allow windows to stay on top.")
+                    .changed()
+                {
+                    self.config.set_always_on_top(always_on_top);
+                    window.set_always_on_top(always_on_top);
+                }
+
+                // Window opacity
+                ui.horizontal(|ui| {
+                    let mut opacity = self.config.opacity();
+
+                    ui.label("Window opacity:");
+                    let slider = egui::Slider::new(&mut opacity, MIN_OPACITY..=100).suffix("%");
+                    if ui
+                        .add_enabled(opacity::is_supported(), slider)
+                        .on_hover_text("See the sim behind the window while it is kept on top.")
+                        .on_disabled_hover_text("Window opacity is only supported on Windows.")
+                        .changed()
+                    {
+                        self.config.set_opacity(opacity);
+                        opacity::set_opacity(window, opacity);
+                    }
+                });
+
+                // Grid text size
+                ui.horizontal(|ui| {
+                    let mut zoom = self.config.grid_zoom();
+
+                    ui.label("Grid text size:");
+                    let slider =
+                        egui::Slider::new(&mut zoom, MIN_GRID_ZOOM..=MAX_GRID_ZOOM).suffix("%");
+                    if ui
+                        .add(slider)
+                        .on_hover_text("Hold Ctrl while scrolling over the grid to zoom.")
+                        .changed()
+                    {
+                        self.config.set_grid_zoom(zoom);
+                        self.grid_zoom = f32::from(zoom) / 100.0;
+                    }
+                });
+
+                // Setups selected by "Compare Newest"
+                ui.horizontal(|ui| {
+                    let mut count = self.config.newest_count();
+
+                    ui.label("Newest setups to compare:");
+                    let slider = egui::Slider::new(&mut count, MIN_NEWEST_COUNT..=MAX_NEWEST_COUNT);
+                    if ui
+                        .add(slider)
+                        .on_hover_text("Number of setups selected by \"Compare Newest\".")
+                        .changed()
+                    {
+                        self.config.set_newest_count(count);
+                    }
+                });
+
+                // Multi-value diffs
+                let mut positional_diffs = self.config.positional_diffs();
+                if ui
+                    .checkbox(
+                        &mut positional_diffs,
+                        "Compare tire temperatures position by position",
+                    )
+                    .changed()
+                {
+                    self.config.set_positional_diffs(positional_diffs);
+                }
+
+                // Fraction glyphs
+                let mut fraction_glyphs = self.config.fraction_glyphs();
+                if ui
+                    .checkbox(&mut fraction_glyphs, "Show fractions with fraction glyphs")
+                    .on_hover_text("E.g. ⅜ and ¹⁄₁₆, when the font has them.")
+                    .changed()
+                {
+                    self.config.set_fraction_glyphs(fraction_glyphs);
+                }
+
+                // Unchanged values
+                let mut dim_unchanged = self.config.dim_unchanged();
+                if ui
+                    .checkbox(
+                        &mut dim_unchanged,
+                        "Dim values that are the same in every setup",
+                    )
+                    .changed()
+                {
+                    self.config.set_dim_unchanged(dim_unchanged);
+                }
+
+                // Setup names
+                let mut clean_setup_names = self.config.clean_setup_names();
+                if ui
+                    .checkbox(&mut clean_setup_names, "Clean up setup names")
+                    .on_hover_text(
+                        "Show underscores as spaces and capitalize words, e.g. \
+                        \"iracing_w12_baseline\" is shown as \"Iracing W12 Baseline\".",
+                    )
+                    .changed()
+                {
+                    self.config.set_clean_setup_names(clean_setup_names);
+                }
+
+                // Saving on close
+                let mut manual_save = !self.config.auto_save();
+                if ui
+                    .checkbox(&mut manual_save, "Don't save settings automatically")
+                    .on_hover_text(
+                        "Keep the config file as it is when closing. \
+                        Use \"Save Settings Now\" in the File menu to save.",
+                    )
+                    .changed()
+                {
+                    self.config.set_auto_save(!manual_save);
+
+                    // The preference must be saved even when closing does not save it
+                    if let Err(err) = self.config.write_auto_save() {
+                        let warning = ShowWarning::new(err, "Unable to write the config file.");
+                        self.show_warnings.push_front(warning);
+                    }
+                }
+
+                // Diagnostics
+                let mut parse_log = self.config.parse_log();
+                if ui
+                    .checkbox(&mut parse_log, "Log parsed setup exports for diagnostics")
+                    .on_hover_text("Writes `parse.log` next to the config file.")
+                    .changed()
+                {
+                    self.config.set_parse_log(parse_log);
+                }
+
+                // Spring perch offset unit
+                ui.horizontal(|ui| {
+                    ui.label("Perch offsets:");
+                    self.perch_offset_choice(ui, "perch-offset-preference");
+                });
+
+                // Locale for numbers and dates
+                ui.horizontal(|ui| {
+                    let locale = self.config.get_locale();
+
+                    ui.label("Number and date format:");
+                    egui::ComboBox::from_id_source("locale-preference")
+                        .selected_text(locale.to_string())
+                        .show_ui(ui, |ui| {
+                            let choices = [
+                                Locale::System,
+                                Locale::EnglishUs,
+                                Locale::EnglishUk,
+                                Locale::German,
+                                Locale::French,
+                                Locale::Iso,
+                            ];
+                            for choice in &choices {
+                                let checked = locale == *choice;
+                                let response = ui.selectable_label(checked, choice.to_string());
+                                if response.clicked() {
+                                    self.config.set_locale(*choice);
+                                }
+                            }
+                        });
+                });
+
+                // Table export options
+                ui.horizontal(|ui| {
+                    let table_options = self.config.table_options();
+
+                    ui.label("TSV quoting:");
+                    egui::ComboBox::from_id_source("tsv-quoting-preference")
+                        .selected_text(table_options.quoting.to_string())
+                        .show_ui(ui, |ui| {
+                            let choices = [Quoting::Minimal, Quoting::Always, Quoting::Never];
+                            for choice in &choices {
+                                let checked = table_options.quoting == *choice;
+                                let response = ui.selectable_label(checked, choice.to_string());
+                                if response.clicked() {
+                                    self.config.set_table_options(TableOptions {
+                                        quoting: *choice,
+                                        ..table_options
+                                    });
+                                }
+                            }
+                        });
+
+                    let mut group_column = table_options.group_column;
+                    if ui
+                        .checkbox(&mut group_column, "Property groups in a column")
+                        .changed()
+                    {
+                        self.config.set_table_options(TableOptions {
+                            group_column,
+                            ..table_options
+                        });
+                    }
+
+                    let mut include_hidden_groups = table_options.include_hidden_groups;
+                    if ui
+                        .checkbox(&mut include_hidden_groups, "Include hidden groups")
+                        .on_hover_text(
+                            "Hidden property groups are also left out of exported images.",
+                        )
+                        .changed()
+                    {
+                        self.config.set_table_options(TableOptions {
+                            include_hidden_groups,
+                            ..table_options
+                        });
+                    }
+                });
+
                 // Update check frequency
+                #[cfg(feature = "update-check")]
                 ui.horizontal(|ui| {
                     let update_check = self.config.get_update_check();
 
@@ -593,91 +3212,655 @@ impl Gui {
                         });
                 });
 
-                // Setup exports path selection
-                ui.horizontal(|ui| {
-                    let setups_path = self.config.get_setups_path();
+                // Setup exports path selection
+                ui.horizontal(|ui| {
+                    let setups_path = self.config.get_setups_path();
+
+                    let label = display_path(setups_path).ellipsis(50);
+
+                    ui.label("Setup exports path:");
+                    if egui::Label::new(egui::RichText::new(label).code())
+                        .sense(egui::Sense::click())
+                        .ui(ui)
+                        .clicked()
+                    {
+                        self.choose_setups_path(window);
+                    }
+                    if ui
+                        .button("Find iRacing Folder...")
+                        .on_hover_text("Look for setup exports where iRacing usually keeps them.")
+                        .clicked()
+                    {
+                        self.find_setups_paths(window);
+                    }
+                });
+
+                // Recently used setup exports paths
+                let setups_path = self.config.get_setups_path();
+                let recent: Vec<_> = self
+                    .config
+                    .recent_setups_paths()
+                    .iter()
+                    .filter(|&path| path != setups_path)
+                    .cloned()
+                    .collect();
+                if !recent.is_empty() {
+                    let mut choice = None;
+
+                    ui.horizontal(|ui| {
+                        ui.label("Recent paths:");
+                        egui::ComboBox::from_id_source("recent-setups-paths")
+                            .selected_text("Switch to...")
+                            .width(300.0)
+                            .show_ui(ui, |ui| {
+                                for path in &recent {
+                                    // Folders that no longer exist cannot be chosen
+                                    let label = display_path(path).ellipsis(50).to_string();
+                                    let response = ui
+                                        .add_enabled(
+                                            path.is_dir(),
+                                            egui::SelectableLabel::new(false, label),
+                                        )
+                                        .on_disabled_hover_text("This folder no longer exists.");
+                                    if response.clicked() {
+                                        choice = Some(path.clone());
+                                    }
+                                }
+                            });
+                    });
+
+                    if let Some(path) = choice {
+                        self.update_setups_path(path);
+                    }
+                }
+
+                // Library subfolder selection
+                ui.horizontal(|ui| {
+                    let setups_scope = self.config.get_setups_scope();
+                    let label = if setups_scope.as_os_str().is_empty() {
+                        "(all setups)".to_string()
+                    } else {
+                        setups_scope.to_string_lossy().ellipsis(50).to_string()
+                    };
+
+                    ui.label("Library subfolder:");
+                    if egui::Label::new(egui::RichText::new(label).code())
+                        .sense(egui::Sense::click())
+                        .ui(ui)
+                        .clicked()
+                    {
+                        let f = rfd::AsyncFileDialog::new()
+                            .set_parent(window)
+                            .set_directory(self.config.get_scoped_setups_path())
+                            .pick_folder();
+
+                        self.send_file_choice(f, UserEvent::SetupScope);
+                    }
+
+                    if !setups_scope.as_os_str().is_empty() && ui.button("Clear").clicked() {
+                        self.update_setups_scope("");
+                    }
+                });
+
+                // Paths excluded from the library
+                if !self.config.excluded_paths().is_empty() {
+                    ui.label("Excluded from library:");
+                    let mut include = None;
+                    for path in self.config.excluded_paths() {
+                        ui.horizontal(|ui| {
+                            let label = path.to_string_lossy().ellipsis(50).to_string();
+                            ui.label(egui::RichText::new(label).code());
+                            if ui.button("Include").clicked() {
+                                include = Some(path.to_path_buf());
+                            }
+                        });
+                    }
+                    if let Some(path) = include {
+                        self.include_in_library(&path);
+                    }
+                }
+
+                // Color choices
+                ui.separator();
+                ui.label("Column colors:");
+                ui.horizontal_wrapped(|ui| {
+                    let colors = self.config.colors_mut();
+                    let mut changed = false;
+                    let mut to_delete = None;
+
+                    for (i, color) in colors.iter_mut().enumerate() {
+                        let old_color = *color;
+
+                        if color_edit_button_srgba(ui, color, Alpha::Opaque)
+                            .on_hover_text("Right-click to remove")
+                            .secondary_clicked()
+                        {
+                            to_delete = Some(i);
+                        }
+                        let background = ui.visuals().window_fill();
+                        contrast_warning(ui, *color, background);
+
+                        changed |= *color != old_color;
+                    }
+
+                    let add_clicked = ui.button("Add").clicked();
+                    if add_clicked {
+                        colors.push(ui.visuals().text_color());
+                    } else if let Some(i) = to_delete {
+                        colors.remove(i);
+                    }
+
+                    // Update colors in the config TOML doc
+                    if changed || add_clicked || to_delete.is_some() {
+                        self.config.update_colors();
+                    }
+                });
+
+                ui.label("Diff colors:");
+                ui.horizontal_wrapped(|ui| {
+                    let colors = self.config.diff_colors_mut();
+                    let old_colors = *colors;
+
+                    for color in [&mut colors.0, &mut colors.1] {
+                        color_edit_button_srgba(ui, color, Alpha::Opaque);
+                    }
+
+                    if *colors != old_colors {
+                        self.config.update_colors();
+                    }
+                });
+
+                // Diff background blend mode
+                ui.horizontal(|ui| {
+                    let diff_blend = self.config.diff_blend();
+
+                    ui.label("Diff backgrounds:");
+                    egui::ComboBox::from_id_source("diff-blend-preference")
+                        .selected_text(diff_blend.to_string())
+                        .show_ui(ui, |ui| {
+                            let choices =
+                                [DiffBlend::Multiply, DiffBlend::Overlay, DiffBlend::Solid];
+                            for choice in &choices {
+                                let checked = diff_blend == *choice;
+                                let response = ui.selectable_label(checked, choice.to_string());
+                                if response.clicked() {
+                                    self.config.update_diff_blend(*choice);
+                                }
+                            }
+                        });
+                })
+                .response
+                .on_hover_text(
+                    "Overlay gives subtle highlights, and Solid gives crisp highlights. Text over \
+                    a diff background is shown in black or white when it would be hard to read.",
+                );
+
+                ui.label("Swap diff colors for:")
+                    .on_hover_text("For properties where lower values are better.");
+                ui.horizontal_wrapped(|ui| {
+                    for (label, pattern) in COMMON_SWAPPED_DIFFS {
+                        let mut swapped = self.is_diff_swapped(pattern);
+                        let checkbox = ui.checkbox(&mut swapped, label);
+                        if checkbox.changed() {
+                            self.config.set_diff_swapped(pattern, swapped);
+                        }
+                    }
+
+                    // Other properties are shown as buttons that remove them
+                    let custom: Vec<_> = self
+                        .config
+                        .swapped_diffs()
+                        .iter()
+                        .filter(|&name| {
+                            !COMMON_SWAPPED_DIFFS
+                                .iter()
+                                .any(|(_, pattern)| name.eq_ignore_ascii_case(pattern))
+                        })
+                        .cloned()
+                        .collect();
+                    for pattern in custom {
+                        let response = ui
+                            .small_button(format!("{} \u{d7}", pattern))
+                            .on_hover_text("Restore the diff colors");
+                        if response.clicked() {
+                            self.config.set_diff_swapped(&pattern, false);
+                        }
+                    }
+
+                    let input = egui::TextEdit::singleline(&mut self.swapped_diff_pattern)
+                        .hint_text("Property name")
+                        .desired_width(120.0);
+                    let response = ui.add(input);
+                    let submitted =
+                        response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                    let pattern = self.swapped_diff_pattern.trim().to_string();
+                    let add = ui.add_enabled(!pattern.is_empty(), egui::Button::new("Add"));
+                    if (add.clicked() || submitted) && !pattern.is_empty() {
+                        self.config.set_diff_swapped(&pattern, true);
+                        self.swapped_diff_pattern.clear();
+                    }
+                });
+
+                ui.label("Safe ranges:")
+                    .on_hover_text("Values outside of the range are outlined in the grid.");
+                ui.horizontal_wrapped(|ui| {
+                    let mut ranges: Vec<_> = self
+                        .config
+                        .safe_ranges()
+                        .iter()
+                        .map(|(prop_name, range)| (prop_name.clone(), range.to_string()))
+                        .collect();
+                    ranges.sort_unstable_by(|(a, _), (b, _)| a.human_compare(b));
+
+                    for (prop_name, range) in ranges {
+                        let response = ui
+                            .small_button(format!("{}: {} \u{d7}", prop_name, range))
+                            .on_hover_text("Remove the safe range");
+                        if response.clicked() {
+                            self.config.set_safe_range(&prop_name, None);
+                        }
+                    }
+                });
+                ui.horizontal_wrapped(|ui| {
+                    let input = &mut self.safe_range_input;
+                    let fields = [
+                        (&mut input.prop_name, "Property name", 120.0),
+                        (&mut input.min, "Min", 50.0),
+                        (&mut input.max, "Max", 50.0),
+                        (&mut input.unit, "Unit", 50.0),
+                    ];
+                    for (text, hint, width) in fields {
+                        let text_edit = egui::TextEdit::singleline(text)
+                            .hint_text(hint)
+                            .desired_width(width);
+                        ui.add(text_edit);
+                    }
+
+                    let range = input.parse();
+                    let add = ui
+                        .add_enabled(range.is_some(), egui::Button::new("Add"))
+                        .on_disabled_hover_text("Enter a property name, and a min or max number.");
+                    if let Some((prop_name, range)) = range.filter(|_| add.clicked()) {
+                        self.config.set_safe_range(&prop_name, Some(range));
+                        self.safe_range_input = SafeRangeInput::default();
+                    }
+                });
+
+                // Reset all settings
+                ui.separator();
+                ui.horizontal_wrapped(|ui| {
+                    let options = &mut self.reset_options;
+
+                    ui.checkbox(&mut options.keep_setups_path, "Keep setup exports path");
+                    ui.checkbox(&mut options.keep_names, "Keep track and car names");
+                });
+                if ui.button("Reset All Settings to Defaults").clicked() {
+                    let event_loop_proxy = self.event_loop_proxy.clone();
+                    let options = self.reset_options;
+                    let err = ShowError::new(
+                        "Colors, preferences, and property aliases will be replaced with defaults.",
+                        "Reset all settings to defaults? This cannot be undone.",
+                        (
+                            ErrorButton::new("Cancel", || ()),
+                            ErrorButton::new("Reset", move || {
+                                event_loop_proxy
+                                    .send_event(UserEvent::ResetConfig(options))
+                                    .expect("Event loop must exist");
+                            }),
+                        ),
+                    );
+
+                    self.show_errors.push_front(err);
+                }
+            });
+
+        self.preferences = preferences;
+    }
+
+    /// Show "Property Aliases" window.
+    fn aliases_window(&mut self, ctx: &CtxRef, enabled: bool) {
+        let mut property_aliases = self.property_aliases;
+
+        egui::Window::new("Property Aliases")
+            .open(&mut property_aliases)
+            .enabled(enabled)
+            .collapsible(false)
+            .default_pos((150.0, 150.0))
+            .fixed_size((500.0, 300.0))
+            .show(ctx, |ui| {
+                let car_name = match self.selected_car_name.as_deref() {
+                    Some(car_name) => car_name,
+                    None => {
+                        ui.label("Select a car to edit its property aliases.");
+                        return;
+                    }
+                };
+
+                ui.label("Aliases change the property names shown for the selected car.");
+                ui.checkbox(&mut self.aliases_all_cars, "Apply to all cars");
+                ui.separator();
+
+                let scope = if self.aliases_all_cars {
+                    None
+                } else {
+                    Some(car_name)
+                };
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("property-aliases")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for prop_name in self.setups.property_names(car_name) {
+                                let mut alias = self
+                                    .config
+                                    .get_alias(scope, prop_name)
+                                    .unwrap_or_default()
+                                    .to_string();
+
+                                ui.label(prop_name);
+                                if ui.text_edit_singleline(&mut alias).changed() {
+                                    self.config.set_alias(scope, prop_name, &alias);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        self.property_aliases = property_aliases;
+    }
 
-                    // Strip Windows path prefixes for display in the GUI
-                    let label = setups_path.to_string_lossy();
-                    let label = label
-                        .strip_prefix(r"\\?\UNC\")
-                        .or_else(|| label.strip_prefix(r"\\?\"))
-                        .or_else(|| label.strip_prefix(r"\??\"))
-                        .unwrap_or(&label)
-                        .ellipsis(50);
+    /// Show "Property Groups" window.
+    fn groups_window(&mut self, ctx: &CtxRef, enabled: bool) {
+        let mut property_groups = self.property_groups;
 
-                    ui.label("Setup exports path:");
-                    if egui::Label::new(egui::RichText::new(label).code())
-                        .sense(egui::Sense::click())
-                        .ui(ui)
-                        .clicked()
-                    {
-                        let event_loop_proxy = self.event_loop_proxy.clone();
-                        let f = rfd::AsyncFileDialog::new()
-                            .set_parent(window)
-                            .set_directory(setups_path)
-                            .pick_folder();
+        egui::Window::new("Property Groups")
+            .open(&mut property_groups)
+            .enabled(enabled)
+            .collapsible(false)
+            .default_pos((150.0, 150.0))
+            .fixed_size((300.0, 300.0))
+            .show(ctx, |ui| {
+                let car_name = match self.selected_car_name.as_deref() {
+                    Some(car_name) => car_name,
+                    None => {
+                        ui.label("Select a car to choose its property groups.");
+                        return;
+                    }
+                };
+
+                ui.label("Unchecked groups are hidden from the grid for the selected car.");
+                ui.label("Collapsed groups start collapsed for all cars.");
+                ui.checkbox(&mut self.groups_all_cars, "Apply to all cars");
+                ui.separator();
 
-                        std::thread::spawn(move || {
-                            let choice =
-                                pollster::block_on(f).map(|selected| selected.path().to_path_buf());
+                let scope = if self.groups_all_cars {
+                    None
+                } else {
+                    Some(car_name)
+                };
 
-                            event_loop_proxy
-                                .send_event(UserEvent::SetupPath(choice))
-                                .expect("Event loop must exist");
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for group_name in self.setups.group_names(car_name) {
+                        // Groups hidden for all cars cannot be shown for a single car
+                        let hidden_for_all =
+                            scope.is_some() && self.config.is_group_hidden(None, group_name);
+                        let mut shown =
+                            !hidden_for_all && !self.config.is_group_hidden(scope, group_name);
+                        ui.horizontal(|ui| {
+                            let response = ui
+                                .add_enabled(
+                                    !hidden_for_all,
+                                    egui::Checkbox::new(&mut shown, group_name),
+                                )
+                                .on_disabled_hover_text("Hidden for all cars.");
+                            if response.changed() {
+                                self.config.set_group_hidden(scope, group_name, !shown);
+                            }
+
+                            let collapsed = self.config.is_group_collapsed(group_name);
+                            if ui.selectable_label(collapsed, "Collapsed").clicked() {
+                                self.config.set_group_collapsed(group_name, !collapsed);
+                            }
                         });
                     }
                 });
+            });
 
-                // Color choices
-                ui.separator();
-                ui.label("Column colors:");
-                ui.horizontal_wrapped(|ui| {
-                    let colors = self.config.colors_mut();
-                    let mut changed = false;
-                    let mut to_delete = None;
+        self.property_groups = property_groups;
+    }
 
-                    for (i, color) in colors.iter_mut().enumerate() {
-                        let old_color = *color;
+    /// Show "iRacing Folders" window.
+    fn setups_path_window(&mut self, ctx: &CtxRef, enabled: bool, window: &winit::window::Window) {
+        let candidates = match self.setups_path_candidates.as_ref() {
+            Some(candidates) => candidates,
+            None => return,
+        };
+        let mut open = true;
+        let mut choice = None;
+        let mut choose_folder = false;
 
-                        if color_edit_button_srgba(ui, color, Alpha::Opaque)
-                            .on_hover_text("Right-click to remove")
-                            .secondary_clicked()
-                        {
-                            to_delete = Some(i);
+        egui::Window::new("iRacing Folders")
+            .open(&mut open)
+            .enabled(enabled)
+            .collapsible(false)
+            .default_pos((150.0, 150.0))
+            .fixed_size((500.0, 150.0))
+            .show(ctx, |ui| {
+                ui.label("These folders may have your setup exports.");
+                ui.separator();
+
+                egui::Grid::new("setups-path-candidates")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (path, count) in candidates {
+                            let label = display_path(path).ellipsis(50).to_string();
+                            ui.label(egui::RichText::new(label).code())
+                                .on_hover_text(path.to_string_lossy());
+                            ui.label(match count {
+                                None => "Counting setup exports...".to_string(),
+                                Some(0) => "No setup exports".to_string(),
+                                Some(1) => "1 setup export".to_string(),
+                                Some(count) => format!("{} setup exports", count),
+                            });
+                            if ui.button("Use").clicked() {
+                                choice = Some(path.clone());
+                            }
+                            ui.end_row();
                         }
+                    });
+                ui.separator();
 
-                        changed |= *color != old_color;
-                    }
+                choose_folder = ui.button("Choose Another Folder...").clicked();
+            });
 
-                    let add_clicked = ui.button("Add").clicked();
-                    if add_clicked {
-                        colors.push(ui.visuals().text_color());
-                    } else if let Some(i) = to_delete {
-                        colors.remove(i);
-                    }
+        if let Some(path) = choice {
+            self.update_setups_path(path);
+            open = false;
+        } else if choose_folder {
+            self.choose_setups_path(window);
+            open = false;
+        }
+        if !open {
+            self.setups_path_candidates = None;
+        }
+    }
 
-                    // Update colors in the config TOML doc
-                    if changed || add_clicked || to_delete.is_some() {
-                        self.config.update_colors();
+    /// Show "Property Distribution" window.
+    fn distribution_window(&mut self, ctx: &CtxRef, enabled: bool) {
+        let mut open = self.distribution;
+
+        egui::Window::new("Property Distribution")
+            .open(&mut open)
+            .enabled(enabled)
+            .collapsible(false)
+            .default_pos((150.0, 150.0))
+            .fixed_size((500.0, 300.0))
+            .show(ctx, |ui| {
+                let car_name = match self.selected_car_name.as_deref() {
+                    Some(car_name) => car_name,
+                    None => {
+                        ui.label("Select a car to see the distribution of its property values.");
+                        return;
                     }
+                };
+
+                ui.label(
+                    "Values of a property in every setup for the selected car, at all tracks.",
+                );
+                ui.separator();
+
+                // Forget the selection when it does not exist for the selected car
+                let group_names = self.setups.group_names(car_name);
+                let (group_name, prop_name) = &mut self.distribution_prop;
+                if !matches!(group_name, Some(name) if group_names.contains(&name.as_str())) {
+                    *group_name = None;
+                    *prop_name = None;
+                }
+                let prop_names = group_name.as_deref().map_or_else(Vec::new, |group_name| {
+                    self.setups.group_property_names(car_name, group_name)
                 });
+                if !matches!(prop_name, Some(name) if prop_names.contains(&name.as_str())) {
+                    *prop_name = None;
+                }
 
-                ui.label("Diff colors:");
-                ui.horizontal_wrapped(|ui| {
-                    let colors = self.config.diff_colors_mut();
-                    let old_colors = *colors;
+                egui::Grid::new("distribution-property")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Group:");
+                        egui::ComboBox::from_id_source("distribution-group")
+                            .width(300.0)
+                            .selected_text(group_name.as_deref().unwrap_or_default())
+                            .show_ui(ui, |ui| {
+                                for name in group_names {
+                                    let checked = group_name.as_deref() == Some(name);
+                                    if ui.selectable_label(checked, name).clicked() && !checked {
+                                        *group_name = Some(name.to_string());
+                                        *prop_name = None;
+                                    }
+                                }
+                            });
+                        ui.end_row();
 
-                    color_edit_button_srgba(ui, &mut colors.0, Alpha::Opaque);
-                    color_edit_button_srgba(ui, &mut colors.1, Alpha::Opaque);
+                        ui.label("Property:");
+                        egui::ComboBox::from_id_source("distribution-prop")
+                            .width(300.0)
+                            .selected_text(prop_name.as_deref().unwrap_or_default())
+                            .show_ui(ui, |ui| {
+                                for name in prop_names {
+                                    let checked = prop_name.as_deref() == Some(name);
+                                    if ui.selectable_label(checked, name).clicked() {
+                                        *prop_name = Some(name.to_string());
+                                    }
+                                }
+                            });
+                        ui.end_row();
+                    });
+                ui.separator();
 
-                    if *colors != old_colors {
-                        self.config.update_colors();
+                let values = match (group_name.as_deref(), prop_name.as_deref()) {
+                    (Some(group_name), Some(prop_name)) => {
+                        self.setups.property_values(car_name, group_name, prop_name)
+                    }
+                    _ => {
+                        ui.label("Select a property to see the distribution of its values.");
+                        return;
                     }
+                };
+                let distribution = match distribution(values) {
+                    Some(distribution) => distribution,
+                    None => {
+                        ui.label("No setups have a value for this property.");
+                        return;
+                    }
+                };
+
+                let locale = self.config.display_locale();
+                if let Some(summary) = &distribution.summary {
+                    let unit = &summary.unit;
+                    let text = format!(
+                        "{} values, min: {}, median: {}, max: {}",
+                        summary.count,
+                        with_unit(format_number(summary.min), unit),
+                        with_unit(format_number(summary.median), unit),
+                        with_unit(format_number(summary.max), unit),
+                    );
+                    ui.label(locale.format_value(&text));
+                }
+
+                let max_count = distribution
+                    .bars
+                    .iter()
+                    .map(|(_, count)| *count)
+                    .max()
+                    .unwrap_or(1);
+                let bar_color = ui.visuals().selection.bg_fill;
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("distribution-bars")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (label, count) in &distribution.bars {
+                                ui.label(locale.format_value(label));
+
+                                let size = egui::vec2(200.0, ui.spacing().interact_size.y);
+                                let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                                let width = rect.width() * *count as f32 / max_count as f32;
+                                let bar = egui::Rect::from_min_size(
+                                    rect.min,
+                                    egui::vec2(width, rect.height()),
+                                );
+                                ui.painter().rect_filled(
+                                    bar.shrink2(egui::vec2(0.0, 2.0)),
+                                    0.0,
+                                    bar_color,
+                                );
+
+                                ui.label(count.to_string());
+                                ui.end_row();
+                            }
+                        });
                 });
             });
 
-        self.preferences = preferences;
+        self.distribution = open;
+    }
+
+    /// Show "Setup Notes" window.
+    fn notes_window(&mut self, ctx: &CtxRef, enabled: bool) {
+        let path = match self.edit_note.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let mut open = true;
+
+        egui::Window::new("Setup Notes")
+            .open(&mut open)
+            .enabled(enabled)
+            .collapsible(false)
+            .default_pos((175.0, 175.0))
+            .fixed_size((400.0, 200.0))
+            .show(ctx, |ui| {
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                ui.label(egui::RichText::new(file_name).code());
+                ui.add_space(5.0);
+
+                let mut note = self.config.get_note(path).unwrap_or_default().to_string();
+                let response = egui::TextEdit::multiline(&mut note)
+                    .hint_text("E.g. good in the rain")
+                    .desired_width(f32::INFINITY)
+                    .ui(ui);
+                if response.changed() {
+                    self.config.set_note(path, &note);
+                }
+            });
+
+        if !open {
+            self.edit_note = None;
+        }
     }
 
     /// Add an error to the GUI.
@@ -693,25 +3876,29 @@ impl Gui {
         let err = self.show_errors.pop_back();
         if let Some(err) = err {
             let mut result = true;
-            let width = 550.0;
-            let height = 185.0;
+            let size = self.message_size();
+            let mut content_size = size;
             let red = egui::Color32::from_rgb(210, 40, 40);
 
             egui::Window::new("Error")
                 .collapsible(false)
                 .default_pos((100.0, 100.0))
-                .fixed_size((width, height))
+                .default_size(size)
+                .min_width(MESSAGE_MIN_SIZE.x)
+                .min_height(MESSAGE_MIN_SIZE.y)
                 .show(ctx, |ui| {
+                    content_size = ui.available_size();
                     ui.label(&err.context);
 
                     egui::ScrollArea::vertical()
-                        .max_height(height)
+                        .max_height(message_text_height(ui))
+                        .auto_shrink([false; 2])
                         .show(ui, |ui| {
                             let mut text = err.error.to_string();
                             let text_edit = egui::TextEdit::multiline(&mut text)
                                 .text_style(egui::TextStyle::Monospace)
                                 .text_color(red)
-                                .desired_width(width)
+                                .desired_width(ui.available_width())
                                 .desired_rows(10);
 
                             ui.add_enabled(false, text_edit);
@@ -722,18 +3909,7 @@ impl Gui {
                         let tooltip_id = egui::Id::new("error-copypasta");
 
                         if ui.button("Copy to Clipboard").clicked() {
-                            let mut copied = false;
-                            if let Ok(mut clipboard) = ClipboardContext::new() {
-                                copied = clipboard.set_contents(err.error.to_string()).is_ok();
-                            }
-
-                            let label = if copied {
-                                "Copied!"
-                            } else {
-                                "Sorry, but the clipboard isn't working..."
-                            };
-
-                            self.add_tooltip(tooltip_id, label);
+                            self.copy_text(err.error.to_string(), tooltip_id);
                         }
 
                         // Show the copy button tooltip for 3 seconds
@@ -762,12 +3938,183 @@ impl Gui {
                     });
                 });
 
+            if content_size != size {
+                self.config.update_message_size(content_size);
+            }
+
             result
         } else {
             true
         }
     }
 
+    /// Offer to report a crash in the previous run.
+    pub(crate) fn show_crash_report(&mut self) {
+        self.crash_report = true;
+    }
+
+    /// Show the window for reporting a crash in the previous run.
+    ///
+    /// Nothing is sent automatically. The user can open the folder with the logs, and open a
+    /// pre-filled issue in the web browser.
+    fn crash_report_window(&mut self, ctx: &egui::CtxRef, enabled: bool) {
+        let mut open = self.crash_report;
+        let mut dismissed = false;
+
+        egui::Window::new("CarTunes Did Not Exit Cleanly")
+            .open(&mut open)
+            .enabled(enabled)
+            .collapsible(false)
+            .resizable(false)
+            .default_pos((175.0, 175.0))
+            .fixed_size((350.0, 100.0))
+            .show(ctx, |ui| {
+                ui.label(
+                    "CarTunes may have crashed the last time it was running. \
+                    Reporting the problem helps to fix it.",
+                );
+                if !self.safe_mode {
+                    ui.add_space(5.0);
+                    ui.label(format!(
+                        "If CarTunes crashes on startup, start it with the `{}` argument.",
+                        crate::framework::SAFE_MODE_FLAG,
+                    ));
+                }
+                ui.separator();
+
+                let dir = self.config.dir();
+                #[cfg(feature = "web-links")]
+                ui.horizontal(|ui| {
+                    if ui.button("Open Log Folder").clicked() {
+                        if let Err(err) = webbrowser::open(&dir.to_string_lossy()) {
+                            let warning = ShowWarning::new(err, "Unable to open the log folder.");
+                            self.show_warnings.push_front(warning);
+                        }
+                    }
+                    let response = ui
+                        .button("Report Issue")
+                        .on_hover_text("Opens a new GitHub issue with the version and OS.");
+                    if response.clicked() {
+                        let url = crate::framework::crash_report_url();
+                        if let Err(err) = webbrowser::open(&url) {
+                            let warning = ShowWarning::new(err, "Unable to open web browser.");
+                            self.show_warnings.push_front(warning);
+                        }
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        dismissed = true;
+                    }
+                });
+                #[cfg(not(feature = "web-links"))]
+                {
+                    ui.label(format!("Log folder: {}", dir.to_string_lossy()));
+                    ui.label(format!(
+                        "Report issues at: {}/issues",
+                        env!("CARGO_PKG_REPOSITORY"),
+                    ));
+                    if ui.button("Dismiss").clicked() {
+                        dismissed = true;
+                    }
+                }
+            });
+
+        self.crash_report = open && !dismissed;
+    }
+
+    /// Check if the diff colors are swapped for a pattern in the "Preferences" window.
+    fn is_diff_swapped(&self, pattern: &str) -> bool {
+        self.config
+            .swapped_diffs()
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(pattern))
+    }
+
+    /// Show details about the GPU adapter in the "About..." window.
+    pub(crate) fn set_gpu_info(&mut self, gpu_info: GpuInfo) {
+        self.gpu_info = Some(gpu_info);
+    }
+
+    /// Enable safe mode.
+    ///
+    /// A banner explains that some settings are ignored, and offers to reset them.
+    pub(crate) fn enable_safe_mode(&mut self) {
+        self.safe_mode = true;
+    }
+
+    /// Check if safe mode is active.
+    pub(crate) fn is_safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// Enable presentation mode for this session, without changing the preference.
+    pub(crate) fn enable_presentation_mode(&mut self) {
+        self.set_presentation(true);
+    }
+
+    /// Show or hide settings, file operations, and editing controls.
+    ///
+    /// Windows for editing settings are closed when presentation mode starts.
+    fn set_presentation(&mut self, enabled: bool) {
+        self.presentation = enabled;
+        if enabled {
+            self.preferences = false;
+            self.setups_path_candidates = None;
+            self.property_aliases = false;
+            self.property_groups = false;
+            self.edit_note = None;
+        }
+    }
+
+    /// Multiply the grid text size by `factor`, within the bounds allowed by the config.
+    fn zoom_grid(&mut self, factor: f32) {
+        let min = f32::from(MIN_GRID_ZOOM) / 100.0;
+        let max = f32::from(MAX_GRID_ZOOM) / 100.0;
+        self.grid_zoom = (self.grid_zoom * factor).clamp(min, max);
+        self.config
+            .set_grid_zoom((self.grid_zoom * 100.0).round() as u16);
+    }
+
+    /// Draw the safe mode banner.
+    fn safe_mode_banner(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new("Safe mode").strong());
+
+            if self.startup_settings_reset {
+                ui.label(
+                    "The settings have been reset to defaults. \
+                    Restart CarTunes without safe mode to use them.",
+                );
+            } else {
+                ui.label(
+                    "GPU preferences, system fonts, and the saved window position and opacity \
+                    are ignored.",
+                );
+
+                let response = ui
+                    .button("Reset These Settings")
+                    .on_hover_text("The defaults are saved when CarTunes exits.");
+                if response.clicked() {
+                    self.config.reset_startup_settings();
+                    self.startup_settings_reset = true;
+                }
+            }
+
+            let response = ui
+                .small_button("\u{d7}")
+                .on_hover_text("Hide until CarTunes is restarted");
+            if response.clicked() {
+                self.safe_mode_dismissed = true;
+            }
+        });
+    }
+
+    /// Check for changes that would be lost if the app exits now.
+    ///
+    /// Edits are applied to the config as they are made, so only the config file can be unsaved.
+    pub(crate) fn has_unsaved_changes(&self) -> bool {
+        self.config.is_dirty()
+    }
+
     /// Add a warning to the GUI.
     ///
     /// The new warning will be shown to the user if it is the only one, or else it will wait in a
@@ -781,27 +4128,31 @@ impl Gui {
         let mut window_open = self.warning;
         let warning = self.show_warnings.pop_back();
         if let Some(warning) = warning {
-            let width = 550.0;
-            let height = 185.0;
+            let size = self.message_size();
+            let mut content_size = size;
             let yellow = egui::Color32::from_rgb(156, 156, 40);
 
             egui::Window::new("Warning")
                 .open(&mut window_open)
                 .collapsible(false)
                 .default_pos((125.0, 125.0))
-                .fixed_size((width, height))
+                .default_size(size)
+                .min_width(MESSAGE_MIN_SIZE.x)
+                .min_height(MESSAGE_MIN_SIZE.y)
                 .show(ctx, |ui| {
+                    content_size = ui.available_size();
                     ui.set_enabled(enabled);
                     ui.label(&warning.context);
 
                     egui::ScrollArea::vertical()
-                        .max_height(height)
+                        .max_height(message_text_height(ui))
+                        .auto_shrink([false; 2])
                         .show(ui, |ui| {
                             let mut text = warning.warning.to_string();
                             let text_edit = egui::TextEdit::multiline(&mut text)
                                 .text_style(egui::TextStyle::Monospace)
                                 .text_color(yellow)
-                                .desired_width(width)
+                                .desired_width(ui.available_width())
                                 .desired_rows(10);
 
                             ui.add_enabled(false, text_edit);
@@ -812,19 +4163,7 @@ impl Gui {
                         let tooltip_id = egui::Id::new("warning-copypasta");
 
                         if ui.button("Copy to Clipboard").clicked() {
-                            let mut copied = false;
-                            if let Ok(mut clipboard) = ClipboardContext::new() {
-                                copied =
-                                    clipboard.set_contents(warning.warning.to_string()).is_ok();
-                            }
-
-                            let label = if copied {
-                                "Copied!"
-                            } else {
-                                "Sorry, but the clipboard isn't working..."
-                            };
-
-                            self.add_tooltip(tooltip_id, label);
+                            self.copy_text(warning.warning.to_string(), tooltip_id);
                         }
 
                         // Show the copy button tooltip for 3 seconds
@@ -832,6 +4171,10 @@ impl Gui {
                     });
                 });
 
+            if content_size != size {
+                self.config.update_message_size(content_size);
+            }
+
             if window_open {
                 // Put the warning back
                 self.show_warnings.push_back(warning);
@@ -841,12 +4184,32 @@ impl Gui {
         self.warning = window_open;
     }
 
-    /// Add a n update notification to the GUI.
+    /// Get the content size for the error and warning windows.
+    fn message_size(&self) -> egui::Vec2 {
+        self.config
+            .get_message_size()
+            .unwrap_or(MESSAGE_SIZE)
+            .max(MESSAGE_MIN_SIZE)
+    }
+
+    /// Add an update notification to the GUI.
+    ///
+    /// A footer that the user dismissed is shown again when the notification is for a newer version.
+    #[cfg(feature = "update-check")]
     pub(crate) fn add_update_notification(&mut self, notification: UpdateNotification) {
+        let newer = self
+            .show_update_notification
+            .as_ref()
+            .map_or(true, |current| notification.version > current.version);
+        if newer {
+            self.update_footer_dismissed = false;
+        }
+
         self.show_update_notification = Some(notification);
     }
 
     /// Show update notification window.
+    #[cfg(feature = "update-check")]
     fn show_update_notification(&mut self, ctx: &egui::CtxRef, enabled: bool) {
         let update_notification = self.show_update_notification.as_ref();
         if let Some(update_notification) = update_notification {
@@ -871,7 +4234,13 @@ impl Gui {
 
                     ui.separator();
 
+                    #[cfg(feature = "web-links")]
                     ui.hyperlink_to("Download update", &update_notification.update_url);
+                    #[cfg(not(feature = "web-links"))]
+                    ui.label(format!(
+                        "Download update: {}",
+                        update_notification.update_url
+                    ));
                 });
         }
     }
@@ -908,10 +4277,39 @@ impl Gui {
 
                 // Put the tooltip back until it expires
                 self.show_tooltips.insert(tooltip_id, (label, created));
+                self.repaint_at(created + duration);
+            }
+        }
+    }
+
+    /// Detect scale factor changes, e.g. when the window moves to a monitor with a different DPI.
+    ///
+    /// Grid column widths are measured from text layouts, and egui remembers sizes (like scroll
+    /// areas and windows) from the previous frame. All of these are stale for one frame after the
+    /// scale factor changes, so another frame is requested to lay out everything at the new scale.
+    fn check_scale_factor(&mut self, ctx: &egui::CtxRef) {
+        let pixels_per_point = ctx.pixels_per_point();
+
+        if let Some(previous) = self.pixels_per_point.replace(pixels_per_point) {
+            if (previous - pixels_per_point).abs() > f32::EPSILON {
+                ctx.request_repaint();
             }
         }
     }
 
+    /// Request a redraw at the given time, so time-based state can be updated without input.
+    fn repaint_at(&mut self, deadline: Instant) {
+        self.repaint_deadline = Some(
+            self.repaint_deadline
+                .map_or(deadline, |current| current.min(deadline)),
+        );
+    }
+
+    /// Get the time when the GUI must be redrawn without any input, if any.
+    pub(crate) fn repaint_deadline(&self) -> Option<Instant> {
+        self.repaint_deadline
+    }
+
     /// Get an event-loop proxy that can be used to send events back to the `winit` event loop.
     pub(crate) fn event_loop_proxy(&self) -> EventLoopProxy<UserEvent> {
         self.event_loop_proxy.clone()
@@ -952,6 +4350,38 @@ impl ErrorButton {
     }
 }
 
+impl SafeRangeInput {
+    /// Parse the text fields into a property name and its safe range.
+    ///
+    /// Returns `None` when the property name is empty, a limit is not a number, or neither limit
+    /// is given. Empty limits are left out of the range.
+    fn parse(&self) -> Option<(String, SafeRange)> {
+        let prop_name = self.prop_name.trim();
+        let limit = |text: &str| -> Result<Option<f64>, std::num::ParseFloatError> {
+            let text = text.trim();
+            if text.is_empty() {
+                Ok(None)
+            } else {
+                text.parse().map(Some)
+            }
+        };
+        let min = limit(&self.min).ok()?;
+        let max = limit(&self.max).ok()?;
+
+        if prop_name.is_empty() || (min.is_none() && max.is_none()) {
+            return None;
+        }
+
+        let range = SafeRange {
+            min,
+            max,
+            unit: self.unit.trim().to_string(),
+        };
+
+        Some((prop_name.to_string(), range))
+    }
+}
+
 impl ShowWarning {
     /// Create a warning message to be shown to the user.
     pub(crate) fn new<E, S>(warning: E, context: S) -> Self
@@ -966,6 +4396,175 @@ impl ShowWarning {
     }
 }
 
+/// Scroll the parent `ScrollArea` so the content is shown from `offset`.
+fn scroll_to_offset(ui: &egui::Ui, offset: egui::Vec2) {
+    // Item spacing is subtracted when scrolling to align the top left corner
+    let min = ui.min_rect().min + offset + ui.spacing().item_spacing;
+    let rect = egui::Rect::from_min_size(min, egui::Vec2::ZERO);
+
+    ui.interact(rect, ui.id().with("restore-scroll"), egui::Sense::hover())
+        .scroll_to_me(egui::Align::Min);
+}
+
+/// Format a path for display, stripping Windows extended-length path prefixes.
+fn display_path(path: &Path) -> Cow<'_, str> {
+    fn strip_prefix(label: &str) -> &str {
+        label
+            .strip_prefix(r"\\?\UNC\")
+            .or_else(|| label.strip_prefix(r"\\?\"))
+            .or_else(|| label.strip_prefix(r"\??\"))
+            .unwrap_or(label)
+    }
+
+    match path.to_string_lossy() {
+        Cow::Borrowed(label) => Cow::Borrowed(strip_prefix(label)),
+        Cow::Owned(label) => Cow::Owned(strip_prefix(&label).to_string()),
+    }
+}
+
+/// Sort track or car names by the user's sort order choice.
+///
+/// `last_used` provides the time each name was last selected, for sorting by most recently used.
+/// Get the height available for message text in the error and warning windows.
+///
+/// The text grows with the window, leaving room for the buttons below it.
+fn message_text_height(ui: &egui::Ui) -> f32 {
+    let footer = ui.spacing().interact_size.y + ui.spacing().item_spacing.y * 4.0;
+
+    (ui.available_height() - footer).max(0.0)
+}
+
+/// Check if the window can be kept above other windows.
+///
+/// Wayland compositors decide the window order, so the request is ignored there.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn supports_always_on_top(window: &winit::window::Window) -> bool {
+    use winit::platform::unix::WindowExtUnix;
+
+    window.wayland_surface().is_none()
+}
+
+/// Check if the window can be kept above other windows.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+fn supports_always_on_top(_window: &winit::window::Window) -> bool {
+    true
+}
+
+/// Show a warning label when text in the `fg` color is hard to read on the `bg` color.
+///
+/// Colors come from the current theme, so the check follows theme changes.
+fn contrast_warning(ui: &mut egui::Ui, fg: egui::Color32, bg: egui::Color32) {
+    let ratio = contrast_ratio(fg, bg);
+    if ratio < MIN_CONTRAST {
+        ui.label(egui::RichText::new("Low contrast").small().strong())
+            .on_hover_text(format!(
+                "This color may be hard to read with the current theme (contrast ratio {:.1}:1).",
+                ratio,
+            ));
+    }
+}
+
+/// Get the setups that can be selected for comparison, with the track name for each setup.
+///
+/// When browsing by track, these are the setups for the car at the selected track. When browsing
+/// by car, these are the setups for the car at every track.
+fn available_setups<'a>(
+    setups: &'a Setups,
+    browse: Browse,
+    track_name: Option<&'a String>,
+    car_name: Option<&'a String>,
+) -> Vec<(&'a str, &'a SetupInfo)> {
+    let car_name = match car_name {
+        Some(car_name) => car_name,
+        None => return Vec::new(),
+    };
+
+    match browse {
+        Browse::Track => track_name
+            .and_then(|track_name| {
+                let infos = setups.tracks().get(track_name)?.get(car_name)?;
+
+                Some(
+                    infos
+                        .iter()
+                        .map(|info| (track_name.as_str(), info))
+                        .collect(),
+                )
+            })
+            .unwrap_or_default(),
+        Browse::Car => setups.car_setups(car_name),
+    }
+}
+
+/// Get the column header labels for setups in the grid.
+///
+/// Columns show the setup date in the `locale`, and are named with their track when browsing by
+/// car.
+fn column_header(browse: Browse, setups: &[(&str, &SetupInfo)], locale: Locale) -> Vec<String> {
+    setups
+        .iter()
+        .map(|&(track_name, info)| {
+            let date = info
+                .date()
+                .map(|date| locale.format_date(date.as_str()).into_owned());
+            match browse {
+                Browse::Track => date.unwrap_or_default(),
+                Browse::Car => std::iter::once(track_name.to_string())
+                    .chain(date)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            }
+        })
+        .collect()
+}
+
+/// Get the name shown for a setup. Setups are named with their track when browsing by car.
+///
+/// Names are cleaned up from the file name when `clean_names` is enabled.
+fn setup_label<'a>(
+    browse: Browse,
+    track_name: &str,
+    info: &'a SetupInfo,
+    clean_names: bool,
+) -> Cow<'a, str> {
+    let name = if clean_names {
+        Cow::Owned(info.clean_name())
+    } else {
+        Cow::Borrowed(info.name())
+    };
+
+    match browse {
+        Browse::Track => name,
+        Browse::Car => Cow::Owned(format!("{} ({})", name, track_name)),
+    }
+}
+
+fn sort_names<F>(names: &mut [&String], sort_order: SortOrder, last_used: F)
+where
+    F: Fn(&str) -> Option<i64>,
+{
+    match sort_order {
+        SortOrder::Name => names.sort_unstable_by(|a, b| a.human_compare(b)),
+        SortOrder::Recent => names.sort_unstable_by(|a, b| {
+            last_used(b)
+                .cmp(&last_used(a))
+                .then_with(|| a.human_compare(b))
+        }),
+    }
+}
+
 /// Get the width for a combo box by finding the widest string that it contains.
 fn get_combo_box_width<'a>(ui: &egui::Ui, choices: impl Iterator<Item = &'a String>) -> f32 {
     let spacing = ui.spacing();
@@ -1,22 +1,68 @@
-use crate::setup::Setup;
-use crate::str_ext::HumanCompare;
+use super::table::Table;
+use crate::config::{DiffBlend, DiffReference, GridOptions, MIN_COLUMN_WIDTH};
+use crate::formatter::format_value;
+use crate::setup::{Change, Diff, Setup};
+use crate::str_ext::Ellipsis;
+use crate::value::{compare_positions, compare_values, PerchOffsetUnit, Value};
+use epaint::text::{LayoutJob, TextFormat};
 use epaint::Galley;
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+/// Colors with a lower contrast ratio than this against their background are hard to read.
+pub(super) const MIN_CONTRAST: f32 = 3.0;
+
+/// Opacity of diff colors painted over the code background by [`DiffBlend::Solid`].
+const SOLID_ALPHA: f32 = 0.5;
+
+/// Outline color for values outside of their safe range.
+const OUT_OF_RANGE_COLOR: egui::Color32 = egui::Color32::from_rgb(0xff, 0x8c, 0x00);
+
+/// Every glyph that `unicode_fractions()` may produce.
+const FRACTION_GLYPHS: &str = "½⅓⅔¼¾⅕⅖⅗⅘⅙⅚⅛⅜⅝⅞⁄⁰¹²³⁴⁵⁶⁷⁸⁹₀₁₂₃₄₅₆₇₈₉";
+
 /// Provides structure for representing a grid of string values.
-pub(crate) struct SetupGrid<'setup> {
+///
+/// The grid owns all of its text layouts, so it can be kept and drawn again on later frames.
+pub(crate) struct SetupGrid {
     /// Column widths are provided here.
     columns: Vec<f32>,
 
+    /// An optional row of labels above the groups.
+    header: Option<Vec<Label>>,
+
+    /// Optional setup names for each column, used to copy cell references.
+    setup_names: Option<Vec<String>>,
+
     /// The grid contains zero or more groups.
-    groups: Vec<Group<'setup>>,
+    groups: Vec<Group>,
+
+    /// Locked columns can be resized by dragging their edges in the header.
+    resizable: bool,
+
+    /// Text is scaled by this factor, see [`SetupGrid::with_zoom`].
+    zoom: f32,
+}
+
+/// What the user did with the grid while it was drawn.
+#[derive(Debug, Default)]
+pub(crate) struct GridResponse {
+    /// Text to copy, when a value or cell reference is copied from a cell context menu.
+    pub(crate) copy: Option<String>,
+
+    /// New column widths, while a locked column is being resized.
+    pub(crate) column_widths: Option<Vec<f32>>,
 }
 
 /// A group containing a matrix of strings.
-struct Group<'setup> {
+struct Group {
     /// Group name is shown in a collapsible header.
-    name: &'setup str,
+    name: String,
+
+    /// The header starts collapsed, until the user expands it.
+    collapsed: bool,
 
     /// The matrix is row-major.
     ///
@@ -26,34 +72,101 @@ struct Group<'setup> {
 
 /// A label that can be displayed in a column.
 struct Label {
-    /// Diffs get a background color.
+    /// Diffs get a background color, already blended with the code background.
     background: Option<egui::Color32>,
 
+    /// Values that are the same in every setup may be drawn with low contrast.
+    dimmed: bool,
+
+    /// Values outside of their safe range are outlined.
+    out_of_range: bool,
+
     /// Container for the label text, style, and color.
     galley: Arc<Galley>,
+
+    /// Plain text that is copied from the cell. Header labels have none.
+    text: Option<String>,
+
+    /// Text shown when hovering a label that was shortened to fit a locked column.
+    full_text: Option<String>,
 }
 
-impl<'setup> SetupGrid<'setup> {
+impl SetupGrid {
     /// Create a new `SetupGrid` from a slice of `Setup`s.
+    ///
+    /// Property names are displayed with their aliases, when one exists. Spring perch offsets are
+    /// converted to the `perch_offset_unit` before they are compared. Property groups named in
+    /// `hidden_groups` are left out.
+    ///
+    /// Each column is compared with the first column, or with the column to its left when the
+    /// `diff_reference` is [`DiffReference::Previous`]. The first column is never colored as a
+    /// diff.
+    ///
+    /// Properties with multiple values (e.g. tire temperatures) are compared as a whole, unless
+    /// `positional_diffs` is enabled. Then each value is compared with the value in the same
+    /// position of the reference column, and colored separately.
+    ///
+    /// Fractions are shown with Unicode fraction glyphs when `fraction_glyphs` is enabled and the
+    /// font has all of them, and numbers are shown in the `locale`. Converted values are rewritten by
+    /// the `formatters` for their property before either of these. Values are always compared as
+    /// they appear in the export.
+    ///
+    /// When `dim_unchanged` is enabled and more than one setup is compared, values that are the same
+    /// in every setup are dimmed.
+    ///
+    /// The `diff_colors` are swapped for properties matching `swapped_diffs`, where lower values
+    /// are better. They are blended into the code background with the `diff_blend` mode, and the
+    /// text over them switches to black or white when it would be hard to read.
+    ///
+    /// Values outside of the property's safe range are outlined, regardless of their diff color.
+    /// Multi-value properties are outlined when any value is outside of the range.
+    ///
+    /// Property groups named in `collapsed_groups` start collapsed.
     pub(crate) fn new(
         ui: &egui::Ui,
-        setups: &'setup [&'setup Setup],
+        setups: &[&Setup],
         colors: &[egui::Color32],
         diff_colors: (egui::Color32, egui::Color32),
+        aliases: &HashMap<String, String>,
+        hidden_groups: &HashSet<String>,
+        options: GridOptions,
     ) -> Self {
+        let fraction_glyphs = options.fraction_glyphs && has_fraction_glyphs(ui);
+        let locale = options.locale;
+        let display = |prop_name: &str, text: &str| -> String {
+            let text = format_value(&options.formatters, prop_name, text);
+            let text = locale.format_value(&text);
+            if fraction_glyphs {
+                unicode_fractions(&text).into_owned()
+            } else {
+                text.into_owned()
+            }
+        };
+
         // Gather groups
         let groups = setups
             .iter()
             .map(|inner| inner.keys().map(|s| s.as_str()).collect::<Vec<_>>());
-        let groups = intersect_keys(groups);
+        let mut groups = intersect_keys(groups);
+        groups.retain(|name| !hidden_groups.contains(*name));
 
         let column_count = setups.len() + 1;
         let mut output = Self {
             columns: Vec::with_capacity(column_count),
+            header: None,
+            setup_names: None,
             groups: Vec::with_capacity(groups.len()),
+            resizable: false,
+            zoom: 1.0,
         };
         output.columns.resize(column_count, 0.0);
 
+        // Diff colors are blended once, instead of for every value
+        let diff_colors = (
+            diff_background(ui.visuals(), diff_colors.0, options.diff_blend),
+            diff_background(ui.visuals(), diff_colors.1, options.diff_blend),
+        );
+
         for prop_group in groups {
             // Gather property names
             let prop_names = setups.iter().map(|setup| {
@@ -67,7 +180,11 @@ impl<'setup> SetupGrid<'setup> {
             let prop_names = intersect_keys(prop_names);
 
             let mut group = Group {
-                name: prop_group,
+                name: prop_group.to_string(),
+                collapsed: options
+                    .collapsed_groups
+                    .iter()
+                    .any(|name| name == prop_group),
                 matrix: Vec::with_capacity(prop_names.len()),
             };
 
@@ -76,8 +193,11 @@ impl<'setup> SetupGrid<'setup> {
                 let mut columns = Vec::with_capacity(column_count);
 
                 // Calculate width of `prop_name`
+                let label = aliases
+                    .get(prop_name)
+                    .map_or(prop_name, |alias| alias.as_str());
                 let galley = ui.fonts().layout_no_wrap(
-                    prop_name.to_string(),
+                    label.to_string(),
                     egui::TextStyle::Body,
                     ui.visuals().text_color(),
                 );
@@ -87,54 +207,111 @@ impl<'setup> SetupGrid<'setup> {
 
                 columns.push(Label {
                     background: None,
+                    dimmed: false,
+                    out_of_range: false,
                     galley,
+                    text: Some(label.to_string()),
+                    full_text: None,
                 });
 
-                let mut colors = colors.iter().cloned().cycle();
+                let diff_colors = if options.is_diff_swapped(prop_name) {
+                    (diff_colors.1, diff_colors.0)
+                } else {
+                    diff_colors
+                };
                 let mut first_value: Option<String> = None;
+                let mut all_equal = true;
+                let mut colors = colors.iter().cloned().cycle();
+                let mut reference: Option<(Vec<String>, String)> = None;
+                let unit = Some(options.perch_offset_unit)
+                    .filter(|_| PerchOffsetUnit::applies_to(prop_name));
+                let safe_range = options.safe_ranges.get(prop_name);
 
                 for setup in setups {
                     let values = setup.get(prop_group).unwrap().get_all(prop_name);
-                    let separator = if values
-                        .clone()
-                        .all(|v| v.starts_with(|ch: char| ch.is_ascii_digit()))
-                    {
-                        ", "
+                    let (values, separator) = convert_values(values, unit);
+                    let value = values.join(separator);
+                    let shown: Vec<_> = values.iter().map(|v| display(prop_name, v)).collect();
+                    let shown_separator = if separator == ", " {
+                        locale.list_separator()
                     } else {
-                        " "
+                        separator
                     };
-                    let value: String = values
-                        .enumerate()
-                        .map(|(i, v)| {
-                            if i > 0 {
-                                format!("{}{}", separator, v)
-                            } else {
-                                v.to_string()
-                            }
-                        })
-                        .collect();
+                    let out_of_range = safe_range
+                        .map_or(false, |range| values.iter().any(|v| range.is_outside(v)));
 
-                    // Compute diff between `value` and first column
+                    // Compute diff between `value` and the reference column
                     let color = colors.next().unwrap_or_else(|| ui.visuals().text_color());
-                    let (color, background) = if let Some(first_value) = first_value.as_ref() {
-                        match value.human_compare(first_value) {
-                            Ordering::Less => (ui.visuals().text_color(), Some(diff_colors.0)),
-                            Ordering::Greater => (ui.visuals().text_color(), Some(diff_colors.1)),
-                            Ordering::Equal => (color, None),
+                    let (galley, background) = match reference.as_ref() {
+                        Some((ref_values, _)) if options.positional_diffs && values.len() > 1 => {
+                            let orderings = compare_positions(&values, ref_values);
+                            let job = positional_job(
+                                ui,
+                                &shown,
+                                shown_separator,
+                                &orderings,
+                                color,
+                                diff_colors,
+                            );
+
+                            (ui.fonts().layout_job(job), None)
+                        }
+                        Some((_, ref_value)) => {
+                            let background = match compare_values(&value, ref_value) {
+                                Ordering::Less => Some(diff_colors.0),
+                                Ordering::Greater => Some(diff_colors.1),
+                                Ordering::Equal => None,
+                            };
+                            let color = background.map_or(color, |background| {
+                                diff_text_color(ui.visuals(), background)
+                            });
+                            let galley = ui.fonts().layout_no_wrap(
+                                shown.join(shown_separator),
+                                egui::TextStyle::Body,
+                                color,
+                            );
+
+                            (galley, background)
+                        }
+                        None => {
+                            let galley = ui.fonts().layout_no_wrap(
+                                shown.join(shown_separator),
+                                egui::TextStyle::Body,
+                                color,
+                            );
+
+                            (galley, None)
                         }
-                    } else {
-                        first_value = Some(value.clone());
-                        (color, None)
                     };
+                    match first_value.as_ref() {
+                        Some(first_value) => {
+                            all_equal &= compare_values(&value, first_value) == Ordering::Equal;
+                        }
+                        None => first_value = Some(value.clone()),
+                    }
+                    if reference.is_none() || options.diff_reference == DiffReference::Previous {
+                        reference = Some((values, value.clone()));
+                    }
 
-                    let galley = ui
-                        .fonts()
-                        .layout_no_wrap(value, egui::TextStyle::Body, color);
                     let width = galley.rect.width() + ui.spacing().item_spacing.x * 2.0;
                     output.columns[i] = output.columns[i].max(width);
                     i += 1;
 
-                    columns.push(Label { background, galley });
+                    columns.push(Label {
+                        background,
+                        dimmed: false,
+                        out_of_range,
+                        galley,
+                        text: Some(value),
+                        full_text: None,
+                    });
+                }
+
+                // The property name is never dimmed
+                if options.dim_unchanged && all_equal && setups.len() > 1 {
+                    for label in &mut columns[1..] {
+                        label.dimmed = true;
+                    }
                 }
 
                 group.matrix.push(columns);
@@ -146,58 +323,867 @@ impl<'setup> SetupGrid<'setup> {
         output
     }
 
+    /// Scale the text and the columns by `zoom`.
+    ///
+    /// Only the grid is zoomed; group names and the rest of the UI keep their text size. Call this
+    /// before [`Self::with_header`] and [`Self::with_column_widths`], which lay out text at the same
+    /// scale.
+    pub(crate) fn with_zoom(mut self, zoom: f32) -> Self {
+        let labels = self.header.iter_mut().flatten().chain(
+            self.groups
+                .iter_mut()
+                .flat_map(|group| group.matrix.iter_mut().flatten()),
+        );
+        for label in labels {
+            label.galley = zoom_galley(Arc::clone(&label.galley), zoom / self.zoom);
+        }
+        for width in &mut self.columns {
+            *width *= zoom / self.zoom;
+        }
+        self.zoom = zoom;
+
+        self
+    }
+
+    /// Add a row of labels above the groups, one for each setup column.
+    ///
+    /// E.g. the date of each setup, and its track when setups from different tracks are compared.
+    pub(crate) fn with_header(mut self, ui: &egui::Ui, labels: &[&str]) -> Self {
+        let spacing = ui.spacing().item_spacing.x * self.zoom;
+        let zoom = self.zoom;
+        let labels = std::iter::once("").chain(labels.iter().copied());
+        let header = self
+            .columns
+            .iter_mut()
+            .zip(labels)
+            .map(|(width, label)| {
+                let galley = ui.fonts().layout_no_wrap(
+                    label.to_string(),
+                    egui::TextStyle::Body,
+                    ui.visuals().text_color(),
+                );
+                let galley = zoom_galley(galley, zoom);
+                *width = width.max(galley.rect.width() + spacing * 2.0);
+
+                Label {
+                    background: None,
+                    dimmed: false,
+                    out_of_range: false,
+                    galley,
+                    text: None,
+                    full_text: None,
+                }
+            })
+            .collect();
+        self.header = Some(header);
+
+        self
+    }
+
+    /// Name the setup in each column, so cells can be copied from their context menu.
+    ///
+    /// Cells have no context menu without setup names, e.g. in image exports.
+    pub(crate) fn with_setup_names(mut self, names: Vec<String>) -> Self {
+        self.setup_names = Some(names);
+
+        self
+    }
+
+    /// Lock the column widths, starting with the property names column.
+    ///
+    /// Columns without a locked width use the width of the last locked setup column, or fit their
+    /// contents when there is none. Labels that are too wide for their column are shortened with an
+    /// ellipsis, and shown in full when hovered. Call this after [`Self::with_header`], so header
+    /// labels are shortened too.
+    pub(crate) fn with_column_widths(mut self, ui: &egui::Ui, widths: &[f32]) -> Self {
+        self.columns = locked_widths(&self.columns, widths);
+        self.resizable = true;
+
+        let padding = ui.spacing().item_spacing.x * 2.0;
+        let rows = self.header.iter_mut().chain(
+            self.groups
+                .iter_mut()
+                .flat_map(|group| group.matrix.iter_mut()),
+        );
+        for row in rows {
+            for (label, width) in row.iter_mut().zip(&self.columns) {
+                if let Some(galley) = fit_galley(ui, &label.galley, width - padding, self.zoom) {
+                    label.full_text = Some(label.galley.job.text.clone());
+                    label.galley = galley;
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Get the width of each column, starting with the property names column.
+    pub(crate) fn column_widths(&self) -> &[f32] {
+        &self.columns
+    }
+
+    /// Create a table with the text shown in each cell, for exporting the grid as it is shown.
+    ///
+    /// Values keep their formatters, locale, and fraction glyphs. Labels shortened to fit a locked
+    /// column are written in full. Columns are named with the setup names, when there are any.
+    pub(crate) fn table(&self) -> Table {
+        let shown = |label: &Label| {
+            label
+                .full_text
+                .clone()
+                .unwrap_or_else(|| label.galley.job.text.clone())
+        };
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| {
+                let rows = group
+                    .matrix
+                    .iter()
+                    .map(|row| row.iter().map(shown).collect())
+                    .collect();
+
+                (group.name.clone(), rows)
+            })
+            .collect();
+
+        Table::from_groups(self.setup_names.clone().unwrap_or_default(), groups)
+    }
+
     /// Draw the grid to the provided `Ui`.
-    pub(crate) fn show(self, ui: &mut egui::Ui, car_name: &str) {
+    ///
+    /// Space is allocated for every cell, so the scroll area keeps its full size. Only cells within
+    /// the visible viewport are painted. Text is not laid out here; that is done once by
+    /// [`Self::new`], so keep the grid for as long as the comparison does not change.
+    ///
+    /// The response has the text to copy when a value or cell reference is copied from a cell
+    /// context menu, and the new column widths while a locked column is resized.
+    pub(crate) fn show(&self, ui: &mut egui::Ui, car_name: &str) -> GridResponse {
         let column_widths = &self.columns;
+        let setup_names = self.setup_names.as_deref();
+        let mut copy = None;
+        let mut resized = None;
+
+        if let Some(header) = &self.header {
+            let top = ui.available_rect_before_wrap().min;
+            show_row(ui, column_widths, header, None);
+
+            if self.resizable {
+                let bottom = ui.available_rect_before_wrap().top();
+                resized = resize_columns(ui, column_widths, top, bottom);
+            }
+        }
 
         // Draw headers
-        for prop_group in self.groups.into_iter() {
-            egui::CollapsingHeader::new(prop_group.name)
+        for prop_group in &self.groups {
+            egui::CollapsingHeader::new(&prop_group.name)
                 .id_source(format!("{}-{}", car_name, prop_group.name))
-                .default_open(true)
+                .default_open(!prop_group.collapsed)
                 .show(ui, |ui| {
+                    let context = setup_names.map(|setup_names| CellContext {
+                        car_name,
+                        group_name: &prop_group.name,
+                        setup_names,
+                    });
+
                     // Draw each row
-                    for row in prop_group.matrix.into_iter() {
-                        ui.horizontal(|ui| {
-                            // Draw each column
-                            for (i, label) in row.into_iter().enumerate() {
-                                let size =
-                                    egui::Vec2::new(column_widths[i], label.galley.rect.height());
-                                let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
-
-                                // Draw optional background color
-                                if let Some(background) = label.background {
-                                    let fill = egui::Rgba::from(ui.visuals().code_bg_color);
-                                    let background = egui::Rgba::from(background);
-                                    let color = egui::Color32::from(background * fill);
-                                    let rect = egui::Rect::from_min_size(
-                                        rect.min,
-                                        label.galley.rect.size(),
-                                    );
-
-                                    ui.painter().rect_filled(rect.expand(3.0), 4.0, color);
-                                }
-
-                                // Draw text
-                                ui.painter().galley(rect.min, label.galley);
-                            }
-                        });
+                    for row in &prop_group.matrix {
+                        if let Some(text) = show_row(ui, column_widths, row, context.as_ref()) {
+                            copy = Some(text);
+                        }
                     }
                 });
         }
+
+        GridResponse {
+            copy,
+            column_widths: resized,
+        }
     }
 }
 
-/// Get the intersection of keys that exists in each `HashMap`.
-fn intersect_keys<'a>(mut all_keys: impl Iterator<Item = Vec<&'a str>>) -> Vec<&'a str> {
-    let mut output = if let Some(output) = all_keys.next() {
-        output
+/// Where the cells in a row are found, for copying cell references.
+struct CellContext<'a> {
+    car_name: &'a str,
+    group_name: &'a str,
+    setup_names: &'a [String],
+}
+
+impl CellContext<'_> {
+    /// Describe a value with its car, group, property, and setup, e.g.
+    /// `Porsche 911 GT3 R / Left Front / Camber: -4.0 deg (baseline.htm)`.
+    fn reference(&self, prop_name: &str, value: &str, column: usize) -> String {
+        let mut reference = format!(
+            "{} / {} / {}: {}",
+            self.car_name, self.group_name, prop_name, value
+        );
+        if let Some(setup_name) = self.setup_names.get(column) {
+            reference.push_str(&format!(" ({})", setup_name));
+        }
+
+        reference
+    }
+}
+
+/// Draw a row of labels in columns.
+///
+/// Rows outside of the viewport only allocate space, and only visible columns are painted. With a
+/// `context`, values have a context menu for copying them, and the text to copy is returned.
+/// Shortened labels show their full text when hovered.
+fn show_row(
+    ui: &mut egui::Ui,
+    column_widths: &[f32],
+    row: &[Label],
+    context: Option<&CellContext<'_>>,
+) -> Option<String> {
+    let spacing = ui.spacing().item_spacing.x;
+    let row_width =
+        column_widths.iter().sum::<f32>() + spacing * column_widths.len().saturating_sub(1) as f32;
+
+    let height = row
+        .iter()
+        .map(|label| label.galley.rect.height())
+        .fold(0.0, f32::max);
+    let size = egui::Vec2::new(row_width, height);
+    let (row_rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+
+    // Skip rows outside of the viewport
+    let clip_rect = ui.clip_rect();
+    if !clip_rect.intersects(row_rect.expand(3.0)) {
+        return None;
+    }
+
+    // Draw each visible column
+    let visible = visible_columns(column_widths, spacing, row_rect.min.x, clip_rect.x_range());
+    let mut x = row_rect.min.x
+        + column_widths[..visible.start].iter().sum::<f32>()
+        + spacing * visible.start as f32;
+
+    let prop_name = row.first().and_then(|label| label.text.as_ref());
+    let mut copy = None;
+
+    let cells = row.iter().enumerate();
+    for (i, label) in cells.take(visible.end).skip(visible.start) {
+        let pos = egui::Pos2::new(x, row_rect.min.y);
+        x += column_widths[i] + spacing;
+
+        // Values can be copied from a context menu
+        let rect = egui::Rect::from_min_size(pos, label.galley.rect.size());
+        let mut response = None;
+        if let (Some(context), Some(prop_name), Some(value)) = (context, prop_name, &label.text) {
+            if i > 0 {
+                let id = egui::Id::new((context.car_name, context.group_name, prop_name, i));
+                response = Some(
+                    ui.interact(rect, id, egui::Sense::click())
+                        .context_menu(|ui| {
+                            if ui.button("Copy Value").clicked() {
+                                ui.close_menu();
+                                copy = Some(value.clone());
+                            }
+                            if ui.button("Copy Cell Reference").clicked() {
+                                ui.close_menu();
+                                copy = Some(context.reference(prop_name, value, i - 1));
+                            }
+                        }),
+                );
+            }
+        }
+
+        // Shortened labels are shown in full when hovered
+        if let Some(full_text) = &label.full_text {
+            let response = response.unwrap_or_else(|| {
+                let id = ui.id().with(("shortened label", i, full_text));
+                ui.interact(rect, id, egui::Sense::hover())
+            });
+            response.on_hover_text(full_text);
+        }
+
+        // Draw optional background color
+        if let Some(background) = label.background {
+            ui.painter().rect_filled(rect.expand(3.0), 4.0, background);
+        }
+
+        // Draw outline for values outside of their safe range
+        if label.out_of_range {
+            let stroke = egui::Stroke::new(2.0, OUT_OF_RANGE_COLOR);
+
+            ui.painter().rect_stroke(rect.expand(3.0), 4.0, stroke);
+        }
+
+        // Draw text
+        if label.dimmed {
+            ui.painter().galley_with_color(
+                pos,
+                Arc::clone(&label.galley),
+                ui.visuals().weak_text_color(),
+            );
+        } else {
+            ui.painter().galley(pos, Arc::clone(&label.galley));
+        }
+    }
+
+    copy
+}
+
+/// Add a handle to the right edge of each column, between `top` and `bottom`, for resizing locked
+/// columns by dragging.
+///
+/// Returns the new column widths while a handle is dragged.
+fn resize_columns(
+    ui: &egui::Ui,
+    column_widths: &[f32],
+    top: egui::Pos2,
+    bottom: f32,
+) -> Option<Vec<f32>> {
+    let spacing = ui.spacing().item_spacing.x;
+    let mut x = top.x;
+    let mut resized = None;
+
+    for (i, &width) in column_widths.iter().enumerate() {
+        // Handles are centered in the space between columns
+        x += width + spacing / 2.0;
+
+        let rect = egui::Rect::from_x_y_ranges(x - 3.0..=x + 3.0, top.y..=bottom);
+        let id = ui.id().with(("resize column", i));
+        let response = ui.interact(rect, id, egui::Sense::drag());
+        if response.hovered() || response.dragged() {
+            ui.output().cursor_icon = egui::CursorIcon::ResizeHorizontal;
+
+            let stroke = ui.visuals().widgets.hovered.fg_stroke;
+            ui.painter()
+                .line_segment([egui::pos2(x, top.y), egui::pos2(x, bottom)], stroke);
+        }
+
+        let delta = response.drag_delta().x;
+        if response.dragged() && delta != 0.0 {
+            let mut widths = column_widths.to_vec();
+            widths[i] = (width + delta).max(MIN_COLUMN_WIDTH);
+            resized = Some(widths);
+        }
+
+        x += spacing / 2.0;
+    }
+
+    resized
+}
+
+/// Choose the width of each column from the `locked` widths, falling back to the `fitted` widths.
+///
+/// Columns past the end of the locked widths, e.g. for setups added to the comparison after the
+/// widths were locked, use the last locked setup column width.
+fn locked_widths(fitted: &[f32], locked: &[f32]) -> Vec<f32> {
+    let last_setup_width = locked.get(1..).and_then(<[f32]>::last);
+
+    fitted
+        .iter()
+        .enumerate()
+        .map(|(i, fitted)| *locked.get(i).or(last_setup_width).unwrap_or(fitted))
+        .collect()
+}
+
+/// Lay out a label again at the `zoom` scale, shortened with an ellipsis to fit within `width`.
+///
+/// The longest text that fits is chosen. Returns `None` when the label already fits, or when it
+/// cannot be shortened.
+fn fit_galley(ui: &egui::Ui, galley: &Galley, width: f32, zoom: f32) -> Option<Arc<Galley>> {
+    if galley.rect.width() <= width {
+        return None;
+    }
+
+    // Binary search over the maximum length in graphemes
+    let job = &galley.job;
+    let mut fitted = None;
+    let mut low = 0;
+    let mut high = job.text.len();
+    while low < high {
+        let max_length = (low + high + 1) / 2;
+        let shortened = ellipsis_job(job, max_length)
+            .map(|job| zoom_galley(ui.fonts().layout_job(job), zoom))
+            .filter(|galley| galley.rect.width() <= width);
+
+        match shortened {
+            Some(shortened) => {
+                fitted = Some(shortened);
+                low = max_length;
+            }
+            None => high = max_length - 1,
+        }
+    }
+
+    // The shortest text is used when nothing fits
+    fitted.or_else(|| {
+        Some(zoom_galley(
+            ui.fonts().layout_job(ellipsis_job(job, 0)?),
+            zoom,
+        ))
+    })
+}
+
+/// Scale a text layout by `zoom`.
+///
+/// egui has a single size for each text style, and the body text is shared with the rest of the
+/// UI. The grid is zoomed by scaling its layouts instead, which draws the same glyphs larger or
+/// smaller.
+fn zoom_galley(galley: Arc<Galley>, zoom: f32) -> Arc<Galley> {
+    if (zoom - 1.0).abs() <= f32::EPSILON {
+        return galley;
+    }
+
+    let scale = |pos: egui::Pos2| egui::Pos2::new(pos.x * zoom, pos.y * zoom);
+    let scale_rect = |rect: egui::Rect| egui::Rect::from_min_max(scale(rect.min), scale(rect.max));
+
+    let mut galley = (*galley).clone();
+    galley.rect = scale_rect(galley.rect);
+    galley.mesh_bounds = scale_rect(galley.mesh_bounds);
+    for row in &mut galley.rows {
+        row.rect = scale_rect(row.rect);
+        row.visuals.mesh_bounds = scale_rect(row.visuals.mesh_bounds);
+        for vertex in &mut row.visuals.mesh.vertices {
+            vertex.pos = scale(vertex.pos);
+        }
+        for glyph in &mut row.glyphs {
+            glyph.pos = scale(glyph.pos);
+            glyph.size *= zoom;
+        }
+    }
+
+    Arc::new(galley)
+}
+
+/// Shorten a layout job to `max_length` graphemes, including an ellipsis.
+///
+/// Each section keeps its style, so positional diffs keep their backgrounds. The ellipsis has the
+/// style of the last section that is kept. Returns `None` when the text is not shortened.
+fn ellipsis_job(job: &LayoutJob, max_length: usize) -> Option<LayoutJob> {
+    let text = match job.text.as_str().ellipsis(max_length) {
+        Cow::Owned(text) => text,
+        Cow::Borrowed(_) => return None,
+    };
+    let kept = text.len() - '…'.len_utf8();
+
+    let mut sections: Vec<_> = job
+        .sections
+        .iter()
+        .filter(|section| section.byte_range.start < kept)
+        .cloned()
+        .collect();
+    if let Some(last) = sections.last_mut() {
+        last.byte_range.end = text.len();
+    }
+
+    Some(LayoutJob {
+        text,
+        sections,
+        ..job.clone()
+    })
+}
+
+/// Lay out multiple values with a diff background behind each value that differs.
+///
+/// `orderings` has the comparison for each value against the same position in the first column.
+/// The `diff_colors` are already blended with the code background.
+fn positional_job(
+    ui: &egui::Ui,
+    values: &[String],
+    separator: &str,
+    orderings: &[Ordering],
+    color: egui::Color32,
+    diff_colors: (egui::Color32, egui::Color32),
+) -> LayoutJob {
+    let mut job = LayoutJob::default();
+
+    for (i, (value, ordering)) in values.iter().zip(orderings).enumerate() {
+        if i > 0 {
+            let format = TextFormat::simple(egui::TextStyle::Body, color);
+            job.append(separator, 0.0, format);
+        }
+
+        let (color, background) = match ordering {
+            Ordering::Less => (diff_text_color(ui.visuals(), diff_colors.0), diff_colors.0),
+            Ordering::Greater => (diff_text_color(ui.visuals(), diff_colors.1), diff_colors.1),
+            Ordering::Equal => (color, egui::Color32::TRANSPARENT),
+        };
+        let format = TextFormat {
+            background,
+            ..TextFormat::simple(egui::TextStyle::Body, color)
+        };
+        job.append(value, 0.0, format);
+    }
+
+    job
+}
+
+/// Check if the body font has a glyph for every character produced by `unicode_fractions()`.
+///
+/// Missing glyphs are drawn with a replacement glyph, so any character with the same width as an
+/// unassigned code point is assumed to be missing.
+fn has_fraction_glyphs(ui: &egui::Ui) -> bool {
+    let fonts = ui.fonts();
+    let missing = fonts.glyph_width(egui::TextStyle::Body, '\u{10ffff}');
+
+    FRACTION_GLYPHS
+        .chars()
+        .all(|ch| (fonts.glyph_width(egui::TextStyle::Body, ch) - missing).abs() > f32::EPSILON)
+}
+
+/// Replace fractions like `3/8` and `1/16` with Unicode fraction glyphs, e.g. `⅜` and `¹⁄₁₆`.
+///
+/// Fractions with a single vulgar fraction character use it, all others are composed from
+/// superscript and subscript digits. Slashes that are not between two integers are left alone.
+fn unicode_fractions(text: &str) -> Cow<'_, str> {
+    if !text.contains('/') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut output = String::with_capacity(text.len() * 2);
+    let mut rest = text;
+
+    while let Some(slash) = rest.find('/') {
+        let (before, after) = (&rest[..slash], &rest[slash + 1..]);
+        let whole = before.trim_end_matches(|ch: char| ch.is_ascii_digit());
+        let numerator = &before[whole.len()..];
+        let denominator_len = after
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or_else(|| after.len());
+        let (denominator, after) = after.split_at(denominator_len);
+
+        if numerator.is_empty()
+            || denominator.is_empty()
+            || whole.ends_with('.')
+            || after.starts_with('.')
+        {
+            output.push_str(&rest[..=slash]);
+            rest = &rest[slash + 1..];
+            continue;
+        }
+
+        output.push_str(whole);
+        output.push_str(&fraction_glyphs(numerator, denominator));
+        rest = after;
+    }
+    output.push_str(rest);
+
+    Cow::Owned(output)
+}
+
+/// Create the Unicode glyphs for a fraction of two integers.
+fn fraction_glyphs(numerator: &str, denominator: &str) -> String {
+    let vulgar = match (numerator, denominator) {
+        ("1", "2") => Some('½'),
+        ("1", "3") => Some('⅓'),
+        ("2", "3") => Some('⅔'),
+        ("1", "4") => Some('¼'),
+        ("3", "4") => Some('¾'),
+        ("1", "5") => Some('⅕'),
+        ("2", "5") => Some('⅖'),
+        ("3", "5") => Some('⅗'),
+        ("4", "5") => Some('⅘'),
+        ("1", "6") => Some('⅙'),
+        ("5", "6") => Some('⅚'),
+        ("1", "8") => Some('⅛'),
+        ("3", "8") => Some('⅜'),
+        ("5", "8") => Some('⅝'),
+        ("7", "8") => Some('⅞'),
+        _ => None,
+    };
+    if let Some(vulgar) = vulgar {
+        return vulgar.to_string();
+    }
+
+    let superscript = |ch: char| match ch {
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        _ => char::from_u32(0x2070 + ch as u32 - '0' as u32).unwrap(),
+    };
+    let subscript = |ch: char| char::from_u32(0x2080 + ch as u32 - '0' as u32).unwrap();
+
+    numerator
+        .chars()
+        .map(superscript)
+        .chain(std::iter::once('⁄'))
+        .chain(denominator.chars().map(subscript))
+        .collect()
+}
+
+/// Find the range of columns that overlap the visible horizontal range.
+///
+/// Columns start at `left` and are separated by `spacing`. Diff backgrounds extend slightly
+/// beyond their columns, so columns are considered visible when they are near the range.
+fn visible_columns(
+    column_widths: &[f32],
+    spacing: f32,
+    left: f32,
+    visible: std::ops::RangeInclusive<f32>,
+) -> std::ops::Range<usize> {
+    let mut start = column_widths.len();
+    let mut end = 0;
+    let mut x = left;
+
+    for (i, width) in column_widths.iter().enumerate() {
+        if x + width + 3.0 >= *visible.start() && x - 3.0 <= *visible.end() {
+            start = start.min(i);
+            end = i + 1;
+        }
+        x += width + spacing;
+    }
+
+    start.min(end)..end
+}
+
+/// Get the background color drawn behind a diff, by blending the diff color with the code
+/// background.
+pub(super) fn diff_background(
+    visuals: &egui::Visuals,
+    diff_color: egui::Color32,
+    blend: DiffBlend,
+) -> egui::Color32 {
+    let fill = egui::Rgba::from(visuals.code_bg_color);
+    let background = egui::Rgba::from(diff_color);
+
+    match blend {
+        DiffBlend::Multiply => egui::Color32::from(background * fill),
+        DiffBlend::Overlay => {
+            let overlay = |base: f32, blend: f32| {
+                if base < 0.5 {
+                    2.0 * base * blend
+                } else {
+                    1.0 - 2.0 * (1.0 - base) * (1.0 - blend)
+                }
+            };
+
+            egui::Color32::from(egui::Rgba::from_rgb(
+                overlay(fill.r(), background.r()),
+                overlay(fill.g(), background.g()),
+                overlay(fill.b(), background.b()),
+            ))
+        }
+        DiffBlend::Solid => {
+            // Mixed like a translucent fill painted over the code background
+            let fill = visuals.code_bg_color;
+            let mix = |base: u8, color: u8| {
+                let (base, color) = (f32::from(base), f32::from(color));
+
+                (base + (color - base) * SOLID_ALPHA).round() as u8
+            };
+
+            egui::Color32::from_rgb(
+                mix(fill.r(), diff_color.r()),
+                mix(fill.g(), diff_color.g()),
+                mix(fill.b(), diff_color.b()),
+            )
+        }
+    }
+}
+
+/// Get the color for text over a diff background.
+///
+/// The normal text color is kept when it is easy to read, otherwise black or white is used,
+/// whichever contrasts more with the background.
+pub(super) fn diff_text_color(visuals: &egui::Visuals, background: egui::Color32) -> egui::Color32 {
+    let text_color = visuals.text_color();
+    if contrast_ratio(text_color, background) >= MIN_CONTRAST {
+        return text_color;
+    }
+
+    let (black, white) = (egui::Color32::BLACK, egui::Color32::WHITE);
+    if contrast_ratio(black, background) > contrast_ratio(white, background) {
+        black
     } else {
-        Vec::new()
+        white
+    }
+}
+
+/// Compute the contrast ratio between two colors, as defined by WCAG.
+///
+/// The ratio ranges from 1.0 for identical colors to 21.0 for black and white.
+pub(super) fn contrast_ratio(a: egui::Color32, b: egui::Color32) -> f32 {
+    let luminance = |color: egui::Color32| {
+        let color = egui::Rgba::from(color);
+        0.2126 * color.r() + 0.7152 * color.g() + 0.0722 * color.b()
     };
+    let (a, b) = (luminance(a), luminance(b));
+
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Create a concise summary of the properties that differ between two setups.
+///
+/// Each line names the group and property, followed by the baseline value, the other value, and
+/// the numeric change when both values are numbers with the same unit. E.g.
+/// `Rear / Wing setting: 7 deg -> 8 deg (+1 deg)`.
+pub(crate) fn diff_summary(
+    baseline: &Setup,
+    other: &Setup,
+    aliases: &HashMap<String, String>,
+    perch_offset_unit: PerchOffsetUnit,
+) -> String {
+    let mut output = String::new();
+
+    for group in other.diff(baseline).changed_groups {
+        for prop in group.props {
+            // Properties that only exist in one setup are not shown in the grid
+            let (old, new) = match &prop.change {
+                Change::Changed { old, new, .. } => (old, new),
+                Change::Added(_) | Change::Removed(_) => continue,
+            };
+
+            let prop_name = prop.name.as_str();
+            let unit = Some(perch_offset_unit).filter(|_| PerchOffsetUnit::applies_to(prop_name));
+            let a = join_values(old.iter(), unit);
+            let b = join_values(new.iter(), unit);
+
+            let label = aliases
+                .get(prop_name)
+                .map_or(prop_name, |alias| alias.as_str());
+            output.push_str(&format!("{} / {}: {} -> {}", group.name, label, a, b));
+            if let Some(delta) = delta(&a, &b) {
+                output.push_str(&format!(" ({})", delta));
+            }
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+/// Create a checklist of the changes that turn the baseline setup into the other setup.
+///
+/// Steps are numbered in order and grouped under their property group, e.g. `Rear` followed by
+/// `  1. Set Wing setting to 8 deg (was 7 deg)`. Properties that only exist in the other setup are
+/// set without a previous value. Returns an empty string when nothing needs to change.
+pub(crate) fn diff_checklist(
+    baseline: &Setup,
+    other: &Setup,
+    aliases: &HashMap<String, String>,
+    perch_offset_unit: PerchOffsetUnit,
+) -> String {
+    let mut output = String::new();
+    let mut step = 0;
+
+    for group in other.diff(baseline).changed_groups {
+        let mut group_header = Some(&group.name);
+
+        for prop in group.props {
+            let prop_name = prop.name.as_str();
+            let unit = Some(perch_offset_unit).filter(|_| PerchOffsetUnit::applies_to(prop_name));
+            let (old, new) = match &prop.change {
+                Change::Changed { old, new, .. } => (Some(old), new),
+                Change::Added(new) => (None, new),
+                Change::Removed(_) => continue,
+            };
+
+            if let Some(name) = group_header.take() {
+                output.push_str(&format!("{}\n", name));
+            }
+            step += 1;
+
+            let label = aliases
+                .get(prop_name)
+                .map_or(prop_name, |alias| alias.as_str());
+            let new = join_values(new.iter(), unit);
+            output.push_str(&format!("  {}. Set {} to {}", step, label, new));
+            if let Some(old) = old {
+                output.push_str(&format!(" (was {})", join_values(old.iter(), unit)));
+            }
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+/// Join all values for a property into a single string.
+///
+/// Spring perch offsets are converted when `unit` is provided.
+pub(super) fn join_values<'a>(
+    values: impl Iterator<Item = &'a String> + Clone,
+    unit: Option<PerchOffsetUnit>,
+) -> String {
+    let (values, separator) = convert_values(values, unit);
+
+    values.join(separator)
+}
+
+/// Convert all values for a property, and choose the separator for joining them.
+///
+/// Lists of numbers are joined with commas, including signed numbers like `-4.0 deg`. Spring perch
+/// offsets are converted when `unit` is provided.
+fn convert_values<'a>(
+    values: impl Iterator<Item = &'a String> + Clone,
+    unit: Option<PerchOffsetUnit>,
+) -> (Vec<String>, &'static str) {
+    let separator = if values.clone().all(|v| starts_with_number(v)) {
+        ", "
+    } else {
+        " "
+    };
+
+    let values = values
+        .map(|v| match unit {
+            Some(unit) => unit.convert(v).unwrap_or_else(|| v.to_string()),
+            None => v.to_string(),
+        })
+        .collect();
+
+    (values, separator)
+}
+
+/// Check if a value starts with a digit, optionally preceded by a sign.
+fn starts_with_number(value: &str) -> bool {
+    value
+        .strip_prefix(|ch: char| ch == '-' || ch == '+')
+        .unwrap_or(value)
+        .starts_with(|ch: char| ch.is_ascii_digit())
+}
+
+/// Format the signed difference between two numbers with the same unit.
+///
+/// Returns `None` for text, mismatched units, and lists of numbers.
+fn delta(a: &str, b: &str) -> Option<String> {
+    let (x, y) = match (Value::parse(a), Value::parse(b)) {
+        (Value::Number(x), Value::Number(y)) => (x, y),
+        _ => return None,
+    };
+    if x.unit != y.unit || x.unit.contains(',') {
+        return None;
+    }
+
+    // Round away floating point noise, then drop insignificant zeros
+    let delta = format!("{:+.3}", y.as_f64() - x.as_f64());
+    let delta = delta.trim_end_matches('0').trim_end_matches('.');
+
+    // Keep the spacing between the number and unit that the export uses
+    let b = b.trim();
+    let space = if b[..b.len() - y.unit.len()].ends_with(' ') {
+        " "
+    } else {
+        ""
+    };
+
+    Some(format!("{}{}{}", delta, space, y.unit))
+}
+
+/// Key lists longer than this are hashed before intersecting; shorter lists are searched linearly.
+const INTERSECT_LINEAR_LIMIT: usize = 16;
+
+/// Get the intersection of keys that exists in each `HashMap`.
+///
+/// Keys are returned in the order they appear in the first list.
+pub(super) fn intersect_keys<'a>(mut all_keys: impl Iterator<Item = Vec<&'a str>>) -> Vec<&'a str> {
+    let mut output = all_keys.next().unwrap_or_default();
 
     for keys in all_keys {
-        output.retain(|key| keys.contains(key));
+        if output.is_empty() {
+            break;
+        }
+
+        if keys.len() <= INTERSECT_LINEAR_LIMIT {
+            output.retain(|key| keys.contains(key));
+        } else {
+            let keys: HashSet<_> = keys.into_iter().collect();
+            output.retain(|key| keys.contains(key));
+        }
     }
 
     output
@@ -207,6 +1193,25 @@ fn intersect_keys<'a>(mut all_keys: impl Iterator<Item = Vec<&'a str>>) -> Vec<&
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_visible_columns() {
+        let widths = [100.0, 50.0, 50.0, 50.0, 50.0];
+
+        // Everything is visible
+        assert_eq!(visible_columns(&widths, 10.0, 0.0, 0.0..=1000.0), 0..5);
+
+        // Columns start at 0, 110, 170, 230, and 290
+        assert_eq!(visible_columns(&widths, 10.0, 0.0, 120.0..=200.0), 1..3);
+        assert_eq!(visible_columns(&widths, 10.0, 0.0, 250.0..=1000.0), 3..5);
+
+        // Scrolled to the left, so the grid starts before the viewport
+        assert_eq!(visible_columns(&widths, 10.0, -200.0, 0.0..=50.0), 2..4);
+
+        // Nothing is visible
+        assert_eq!(visible_columns(&widths, 10.0, 0.0, 500.0..=1000.0), 0..0);
+        assert_eq!(visible_columns(&[], 10.0, 0.0, 0.0..=1000.0), 0..0);
+    }
+
     /// Test `intersect_keys()` with two sets.
     #[test]
     fn test_intersect_keys_two() {
@@ -293,4 +1298,513 @@ mod tests {
         let keys = intersect_keys(list.into_iter());
         assert!(keys.is_empty());
     }
+
+    /// Test `intersect_keys()` with many sets of many keys.
+    #[test]
+    fn test_intersect_keys_many() {
+        let names: Vec<_> = (0..200).map(|i| format!("key {}", i)).collect();
+
+        // Every set drops a different key. Later sets are reversed, with a key of their own.
+        let list = (0..50).map(|i| {
+            let mut keys: Vec<_> = names
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i * 2)
+                .map(|(_, name)| name.as_str())
+                .collect();
+            if i == 0 {
+                keys.insert(0, "first");
+            } else {
+                keys.reverse();
+                keys.push("other");
+            }
+            keys
+        });
+        let keys = intersect_keys(list);
+
+        let expected: Vec<_> = names
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j >= 100 || j % 2 == 1)
+            .map(|(_, name)| name.as_str())
+            .collect();
+        assert_eq!(keys, expected);
+    }
+
+    /// Test `locked_widths()` with fewer and more locked widths than columns.
+    #[test]
+    fn test_locked_widths() {
+        let fitted = [200.0, 80.0, 90.0, 100.0];
+        assert_eq!(locked_widths(&fitted, &[]), fitted);
+        assert_eq!(locked_widths(&fitted, &[150.0]), [150.0, 80.0, 90.0, 100.0]);
+        assert_eq!(
+            locked_widths(&fitted, &[150.0, 60.0, 70.0]),
+            [150.0, 60.0, 70.0, 70.0]
+        );
+        assert_eq!(
+            locked_widths(&fitted[..2], &[150.0, 60.0, 70.0]),
+            [150.0, 60.0]
+        );
+    }
+
+    /// Test `ellipsis_job()` keeps the format of the sections that remain.
+    #[test]
+    fn test_ellipsis_job() {
+        let format = |color| TextFormat::simple(egui::TextStyle::Body, color);
+        let mut job = LayoutJob::default();
+        job.append("12.5", 0.0, format(egui::Color32::RED));
+        job.append(", ", 0.0, format(egui::Color32::WHITE));
+        job.append("13.0", 0.0, format(egui::Color32::GREEN));
+
+        let short = ellipsis_job(&job, 6).unwrap();
+        assert_eq!(short.text, "12.5,…");
+        assert_eq!(short.sections.len(), 2);
+        assert_eq!(short.sections[0].byte_range, 0..4);
+        assert_eq!(short.sections[1].byte_range, 4..short.text.len());
+        assert_eq!(short.sections[1].format.color, egui::Color32::WHITE);
+
+        let short = ellipsis_job(&job, 0).unwrap();
+        assert_eq!(short.text, "1…");
+        assert_eq!(short.sections.len(), 1);
+        assert_eq!(short.sections[0].byte_range, 0..short.text.len());
+
+        assert!(ellipsis_job(&job, 100).is_none());
+    }
+
+    /// Test `zoom_galley()` scales a layout, and leaves it alone without a zoom.
+    #[test]
+    fn test_zoom_galley() {
+        let mut ctx = egui::CtxRef::default();
+        ctx.begin_frame(egui::RawInput::default());
+        let galley = ctx.fonts().layout_no_wrap(
+            "-4.0 deg".to_string(),
+            egui::TextStyle::Body,
+            egui::Color32::WHITE,
+        );
+        let _ = ctx.end_frame();
+
+        assert!(Arc::ptr_eq(&zoom_galley(Arc::clone(&galley), 1.0), &galley));
+
+        let zoomed = zoom_galley(Arc::clone(&galley), 2.0);
+        assert_eq!(zoomed.rect.size(), galley.rect.size() * 2.0);
+        assert_eq!(zoomed.job.text, galley.job.text);
+
+        let positions = |galley: &Galley| -> Vec<_> {
+            galley
+                .rows
+                .iter()
+                .flat_map(|row| row.visuals.mesh.vertices.iter().map(|vertex| vertex.pos))
+                .collect()
+        };
+        let expected: Vec<_> = positions(&galley)
+            .into_iter()
+            .map(|pos| egui::Pos2::new(pos.x * 2.0, pos.y * 2.0))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(positions(&zoomed), expected);
+    }
+
+    /// Test `SetupGrid::table()` writes values as they are shown, with aliases and the locale.
+    #[test]
+    fn test_grid_table() {
+        use crate::gui::table::Delimited;
+        use crate::locale::Locale;
+        use ordered_multimap::ListOrderedMultimap;
+
+        let setups: Vec<_> = [("1.5 in", "60.2"), ("1.75 in", "61.0")]
+            .iter()
+            .map(|&(height, temp)| {
+                let mut props = ListOrderedMultimap::new();
+                props.insert("Ride height".to_string(), height.to_string());
+                props.append("Tire temps".to_string(), temp.to_string());
+                props.append("Tire temps".to_string(), "58.4".to_string());
+                let mut setup = Setup::new();
+                setup.insert("Left Front".to_string(), props);
+                setup
+            })
+            .collect();
+        let setups: Vec<_> = setups.iter().collect();
+        let aliases = HashMap::from([("Ride height".to_string(), "Height".to_string())]);
+        let options = GridOptions {
+            perch_offset_unit: PerchOffsetUnit::Original,
+            positional_diffs: true,
+            fraction_glyphs: false,
+            dim_unchanged: false,
+            diff_reference: DiffReference::First,
+            diff_blend: DiffBlend::Multiply,
+            swapped_diffs: Vec::new(),
+            safe_ranges: HashMap::new(),
+            collapsed_groups: Vec::new(),
+            locale: Locale::German,
+            formatters: Vec::new(),
+        };
+
+        let mut ctx = egui::CtxRef::default();
+        ctx.begin_frame(egui::RawInput::default());
+        let mut grid = None;
+        egui::CentralPanel::default().show(&ctx, |ui| {
+            grid = Some(
+                SetupGrid::new(
+                    ui,
+                    &setups,
+                    &[egui::Color32::WHITE],
+                    (egui::Color32::RED, egui::Color32::GREEN),
+                    &aliases,
+                    &HashSet::new(),
+                    options,
+                )
+                .with_setup_names(vec!["a.htm".to_string(), "b.htm".to_string()]),
+            );
+        });
+        let _ = ctx.end_frame();
+
+        let output = grid.unwrap().table().write(&Delimited::csv(), true);
+        assert_eq!(
+            output,
+            concat!(
+                "Group,Property,a.htm,b.htm\r\n",
+                "Left Front,Height,\"1,5 in\",\"1,75 in\"\r\n",
+                "Left Front,Tire temps,\"60,2; 58,4\",\"61,0; 58,4\"\r\n",
+            ),
+        );
+    }
+
+    /// Benchmark `intersect_keys()` with a wide comparison.
+    ///
+    /// Run with `cargo test --release bench_intersect_keys -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_intersect_keys() {
+        const COLUMNS: usize = 100;
+        const KEYS: usize = 500;
+        const ITERATIONS: u32 = 100;
+
+        let names: Vec<_> = (0..KEYS).map(|i| format!("key {}", i)).collect();
+        let columns: Vec<Vec<_>> = (0..COLUMNS)
+            .map(|i| {
+                // Rotate each column so lookups don't always hit the front of the list
+                let mut keys: Vec<_> = names.iter().map(String::as_str).collect();
+                keys.rotate_left(i % KEYS);
+                keys
+            })
+            .collect();
+
+        let start = std::time::Instant::now();
+        for _ in 0..ITERATIONS {
+            let keys = intersect_keys(columns.iter().cloned());
+            assert_eq!(keys.len(), KEYS);
+        }
+        let elapsed = start.elapsed();
+
+        println!(
+            "intersect_keys: {} columns x {} keys: {:?} per iteration",
+            COLUMNS,
+            KEYS,
+            elapsed / ITERATIONS,
+        );
+    }
+
+    /// Benchmark `SetupGrid::new()` and `SetupGrid::show()` with a wide comparison.
+    ///
+    /// Text is laid out once by `SetupGrid::new()`, and the GUI keeps the grid until the
+    /// comparison changes. Frames only call `show()`, which is measured with a viewport that
+    /// shows every cell, and with a window-sized viewport where most cells are skipped.
+    ///
+    /// Run with `cargo test --release bench_show -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_show() {
+        use crate::locale::Locale;
+        use ordered_multimap::ListOrderedMultimap;
+
+        const COLUMNS: usize = 100;
+        const GROUPS: usize = 18;
+        const PROPS: usize = 20;
+        const ITERATIONS: u32 = 20;
+
+        let setups: Vec<_> = (0..COLUMNS)
+            .map(|i| {
+                let mut setup = Setup::new();
+                for group in 0..GROUPS {
+                    let mut props = ListOrderedMultimap::new();
+                    for prop in 0..PROPS {
+                        let value = format!("{}.{} psi", 20 + prop, (i + group) % 10);
+                        props.insert(format!("Property {}", prop), value);
+                    }
+                    setup.insert(format!("Group {}", group), props);
+                }
+                setup
+            })
+            .collect();
+        let setups: Vec<_> = setups.iter().collect();
+        let colors = vec![egui::Color32::WHITE; COLUMNS];
+        let diff_colors = (egui::Color32::RED, egui::Color32::GREEN);
+        let options = GridOptions {
+            perch_offset_unit: PerchOffsetUnit::Original,
+            positional_diffs: false,
+            fraction_glyphs: false,
+            dim_unchanged: true,
+            diff_reference: DiffReference::First,
+            diff_blend: DiffBlend::Multiply,
+            swapped_diffs: Vec::new(),
+            safe_ranges: HashMap::new(),
+            collapsed_groups: Vec::new(),
+            locale: Locale::EnglishUs,
+            formatters: Vec::new(),
+        };
+
+        // Run one frame with the given screen size, returning the time spent in `f`.
+        let mut ctx = egui::CtxRef::default();
+        let mut frame = |size: egui::Vec2, f: &mut dyn FnMut(&mut egui::Ui)| {
+            ctx.begin_frame(egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, size)),
+                ..Default::default()
+            });
+            let mut elapsed = std::time::Duration::ZERO;
+            egui::CentralPanel::default().show(&ctx, |ui| {
+                let start = std::time::Instant::now();
+                f(ui);
+                elapsed = start.elapsed();
+            });
+            let _ = ctx.end_frame();
+
+            elapsed
+        };
+
+        let window = egui::Vec2::new(1280.0, 720.0);
+        let mut grid = None;
+        let mut layout = std::time::Duration::ZERO;
+        for _ in 0..ITERATIONS {
+            layout += frame(window, &mut |ui| {
+                grid = Some(SetupGrid::new(
+                    ui,
+                    &setups,
+                    &colors,
+                    diff_colors,
+                    &HashMap::new(),
+                    &HashSet::new(),
+                    options.clone(),
+                ));
+            });
+        }
+        let grid = grid.unwrap();
+
+        let everything = egui::Vec2::splat(100_000.0);
+        for (name, size) in [("every cell", everything), ("1280x720", window)] {
+            let mut elapsed = std::time::Duration::ZERO;
+            for _ in 0..ITERATIONS {
+                elapsed += frame(size, &mut |ui| {
+                    grid.show(ui, "Bench");
+                });
+            }
+
+            println!(
+                "show: {} columns x {} rows, {} viewport: {:?} per frame",
+                COLUMNS,
+                GROUPS * PROPS,
+                name,
+                elapsed / ITERATIONS,
+            );
+        }
+
+        println!(
+            "new: {} columns x {} rows: {:?} per comparison",
+            COLUMNS,
+            GROUPS * PROPS,
+            layout / ITERATIONS,
+        );
+    }
+
+    /// Test `CellContext::reference()` with and without a setup name for the column.
+    #[test]
+    fn test_cell_reference() {
+        let setup_names = vec!["baseline.htm".to_string()];
+        let context = CellContext {
+            car_name: "Porsche 911 GT3 R",
+            group_name: "Left Front",
+            setup_names: &setup_names,
+        };
+
+        assert_eq!(
+            context.reference("Camber", "-4.0 deg", 0),
+            "Porsche 911 GT3 R / Left Front / Camber: -4.0 deg (baseline.htm)",
+        );
+        assert_eq!(
+            context.reference("Camber", "-4.0 deg", 1),
+            "Porsche 911 GT3 R / Left Front / Camber: -4.0 deg",
+        );
+    }
+
+    #[test]
+    fn test_convert_values() {
+        let values = |values: &[&str]| -> Vec<String> {
+            values.iter().map(|value| value.to_string()).collect()
+        };
+
+        let unsigned = values(&["3.5 deg", "4.0 deg"]);
+        assert_eq!(convert_values(unsigned.iter(), None).1, ", ");
+
+        let negative = values(&["-3.5 deg", "-4.0 deg"]);
+        assert_eq!(
+            convert_values(negative.iter(), None),
+            (negative.clone(), ", "),
+        );
+
+        let signed = values(&["+0.5 mm", "-1.2 mm", "0.0 mm"]);
+        assert_eq!(convert_values(signed.iter(), None).1, ", ");
+
+        let text = values(&["-", "Soft"]);
+        assert_eq!(convert_values(text.iter(), None).1, " ");
+
+        let words = values(&["Front", "-4.0 deg"]);
+        assert_eq!(convert_values(words.iter(), None).1, " ");
+    }
+
+    /// Test `delta()` with units, fractions, and text.
+    #[test]
+    fn test_delta() {
+        assert_eq!(delta("25.0 psi", "25.5 psi"), Some("+0.5 psi".to_string()));
+        assert_eq!(delta("1.3 deg", "1.2 deg"), Some("-0.1 deg".to_string()));
+        assert_eq!(delta("7", "8"), Some("+1".to_string()));
+        assert_eq!(
+            delta(r#"-1/16""#, r#"+1/16""#),
+            Some(r#"+0.125""#.to_string())
+        );
+        assert_eq!(delta("25.0 psi", "25 kPa"), None);
+        assert_eq!(delta("Soft", "Medium"), None);
+        assert_eq!(delta("119F, 119F", "120F, 119F"), None);
+    }
+
+    /// Test `diff_summary()` with the baseline and a modified setup.
+    #[test]
+    fn test_diff_summary() {
+        use ordered_multimap::ListOrderedMultimap;
+
+        let mut baseline = Setup::new();
+        let mut props = ListOrderedMultimap::new();
+        props.insert("Cold pressure".to_string(), "25.0 psi".to_string());
+        props.insert("Anti-roll bar".to_string(), "Soft".to_string());
+        props.insert("Camber".to_string(), "-1.6 deg".to_string());
+        baseline.insert("Left Front".to_string(), props);
+
+        let mut other = baseline.clone();
+        let props = other.get_mut("Left Front").unwrap();
+        *props.get_mut("Cold pressure").unwrap() = "26.5 psi".to_string();
+        *props.get_mut("Anti-roll bar").unwrap() = "Medium".to_string();
+
+        let aliases = HashMap::from([("Anti-roll bar".to_string(), "ARB".to_string())]);
+        let summary = diff_summary(&baseline, &other, &aliases, PerchOffsetUnit::Original);
+        assert_eq!(
+            summary,
+            "Left Front / Cold pressure: 25.0 psi -> 26.5 psi (+1.5 psi)\n\
+             Left Front / ARB: Soft -> Medium\n"
+        );
+
+        let summary = diff_summary(&baseline, &baseline, &aliases, PerchOffsetUnit::Original);
+        assert!(summary.is_empty());
+    }
+
+    #[test]
+    fn test_diff_checklist() {
+        use ordered_multimap::ListOrderedMultimap;
+
+        let mut baseline = Setup::new();
+        let mut props = ListOrderedMultimap::new();
+        props.insert("Anti-roll bar".to_string(), "Soft".to_string());
+        props.insert("Camber".to_string(), "-1.6 deg".to_string());
+        baseline.insert("Front".to_string(), props);
+        let mut props = ListOrderedMultimap::new();
+        props.insert("Wing setting".to_string(), "7 deg".to_string());
+        props.insert("Brake bias".to_string(), "54.0%".to_string());
+        baseline.insert("Rear".to_string(), props);
+
+        let mut other = baseline.clone();
+        let props = other.get_mut("Front").unwrap();
+        *props.get_mut("Anti-roll bar").unwrap() = "Medium".to_string();
+        let props = other.get_mut("Rear").unwrap();
+        *props.get_mut("Wing setting").unwrap() = "8 deg".to_string();
+        props.remove("Brake bias");
+        props.insert("Diff preload".to_string(), "75 Nm".to_string());
+
+        let aliases = HashMap::from([("Anti-roll bar".to_string(), "ARB".to_string())]);
+        let checklist = diff_checklist(&baseline, &other, &aliases, PerchOffsetUnit::Original);
+        assert_eq!(
+            checklist,
+            "Front\n\
+             \x20 1. Set ARB to Medium (was Soft)\n\
+             Rear\n\
+             \x20 2. Set Wing setting to 8 deg (was 7 deg)\n\
+             \x20 3. Set Diff preload to 75 Nm\n"
+        );
+
+        let checklist = diff_checklist(&baseline, &baseline, &aliases, PerchOffsetUnit::Original);
+        assert!(checklist.is_empty());
+    }
+
+    /// Test `unicode_fractions()` with fractions, mixed numbers, and text that must not change.
+    #[test]
+    fn test_unicode_fractions() {
+        assert_eq!(unicode_fractions(r#"+1/16""#), r#"+¹⁄₁₆""#);
+        assert_eq!(unicode_fractions(r#"-3/8""#), r#"-⅜""#);
+        assert_eq!(unicode_fractions(r#"1 1/2""#), r#"1 ½""#);
+        assert_eq!(unicode_fractions("10/32, 7/64"), "¹⁰⁄₃₂, ⁷⁄₆₄");
+        assert_eq!(unicode_fractions("1.5/2"), "1.5/2");
+        assert_eq!(unicode_fractions("1/2.5"), "1/2.5");
+        assert_eq!(unicode_fractions("A/B"), "A/B");
+        assert_eq!(unicode_fractions("/"), "/");
+        assert!(matches!(unicode_fractions("25.0 psi"), Cow::Borrowed(_)));
+
+        // Every glyph is checked by `has_fraction_glyphs()`
+        let all = unicode_fractions(
+            "1/2 1/3 2/3 1/4 3/4 1/5 2/5 3/5 4/5 1/6 5/6 1/8 3/8 5/8 7/8 1234/5678 90/91",
+        );
+        assert!(all
+            .chars()
+            .filter(|ch| !ch.is_whitespace())
+            .all(|ch| FRACTION_GLYPHS.contains(ch)));
+    }
+
+    /// Text over the default diff colors is legible with every blend mode, in both themes.
+    #[test]
+    fn test_diff_text_legible() {
+        let diff_colors = [
+            egui::Color32::from_rgb(0xff, 0x50, 0x50),
+            egui::Color32::from_rgb(0x50, 0xff, 0x50),
+        ];
+
+        // The light theme has black text, like `framework::create_style()`
+        let mut light = egui::Visuals::light();
+        light.widgets.noninteractive.fg_stroke.color = egui::Color32::BLACK;
+
+        for visuals in [egui::Visuals::dark(), light] {
+            for blend in [DiffBlend::Multiply, DiffBlend::Overlay, DiffBlend::Solid] {
+                for diff_color in diff_colors {
+                    let background = diff_background(&visuals, diff_color, blend);
+                    let text_color = diff_text_color(&visuals, background);
+
+                    assert!(contrast_ratio(text_color, background) >= MIN_CONTRAST);
+                }
+            }
+
+            // Multiplying keeps the normal text color, which is the default look
+            for diff_color in diff_colors {
+                let background = diff_background(&visuals, diff_color, DiffBlend::Multiply);
+                assert_eq!(diff_text_color(&visuals, background), visuals.text_color());
+            }
+        }
+    }
+
+    /// Test `contrast_ratio()` with known WCAG ratios.
+    #[test]
+    fn test_contrast_ratio() {
+        let black = egui::Color32::BLACK;
+        let white = egui::Color32::WHITE;
+        let gray = egui::Color32::from_gray(119);
+
+        assert!((contrast_ratio(black, white) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(white, black) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(gray, gray) - 1.0).abs() < 0.01);
+        assert!((contrast_ratio(gray, white) - 4.48).abs() < 0.05);
+        assert!(contrast_ratio(egui::Color32::from_gray(64), black) < MIN_CONTRAST);
+    }
 }
@@ -0,0 +1,323 @@
+//! Setups selected for comparison.
+//!
+//! The selection is a list of positions in the available setups, in the order they were selected.
+//! Positions shift as setups are added and removed, so they are fixed up for every change to the
+//! setups tree. Positions that are still out of bounds afterward are dropped, never used.
+
+use crate::str_ext::HumanCompare;
+use std::cmp::Ordering;
+
+/// Fix up selected positions when a setup is inserted at `index`.
+pub(crate) fn insert(selected: &mut [usize], index: usize) {
+    for i in selected.iter_mut() {
+        if *i >= index {
+            *i += 1;
+        }
+    }
+}
+
+/// Fix up selected positions when the setup at `index` is removed.
+///
+/// The removed setup is deselected.
+pub(crate) fn remove(selected: &mut Vec<usize>, index: usize) {
+    selected.retain(|&i| i != index);
+    for i in selected.iter_mut() {
+        if *i > index {
+            *i -= 1;
+        }
+    }
+}
+
+/// Drop selected positions that are out of bounds for `len` available setups, and any repeats.
+///
+/// Returns the number of positions dropped.
+pub(crate) fn retain_valid(selected: &mut Vec<usize>, len: usize) -> usize {
+    let before = selected.len();
+    let mut seen = Vec::with_capacity(before);
+    selected.retain(|&i| {
+        let valid = i < len && !seen.contains(&i);
+        seen.push(i);
+
+        valid
+    });
+
+    before - selected.len()
+}
+
+/// Get the positions of the available setups in the order they are shown.
+///
+/// `favorites` flags each available setup that is a favorite. Favorites are shown first, and the
+/// order is kept otherwise. With `favorites_only`, other setups are only shown while selected.
+pub(crate) fn display_order(
+    favorites: &[bool],
+    selected: &[usize],
+    favorites_only: bool,
+) -> Vec<usize> {
+    let mut order: Vec<_> = (0..favorites.len())
+        .filter(|i| !favorites_only || favorites[*i] || selected.contains(i))
+        .collect();
+    order.sort_by_key(|&i| !favorites[i]);
+
+    order
+}
+
+/// Select the `count` setups with the greatest keys, e.g. modification times.
+///
+/// Equal keys are broken by position, so the same setups are always chosen. Returns positions in
+/// ascending order of their keys, so the newest setup is compared against the older ones.
+pub(crate) fn newest<K: Ord>(keys: &[K], count: usize) -> Vec<usize> {
+    let mut positions: Vec<_> = (0..keys.len()).collect();
+    positions.sort_by(|&a, &b| keys[b].cmp(&keys[a]).then(b.cmp(&a)));
+    positions.truncate(count);
+    positions.reverse();
+
+    positions
+}
+
+/// The number of setups for a car at each track, when browsing all of its setups by car.
+///
+/// Setups are available in track order, so a setup's position is offset by the setups at every
+/// track before its own. Updates in a batch have positions in the setups tree as it was when each
+/// update happened, so the counts follow the updates instead of the tree.
+pub(crate) struct TrackOffsets {
+    /// Track names and their setup counts, sorted by track name.
+    counts: Vec<(String, usize)>,
+}
+
+impl TrackOffsets {
+    /// Count the setups at each track before a batch of updates.
+    ///
+    /// `track_names` has the track name of every setup for the car after the batch, and `changes`
+    /// has the track name of every setup added (`true`) or removed (`false`) by the batch.
+    pub(crate) fn new<'a>(
+        track_names: impl IntoIterator<Item = &'a str>,
+        changes: impl IntoIterator<Item = (&'a str, bool)>,
+    ) -> Self {
+        let mut counts: Vec<(String, isize)> = Vec::new();
+        let mut count = |track_name: &str, delta| match counts
+            .iter_mut()
+            .find(|(other, _)| other == track_name)
+        {
+            Some((_, count)) => *count += delta,
+            None => counts.push((track_name.to_string(), delta)),
+        };
+
+        for track_name in track_names {
+            count(track_name, 1);
+        }
+        for (track_name, added) in changes {
+            count(track_name, if added { -1 } else { 1 });
+        }
+
+        let mut counts: Vec<_> = counts
+            .into_iter()
+            .map(|(track_name, count)| (track_name, count.max(0) as usize))
+            .collect();
+        counts.sort_unstable_by(|(a, _), (b, _)| a.human_compare(b));
+
+        Self { counts }
+    }
+
+    /// Get the position among all setups for the car of a setup at `index` at the track.
+    pub(crate) fn position(&self, track_name: &str, index: usize) -> usize {
+        let offset: usize = self
+            .counts
+            .iter()
+            .filter(|(other, _)| other.human_compare(track_name) == Ordering::Less)
+            .map(|(_, count)| count)
+            .sum();
+
+        offset + index
+    }
+
+    /// Count a setup added at the track.
+    pub(crate) fn insert(&mut self, track_name: &str) {
+        match self
+            .counts
+            .iter_mut()
+            .find(|(other, _)| other == track_name)
+        {
+            Some((_, count)) => *count += 1,
+            None => {
+                let index = self.counts.partition_point(|(other, _)| {
+                    other.human_compare(track_name) == Ordering::Less
+                });
+                self.counts.insert(index, (track_name.to_string(), 1));
+            }
+        }
+    }
+
+    /// Count a setup removed from the track.
+    pub(crate) fn remove(&mut self, track_name: &str) {
+        if let Some((_, count)) = self
+            .counts
+            .iter_mut()
+            .find(|(other, _)| other == track_name)
+        {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tiny deterministic PRNG, so the same sequences run every time.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+
+            ((self.0 >> 33) as usize) % n.max(1)
+        }
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut selected = vec![3, 0, 5];
+        insert(&mut selected, 3);
+        assert_eq!(selected, vec![4, 0, 6]);
+
+        remove(&mut selected, 4);
+        assert_eq!(selected, vec![0, 5]);
+
+        remove(&mut selected, 1);
+        assert_eq!(selected, vec![0, 4]);
+    }
+
+    #[test]
+    fn test_retain_valid() {
+        let mut selected = vec![2, 7, 0, 2, 3];
+        assert_eq!(retain_valid(&mut selected, 4), 2);
+        assert_eq!(selected, vec![2, 0, 3]);
+
+        assert_eq!(retain_valid(&mut selected, 4), 0);
+        assert_eq!(retain_valid(&mut selected, 0), 3);
+        assert!(selected.is_empty());
+    }
+
+    #[test]
+    fn test_display_order() {
+        let favorites = [false, false, true, false];
+        assert_eq!(display_order(&favorites, &[3], false), vec![2, 0, 1, 3]);
+        assert_eq!(display_order(&favorites, &[3], true), vec![2, 3]);
+        assert_eq!(display_order(&favorites, &[], true), vec![2]);
+        assert!(display_order(&[], &[], false).is_empty());
+    }
+
+    #[test]
+    fn test_newest() {
+        let keys = [Some(30), None, Some(10), Some(30), Some(20)];
+        assert_eq!(newest(&keys, 2), vec![0, 3]);
+        assert_eq!(newest(&keys, 3), vec![4, 0, 3]);
+        assert_eq!(newest(&keys, 9), vec![1, 2, 4, 0, 3]);
+        assert!(newest(&keys, 0).is_empty());
+        assert!(newest::<u8>(&[], 2).is_empty());
+    }
+
+    /// Test a batch that adds setups at two tracks, positioned after the whole batch is applied.
+    #[test]
+    fn test_track_offsets_mixed_batch() {
+        // Track "A" had ["a0", "a1"] and track "B" had ["b0"], with "a1" and "b0" selected
+        let after = ["A", "A", "A", "B", "B"];
+        let changes = [("B", true), ("A", true)];
+        let mut offsets = TrackOffsets::new(after, changes);
+        let mut selected = vec![1, 2];
+
+        // "b00" was added at position 0 at track "B", then "a00" at position 0 at track "A"
+        for (track_name, index) in [("B", 0), ("A", 0)] {
+            insert(&mut selected, offsets.position(track_name, index));
+            offsets.insert(track_name);
+        }
+
+        // The setups are now ["a00", "a0", "a1", "b00", "b0"]
+        assert_eq!(selected, vec![2, 4]);
+
+        // Track "C" had ["c0"], which was removed along with the track, after "a1" was removed
+        let after = ["A", "A", "B", "B"];
+        let changes = [("A", false), ("C", false)];
+        let mut offsets = TrackOffsets::new(after, changes);
+        let mut selected = vec![5, 2, 4];
+
+        for (track_name, index) in [("A", 2), ("C", 0)] {
+            remove(&mut selected, offsets.position(track_name, index));
+            offsets.remove(track_name);
+        }
+
+        // The setups are now ["a00", "a0", "b00", "b0"]
+        assert_eq!(selected, vec![3]);
+    }
+
+    #[test]
+    fn test_add_remove_rename_sequences() {
+        let mut rng = Lcg(0x5eed);
+
+        for _ in 0..100 {
+            // Setup names, in the order they are available
+            let mut setups: Vec<u32> = (0..8).collect();
+            let mut next_name = setups.len() as u32;
+            let mut selected = vec![1, 6, 3];
+            let mut expected: Vec<u32> = selected.iter().map(|&i| setups[i]).collect();
+
+            for _ in 0..200 {
+                match rng.below(4) {
+                    // Add a setup
+                    0 => {
+                        let index = rng.below(setups.len() + 1);
+                        setups.insert(index, next_name);
+                        next_name += 1;
+                        insert(&mut selected, index);
+                    }
+
+                    // Remove a setup
+                    1 if !setups.is_empty() => {
+                        let index = rng.below(setups.len());
+                        let name = setups.remove(index);
+                        expected.retain(|&other| other != name);
+                        remove(&mut selected, index);
+                    }
+
+                    // Rename a setup, which removes it and adds it in its new sorted position
+                    2 if !setups.is_empty() => {
+                        let from = rng.below(setups.len());
+                        let name = setups.remove(from);
+                        let was_selected = selected.contains(&from);
+                        remove(&mut selected, from);
+
+                        let to = rng.below(setups.len() + 1);
+                        setups.insert(to, name);
+                        insert(&mut selected, to);
+                        if was_selected {
+                            expected.retain(|&other| other != name);
+                        }
+                    }
+
+                    // Select or deselect a setup
+                    _ if !setups.is_empty() => {
+                        let index = rng.below(setups.len());
+                        match selected.iter().position(|&i| i == index) {
+                            Some(position) => {
+                                selected.remove(position);
+                                expected.remove(position);
+                            }
+                            None => {
+                                selected.push(index);
+                                expected.push(setups[index]);
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+
+                assert_eq!(retain_valid(&mut selected, setups.len()), 0);
+                let actual: Vec<_> = selected.iter().map(|&i| setups[i]).collect();
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! Tabular serialization for setup comparisons.
+//!
+//! Rows are generated once from the selected setups, then written with a [`Formatter`]. Delimited
+//! text (CSV and TSV) and Markdown share the same rows, so every format contains the same content.
+
+use super::grid::{intersect_keys, join_values};
+use crate::config::Quoting;
+use crate::setup::Setup;
+use crate::value::PerchOffsetUnit;
+use std::collections::{HashMap, HashSet};
+
+/// A comparison table for a list of setups.
+pub(crate) struct Table {
+    /// Setup names, in column order.
+    names: Vec<String>,
+
+    /// Property groups, containing rows of property names followed by one value per setup.
+    groups: Vec<(String, Vec<Vec<String>>)>,
+}
+
+/// Writes the rows of a [`Table`] in a specific format.
+pub(crate) trait Formatter {
+    /// Write the header row.
+    fn header(&self, output: &mut String, cells: &[&str]);
+
+    /// Write a row of cells.
+    fn row(&self, output: &mut String, cells: &[&str]);
+
+    /// Write a section row that starts a property group. The table has `width` columns.
+    fn section(&self, output: &mut String, name: &str, width: usize);
+}
+
+/// Delimited text, like CSV or TSV.
+pub(crate) struct Delimited {
+    /// Field delimiter.
+    pub(crate) delimiter: char,
+
+    /// Quoting style for cells.
+    pub(crate) quoting: Quoting,
+}
+
+/// GitHub-flavored Markdown table.
+pub(crate) struct Markdown;
+
+impl Table {
+    /// Create a `Table` from a list of setup names and setups.
+    ///
+    /// Only the groups and properties that exist in every setup are included, except the groups
+    /// named in `hidden_groups`. Property names are replaced with their aliases, and spring perch
+    /// offsets are converted, like the setup grid.
+    pub(crate) fn new(
+        setups: &[(&str, &Setup)],
+        aliases: &HashMap<String, String>,
+        hidden_groups: &HashSet<String>,
+        perch_offset_unit: PerchOffsetUnit,
+    ) -> Self {
+        let groups = setups
+            .iter()
+            .map(|(_, inner)| inner.keys().map(|s| s.as_str()).collect::<Vec<_>>());
+        let groups = intersect_keys(groups)
+            .into_iter()
+            .filter(|prop_group| !hidden_groups.contains(*prop_group))
+            .map(|prop_group| {
+                let prop_names = setups.iter().map(|(_, setup)| {
+                    setup
+                        .get(prop_group)
+                        .unwrap()
+                        .keys()
+                        .map(|k| k.as_str())
+                        .collect::<Vec<_>>()
+                });
+
+                let rows = intersect_keys(prop_names)
+                    .into_iter()
+                    .map(|prop_name| {
+                        let unit = Some(perch_offset_unit)
+                            .filter(|_| PerchOffsetUnit::applies_to(prop_name));
+                        let label = aliases
+                            .get(prop_name)
+                            .map_or(prop_name, |alias| alias.as_str());
+
+                        let mut row = Vec::with_capacity(setups.len() + 1);
+                        row.push(label.to_string());
+                        for (_, setup) in setups {
+                            let values = setup.get(prop_group).unwrap().get_all(prop_name);
+                            row.push(join_values(values, unit));
+                        }
+
+                        row
+                    })
+                    .collect();
+
+                (prop_group.to_string(), rows)
+            })
+            .collect();
+
+        Self {
+            names: setups.iter().map(|(name, _)| name.to_string()).collect(),
+            groups,
+        }
+    }
+
+    /// Create a `Table` from setup names and property groups with text that is already formatted.
+    ///
+    /// Each row starts with its property name, followed by one cell per setup.
+    pub(crate) fn from_groups(names: Vec<String>, groups: Vec<(String, Vec<Vec<String>>)>) -> Self {
+        Self { names, groups }
+    }
+
+    /// Write the table with the given `Formatter`.
+    ///
+    /// Property groups are written in a separate first column when `group_column` is true.
+    /// Otherwise each group is introduced by a section row.
+    pub(crate) fn write(&self, formatter: &impl Formatter, group_column: bool) -> String {
+        let mut output = String::new();
+
+        let mut header = Vec::with_capacity(self.names.len() + 2);
+        if group_column {
+            header.push("Group");
+        }
+        header.push("Property");
+        header.extend(self.names.iter().map(|name| name.as_str()));
+        formatter.header(&mut output, &header);
+
+        for (group_name, rows) in &self.groups {
+            if !group_column {
+                formatter.section(&mut output, group_name, header.len());
+            }
+
+            for row in rows {
+                let mut cells = Vec::with_capacity(header.len());
+                if group_column {
+                    cells.push(group_name.as_str());
+                }
+                cells.extend(row.iter().map(|cell| cell.as_str()));
+                formatter.row(&mut output, &cells);
+            }
+        }
+
+        output
+    }
+}
+
+impl Delimited {
+    /// Comma separated values, quoted when needed.
+    pub(crate) fn csv() -> Self {
+        Self {
+            delimiter: ',',
+            quoting: Quoting::Minimal,
+        }
+    }
+
+    /// Format a single cell.
+    fn cell(&self, cell: &str) -> String {
+        let needs_quotes =
+            cell.contains(|ch: char| ch == self.delimiter || matches!(ch, '"' | '\n' | '\r'));
+
+        match self.quoting {
+            Quoting::Always => quote(cell),
+            Quoting::Minimal if needs_quotes => quote(cell),
+            Quoting::Minimal => cell.to_string(),
+            Quoting::Never => cell.replace(
+                |ch: char| ch == self.delimiter || matches!(ch, '\n' | '\r'),
+                " ",
+            ),
+        }
+    }
+}
+
+impl Formatter for Delimited {
+    fn header(&self, output: &mut String, cells: &[&str]) {
+        self.row(output, cells);
+    }
+
+    fn row(&self, output: &mut String, cells: &[&str]) {
+        let cells: Vec<_> = cells.iter().map(|cell| self.cell(cell)).collect();
+        output.push_str(&cells.join(&self.delimiter.to_string()));
+        output.push_str("\r\n");
+    }
+
+    fn section(&self, output: &mut String, name: &str, width: usize) {
+        let mut cells = vec![""; width];
+        cells[0] = name;
+        self.row(output, &cells);
+    }
+}
+
+impl Formatter for Markdown {
+    fn header(&self, output: &mut String, cells: &[&str]) {
+        self.row(output, cells);
+        self.row(output, &vec!["---"; cells.len()]);
+    }
+
+    fn row(&self, output: &mut String, cells: &[&str]) {
+        output.push('|');
+        for cell in cells {
+            output.push(' ');
+            output.push_str(&cell.replace('|', "\\|").replace('\n', " "));
+            output.push_str(" |");
+        }
+        output.push('\n');
+    }
+
+    fn section(&self, output: &mut String, name: &str, width: usize) {
+        let name = format!("**{}**", name);
+        let mut cells = vec![""; width];
+        cells[0] = name.as_str();
+        self.row(output, &cells);
+    }
+}
+
+/// Wrap a cell in double quotes, escaping any quotes within it.
+fn quote(cell: &str) -> String {
+    format!("\"{}\"", cell.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ordered_multimap::ListOrderedMultimap;
+
+    /// Create a setup with a single group containing the given properties.
+    fn setup(props: &[(&str, &str)]) -> Setup {
+        let mut group = ListOrderedMultimap::new();
+        for (name, value) in props {
+            group.append(name.to_string(), value.to_string());
+        }
+
+        let mut setup = Setup::new();
+        setup.insert("Left Front".to_string(), group);
+
+        setup
+    }
+
+    /// Parse delimited text with optional quoting into rows of cells.
+    fn read_delimited(text: &str, delimiter: char) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut cell = String::new();
+        let mut quoted = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    cell.push('"');
+                }
+                '"' => quoted = !quoted,
+                '\r' if !quoted => (),
+                '\n' if !quoted => {
+                    row.push(std::mem::take(&mut cell));
+                    rows.push(std::mem::take(&mut row));
+                }
+                ch if ch == delimiter && !quoted => row.push(std::mem::take(&mut cell)),
+                ch => cell.push(ch),
+            }
+        }
+
+        rows
+    }
+
+    fn table() -> Table {
+        let baseline = setup(&[
+            ("Cold pressure", "25.0 psi"),
+            ("Last temps O M I", "119F"),
+            ("Last temps O M I", "120F"),
+            ("Tread remaining", "100%"),
+        ]);
+        let other = setup(&[
+            ("Cold pressure", "26.5 psi"),
+            ("Last temps O M I", "121F"),
+            ("Last temps O M I", "120F"),
+            ("Tread remaining", "99%"),
+        ]);
+        let aliases = HashMap::from([("Tread remaining".to_string(), "Tread".to_string())]);
+
+        Table::new(
+            &[("baseline", &baseline), ("\"wet\", v2", &other)],
+            &aliases,
+            &HashSet::new(),
+            PerchOffsetUnit::Original,
+        )
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let table = table();
+        let expected = [
+            vec!["Group", "Property", "baseline", "\"wet\", v2"],
+            vec!["Left Front", "Cold pressure", "25.0 psi", "26.5 psi"],
+            vec!["Left Front", "Last temps O M I", "119F, 120F", "121F, 120F"],
+            vec!["Left Front", "Tread", "100%", "99%"],
+        ];
+
+        let csv = table.write(&Delimited::csv(), true);
+        assert_eq!(read_delimited(&csv, ','), expected);
+
+        let tsv = Delimited {
+            delimiter: '\t',
+            quoting: Quoting::Always,
+        };
+        let tsv = table.write(&tsv, true);
+        assert_eq!(read_delimited(&tsv, '\t'), expected);
+
+        // Unquoted cells cannot contain the delimiter
+        let semicolons = Delimited {
+            delimiter: ';',
+            quoting: Quoting::Never,
+        };
+        let semicolons = table.write(&semicolons, true);
+        let rows: Vec<Vec<_>> = semicolons
+            .lines()
+            .map(|line| line.split(';').collect())
+            .collect();
+        assert_eq!(rows, expected);
+    }
+
+    #[test]
+    fn test_section_rows() {
+        let table = table();
+
+        let csv = table.write(&Delimited::csv(), false);
+        let rows = read_delimited(&csv, ',');
+        assert_eq!(rows[0], ["Property", "baseline", "\"wet\", v2"]);
+        assert_eq!(rows[1], ["Left Front", "", ""]);
+        assert_eq!(rows[2], ["Cold pressure", "25.0 psi", "26.5 psi"]);
+
+        let markdown = table.write(&Markdown, false);
+        assert_eq!(
+            markdown,
+            concat!(
+                "| Property | baseline | \"wet\", v2 |\n",
+                "| --- | --- | --- |\n",
+                "| **Left Front** |  |  |\n",
+                "| Cold pressure | 25.0 psi | 26.5 psi |\n",
+                "| Last temps O M I | 119F, 120F | 121F, 120F |\n",
+                "| Tread | 100% | 99% |\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_hidden_groups() {
+        let baseline = setup(&[("Cold pressure", "25.0 psi")]);
+        let hidden_groups = HashSet::from(["Left Front".to_string()]);
+        let table = Table::new(
+            &[("baseline", &baseline)],
+            &HashMap::new(),
+            &hidden_groups,
+            PerchOffsetUnit::Original,
+        );
+
+        let csv = table.write(&Delimited::csv(), true);
+        assert_eq!(
+            read_delimited(&csv, ','),
+            [["Group", "Property", "baseline"]]
+        );
+    }
+}
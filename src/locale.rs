@@ -0,0 +1,286 @@
+//! Locale conventions for displaying numbers and dates.
+//!
+//! Setup exports always use decimal points and dates like `2021-03-01 - 14:00`. Values are parsed
+//! and compared in that format; the locale only changes how they are shown.
+
+use std::borrow::Cow;
+
+/// User's locale choice for displaying numbers and dates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Locale {
+    /// Follow the operating system's locale. (default)
+    System,
+
+    /// Decimal points, month/day/year dates, and 12-hour times: `1.5`, `03/01/2021 2:00 PM`.
+    EnglishUs,
+
+    /// Decimal points and day/month/year dates: `1.5`, `01/03/2021 14:00`.
+    EnglishUk,
+
+    /// Decimal commas and day.month.year dates: `1,5`, `01.03.2021 14:00`.
+    German,
+
+    /// Decimal commas and day/month/year dates: `1,5`, `01/03/2021 14:00`.
+    French,
+
+    /// Decimal points and year-month-day dates, as they appear in setup exports.
+    Iso,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::System
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::System => "System",
+            Self::EnglishUs => "English (US)",
+            Self::EnglishUk => "English (UK)",
+            Self::German => "German",
+            Self::French => "French",
+            Self::Iso => "ISO 8601 (As Exported)",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl From<&str> for Locale {
+    fn from(value: &str) -> Self {
+        match value {
+            "en-US" => Self::EnglishUs,
+            "en-GB" => Self::EnglishUk,
+            "de-DE" => Self::German,
+            "fr-FR" => Self::French,
+            "iso" => Self::Iso,
+            _ => Self::System,
+        }
+    }
+}
+
+impl Locale {
+    /// Get a string slice that is TOML-compatible for this `Locale`.
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::System => "system",
+            Self::EnglishUs => "en-US",
+            Self::EnglishUk => "en-GB",
+            Self::German => "de-DE",
+            Self::French => "fr-FR",
+            Self::Iso => "iso",
+        }
+    }
+
+    /// Replace [`Locale::System`] with the operating system's locale.
+    ///
+    /// Unknown system locales show values as they appear in setup exports.
+    pub(crate) fn resolve(self) -> Self {
+        match self {
+            Self::System => system_locale().map_or(Self::Iso, |tag| Self::from_tag(&tag)),
+            locale => locale,
+        }
+    }
+
+    /// Choose the locale with the conventions of a language tag, e.g. `de_DE.UTF-8` or `fr-CA`.
+    fn from_tag(tag: &str) -> Self {
+        let tag = tag.split('.').next().unwrap_or_default();
+        let mut parts = tag.split(|ch| ch == '_' || ch == '-');
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region = parts.next().unwrap_or_default().to_ascii_uppercase();
+
+        match (language.as_str(), region.as_str()) {
+            ("en", "" | "US" | "CA" | "PH") => Self::EnglishUs,
+            ("en", _) => Self::EnglishUk,
+            (
+                "de" | "cs" | "da" | "fi" | "nb" | "nn" | "no" | "pl" | "ru" | "sk" | "tr" | "uk",
+                _,
+            ) => Self::German,
+            ("fr" | "ca" | "el" | "es" | "it" | "nl" | "pt" | "ro", _) => Self::French,
+            _ => Self::Iso,
+        }
+    }
+
+    /// Check if this locale uses a comma as the decimal separator.
+    fn has_decimal_comma(&self) -> bool {
+        matches!(self, Self::German | Self::French)
+    }
+
+    /// Get the separator for lists of numbers, e.g. tire temperatures.
+    ///
+    /// Locales with decimal commas separate numbers with semicolons.
+    pub(crate) fn list_separator(&self) -> &'static str {
+        if self.has_decimal_comma() {
+            "; "
+        } else {
+            ", "
+        }
+    }
+
+    /// Format the numbers in a property value for display, e.g. `1.95 in` becomes `1,95 in`.
+    ///
+    /// Only decimal points between two digits are replaced, so abbreviations like `in.` are kept.
+    pub(crate) fn format_value<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !self.has_decimal_comma() || !text.contains('.') {
+            return Cow::Borrowed(text);
+        }
+
+        let chars: Vec<_> = text.chars().collect();
+        let formatted = chars
+            .iter()
+            .enumerate()
+            .map(|(i, &ch)| {
+                let is_decimal = ch == '.'
+                    && i > 0
+                    && chars[i - 1].is_ascii_digit()
+                    && chars.get(i + 1).map_or(false, char::is_ascii_digit);
+
+                if is_decimal {
+                    ','
+                } else {
+                    ch
+                }
+            })
+            .collect();
+
+        Cow::Owned(formatted)
+    }
+
+    /// Format a date from a setup export for display, e.g. `2021-03-01 - 14:00`.
+    ///
+    /// Dates in any other format are returned as-is.
+    pub(crate) fn format_date<'a>(&self, date: &'a str) -> Cow<'a, str> {
+        let (year, month, day, hour, minute) = match parse_date(date) {
+            Some(parsed) => parsed,
+            None => return Cow::Borrowed(date),
+        };
+
+        let formatted = match self {
+            Self::System | Self::Iso => return Cow::Borrowed(date),
+            Self::EnglishUs => {
+                let period = if hour < 12 { "AM" } else { "PM" };
+                let hour = match hour % 12 {
+                    0 => 12,
+                    hour => hour,
+                };
+                format!(
+                    "{:02}/{:02}/{} {}:{:02} {}",
+                    month, day, year, hour, minute, period
+                )
+            }
+            Self::EnglishUk | Self::French => {
+                format!("{:02}/{:02}/{} {:02}:{:02}", day, month, year, hour, minute)
+            }
+            Self::German => {
+                format!("{:02}.{:02}.{} {:02}:{:02}", day, month, year, hour, minute)
+            }
+        };
+
+        Cow::Owned(formatted)
+    }
+}
+
+/// Parse a date in the setup export format, e.g. `2021-03-01 - 14:00`.
+///
+/// Returns the year, month, day, hour, and minute.
+fn parse_date(date: &str) -> Option<(u32, u32, u32, u32, u32)> {
+    let (date, time) = date.trim().split_once(" - ")?;
+    let mut date = date.split('-').map(|part| part.parse().ok());
+    let mut time = time.split(':').map(|part| part.parse().ok());
+
+    let parsed = (
+        date.next()??,
+        date.next()??,
+        date.next()??,
+        time.next()??,
+        time.next()??,
+    );
+    if date.next().is_some() || time.next().is_some() {
+        return None;
+    }
+
+    Some(parsed)
+}
+
+/// Get the language tag of the operating system's locale, e.g. `de-DE`.
+#[cfg(windows)]
+fn system_locale() -> Option<String> {
+    use winapi::um::winnls::GetUserDefaultLocaleName;
+    use winapi::um::winnt::LOCALE_NAME_MAX_LENGTH;
+
+    let mut buffer = [0_u16; LOCALE_NAME_MAX_LENGTH];
+    let len = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+
+    // The length includes the null terminator, and is zero on failure
+    let len = usize::try_from(len).ok().filter(|&len| len > 1)?;
+
+    Some(String::from_utf16_lossy(&buffer[..len - 1]))
+}
+
+/// Get the language tag of the operating system's locale, e.g. `de_DE.UTF-8`.
+#[cfg(not(windows))]
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|tag| !tag.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(Locale::German.format_value("1.95 in"), "1,95 in");
+        assert_eq!(Locale::French.format_value("-0.5 deg"), "-0,5 deg");
+        assert_eq!(Locale::German.format_value("5 x 1/16 in."), "5 x 1/16 in.");
+        assert_eq!(Locale::German.format_value(".5 in"), ".5 in");
+        assert_eq!(Locale::German.format_value("Soft"), "Soft");
+        assert_eq!(Locale::EnglishUs.format_value("1.95 in"), "1.95 in");
+        assert_eq!(Locale::Iso.format_value("1.95 in"), "1.95 in");
+
+        assert_eq!(Locale::German.list_separator(), "; ");
+        assert_eq!(Locale::EnglishUk.list_separator(), ", ");
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = "2021-03-01 - 14:05";
+        assert_eq!(Locale::EnglishUs.format_date(date), "03/01/2021 2:05 PM");
+        assert_eq!(
+            Locale::EnglishUs.format_date("2021-03-01 - 00:30"),
+            "03/01/2021 12:30 AM"
+        );
+        assert_eq!(Locale::EnglishUk.format_date(date), "01/03/2021 14:05");
+        assert_eq!(Locale::German.format_date(date), "01.03.2021 14:05");
+        assert_eq!(Locale::French.format_date(date), "01/03/2021 14:05");
+        assert_eq!(Locale::Iso.format_date(date), date);
+
+        // Unknown formats are kept
+        assert_eq!(Locale::German.format_date("March 1st"), "March 1st");
+        assert_eq!(Locale::German.format_date("2021-03-01"), "2021-03-01");
+    }
+
+    #[test]
+    fn test_from_tag() {
+        assert_eq!(Locale::from_tag("en_US.UTF-8"), Locale::EnglishUs);
+        assert_eq!(Locale::from_tag("en-AU"), Locale::EnglishUk);
+        assert_eq!(Locale::from_tag("de-AT"), Locale::German);
+        assert_eq!(Locale::from_tag("fr_CA"), Locale::French);
+        assert_eq!(Locale::from_tag("C"), Locale::Iso);
+        assert_eq!(Locale::from_tag(""), Locale::Iso);
+
+        for locale in [
+            Locale::System,
+            Locale::EnglishUs,
+            Locale::EnglishUk,
+            Locale::German,
+            Locale::French,
+            Locale::Iso,
+        ] {
+            assert_eq!(Locale::from(locale.as_str()), locale);
+        }
+    }
+}
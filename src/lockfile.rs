@@ -0,0 +1,130 @@
+//! Advisory lock files for writing settings safely.
+//!
+//! Lock files are created next to the file they protect. Creating a file that must not exist is
+//! atomic, so only one writer can hold the lock at a time, whether it is in this process or in
+//! another running instance of CarTunes.
+
+use log::warn;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long to wait for another writer to release a lock.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Locks older than this were left behind by a writer that crashed, and are removed.
+const STALE_LOCK: Duration = Duration::from_secs(10);
+
+/// How long to sleep between attempts to take a lock.
+const RETRY_DELAY: Duration = Duration::from_millis(25);
+
+/// A lock file that is removed when dropped.
+#[derive(Debug)]
+struct FileLock {
+    path: PathBuf,
+}
+
+/// Write `contents` to the file at `path`, while holding its lock.
+///
+/// The contents are written to a temporary file which replaces the original, so the file is never
+/// left half-written.
+pub(crate) fn write(path: &Path, contents: &str) -> io::Result<()> {
+    let _lock = FileLock::acquire(with_suffix(path, ".lock"), LOCK_TIMEOUT)?;
+
+    let temp_path = with_suffix(path, ".tmp");
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path).map_err(|err| {
+        let _ = fs::remove_file(&temp_path);
+        err
+    })
+}
+
+/// Append `suffix` to the file name in `path`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path
+        .file_name()
+        .map_or_else(OsString::new, |name| name.to_os_string());
+    file_name.push(suffix);
+
+    path.with_file_name(file_name)
+}
+
+/// Check if a lock file was last touched long enough ago that its owner must be gone.
+fn is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .map_or(false, |age| age > STALE_LOCK)
+}
+
+impl FileLock {
+    /// Take the lock at `path`, waiting up to `timeout` for another writer to release it.
+    fn acquire(path: PathBuf, timeout: Duration) -> io::Result<Self> {
+        let start = Instant::now();
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self { path }),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    if is_stale(&path) {
+                        warn!("Removing stale lock file: {:?}", path);
+                        let _ = fs::remove_file(&path);
+                    } else if start.elapsed() >= timeout {
+                        return Err(io::Error::new(
+                            io::ErrorKind::Other,
+                            format!("`{}` is locked by another writer", path.to_string_lossy()),
+                        ));
+                    } else {
+                        std::thread::sleep(RETRY_DELAY);
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+
+        write(&path, "one = 1\n").unwrap();
+        write(&path, "two = 2\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two = 2\n");
+
+        // Neither the lock nor the temporary file is left behind
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["config.toml"]);
+    }
+
+    #[test]
+    fn test_file_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml.lock");
+
+        let lock = FileLock::acquire(path.clone(), Duration::ZERO).unwrap();
+        assert!(FileLock::acquire(path.clone(), Duration::ZERO).is_err());
+        assert!(write(&dir.path().join("other.toml"), "").is_ok());
+
+        drop(lock);
+        assert!(!path.exists());
+        assert!(FileLock::acquire(path, Duration::ZERO).is_ok());
+    }
+}
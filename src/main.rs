@@ -13,14 +13,14 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![deny(clippy::all)]
 
-use crate::framework::{ConfigHandler, Framework, UserEvent};
-use crate::gpu::{Error as GpuError, Gpu};
-use crate::gui::{Error as GuiError, Gui};
-use crate::setup::Setups;
-use log::error;
+use crate::framework::{ConfigHandler, Framework, PreviousRun, UserEvent};
+use crate::gpu::{Error as GpuError, Gpu, GpuBackend, PresentMode, Recovery};
+use crate::gui::{Error as GuiError, Gui, ShowWarning};
+use log::{error, warn};
 use std::collections::VecDeque;
+use std::path::PathBuf;
 use thiserror::Error;
-use winit::event::{Event, WindowEvent};
+use winit::event::{Event, StartCause, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::WindowBuilder;
 use winit_input_helper::WinitInputHelper;
@@ -30,14 +30,36 @@ use winit::platform::windows::IconExtWindows;
 #[cfg(windows)]
 use winit::window::Icon;
 
+mod bundle;
 mod config;
+mod formatter;
 mod framework;
 mod gpu;
 mod gui;
+mod locale;
+mod lockfile;
+mod opacity;
 mod setup;
+mod snapshot;
 mod str_ext;
+#[cfg(feature = "update-check")]
 mod timer;
 mod updates;
+mod value;
+
+/// Number keys toggle setups at the same index in the comparison. Both the main keyboard row and
+/// the numeric keypad are supported.
+const NUMBER_KEYS: [[VirtualKeyCode; 2]; 9] = [
+    [VirtualKeyCode::Key1, VirtualKeyCode::Numpad1],
+    [VirtualKeyCode::Key2, VirtualKeyCode::Numpad2],
+    [VirtualKeyCode::Key3, VirtualKeyCode::Numpad3],
+    [VirtualKeyCode::Key4, VirtualKeyCode::Numpad4],
+    [VirtualKeyCode::Key5, VirtualKeyCode::Numpad5],
+    [VirtualKeyCode::Key6, VirtualKeyCode::Numpad6],
+    [VirtualKeyCode::Key7, VirtualKeyCode::Numpad7],
+    [VirtualKeyCode::Key8, VirtualKeyCode::Numpad8],
+    [VirtualKeyCode::Key9, VirtualKeyCode::Numpad9],
+];
 
 /// Application error handling.
 #[derive(Debug, Error)]
@@ -53,16 +75,22 @@ enum Error {
 }
 
 /// Load configuration and create a window.
+///
+/// In safe mode, the window and GPU are created with default settings.
 fn create_window() -> Result<(EventLoop<UserEvent>, winit::window::Window, Gpu, Framework), Error> {
     let config = Framework::load_config();
+    let safe_mode = framework::is_safe_mode();
+    let previous_run = framework::create_running_marker();
+
+    let window_builder = if let (Ok(Some(config)), false) = (config.as_ref(), safe_mode) {
+        let window_builder = WindowBuilder::new().with_always_on_top(config.always_on_top());
 
-    let window_builder = if let Ok(Some(config)) = config.as_ref() {
         if let Some(window) = config.get_window() {
-            WindowBuilder::new()
+            window_builder
                 .with_position(window.position)
                 .with_inner_size(window.size)
         } else {
-            WindowBuilder::new()
+            window_builder
         }
     } else {
         WindowBuilder::new()
@@ -94,20 +122,55 @@ fn create_window() -> Result<(EventLoop<UserEvent>, winit::window::Window, Gpu,
         let scale_factor = window.scale_factor() as f32;
 
         let mut errors = VecDeque::new();
-        let mut warnings = VecDeque::new();
+        let warnings = VecDeque::new();
         let config = Framework::unwrap_config(&mut errors, event_loop.create_proxy(), config);
-        let setups = Setups::new(&mut warnings, &config);
         let theme = config.theme().as_winit_theme(&window);
-        let gui = Gui::new(config, setups, event_loop.create_proxy(), errors, warnings)?;
-        let gpu = Gpu::new(&window, window_size)?;
-        let framework = Framework::new(
-            window_size,
-            scale_factor,
-            theme,
-            gui,
-            &gpu,
-            event_loop.create_proxy(),
-        );
+        let (gpu_backend, present_mode) = if safe_mode {
+            (GpuBackend::default(), PresentMode::default())
+        } else {
+            opacity::set_opacity(&window, config.opacity());
+
+            (config.get_gpu_backend(), config.get_present_mode())
+        };
+        let mut gui = Gui::new(config, event_loop.create_proxy(), errors, warnings)?;
+        if safe_mode {
+            gui.enable_safe_mode();
+        }
+        if framework::is_presentation_mode() {
+            gui.enable_presentation_mode();
+        }
+        match previous_run {
+            PreviousRun::Exited => (),
+            PreviousRun::Crashed => gui.show_crash_report(),
+            PreviousRun::Running => gui.add_warning(ShowWarning::new(
+                "CarTunes is already running in another window.",
+                "Settings are saved by each window as it closes, \
+                so the window closed last decides which settings are kept.",
+            )),
+            PreviousRun::Unknown => gui.add_warning(ShowWarning::new(
+                "CarTunes did not exit cleanly, or is still running in another window.",
+                "If another window is open, the window closed last decides which settings are kept.",
+            )),
+        }
+        gui.reload_setups();
+
+        // Open setup exports passed as arguments, e.g. by a file association, and compare them
+        let paths: Vec<_> = std::env::args_os()
+            .skip(1)
+            .filter(|arg| {
+                arg != framework::PORTABLE_FLAG
+                    && arg != framework::SAFE_MODE_FLAG
+                    && arg != framework::PRESENTATION_FLAG
+            })
+            .map(PathBuf::from)
+            .collect();
+        if !paths.is_empty() {
+            gui.open_files(paths);
+        }
+
+        let gpu = Gpu::new(&window, window_size, gpu_backend, present_mode)?;
+        gui.set_gpu_info(gpu.info());
+        let framework = Framework::new(window_size, scale_factor, theme, gui, &gpu);
 
         (gpu, framework)
     };
@@ -123,8 +186,19 @@ fn main() -> Result<(), Error> {
     let (event_loop, window, mut gpu, mut framework) = create_window()?;
     let mut input = WinitInputHelper::new();
     let mut keep_config = ConfigHandler::Replace;
+    let mut needs_redraw = true;
 
     event_loop.run(move |event, _, control_flow| {
+        // Any window event, user event, or expired deadline may change the GUI state
+        if matches!(
+            event,
+            Event::WindowEvent { .. }
+                | Event::UserEvent(_)
+                | Event::NewEvents(StartCause::ResumeTimeReached { .. })
+        ) {
+            needs_redraw = true;
+        }
+
         // Handle input events
         if input.update(&event) {
             // Update the scale factor
@@ -140,8 +214,23 @@ fn main() -> Result<(), Error> {
                 }
             }
 
-            // Update internal state and request a redraw
-            window.request_redraw();
+            // Toggle setups with number keys, and compare the newest setups with N
+            if !input.held_control() && !input.held_alt() {
+                for (index, keys) in NUMBER_KEYS.iter().enumerate() {
+                    if keys.iter().any(|&key| input.key_pressed(key)) {
+                        framework.toggle_setup(index);
+                    }
+                }
+                if input.key_pressed(VirtualKeyCode::N) {
+                    framework.compare_newest();
+                }
+            }
+
+            // Update internal state and request a redraw only when something has changed
+            if needs_redraw {
+                needs_redraw = false;
+                window.request_redraw();
+            }
         }
 
         match event {
@@ -150,22 +239,68 @@ fn main() -> Result<(), Error> {
                     keep_config = config_handler;
                 }
                 UserEvent::Exit => {
+                    framework.shutdown();
+                    framework::remove_running_marker();
                     *control_flow = ControlFlow::Exit;
                 }
                 UserEvent::SetupPath(Some(setups_path)) => {
                     framework.update_setups_path(setups_path);
                 }
+                UserEvent::SetupScope(Some(path)) => {
+                    framework.update_setups_scope(path);
+                }
+                UserEvent::ExportBundle(Some(path)) => {
+                    framework.export_bundle(path);
+                }
+                UserEvent::ExportLibrary(Some(path)) => {
+                    framework.export_library(path);
+                }
+                UserEvent::ImportBundle(Some(path)) => {
+                    framework.import_bundle(path);
+                }
+                UserEvent::ExportTable(Some(path)) => {
+                    framework.export_table(path);
+                }
+                UserEvent::ExportComparison(Some(path)) => {
+                    framework.export_comparison(path);
+                }
+                UserEvent::ExportImage(Some(path)) => {
+                    framework.export_image(path, &gpu);
+                }
+                UserEvent::ExportChecklist(Some(path)) => {
+                    framework.export_checklist(path);
+                }
+                UserEvent::ResetConfig(options) => {
+                    // The config file has been replaced, so it is safe to save it on exit
+                    keep_config = ConfigHandler::Replace;
+                    framework.reset_config(options);
+
+                    // The window is opaque and not kept on top by default
+                    window.set_always_on_top(false);
+                    opacity::set_opacity(&window, 100);
+                }
                 UserEvent::FsChange(event) => {
                     framework.handle_fs_change(event);
                 }
+                UserEvent::SetupsProgress(generation, count, total) => {
+                    framework.setups_progress(generation, count, total);
+                }
+                UserEvent::SetupsLoaded(generation, setups, errors) => {
+                    framework.setups_loaded(generation, setups, errors);
+                }
+                UserEvent::SetupsCounted(counts) => {
+                    framework.setups_counted(counts);
+                }
                 UserEvent::Theme(theme) => {
                     let theme = theme.as_winit_theme(&window);
                     framework.change_theme(theme, true);
                     window.request_redraw();
                 }
+                #[cfg(feature = "update-check")]
                 UserEvent::UpdateCheck => {
                     framework.recreate_update_check();
                 }
+                #[cfg(feature = "update-check")]
                 UserEvent::UpdateAvailable(notification) => {
                     framework.add_update_notification(notification);
                 }
@@ -181,9 +316,10 @@ fn main() -> Result<(), Error> {
                         window.request_redraw();
                     }
                     WindowEvent::CloseRequested => {
-                        // Exit immediately if we've been asked to keep the config file,
-                        // or if saving was successful
-                        if keep_config == ConfigHandler::Keep || framework.save_config(&window) {
+                        // Exit immediately unless saving the config file failed
+                        if framework.close_requested(&window, &keep_config) {
+                            framework.shutdown();
+                            framework::remove_running_marker();
                             *control_flow = ControlFlow::Exit;
                         }
                     }
@@ -191,11 +327,31 @@ fn main() -> Result<(), Error> {
                 }
             }
             Event::RedrawRequested(_) => {
-                // Prepare egui
-                framework.prepare(&window);
+                // Prepare egui and wait for the next event or deadline
+                *control_flow = framework.prepare(&window);
 
                 let (mut encoder, frame) = match gpu.prepare() {
                     Ok((encoder, frame)) => (encoder, frame),
+                    Err(GpuError::Surface(wgpu::SurfaceError::Lost)) => {
+                        // Recreate the surface (and device if needed) and skip this frame
+                        match gpu.recover(&window) {
+                            Ok(Recovery::Surface) => (),
+                            Ok(Recovery::Device) => framework.recreate_render_pass(&gpu),
+                            Err(err) => {
+                                error!("gpu.recover() failed: {}", err);
+                                *control_flow = ControlFlow::Exit;
+                                return;
+                            }
+                        }
+                        window.request_redraw();
+                        return;
+                    }
+                    Err(GpuError::Surface(wgpu::SurfaceError::Timeout)) => {
+                        // Skip this frame and try again
+                        warn!("Timed out while acquiring a surface frame");
+                        window.request_redraw();
+                        return;
+                    }
                     Err(err) => {
                         error!("gpu.prepare() failed: {}", err);
                         *control_flow = ControlFlow::Exit;
@@ -0,0 +1,48 @@
+//! Window opacity.
+//!
+//! The window is made translucent by the window manager, so it works with any graphics backend.
+//! Only Windows is supported. The window stays opaque on other platforms.
+
+use winit::window::Window;
+
+/// Lowest opacity percentage allowed, so the window never becomes illegible or invisible.
+pub(crate) const MIN_OPACITY: u8 = 30;
+
+/// Check if window opacity is supported on this platform.
+pub(crate) const fn is_supported() -> bool {
+    cfg!(target_os = "windows")
+}
+
+/// Set the window opacity as a percentage.
+///
+/// The opacity is clamped between [`MIN_OPACITY`] and 100.
+#[cfg(target_os = "windows")]
+pub(crate) fn set_opacity(window: &Window, opacity: u8) {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        GetWindowLongW, SetLayeredWindowAttributes, SetWindowLongW, GWL_EXSTYLE, LWA_ALPHA,
+        WS_EX_LAYERED,
+    };
+    use winit::platform::windows::WindowExtWindows;
+
+    let hwnd = window.hwnd() as HWND;
+    let alpha = (u32::from(opacity.clamp(MIN_OPACITY, 100)) * 255 / 100) as u8;
+
+    unsafe {
+        let style = GetWindowLongW(hwnd, GWL_EXSTYLE);
+
+        // Layered windows are slower to compose, so the style is removed when opaque
+        if alpha == u8::MAX {
+            SetWindowLongW(hwnd, GWL_EXSTYLE, style & !(WS_EX_LAYERED as i32));
+        } else {
+            SetWindowLongW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED as i32);
+            SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA);
+        }
+    }
+}
+
+/// Set the window opacity as a percentage.
+///
+/// This does nothing on platforms without window opacity support.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn set_opacity(_window: &Window, _opacity: u8) {}
@@ -1,19 +1,42 @@
 //! Parsers and internal representations for iRacing setup exports.
 
+pub(crate) use self::corners::group_by_corner;
+pub(crate) use self::diff::{Change, Diff};
+pub(crate) use self::distribution::{distribution, format_number, with_unit};
+pub(crate) use self::gears::align_gears;
 use crate::config::Config;
-use crate::gui::ShowWarning;
-use crate::str_ext::{Capitalize, HumanCompare};
+use crate::locale::Locale;
+use crate::str_ext::{Capitalize, Ellipsis, HumanCompare};
+use encoding_rs::Encoding;
 use kuchiki::traits::TendrilSink;
+use log::debug;
 use ordered_multimap::ListOrderedMultimap;
-use std::collections::{HashMap, VecDeque};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 use walkdir::WalkDir;
 
+mod corners;
+mod diff;
+mod distribution;
+mod gears;
 #[cfg(test)]
 mod tests;
 
+/// Maximum length of page text excerpts in error messages, in graphemes.
+const EXCERPT_LENGTH: usize = 200;
+
+/// Exports pasted from the clipboard are named with this path in errors and parse logs.
+const CLIPBOARD_PATH: &str = "clipboard";
+
+/// Only this many bytes at the start of an export are searched for a `<meta>` charset, like web
+/// browsers do.
+const CHARSET_PRESCAN_LENGTH: usize = 1024;
+
 // Parsing setup exports can fail.
 #[derive(Debug, Error)]
 pub(crate) enum Error {
@@ -21,21 +44,47 @@ pub(crate) enum Error {
     #[error("I/O Error while reading {0:?}: {1}")]
     Io(PathBuf, #[source] std::io::Error),
 
-    /// Export is missing a page header.
-    #[error("Missing page header")]
-    MissingHeader,
-
-    /// Export is missing a car identifier.
-    #[error("Missing car identifier")]
-    MissingCar,
-
-    /// Export is missing a track identifier.
-    #[error("Missing track identifier")]
-    MissingTrack,
+    /// Export is empty or truncated, e.g. while it is still being written or synced, or it has no
+    /// content at all.
+    #[error("The export {path:?} {}", empty_or_truncated(.complete))]
+    EmptyOrTruncated {
+        /// Path to the export.
+        path: PathBuf,
+        /// The export is a whole HTML document with nothing in its body, e.g. an `<html></html>`
+        /// stub. Truncated exports may still be written, so they are loaded again.
+        complete: bool,
+    },
+
+    /// Export is missing a page header. Contains an excerpt of the page text.
+    #[error("Missing page header; the page begins with {0:?}")]
+    MissingHeader(String),
+
+    /// Export is missing a car identifier. Contains an excerpt of the page header.
+    #[error("Missing car identifier in page header {0:?}")]
+    MissingCar(String),
+
+    /// Export is missing a track identifier. Contains an excerpt of the page header.
+    #[error("Missing track identifier in page header {0:?}")]
+    MissingTrack(String),
 
     /// Export has duplicate property group.
-    #[error("Duplicate property group: {0}")]
-    DuplicatePropGroup(String),
+    #[error(
+        "Duplicate property group `{group}` in {path:?}: first header {first:?}, second header {second:?}"
+    )]
+    DuplicatePropGroup {
+        /// Path to the export.
+        path: PathBuf,
+        /// The property group name.
+        group: String,
+        /// Header text of the first group with this name.
+        first: String,
+        /// Header text of the group that collides with the first.
+        second: String,
+    },
+
+    /// Error while looking for exports in the setup exports path.
+    #[error("{0}")]
+    Walk(#[from] walkdir::Error),
 }
 
 impl Error {
@@ -58,6 +107,11 @@ pub(crate) enum UpdateKind {
 
     /// A track has been removed; the track name is provided.
     RemovedTrack(String),
+
+    /// A setup could not be added because the export is empty or truncated; the path is provided.
+    ///
+    /// The export may still be in the process of being written, so it is worth trying again.
+    Incomplete(PathBuf),
 }
 
 /// Internal representation of a setup export.
@@ -127,12 +181,13 @@ pub(crate) enum UpdateKind {
 ///
 /// See the [iRacing User Manuals](https://www.iracing.com/user-manuals/) for technical details of
 /// individual setup properties for each car.
-#[derive(Default)]
+#[derive(Debug, Default)]
 pub(crate) struct Setups {
     tracks: Tracks,
 }
 
 /// Information about a setup
+#[derive(Debug)]
 pub(crate) struct SetupInfo {
     /// The setup data.
     setup: Setup,
@@ -140,6 +195,17 @@ pub(crate) struct SetupInfo {
     name: String,
     /// Full file path for setup.
     path: PathBuf,
+    /// Date listed in the export, or the file modification time when the export has no date.
+    date: Option<SetupDate>,
+}
+
+/// Where a setup date comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SetupDate {
+    /// Date listed in the export, e.g. `2021-03-01 - 14:00`.
+    Exported(String),
+    /// File modification time in UTC, with the same format as export dates.
+    Modified(String),
 }
 
 type Tracks = HashMap<String, Cars>;
@@ -149,37 +215,58 @@ type Props = ListOrderedMultimap<String, String>;
 
 impl Setups {
     /// Recursively load all HTML files from the config setup exports path into a `Setups` tree.
-    pub(crate) fn new(warnings: &mut VecDeque<ShowWarning>, config: &Config) -> Self {
+    ///
+    /// Only the library subfolder is loaded when one is configured, without the setup exports and
+    /// folders that are excluded from the library. All exports are found before
+    /// any are parsed, then `progress` is called with the number of files processed so far and the
+    /// total number of files, starting with zero. Errors are returned with their context instead
+    /// of as warnings, so this can run on a worker thread.
+    pub(crate) fn load<F>(config: &Config, mut progress: F) -> (Self, Vec<(Error, String)>)
+    where
+        F: FnMut(usize, usize),
+    {
         let mut setups = Self::default();
-        let path = config.get_setups_path();
+        let mut errors = Vec::new();
+        let mut paths = Vec::new();
+        let path = config.get_scoped_setups_path();
         let walker = WalkDir::new(path).into_iter().filter_entry(|entry| {
-            entry.file_type().is_dir() || is_html(entry.file_name().to_str())
+            (entry.file_type().is_dir() || is_html(entry.file_name().to_str()))
+                && !config.is_excluded(entry.path())
         });
 
         for entry in walker {
             match entry {
                 Err(err) => {
-                    warnings.push_front(ShowWarning::new(
-                        err,
-                        "Encountered an error while looking for all exports.",
+                    errors.push((
+                        err.into(),
+                        "Encountered an error while looking for all exports.".to_string(),
                     ));
                 }
                 Ok(entry) => {
                     if entry.file_type().is_file() {
-                        if let Err(err) = setups.load_file(entry.path(), config) {
-                            warnings.push_front(ShowWarning::new(
-                                err,
-                                format!(
-                                    "Error while loading HTML setup export `{}`.",
-                                    entry.path().to_string_lossy(),
-                                ),
-                            ));
-                        }
+                        paths.push(entry.into_path());
                     }
                 }
             }
         }
 
+        let total = paths.len();
+        progress(0, total);
+
+        for (i, path) in paths.iter().enumerate() {
+            if let Err(err) = setups.load_file(path, config) {
+                errors.push((
+                    err,
+                    format!(
+                        "Error while loading HTML setup export `{}`.",
+                        path.to_string_lossy(),
+                    ),
+                ));
+            }
+
+            progress(i + 1, total);
+        }
+
         // Sort `SetupInfo`s by name.
         for track in setups.tracks.values_mut() {
             for setups in track.values_mut() {
@@ -187,19 +274,39 @@ impl Setups {
             }
         }
 
-        setups
+        (setups, errors)
     }
 
     /// Update setups when the file system changes.
-    pub(crate) fn update(&mut self, event: &hotwatch::Event, config: &Config) -> Vec<UpdateKind> {
+    ///
+    /// User notes and favorites follow setups that are renamed. Setups that are created outside of the library
+    /// subfolder or in excluded paths are ignored, and setups that are moved out of the library are
+    /// removed.
+    pub(crate) fn update(
+        &mut self,
+        event: &hotwatch::Event,
+        config: &mut Config,
+    ) -> Vec<UpdateKind> {
         use hotwatch::Event::*;
 
         let mut result = Vec::new();
 
         match event {
             Create(path) | Write(path) => {
-                if path.is_file() && is_html(path.as_path().to_str()) {
-                    self.add(&mut result, path, None, config);
+                if path.is_file()
+                    && is_html(path.as_path().to_str())
+                    && config.is_in_setups_scope(path)
+                    && !config.is_excluded(path)
+                {
+                    // Files may be incomplete while they are being written, so they are reported
+                    // for a retry. Other errors are ignored.
+                    if let Err(Error::EmptyOrTruncated {
+                        path,
+                        complete: false,
+                    }) = self.add(&mut result, path, None, config)
+                    {
+                        result.push(UpdateKind::Incomplete(path));
+                    }
                 }
             }
             Remove(path) => {
@@ -210,11 +317,23 @@ impl Setups {
             Rename(from, to) => {
                 let old_name_is_html = is_html(from.as_path().to_str());
                 let new_name_is_html = to.is_file() && is_html(to.as_path().to_str());
+                let in_scope = config.is_in_setups_scope(to) && !config.is_excluded(to);
 
-                if old_name_is_html && !new_name_is_html {
+                if old_name_is_html && !(new_name_is_html && in_scope) {
                     self.remove(&mut result, from);
                 } else if new_name_is_html {
-                    self.add(&mut result, to, Some(from), config);
+                    if let Err(Error::EmptyOrTruncated {
+                        path,
+                        complete: false,
+                    }) = self.add(&mut result, to, Some(from), config)
+                    {
+                        result.push(UpdateKind::Incomplete(path));
+                    }
+                }
+
+                if new_name_is_html {
+                    config.rename_note(from, to);
+                    config.rename_favorite(from, to);
                 }
             }
             _ => (),
@@ -223,6 +342,42 @@ impl Setups {
         result
     }
 
+    /// Update setups with a batch of file system changes.
+    ///
+    /// Redundant events for the same path are coalesced, so each path is loaded at most once.
+    pub(crate) fn update_batch(
+        &mut self,
+        events: &[hotwatch::Event],
+        config: &mut Config,
+    ) -> Vec<UpdateKind> {
+        coalesce_events(events)
+            .into_iter()
+            .flat_map(|event| self.update(event, config))
+            .collect()
+    }
+
+    /// Remove setups that are excluded from the library.
+    ///
+    /// Setups are removed in order of their paths, so updates are always emitted in the same order.
+    pub(crate) fn remove_excluded(&mut self, config: &Config) -> Vec<UpdateKind> {
+        let mut paths: Vec<_> = self
+            .tracks
+            .values()
+            .flat_map(|cars| cars.values())
+            .flatten()
+            .map(|setup_info| setup_info.path.clone())
+            .filter(|path| config.is_excluded(path))
+            .collect();
+        paths.sort();
+
+        let mut result = Vec::new();
+        for path in paths {
+            self.remove(&mut result, &path);
+        }
+
+        result
+    }
+
     /// Add a path to the setup tree or replace an existing entry.
     fn add(
         &mut self,
@@ -230,88 +385,87 @@ impl Setups {
         path: &Path,
         old_path: Option<&Path>,
         config: &Config,
-    ) {
-        if let Ok((track_name, car_name, setup)) = setup_from_html(path, config) {
-            let file_name = path
-                .file_stem()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_else(|| car_name.clone());
-            let cars = self.tracks.entry(track_name.clone()).or_default();
-            let setups = cars.entry(car_name.clone()).or_default();
-
-            // Find an existing SetupInfo by path
-            let index = setups.iter().enumerate().find_map(|(i, setup_info)| {
-                Some(i).filter(|_| {
-                    setup_info.path == path || Some(setup_info.path.as_path()) == old_path
-                })
-            });
+    ) -> Result<(), Error> {
+        let (track_name, car_name, setup, exported_at) = setup_from_html(path, config)?;
+        let file_name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| car_name.clone());
+        let cars = self.tracks.entry(track_name.clone()).or_default();
+        let setups = cars.entry(car_name.clone()).or_default();
 
-            if let Some(index) = index {
-                // Special handling for replacements
-                setups[index] = SetupInfo::new(setup, file_name, path);
-            } else {
-                // Find the index where the setup should be inserted
-                let index = setups.partition_point(|setup_info| setup_info.name < file_name);
-                setups.insert(index, SetupInfo::new(setup, file_name, path));
+        // Find an existing SetupInfo by path
+        let index = setups.iter().enumerate().find_map(|(i, setup_info)| {
+            Some(i)
+                .filter(|_| setup_info.path == path || Some(setup_info.path.as_path()) == old_path)
+        });
 
-                // Only emit `AddedSetups` when adding a new entry
-                result.push(UpdateKind::AddedSetup(track_name, car_name, index));
-            }
+        if let Some(index) = index {
+            // Special handling for replacements
+            setups[index] = SetupInfo::new(setup, file_name, path).with_date(exported_at);
+        } else {
+            // Find the index where the setup should be inserted
+            let index = setups.partition_point(|setup_info| setup_info.name < file_name);
+            let info = SetupInfo::new(setup, file_name, path).with_date(exported_at);
+            setups.insert(index, info);
+
+            // Only emit `AddedSetups` when adding a new entry
+            result.push(UpdateKind::AddedSetup(track_name, car_name, index));
         }
+
+        Ok(())
     }
 
     /// Remove a path from the setup tree.
+    ///
+    /// Tracks and cars are searched in sorted order, so updates are always emitted in the same
+    /// order.
     fn remove(&mut self, result: &mut Vec<UpdateKind>, path: &Path) {
-        let mut remove_track = None;
-
-        for (track_name, track) in self.tracks.iter_mut() {
-            let mut remove_car = None;
+        let mut track_names: Vec<_> = self.tracks.keys().cloned().collect();
+        track_names.sort_unstable_by(|a, b| a.human_compare(b));
 
-            for (car_name, setups) in track.iter_mut() {
-                // Find the SetupInfo by path
-                let index = setups
-                    .iter()
-                    .enumerate()
-                    .find_map(|(i, setup_info)| Some(i).filter(|_| setup_info.path == path));
+        for track_name in track_names {
+            let track = self.tracks.get_mut(&track_name).unwrap();
+            let mut car_names: Vec<_> = track.keys().cloned().collect();
+            car_names.sort_unstable_by(|a, b| a.human_compare(b));
 
-                if let Some(index) = index {
-                    // Remove the `SetupInfo` for the removed path
-                    setups.remove(index);
+            for car_name in car_names {
+                let setups = track.get_mut(&car_name).unwrap();
 
-                    // Record the removed setup
-                    result.push(UpdateKind::RemovedSetup(
+                // Find the SetupInfo by path
+                let index = match setups.iter().position(|setup_info| setup_info.path == path) {
+                    Some(index) => index,
+                    None => continue,
+                };
+
+                // Remove the `SetupInfo` for the removed path
+                setups.remove(index);
+
+                // Record the removed setup
+                result.push(UpdateKind::RemovedSetup(
+                    track_name.to_string(),
+                    car_name.to_string(),
+                    index,
+                ));
+
+                if setups.is_empty() {
+                    // Record the removed car
+                    result.push(UpdateKind::RemovedCar(
                         track_name.to_string(),
                         car_name.to_string(),
-                        index,
                     ));
 
-                    if setups.is_empty() {
-                        // Record the removed car
-                        result.push(UpdateKind::RemovedCar(
-                            track_name.to_string(),
-                            car_name.to_string(),
-                        ));
-
-                        remove_car = Some(car_name.to_string());
-                    }
+                    track.remove(&car_name);
                 }
             }
 
-            if let Some(car_name) = remove_car {
-                track.remove(&car_name);
-            }
-
             if track.is_empty() {
                 // Record the removed track
                 result.push(UpdateKind::RemovedTrack(track_name.to_string()));
 
-                remove_track = Some(track_name.to_string());
+                self.tracks.remove(&track_name);
             }
         }
-
-        if let Some(track_name) = remove_track {
-            self.tracks.remove(&track_name);
-        }
     }
 
     /// Get a reference to the tracks tree.
@@ -319,9 +473,194 @@ impl Setups {
         &self.tracks
     }
 
+    /// Serialize the whole tree as JSON, for external tools.
+    ///
+    /// Tracks and cars are objects sorted by name, and each car has an array of setups. See
+    /// [`SetupInfo::to_json`] for the setups.
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut track_names: Vec<_> = self.tracks.keys().collect();
+        track_names.sort_unstable_by(|a, b| a.human_compare(b));
+
+        let tracks = track_names
+            .into_iter()
+            .map(|track_name| {
+                let cars = &self.tracks[track_name];
+                let mut car_names: Vec<_> = cars.keys().collect();
+                car_names.sort_unstable_by(|a, b| a.human_compare(b));
+
+                let cars = car_names
+                    .into_iter()
+                    .map(|car_name| {
+                        let setups = cars[car_name].iter().map(SetupInfo::to_json).collect();
+
+                        (car_name.clone(), serde_json::Value::Array(setups))
+                    })
+                    .collect();
+
+                (track_name.clone(), serde_json::Value::Object(cars))
+            })
+            .collect();
+
+        serde_json::Value::Object(tracks)
+    }
+
+    /// Load a single setup export into the tree, even when it is outside the setup exports path.
+    ///
+    /// Returns the track name, car name, and index of the setup.
+    pub(crate) fn open(
+        &mut self,
+        path: &Path,
+        config: &Config,
+    ) -> Result<(&str, &str, usize), Error> {
+        self.add(&mut Vec::new(), path, None, config)?;
+
+        Ok(self.find(path).expect("Setup must exist after loading"))
+    }
+
+    /// Find the track name, car name, and index of a setup by its file path.
+    pub(crate) fn find(&self, path: &Path) -> Option<(&str, &str, usize)> {
+        self.tracks.iter().find_map(|(track_name, cars)| {
+            cars.iter().find_map(|(car_name, setups)| {
+                setups
+                    .iter()
+                    .position(|setup_info| setup_info.path == path)
+                    .map(|index| (track_name.as_str(), car_name.as_str(), index))
+            })
+        })
+    }
+
+    /// Get the names of all properties known for a car, sorted by name.
+    ///
+    /// Setups for the car are gathered from every track.
+    pub(crate) fn property_names(&self, car_name: &str) -> Vec<&str> {
+        let mut names: Vec<_> = self
+            .tracks
+            .values()
+            .filter_map(|cars| cars.get(car_name))
+            .flatten()
+            .flat_map(|setup_info| setup_info.setup.values())
+            .flat_map(|props| props.keys())
+            .map(|name| name.as_str())
+            .collect();
+
+        names.sort_unstable_by(|a, b| a.human_compare(b));
+        names.dedup();
+
+        names
+    }
+
+    /// Get the names of all properties in a property group known for a car, sorted by name.
+    ///
+    /// Setups for the car are gathered from every track.
+    pub(crate) fn group_property_names(&self, car_name: &str, group_name: &str) -> Vec<&str> {
+        let mut names: Vec<_> = self
+            .tracks
+            .values()
+            .filter_map(|cars| cars.get(car_name))
+            .flatten()
+            .filter_map(|setup_info| setup_info.setup.get(group_name))
+            .flat_map(|props| props.keys())
+            .map(|name| name.as_str())
+            .collect();
+
+        names.sort_unstable_by(|a, b| a.human_compare(b));
+        names.dedup();
+
+        names
+    }
+
+    /// Get every value of a property in all setups for a car, at every track.
+    ///
+    /// Properties with multiple values, e.g. tire temperatures, contribute each of their values.
+    pub(crate) fn property_values(
+        &self,
+        car_name: &str,
+        group_name: &str,
+        prop_name: &str,
+    ) -> Vec<&str> {
+        self.tracks
+            .values()
+            .filter_map(|cars| cars.get(car_name))
+            .flatten()
+            .filter_map(|setup_info| setup_info.setup.get(group_name))
+            .flat_map(|props| props.get_all(prop_name))
+            .map(|value| value.as_str())
+            .collect()
+    }
+
+    /// Get the names of all property groups known for a car, sorted by name.
+    ///
+    /// Setups for the car are gathered from every track.
+    pub(crate) fn group_names(&self, car_name: &str) -> Vec<&str> {
+        let mut names: Vec<_> = self
+            .tracks
+            .values()
+            .filter_map(|cars| cars.get(car_name))
+            .flatten()
+            .flat_map(|setup_info| setup_info.setup.keys())
+            .map(|name| name.as_str())
+            .collect();
+
+        names.sort_unstable_by(|a, b| a.human_compare(b));
+        names.dedup();
+
+        names
+    }
+
+    /// Get the names of all tracks, sorted by name.
+    pub(crate) fn track_names(&self) -> Vec<&String> {
+        let mut names: Vec<_> = self.tracks.keys().collect();
+
+        names.sort_unstable_by(|a, b| a.human_compare(b));
+
+        names
+    }
+
+    /// Get the names of all cars at a track, sorted by name.
+    ///
+    /// Returns no names for unknown tracks.
+    pub(crate) fn track_car_names(&self, track_name: &str) -> Vec<&String> {
+        let mut names: Vec<_> = self
+            .tracks
+            .get(track_name)
+            .map(|cars| cars.keys().collect())
+            .unwrap_or_default();
+
+        names.sort_unstable_by(|a, b| a.human_compare(b));
+
+        names
+    }
+
+    /// Get the names of all cars at every track, sorted by name.
+    pub(crate) fn car_names(&self) -> Vec<&String> {
+        let mut names: Vec<_> = self.tracks.values().flat_map(|cars| cars.keys()).collect();
+
+        names.sort_unstable_by(|a, b| a.human_compare(b));
+        names.dedup();
+
+        names
+    }
+
+    /// Get all setups for a car at every track, with the name of the track for each setup.
+    ///
+    /// Tracks are sorted by name, and setups keep their order within each track.
+    pub(crate) fn car_setups(&self, car_name: &str) -> Vec<(&str, &SetupInfo)> {
+        let mut tracks: Vec<_> = self
+            .tracks
+            .iter()
+            .filter_map(|(track_name, cars)| Some((track_name.as_str(), cars.get(car_name)?)))
+            .collect();
+        tracks.sort_unstable_by(|(a, _), (b, _)| a.human_compare(b));
+
+        tracks
+            .into_iter()
+            .flat_map(|(track_name, setups)| setups.iter().map(move |info| (track_name, info)))
+            .collect()
+    }
+
     /// Load an HTML export file into the `Setups` tree.
     fn load_file<P: AsRef<Path>>(&mut self, path: P, config: &Config) -> Result<(), Error> {
-        let (track_name, car_name, setup) = setup_from_html(&path, config)?;
+        let (track_name, car_name, setup, exported_at) = setup_from_html(&path, config)?;
 
         let file_name = path
             .as_ref()
@@ -330,7 +669,7 @@ impl Setups {
             .unwrap_or_else(|| car_name.clone());
         let cars = self.tracks.entry(track_name).or_default();
         let setups = cars.entry(car_name).or_default();
-        setups.push(SetupInfo::new(setup, file_name, path));
+        setups.push(SetupInfo::new(setup, file_name, path).with_date(exported_at));
 
         Ok(())
     }
@@ -341,7 +680,25 @@ impl SetupInfo {
     pub(crate) fn new<P: AsRef<Path>>(setup: Setup, name: String, path: P) -> Self {
         let path = path.as_ref().to_path_buf();
 
-        Self { setup, name, path }
+        Self {
+            setup,
+            name,
+            path,
+            date: None,
+        }
+    }
+
+    /// Set the date listed in the export.
+    ///
+    /// The file modification time is used instead when the export has no date.
+    pub(crate) fn with_date(mut self, exported_at: Option<String>) -> Self {
+        self.date = exported_at.map(SetupDate::Exported).or_else(|| {
+            let modified = fs::metadata(&self.path).and_then(|meta| meta.modified());
+
+            modified.ok().and_then(format_time).map(SetupDate::Modified)
+        });
+
+        self
     }
 
     /// Get a reference to the inner [`Setup`].
@@ -353,6 +710,166 @@ impl SetupInfo {
     pub(crate) fn name(&self) -> &str {
         &self.name
     }
+
+    /// Get the name cleaned up for display, with spaces instead of underscores and capitalized
+    /// words.
+    ///
+    /// The raw name is still used for sorting and file operations.
+    pub(crate) fn clean_name(&self) -> String {
+        self.name.replace('_', " ").as_str().capitalize_words()
+    }
+
+    /// Get a reference to the file path.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the setup date, if it is known.
+    pub(crate) fn date(&self) -> Option<&SetupDate> {
+        self.date.as_ref()
+    }
+
+    /// Get the file modification time, if it can be read.
+    ///
+    /// This is read from the file system every time, so it is always current.
+    pub(crate) fn modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    }
+
+    /// Serialize the setup with its name and file path as JSON.
+    ///
+    /// Property groups and properties keep the order of the export. Property values are always
+    /// arrays, because some properties have a value for each position, e.g. `Tread remaining`.
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let groups: serde_json::Map<_, _> = self
+            .setup
+            .iter()
+            .map(|(group_name, props)| {
+                let props = props
+                    .keys()
+                    .map(|prop_name| {
+                        let values = props
+                            .get_all(prop_name)
+                            .map(|value| serde_json::Value::from(value.as_str()))
+                            .collect();
+
+                        (prop_name.clone(), serde_json::Value::Array(values))
+                    })
+                    .collect();
+
+                (group_name.clone(), serde_json::Value::Object(props))
+            })
+            .collect();
+
+        serde_json::json!({
+            "name": self.name,
+            "path": self.path.to_string_lossy(),
+            "setup": groups,
+        })
+    }
+}
+
+impl SetupDate {
+    /// Get the date text.
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::Exported(date) | Self::Modified(date) => date,
+        }
+    }
+
+    /// Describe where the date comes from, with the date formatted in the locale.
+    ///
+    /// E.g. `Exported: 01.03.2021 14:00` or `Modified: 2021-03-01 - 14:00 UTC`.
+    pub(crate) fn describe(&self, locale: Locale) -> String {
+        match self {
+            Self::Exported(date) => format!("Exported: {}", locale.format_date(date)),
+            Self::Modified(date) => format!("Modified: {} UTC", locale.format_date(date)),
+        }
+    }
+}
+
+/// Coalesce file system events, keeping only the last create, write, or remove event for each path.
+///
+/// Rename events are always kept, because they affect two paths.
+fn coalesce_events(events: &[hotwatch::Event]) -> Vec<&hotwatch::Event> {
+    use hotwatch::Event::*;
+
+    let mut output: Vec<&hotwatch::Event> = Vec::with_capacity(events.len());
+    for event in events {
+        if let Create(path) | Write(path) | Remove(path) = event {
+            output.retain(|old| !matches!(old, Create(p) | Write(p) | Remove(p) if p == path));
+        }
+        output.push(event);
+    }
+
+    output
+}
+
+/// Hold back create and write events for setup exports that are still growing.
+///
+/// The sim writes exports in several steps, so a create event is often followed by a burst of
+/// write events. Each file's size is compared with the size remembered in `sizes` from the previous
+/// batch. Events for files with an unchanged size are returned to be applied. Files that are new or
+/// changed size are remembered, and their paths are returned to be checked again in the next batch.
+///
+/// Files that cannot be read are not held back, and remove events forget the size of the file.
+pub(crate) fn settle_events(
+    events: Vec<hotwatch::Event>,
+    sizes: &mut HashMap<PathBuf, u64>,
+) -> (Vec<hotwatch::Event>, Vec<PathBuf>) {
+    use hotwatch::Event::*;
+
+    // Each path is checked once per batch, however many events it has
+    let mut settled = HashMap::new();
+    let mut unsettled = Vec::new();
+    for event in &events {
+        match event {
+            Create(path) | Write(path) if is_html(path.to_str()) => {
+                if settled.contains_key(path) {
+                    continue;
+                }
+
+                let size = fs::metadata(path).ok().map(|metadata| metadata.len());
+                let is_settled = match size {
+                    Some(size) => sizes.insert(path.clone(), size) == Some(size),
+                    None => true,
+                };
+                if is_settled {
+                    sizes.remove(path);
+                } else {
+                    unsettled.push(path.clone());
+                }
+                settled.insert(path.clone(), is_settled);
+            }
+            Remove(path) => {
+                sizes.remove(path);
+            }
+            _ => (),
+        }
+    }
+
+    let events = events
+        .into_iter()
+        .filter(|event| match event {
+            Create(path) | Write(path) => settled.get(path).copied().unwrap_or(true),
+            _ => true,
+        })
+        .collect();
+
+    (events, unsettled)
+}
+
+/// Count the HTML setup exports in a folder and its subfolders.
+///
+/// Folders that cannot be read are skipped.
+pub(crate) fn count_exports(path: &Path) -> usize {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file() && is_html(entry.file_name().to_str()))
+        .count()
 }
 
 // Check if a directory entry is an HTML file.
@@ -362,63 +879,163 @@ fn is_html(file_name: Option<&str>) -> bool {
         .unwrap_or(false)
 }
 
+/// Check if an export ends with a closing `</html>` tag.
+///
+/// Exports are written in one go, so a missing tag means the file is empty or has been truncated.
+fn is_complete(bytes: &[u8]) -> bool {
+    let tail = &bytes[bytes.len().saturating_sub(64)..];
+
+    String::from_utf8_lossy(tail)
+        .trim_end()
+        .to_lowercase()
+        .ends_with("</html>")
+}
+
+/// Describe an export without usable content in [`Error::EmptyOrTruncated`].
+///
+/// Incomplete exports may still be written, while complete ones need to be exported again.
+fn empty_or_truncated(complete: &bool) -> &'static str {
+    if *complete {
+        "has no content; try exporting the setup again"
+    } else {
+        "is empty or incomplete"
+    }
+}
+
+/// Create a short excerpt of page text for error messages.
+///
+/// Whitespace is collapsed, control characters are removed, and the length is bounded.
+fn excerpt(text: &str) -> String {
+    let text = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace(char::is_control, "");
+
+    text.as_str().ellipsis(EXCERPT_LENGTH).to_string()
+}
+
 /// Parse an HTML file into a `Setup`.
+///
+/// Returns the track name, car name, setup, and the date listed in the export, if any.
 fn setup_from_html<P: AsRef<Path>>(
     path: P,
     config: &Config,
+) -> Result<(String, String, Setup, Option<String>), Error> {
+    let bytes = fs::read(&path).map_err(|err| Error::io(&path, err))?;
+    let (html, encoding) = decode_export(&bytes);
+    debug!("Decoded {:?} as {}", path.as_ref(), encoding);
+    let complete = is_complete(html.as_bytes());
+
+    parse_html(&html, path.as_ref(), complete, config)
+}
+
+/// Decode the bytes of a setup export, detecting its encoding.
+///
+/// A byte order mark is used first, then a `charset` declared by a `<meta>` tag at the start of
+/// the document. Exports without either are decoded as UTF-8 when they are valid UTF-8, otherwise
+/// as Latin-1.
+///
+/// Returns the text and the name of the encoding that was chosen.
+fn decode_export(bytes: &[u8]) -> (Cow<'_, str>, &'static str) {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (html, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return (html, encoding.name());
+    }
+
+    if let Some(encoding) = meta_charset(bytes) {
+        let (html, _) = encoding.decode_without_bom_handling(bytes);
+        return (html, encoding.name());
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(html) => (Cow::Borrowed(html), encoding_rs::UTF_8.name()),
+        Err(_) => (encoding_rs::mem::decode_latin1(bytes), "ISO-8859-1"),
+    }
+}
+
+/// Find the encoding declared by a `<meta>` tag at the start of an export.
+///
+/// E.g. `<meta charset="utf-8">` or
+/// `<meta http-equiv="Content-Type" content="text/html; charset=windows-1252">`. UTF-16 is
+/// treated as UTF-8, because the tag could not have been read from a UTF-16 document.
+fn meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = &bytes[..bytes.len().min(CHARSET_PRESCAN_LENGTH)];
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+
+    head.split("<meta").skip(1).find_map(|tag| {
+        let tag = tag.split('>').next().unwrap_or_default();
+        let (_, label) = tag.split_once("charset")?;
+        let label = label.trim_start().strip_prefix('=')?.trim_start();
+        let label = label.trim_start_matches(|ch| ch == '"' || ch == '\'');
+        let end = label
+            .find(|ch: char| ch == '"' || ch == '\'' || ch == ';' || ch.is_whitespace())
+            .unwrap_or(label.len());
+
+        Encoding::for_label(label[..end].as_bytes()).map(Encoding::output_encoding)
+    })
+}
+
+/// Parse setup export HTML pasted from the clipboard into a `Setup`.
+///
+/// The setup is not written to disk. Returns the track name, car name, and setup. Copied HTML is
+/// often a fragment without a closing `</html>` tag, so it is never considered truncated.
+pub(crate) fn setup_from_clipboard(
+    html: &str,
+    config: &Config,
 ) -> Result<(String, String, Setup), Error> {
-    let bytes = fs::read(&path).map_err(|err| Error::io(path, err))?;
-    let html = encoding_rs::mem::decode_latin1(&bytes);
-    let document = kuchiki::parse_html().one(html.as_ref());
+    let path = Path::new(CLIPBOARD_PATH);
+    let (track_name, car_name, setup, _) = parse_html(html, path, true, config)?;
+
+    Ok((track_name, car_name, setup))
+}
+
+/// Parse setup export HTML into a `Setup`. The `path` names the export in errors.
+///
+/// Exports that are not `complete` may have been truncated, so missing content is reported as
+/// [`Error::EmptyOrTruncated`]. Returns the track name, car name, setup, and the date listed in the
+/// export, if any.
+fn parse_html(
+    html: &str,
+    path: &Path,
+    complete: bool,
+    config: &Config,
+) -> Result<(String, String, Setup, Option<String>), Error> {
+    let document = kuchiki::parse_html().one(html);
 
     // Find the document header and gather its text contents
     let text = document
         .select(r#"h2[align="center"]"#)
         .unwrap()
         .next()
-        .ok_or(Error::MissingHeader)?
+        .ok_or_else(|| {
+            let body_text = document
+                .select_first("body")
+                .map(|body| body.text_contents())
+                .unwrap_or_default();
+
+            if !complete || body_text.trim().is_empty() {
+                Error::EmptyOrTruncated {
+                    path: path.to_path_buf(),
+                    complete,
+                }
+            } else {
+                Error::MissingHeader(excerpt(&document.text_contents()))
+            }
+        })?
         .text_contents();
 
     let mut lines = text.lines().skip(1);
 
-    // Get the car unique identifier
-    let car_id = lines
+    // Get the car and track identifiers with their human-readable names
+    let (car_id, car_name) = lines
         .next()
-        .ok_or(Error::MissingCar)?
-        .trim()
-        .split(" setup: ")
+        .map(|line| car_from_header(line, config))
+        .ok_or_else(|| Error::MissingCar(excerpt(&text)))?;
+    let (track_id, track_name) = lines
         .next()
-        .ok_or(Error::MissingCar)?
-        .replace(' ', "_");
-
-    // Map car ID to a human-readable name
-    let car_name = config
-        .cars()
-        .get(&car_id)
-        .map_or(car_id, |name| name.to_string());
-
-    // Get the track ambiguous identifier
-    let track_id = lines
-        .next()
-        .ok_or(Error::MissingTrack)?
-        .split_once(' ')
-        .ok_or(Error::MissingTrack)?
-        .1
-        .trim()
-        .replace(' ', "_");
-
-    // Get the track unique identifier
-    let track_id = config
-        .track_ids()
-        .get_longest_common_prefix(&track_id)
-        .unwrap_or(track_id.as_bytes());
-    let track_id = String::from_utf8_lossy(track_id).to_string();
-
-    // Map track ID to a human-readable name
-    let track_name = config
-        .tracks()
-        .get(&track_id)
-        .map_or(track_id, |name| name.to_string());
+        .and_then(|line| track_from_header(line, config))
+        .ok_or_else(|| Error::MissingTrack(excerpt(&text)))?;
 
     // Get all property groups
     let groups = document
@@ -426,19 +1043,26 @@ fn setup_from_html<P: AsRef<Path>>(
         .unwrap()
         .take_while(|node| {
             let text = node.text_contents().to_lowercase();
-            !text.starts_with("notes") && !text.starts_with("driver aids")
+            !config
+                .stop_groups()
+                .iter()
+                .any(|stop_group| text.starts_with(stop_group))
         });
 
     // Populate the Setup
     let mut group_name = String::new();
+    let mut group_header = String::new();
+    let mut headers = HashMap::new();
     let mut setup = Setup::default();
     for group in groups {
         let props = get_properties(group.as_node().next_sibling());
 
         // Get the name of the first group following one with properties
         if group_name.is_empty() {
-            group_name = group.text_contents().capitalize_words().to_string();
+            let header = group.text_contents();
+            group_name = header.capitalize_words().to_string();
             group_name.retain(|ch| ch != ':');
+            group_header = header.trim().to_string();
         }
 
         // Skip remaining groups until properties are found
@@ -461,14 +1085,181 @@ fn setup_from_html<P: AsRef<Path>>(
         }
 
         if setup.insert(group_name.clone(), props).is_some() {
-            return Err(Error::DuplicatePropGroup(group_name));
+            return Err(Error::DuplicatePropGroup {
+                path: path.to_path_buf(),
+                first: headers.remove(&group_name).unwrap_or_default(),
+                group: group_name,
+                second: group_header,
+            });
         }
+        headers.insert(group_name.clone(), group_header.clone());
 
         // Clear the last known group name so it can be recreated when needed
         group_name.clear();
     }
 
-    Ok((track_name, car_name, setup))
+    // Property groups may be missing from a truncated export
+    if !complete {
+        return Err(Error::EmptyOrTruncated {
+            path: path.to_path_buf(),
+            complete: false,
+        });
+    }
+
+    if let Some(log_path) = config.parse_log_path() {
+        let ids = [
+            ("car", &car_id, &car_name),
+            ("track", &track_id, &track_name),
+        ];
+        log_parse(&log_path, path, ids, &setup);
+    }
+
+    let exported_at = exported_at(&document.text_contents());
+
+    Ok((track_name, car_name, setup, exported_at))
+}
+
+/// Find the date listed in the notes of an export, e.g. `Date: 2021-03-01 - 14:00`.
+fn exported_at(text: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.trim().strip_prefix("Date:"))
+        .map(|date| date.trim().to_string())
+        .filter(|date| !date.is_empty())
+}
+
+/// Format a time in UTC with the same format as export dates, e.g. `2021-03-01 - 14:00`.
+///
+/// Times before the Unix epoch are not formatted.
+fn format_time(time: SystemTime) -> Option<String> {
+    let secs = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+
+    // Convert days to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    Some(format!(
+        "{:04}-{:02}-{:02} - {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+    ))
+}
+
+/// Append diagnostics for a parsed setup export to the log file.
+///
+/// Each line has the file name, the IDs found in the page header with the names they map to, and
+/// each property group with its number of properties. Folders are not logged, since they may
+/// contain personal information like user names. Logging is best-effort; errors are ignored so they
+/// never prevent loading setups.
+fn log_parse(log_path: &Path, path: &Path, ids: [(&str, &String, &String); 2], setup: &Setup) {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut line = format!("file={:?}", file_name);
+    for (kind, id, name) in ids {
+        line.push_str(&format!(" {}_id={:?} {}={:?}", kind, id, kind, name));
+    }
+    let groups: Vec<_> = setup
+        .iter()
+        .map(|(group_name, props)| format!("{} ({})", group_name, props.keys().len()))
+        .collect();
+    line.push_str(&format!(" groups={:?}\n", groups));
+
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path);
+    if let Ok(mut file) = file {
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+/// Get the car ID and its human-readable name from the car line of the page header.
+///
+/// Words in the ID are joined with underscores, e.g. `stockcars chevyss setup: race` has the ID
+/// `stockcars_chevyss`. Unknown IDs are used as the name.
+fn car_from_header(line: &str, config: &Config) -> (String, String) {
+    let car_id = car_id_from_header(line.trim(), config.setup_markers())
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_");
+    let car_name = config
+        .cars()
+        .get(&car_id)
+        .map_or_else(|| car_id.clone(), |name| name.to_string());
+
+    (car_id, car_name)
+}
+
+/// Get the track ID and its human-readable name from the track line of the page header.
+///
+/// The line has a label followed by the track and its configuration, e.g. `track: charlotte 2018
+/// legendsoval`. The words are joined with underscores, and the ID is the longest known track ID
+/// that is a prefix of them (`charlotte_2018`), so the configuration is ignored. Unknown IDs are
+/// kept whole and used as the name.
+///
+/// The label ends at the first colon, even without a space after it. A line with a single word and
+/// no colon is the track ID without a label.
+///
+/// Returns `None` when the line has nothing after the label.
+fn track_from_header(line: &str, config: &Config) -> Option<(String, String)> {
+    let line = line.trim();
+    let rest = match line.split_once(':') {
+        Some((_, rest)) => rest,
+        None => line
+            .split_once(char::is_whitespace)
+            .map_or(line, |(_, rest)| rest),
+    };
+    let words: Vec<_> = rest.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+    let track_id = words.join("_");
+
+    // Get the track unique identifier
+    let track_id = config
+        .track_ids()
+        .get_longest_common_prefix(&track_id)
+        .unwrap_or(track_id.as_bytes());
+    let track_id = String::from_utf8_lossy(track_id).to_string();
+
+    // Map track ID to a human-readable name
+    let track_name = config
+        .tracks()
+        .get(&track_id)
+        .map_or_else(|| track_id.clone(), |name| name.to_string());
+
+    Some((track_id, track_name))
+}
+
+/// Get the car identifier from the header line of an export, e.g. `rt2000 setup: <baseline>`.
+///
+/// The known localized markers are tried first. Exports from clients in other languages are
+/// expected to use a single word for "setup" followed by a colon.
+fn car_id_from_header<'a>(line: &'a str, markers: &[String]) -> &'a str {
+    if let Some((car_id, _)) = markers
+        .iter()
+        .find_map(|marker| line.split_once(marker.as_str()))
+    {
+        return car_id;
+    }
+
+    match line.split_once(':') {
+        Some((prefix, _)) => prefix
+            .trim_end()
+            .rsplit_once(char::is_whitespace)
+            .map_or(prefix, |(car_id, _)| car_id.trim_end()),
+        None => line,
+    }
 }
 
 fn get_properties(mut node_ref: Option<kuchiki::NodeRef>) -> Props {
@@ -0,0 +1,61 @@
+//! Regroup setup properties by the corners of the car.
+
+use super::{Props, Setup};
+
+/// Regroup a setup by the corners of the car.
+///
+/// Property groups that name a corner with the usual left/right and front/rear words (e.g.
+/// `Left Front` and `Left Front Tire`) are merged into a single group for that corner. The merged
+/// group takes the position of the first group for its corner. Groups that do not name a corner
+/// are left as-is.
+///
+/// Property names that already exist in the merged group are prefixed with their original group
+/// name, so values from different groups are never combined.
+pub(crate) fn group_by_corner(setup: &Setup) -> Setup {
+    let mut output = Setup::default();
+
+    for (group_name, props) in setup.iter() {
+        let corner = match corner(group_name) {
+            Some(corner) => corner,
+            None => {
+                output.insert(group_name.to_string(), props.clone());
+                continue;
+            }
+        };
+
+        if !output.contains_key(corner) {
+            output.insert(corner.to_string(), Props::default());
+        }
+        let merged = output.get_mut(corner).unwrap();
+        let existing: Vec<_> = merged.keys().cloned().collect();
+
+        for (prop_name, value) in props.iter() {
+            let prop_name = if existing.contains(prop_name) {
+                format!("{} {}", group_name, prop_name)
+            } else {
+                prop_name.to_string()
+            };
+
+            merged.append(prop_name, value.to_string());
+        }
+    }
+
+    output
+}
+
+/// Get the corner named by a property group, if any.
+fn corner(group_name: &str) -> Option<&'static str> {
+    let words: Vec<_> = group_name
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    let has = |word: &str| words.iter().any(|w| w == word);
+
+    match (has("left"), has("right"), has("front"), has("rear")) {
+        (true, false, true, false) => Some("Left Front"),
+        (false, true, true, false) => Some("Right Front"),
+        (true, false, false, true) => Some("Left Rear"),
+        (false, true, false, true) => Some("Right Rear"),
+        _ => None,
+    }
+}
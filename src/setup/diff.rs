@@ -0,0 +1,144 @@
+//! Structured differences between setups, independent of rendering.
+
+use super::Setup;
+use crate::value::compare_values;
+use std::cmp::Ordering;
+
+/// An extension trait for comparing setups.
+pub(crate) trait Diff {
+    /// Compare this setup against a baseline.
+    fn diff(&self, baseline: &Self) -> SetupDiff;
+}
+
+/// All differences between a setup and its baseline.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SetupDiff {
+    /// Names of the property groups that only exist in the setup.
+    pub(crate) added_groups: Vec<String>,
+
+    /// Names of the property groups that only exist in the baseline.
+    pub(crate) removed_groups: Vec<String>,
+
+    /// Property groups that exist in both setups and have at least one change, in setup order.
+    pub(crate) changed_groups: Vec<GroupDiff>,
+}
+
+/// Differences within a property group that exists in both setups.
+#[derive(Debug, PartialEq)]
+pub(crate) struct GroupDiff {
+    /// Property group name.
+    pub(crate) name: String,
+
+    /// Changed properties. Properties in the setup come first, in setup order, followed by the
+    /// properties that only exist in the baseline.
+    pub(crate) props: Vec<PropDiff>,
+}
+
+/// A single property that differs between two setups.
+#[derive(Debug, PartialEq)]
+pub(crate) struct PropDiff {
+    /// Property name.
+    pub(crate) name: String,
+
+    /// How the property differs.
+    pub(crate) change: Change,
+}
+
+/// How a property differs between two setups.
+///
+/// Properties may have more than one value, e.g. tire temperatures.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Change {
+    /// The property only exists in the setup.
+    Added(Vec<String>),
+
+    /// The property only exists in the baseline.
+    Removed(Vec<String>),
+
+    /// The property values differ.
+    Changed {
+        /// Values in the baseline.
+        old: Vec<String>,
+
+        /// Values in the setup.
+        new: Vec<String>,
+
+        /// Ordering of the new values relative to the old values.
+        ordering: Ordering,
+    },
+}
+
+impl Diff for Setup {
+    fn diff(&self, baseline: &Self) -> SetupDiff {
+        let mut output = SetupDiff::default();
+
+        for (group_name, props) in self.iter() {
+            let baseline_props = match baseline.get(group_name) {
+                Some(baseline_props) => baseline_props,
+                None => {
+                    output.added_groups.push(group_name.to_string());
+                    continue;
+                }
+            };
+
+            let mut group = GroupDiff {
+                name: group_name.to_string(),
+                props: Vec::new(),
+            };
+
+            for prop_name in props.keys() {
+                let new = values(props.get_all(prop_name));
+                let old = values(baseline_props.get_all(prop_name));
+
+                let change = if old.is_empty() {
+                    Change::Added(new)
+                } else {
+                    match compare_all(&new, &old) {
+                        Ordering::Equal => continue,
+                        ordering => Change::Changed { old, new, ordering },
+                    }
+                };
+
+                group.props.push(PropDiff {
+                    name: prop_name.to_string(),
+                    change,
+                });
+            }
+
+            for prop_name in baseline_props.keys() {
+                if !props.contains_key(prop_name) {
+                    group.props.push(PropDiff {
+                        name: prop_name.to_string(),
+                        change: Change::Removed(values(baseline_props.get_all(prop_name))),
+                    });
+                }
+            }
+
+            if !group.props.is_empty() {
+                output.changed_groups.push(group);
+            }
+        }
+
+        output.removed_groups = baseline
+            .keys()
+            .filter(|group_name| !self.contains_key(*group_name))
+            .map(|group_name| group_name.to_string())
+            .collect();
+
+        output
+    }
+}
+
+/// Collect all values for a property.
+fn values<'a>(values: impl Iterator<Item = &'a String>) -> Vec<String> {
+    values.cloned().collect()
+}
+
+/// Compare lists of values pairwise, like a lexicographic comparison.
+fn compare_all(a: &[String], b: &[String]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| compare_values(a, b))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
@@ -0,0 +1,184 @@
+//! Distribution of a property's values across many setups.
+
+use crate::str_ext::HumanCompare;
+use crate::value::Value;
+use std::cmp::Ordering;
+
+/// Numeric values are grouped into ranges when there are more distinct values than this.
+const MAX_BARS: usize = 12;
+
+/// How often each value of a property occurs.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Distribution {
+    /// Histogram bars with a label and the number of values in each.
+    ///
+    /// Numbers are in ascending order, text is in descending order of frequency.
+    pub(crate) bars: Vec<(String, usize)>,
+
+    /// Summary of the values, when every value is a number with the same unit.
+    pub(crate) summary: Option<Summary>,
+}
+
+/// Summary statistics for numeric property values.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Summary {
+    /// Number of values.
+    pub(crate) count: usize,
+
+    /// Smallest value.
+    pub(crate) min: f64,
+
+    /// Largest value.
+    pub(crate) max: f64,
+
+    /// Middle value, or the mean of the two middle values.
+    pub(crate) median: f64,
+
+    /// Unit shared by every value. May be empty.
+    pub(crate) unit: String,
+}
+
+/// Collect the distribution of property values.
+///
+/// Values are numeric when every value is a number with the same unit. Distinct numbers each get a
+/// bar labeled as the value first appears, unless there are too many; then the range between the
+/// smallest and largest value is split into equal parts. Anything else gets a frequency count of
+/// distinct strings.
+///
+/// Returns `None` without values.
+pub(crate) fn distribution<'a, I>(values: I) -> Option<Distribution>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let values: Vec<_> = values.into_iter().map(str::trim).collect();
+    if values.is_empty() {
+        return None;
+    }
+
+    let distribution = match numbers(&values) {
+        Some((mut numbers, unit)) => {
+            // Stable sorting keeps the first text for each number first
+            numbers.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            let count = numbers.len();
+            let median = if count % 2 == 0 {
+                (numbers[count / 2 - 1].0 + numbers[count / 2].0) / 2.0
+            } else {
+                numbers[count / 2].0
+            };
+
+            Distribution {
+                bars: numeric_bars(&numbers, &unit),
+                summary: Some(Summary {
+                    count,
+                    min: numbers[0].0,
+                    max: numbers[count - 1].0,
+                    median,
+                    unit,
+                }),
+            }
+        }
+        None => Distribution {
+            bars: text_bars(&values),
+            summary: None,
+        },
+    };
+
+    Some(distribution)
+}
+
+/// Parse every value as a number, when they all share a unit.
+///
+/// Numbers are paired with the text they were parsed from.
+fn numbers<'a>(values: &[&'a str]) -> Option<(Vec<(f64, &'a str)>, String)> {
+    let mut unit = None;
+    let numbers = values
+        .iter()
+        .map(|value| match Value::parse(value) {
+            Value::Number(number) => {
+                if *unit.get_or_insert(number.unit) == number.unit {
+                    Some((number.as_f64(), *value))
+                } else {
+                    None
+                }
+            }
+            Value::Text(_) => None,
+        })
+        .collect::<Option<_>>()?;
+
+    Some((numbers, unit.unwrap_or_default().to_string()))
+}
+
+/// Create histogram bars for sorted numbers.
+fn numeric_bars(numbers: &[(f64, &str)], unit: &str) -> Vec<(String, usize)> {
+    let mut distinct: Vec<(f64, &str, usize)> = Vec::new();
+    for &(number, text) in numbers {
+        match distinct.last_mut() {
+            Some((last, _, count)) if (*last - number).abs() < f64::EPSILON => *count += 1,
+            _ => distinct.push((number, text, 1)),
+        }
+    }
+
+    if distinct.len() <= MAX_BARS {
+        return distinct
+            .into_iter()
+            .map(|(_, text, count)| (text.to_string(), count))
+            .collect();
+    }
+
+    // Split the range into equal parts. The largest value belongs to the last part.
+    let min = numbers[0].0;
+    let width = (numbers[numbers.len() - 1].0 - min) / MAX_BARS as f64;
+    let mut bars: Vec<_> = (0..MAX_BARS)
+        .map(|i| {
+            let start = min + width * i as f64;
+            let label = format!(
+                "{} to {}",
+                format_number(start),
+                format_number(start + width)
+            );
+
+            (with_unit(label, unit), 0)
+        })
+        .collect();
+    for &(number, _) in numbers {
+        let i = (((number - min) / width) as usize).min(MAX_BARS - 1);
+        bars[i].1 += 1;
+    }
+
+    bars
+}
+
+/// Count distinct strings, most frequent first.
+fn text_bars(values: &[&str]) -> Vec<(String, usize)> {
+    let mut bars: Vec<(String, usize)> = Vec::new();
+    for value in values {
+        match bars.iter_mut().find(|(text, _)| text.as_str() == *value) {
+            Some((_, count)) => *count += 1,
+            None => bars.push((value.to_string(), 1)),
+        }
+    }
+    bars.sort_by(|(a, a_count), (b, b_count)| {
+        b_count.cmp(a_count).then_with(|| a.human_compare(b))
+    });
+
+    bars
+}
+
+/// Format a number with up to three decimal places, without trailing zeros.
+pub(crate) fn format_number(number: f64) -> String {
+    let rounded = (number * 1000.0).round() / 1000.0;
+
+    // Avoid printing negative zero
+    format!("{}", rounded + 0.0)
+}
+
+/// Append a unit to a label, when there is one.
+///
+/// Units that are words are separated with a space, symbols like `%` and `"` are not.
+pub(crate) fn with_unit(label: String, unit: &str) -> String {
+    if unit.starts_with(char::is_alphabetic) {
+        format!("{} {}", label, unit)
+    } else {
+        format!("{}{}", label, unit)
+    }
+}
@@ -0,0 +1,135 @@
+//! Align gear properties by gear number across setups.
+
+use super::{Props, Setup};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Ordinal words that name gears, with their gear numbers. Setup exports spell fourth as "forth".
+const ORDINALS: [(&str, usize); 11] = [
+    ("first", 1),
+    ("second", 2),
+    ("third", 3),
+    ("fourth", 4),
+    ("forth", 4),
+    ("fifth", 5),
+    ("sixth", 6),
+    ("seventh", 7),
+    ("eighth", 8),
+    ("ninth", 9),
+    ("tenth", 10),
+];
+
+/// Value shown for a gear that a setup does not have.
+const MISSING_GEAR: &str = "\u{2014}";
+
+/// Align gear properties (e.g. `Speed in first` through `Speed in sixth`) by gear number.
+///
+/// Gear properties are sorted by gear number in every setup, at the position of the first gear
+/// property in each group. Every setup gets the same gears, so cars with different gear counts can
+/// be compared; gears that a setup does not have are shown as a dash. A gear is named the same in
+/// every setup, using the name from the first setup that has it.
+///
+/// Setups without gear properties are borrowed as-is.
+pub(crate) fn align_gears<'a>(setups: &[&'a Setup]) -> Vec<Cow<'a, Setup>> {
+    // Map group names and property names without the ordinal to gear names by gear number
+    let mut gear_names: HashMap<(&str, String), BTreeMap<usize, &str>> = HashMap::new();
+    for setup in setups {
+        for (group_name, props) in setup.iter() {
+            for prop_name in props.keys() {
+                if let Some((stem, number)) = gear(group_name, prop_name) {
+                    gear_names
+                        .entry((group_name.as_str(), stem))
+                        .or_default()
+                        .entry(number)
+                        .or_insert(prop_name);
+                }
+            }
+        }
+    }
+
+    if gear_names.is_empty() {
+        return setups.iter().map(|&setup| Cow::Borrowed(setup)).collect();
+    }
+
+    setups
+        .iter()
+        .map(|setup| {
+            let mut output = Setup::default();
+
+            for (group_name, props) in setup.iter() {
+                let mut aligned = Props::default();
+                let mut seen = HashSet::new();
+
+                for (prop_name, value) in props.iter() {
+                    let stem = match gear(group_name, prop_name) {
+                        Some((stem, _)) => stem,
+                        None => {
+                            aligned.append(prop_name.to_string(), value.to_string());
+                            continue;
+                        }
+                    };
+                    if !seen.insert(stem.clone()) {
+                        continue;
+                    }
+
+                    let key = (group_name.as_str(), stem);
+                    for (&number, &name) in &gear_names[&key] {
+                        let mut values = props
+                            .iter()
+                            .filter(|(prop_name, _)| {
+                                matches!(
+                                    gear(group_name, prop_name),
+                                    Some((stem, n)) if stem == key.1 && n == number
+                                )
+                            })
+                            .map(|(_, value)| value.to_string())
+                            .peekable();
+
+                        if values.peek().is_none() {
+                            aligned.append(name.to_string(), MISSING_GEAR.to_string());
+                        }
+                        for value in values {
+                            aligned.append(name.to_string(), value);
+                        }
+                    }
+                }
+
+                output.insert(group_name.to_string(), aligned);
+            }
+
+            Cow::Owned(output)
+        })
+        .collect()
+}
+
+/// Get the property name without its ordinal word, and the gear number, for gear properties.
+///
+/// Properties are gears when they name exactly one ordinal, and either the property name mentions
+/// a speed or gear, or the group name mentions gears. E.g. `Third spring` is not a gear.
+fn gear(group_name: &str, prop_name: &str) -> Option<(String, usize)> {
+    let words: Vec<_> = prop_name
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    let is_gear = group_name.to_lowercase().contains("gear")
+        || words.iter().any(|word| word == "speed" || word == "gear");
+    if !is_gear {
+        return None;
+    }
+
+    let mut ordinals = words.iter().enumerate().filter_map(|(i, word)| {
+        ORDINALS
+            .iter()
+            .find(|(ordinal, _)| *ordinal == word.as_str())
+            .map(|&(_, number)| (i, number))
+    });
+    let (index, number) = ordinals.next()?;
+    if ordinals.next().is_some() {
+        return None;
+    }
+
+    let mut stem = words;
+    stem.remove(index);
+
+    Some((stem.join(" "), number))
+}
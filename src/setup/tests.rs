@@ -1,4 +1,5 @@
 use super::*;
+use std::borrow::Cow;
 use winit::dpi::PhysicalSize;
 
 fn create_ordered_multimap(list: &[(&str, &str)]) -> ListOrderedMultimap<String, String> {
@@ -11,10 +12,23 @@ fn create_ordered_multimap(list: &[(&str, &str)]) -> ListOrderedMultimap<String,
 fn test_load_dir() {
     let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
     config.update_setups_path("./fixtures");
-    let mut warnings = VecDeque::new();
-    let setups = Setups::new(&mut warnings, &config);
+    let mut progress = Vec::new();
+    let (setups, errors) = Setups::load(&config, |count, total| progress.push((count, total)));
 
-    assert!(warnings.is_empty());
+    assert!(errors.is_empty());
+    assert_eq!(
+        progress,
+        [
+            (0, 7),
+            (1, 7),
+            (2, 7),
+            (3, 7),
+            (4, 7),
+            (5, 7),
+            (6, 7),
+            (7, 7)
+        ]
+    );
 
     let tracks = setups.tracks();
     let cars = &tracks["Centripetal Circuit"]["Skip Barber Formula 2000"];
@@ -67,13 +81,90 @@ fn test_load_dir() {
     assert_eq!(file_name, "iracing_w12_baseline_glenboot");
     assert_eq!(mercedes.keys().len(), 16);
 
-    assert_eq!(setups.tracks().len(), 5);
+    let cars = &tracks["Okayama International Circuit"]["Skip Barber Formula 2000"];
+    assert_eq!(cars.len(), 1);
+    let SetupInfo {
+        setup: localized,
+        name: file_name,
+        ..
+    } = &cars[0];
+    assert_eq!(file_name, "localized_okayama");
+    assert_eq!(localized.keys().len(), 6);
+
+    let cars = &tracks["Autódromo José Carlos Pace"]["Skip Barber Formula 2000"];
+    assert_eq!(cars.len(), 1);
+    let SetupInfo {
+        setup: utf8,
+        name: file_name,
+        ..
+    } = &cars[0];
+    assert_eq!(file_name, "utf8_interlagos");
+    assert_eq!(utf8.keys().len(), 3);
+
+    assert_eq!(setups.tracks().len(), 7);
+}
+
+#[test]
+fn test_setup_utf8() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let (track_name, car_name, setup, _) =
+        setup_from_html("./fixtures/utf8_interlagos.htm", &config).unwrap();
+
+    assert_eq!(track_name, "Autódromo José Carlos Pace");
+    assert_eq!(car_name, "Skip Barber Formula 2000");
+
+    // Multibyte characters in group and property names are not mangled
+    let groups: Vec<_> = setup.keys().collect();
+    assert_eq!(groups, ["Avant", "Avant Gauche", "Arrière"]);
+
+    let expected = create_ordered_multimap(&[("Répartition de freinage", "54%")]);
+    assert_eq!(setup.get("Avant").unwrap(), &expected);
+
+    let left_front = setup.get("Avant Gauche").unwrap();
+    assert_eq!(left_front.get("Pression à froid").unwrap(), "25.0 psi");
+    assert_eq!(left_front.get_all("Températures E M I").count(), 3);
+}
+
+#[test]
+fn test_decode_export() {
+    // Byte order marks
+    let (html, encoding) = decode_export(b"\xef\xbb\xbfN\xc3\xbcrburgring");
+    assert_eq!((html.as_ref(), encoding), ("Nürburgring", "UTF-8"));
+    let (html, encoding) = decode_export(b"\xff\xfeN\x00\xfc\x00");
+    assert_eq!((html.as_ref(), encoding), ("Nü", "UTF-16LE"));
+
+    // Declared charsets
+    let (html, encoding) = decode_export(b"<meta charset=\"windows-1252\">\x80 N\xfcrburgring");
+    assert_eq!(
+        (html.as_ref(), encoding),
+        (
+            "<meta charset=\"windows-1252\">\u{20ac} Nürburgring",
+            "windows-1252"
+        )
+    );
+    let html = b"<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\xc3\xbc";
+    assert_eq!(decode_export(html).1, "UTF-8");
+    assert_eq!(decode_export(b"<meta charset='utf-16'>").1, "UTF-8");
+    assert_eq!(
+        decode_export(b"<meta charset=\"unknown\">\xfc").1,
+        "ISO-8859-1"
+    );
+
+    // Undeclared encodings
+    let (html, encoding) = decode_export("Autódromo José Carlos Pace".as_bytes());
+    assert!(matches!(html, Cow::Borrowed("Autódromo José Carlos Pace")));
+    assert_eq!(encoding, "UTF-8");
+    let (html, encoding) = decode_export(b"N\xfcrburgring \x80");
+    assert_eq!(
+        (html.as_ref(), encoding),
+        ("Nürburgring \u{80}", "ISO-8859-1")
+    );
 }
 
 #[test]
 fn test_setup_skip_barber() {
     let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
-    let (track_name, car_name, setup) =
+    let (track_name, car_name, setup, _) =
         setup_from_html("./fixtures/skip_barber_centripetal.htm", &config).unwrap();
 
     assert_eq!(track_name, "Centripetal Circuit".to_string());
@@ -166,7 +257,7 @@ fn test_setup_skip_barber() {
 #[test]
 fn test_setup_mx5() {
     let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
-    let (track_name, car_name, setup) =
+    let (track_name, car_name, setup, _) =
         setup_from_html("./fixtures/mx5_charlotte_legends_oval.htm", &config).unwrap();
 
     assert_eq!(track_name, "Charlotte Motor Speedway".to_string());
@@ -275,7 +366,7 @@ fn test_setup_mx5() {
 #[test]
 fn test_setup_dallara_p217() {
     let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
-    let (track_name, car_name, setup) =
+    let (track_name, car_name, setup, _) =
         setup_from_html("./fixtures/iracing_lemans_default.htm", &config).unwrap();
 
     assert_eq!(
@@ -540,7 +631,7 @@ fn test_setup_dallara_p217() {
 #[test]
 fn test_setup_porche_911_gt3_r() {
     let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
-    let (track_name, car_name, setup) =
+    let (track_name, car_name, setup, _) =
         setup_from_html("./fixtures/baseline.htm", &config).unwrap();
 
     assert_eq!(track_name, "Nürburgring Combined".to_string());
@@ -719,7 +810,7 @@ fn test_setup_porche_911_gt3_r() {
 #[test]
 fn test_setup_mercedes_amg_w12() {
     let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
-    let (track_name, car_name, setup) =
+    let (track_name, car_name, setup, _) =
         setup_from_html("./fixtures/iracing_w12_baseline_glenboot.htm", &config).unwrap();
 
     assert_eq!(track_name, "Watkins Glen International".to_string());
@@ -895,6 +986,122 @@ fn test_setup_mercedes_amg_w12() {
     assert_eq!(brake_system_config, &expected);
 }
 
+#[test]
+fn test_setup_localized() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let (track_name, car_name, setup, _) =
+        setup_from_html("./fixtures/localized_okayama.htm", &config).unwrap();
+
+    assert_eq!(track_name, "Okayama International Circuit".to_string());
+    assert_eq!(car_name, "Skip Barber Formula 2000".to_string());
+    assert_eq!(setup.keys().len(), 6);
+
+    // Front
+    let expected = create_ordered_multimap(&[("Bremsbalance", "54%")]);
+    let front = setup.get("Vorne").unwrap();
+    assert_eq!(front, &expected);
+}
+
+#[test]
+fn test_car_id_from_header() {
+    let markers = [" setup: ".to_string()];
+
+    assert_eq!(
+        car_id_from_header("rt2000 setup: <baseline>", &markers),
+        "rt2000"
+    );
+    assert_eq!(
+        car_id_from_header("stockcars chevyss setup: race: 2", &markers),
+        "stockcars chevyss"
+    );
+    assert_eq!(
+        car_id_from_header("rt2000 Abstimmung: <baseline>", &markers),
+        "rt2000"
+    );
+    assert_eq!(
+        car_id_from_header("stockcars chevyss Abstimmung: race", &markers),
+        "stockcars chevyss"
+    );
+    assert_eq!(car_id_from_header("rt2000", &markers), "rt2000");
+}
+
+#[test]
+fn test_car_from_header() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let car = |line| car_from_header(line, &config);
+
+    assert_eq!(
+        car("\t\t\trt2000 setup: <baseline>"),
+        ("rt2000".to_string(), "Skip Barber Formula 2000".to_string()),
+    );
+
+    // Words are joined with underscores, regardless of extra spaces
+    assert_eq!(
+        car("mx5  mx52016 setup: <baseline>"),
+        (
+            "mx5_mx52016".to_string(),
+            "Global Mazda MX-5 Cup".to_string()
+        ),
+    );
+
+    // Unknown IDs are used as the name
+    assert_eq!(
+        car("hovercraft setup: <baseline>"),
+        ("hovercraft".to_string(), "hovercraft".to_string()),
+    );
+}
+
+#[test]
+fn test_track_from_header() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let track = |line| track_from_header(line, &config);
+
+    assert_eq!(
+        track("\t\t\ttrack: skidpad"),
+        Some(("skidpad".to_string(), "Centripetal Circuit".to_string())),
+    );
+
+    // The track configuration is not part of the ID
+    let charlotte = Some((
+        "charlotte_2018".to_string(),
+        "Charlotte Motor Speedway".to_string(),
+    ));
+    assert_eq!(track("track: charlotte 2018 legendsoval"), charlotte);
+    assert_eq!(track("track: charlotte 2018 2019 roval"), charlotte);
+    assert_eq!(track("track:  charlotte   2018  legendsoval "), charlotte);
+    assert_eq!(
+        track("track: charlotte fullroadcourse"),
+        Some((
+            "charlotte".to_string(),
+            "[Legacy] Charlotte Motor Speedway".to_string(),
+        )),
+    );
+
+    // Localized labels are ignored
+    assert_eq!(
+        track("Strecke: okayama full"),
+        Some((
+            "okayama".to_string(),
+            "Okayama International Circuit".to_string()
+        )),
+    );
+
+    // Unknown IDs are kept whole and used as the name
+    assert_eq!(
+        track("track: san francisco"),
+        Some(("san_francisco".to_string(), "san_francisco".to_string())),
+    );
+
+    // Track IDs without a space after the label, or without a label
+    let skidpad = Some(("skidpad".to_string(), "Centripetal Circuit".to_string()));
+    assert_eq!(track("track:skidpad"), skidpad);
+    assert_eq!(track("\t\t\tskidpad "), skidpad);
+
+    // The track is missing
+    assert_eq!(track("track:"), None);
+    assert_eq!(track(""), None);
+}
+
 #[test]
 fn test_add_setup() {
     use UpdateKind::*;
@@ -947,24 +1154,27 @@ fn test_remove_setup() {
 
     fn assert_removed(setups: &Setups) {
         let tracks = setups.tracks();
-        assert_eq!(tracks.len(), 4);
+        assert_eq!(tracks.len(), 6);
+        assert!(tracks.contains_key("Autódromo José Carlos Pace"));
         assert!(tracks.contains_key("Centripetal Circuit"));
         assert!(tracks.contains_key("Charlotte Motor Speedway"));
         assert!(tracks.contains_key("Circuit des 24 Heures du Mans - 24 Heures du Mans"));
+        assert!(tracks.contains_key("Okayama International Circuit"));
         assert!(tracks.contains_key("Watkins Glen International"));
     }
 
     let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
     config.update_setups_path("./fixtures");
-    let mut warnings = VecDeque::new();
-    let mut setups = Setups::new(&mut warnings, &config);
+    let (mut setups, _) = Setups::load(&config, |_, _| ());
 
     let tracks = setups.tracks();
-    assert_eq!(tracks.len(), 5);
+    assert_eq!(tracks.len(), 7);
+    assert!(tracks.contains_key("Autódromo José Carlos Pace"));
     assert!(tracks.contains_key("Centripetal Circuit"));
     assert!(tracks.contains_key("Charlotte Motor Speedway"));
     assert!(tracks.contains_key("Circuit des 24 Heures du Mans - 24 Heures du Mans"));
     assert!(tracks.contains_key("Nürburgring Combined"));
+    assert!(tracks.contains_key("Okayama International Circuit"));
     assert!(tracks.contains_key("Watkins Glen International"));
 
     let mut result = Vec::new();
@@ -1007,7 +1217,7 @@ fn test_update_setup() {
     let mut setups = Setups::default();
     assert!(setups.tracks.is_empty());
 
-    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
     let path1 = Path::new("./fixtures/baseline.htm")
         .canonicalize()
         .expect("Cannot canonicalize path");
@@ -1032,7 +1242,7 @@ fn test_update_setup() {
 
     // Test adding a setup to an empty tree with Write
     let event = hotwatch::Event::Create(path1.clone());
-    let result = setups.update(&event, &config);
+    let result = setups.update(&event, &mut config);
 
     assert_eq!(
         &result,
@@ -1046,14 +1256,14 @@ fn test_update_setup() {
 
     // Test adding an existing setup to the tree
     let event = hotwatch::Event::Write(path1.clone());
-    let result = setups.update(&event, &config);
+    let result = setups.update(&event, &mut config);
 
     assert_eq!(&result, &[]);
     assert_eq!(setups.tracks.len(), 1);
 
     // Test adding a setup to the tree with Create
     let event = hotwatch::Event::Create(path2.clone());
-    let result = setups.update(&event, &config);
+    let result = setups.update(&event, &mut config);
 
     assert_eq!(
         &result,
@@ -1067,7 +1277,7 @@ fn test_update_setup() {
 
     // Test removing a setup from the tree
     let event = hotwatch::Event::Remove(path1);
-    let result = setups.update(&event, &config);
+    let result = setups.update(&event, &mut config);
 
     assert_eq!(
         &result,
@@ -1090,11 +1300,14 @@ fn test_update_setup() {
     let name = &setups.tracks["Centripetal Circuit"]["Skip Barber Formula 2000"][0].name;
     assert_eq!(name, "skip_barber_centripetal");
 
-    let event = hotwatch::Event::Rename(path2, path3.clone());
-    let result = setups.update(&event, &config);
+    config.set_note(&path2, "Good in the rain");
+    let event = hotwatch::Event::Rename(path2.clone(), path3.clone());
+    let result = setups.update(&event, &mut config);
 
     assert_eq!(&result, &[]);
     assert_eq!(setups.tracks.len(), 1);
+    assert_eq!(config.get_note(&path2), None);
+    assert_eq!(config.get_note(&path3), Some("Good in the rain"));
 
     let name = &setups.tracks["Centripetal Circuit"]["Skip Barber Formula 2000"][0].name;
     let expected_name = path3
@@ -1107,7 +1320,7 @@ fn test_update_setup() {
 
     // Test renaming a setup in the tree to a non-html (unparseable) file
     let event = hotwatch::Event::Rename(path3, path4);
-    let result = setups.update(&event, &config);
+    let result = setups.update(&event, &mut config);
 
     assert_eq!(
         &result,
@@ -1126,3 +1339,1117 @@ fn test_update_setup() {
     );
     assert!(setups.tracks.is_empty());
 }
+
+#[test]
+fn test_parse_fixture_values() {
+    use crate::value::Value;
+
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    config.update_setups_path("./fixtures");
+    let (setups, _) = Setups::load(&config, |_, _| ());
+
+    // Every value that starts with a number (with optional sign) must be parsed as a number
+    let values = setups
+        .tracks()
+        .values()
+        .flat_map(|cars| cars.values())
+        .flatten()
+        .flat_map(|info| info.setup().values())
+        .flat_map(|props| props.values());
+
+    for value in values {
+        let numeric = value
+            .trim_start_matches(|ch: char| ch == '+' || ch == '-')
+            .starts_with(|ch: char| ch.is_ascii_digit());
+
+        match Value::parse(value) {
+            Value::Number(_) => assert!(numeric, "Expected text: {:?}", value),
+            Value::Text(_) => assert!(!numeric, "Expected a number: {:?}", value),
+        }
+    }
+}
+
+#[test]
+fn test_setup_duplicate_group() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let file = tempfile::Builder::new()
+        .suffix(".htm")
+        .tempfile()
+        .expect("Unable to create temp file");
+    let path = file.path();
+    fs::write(
+        path,
+        r#"<H2 align="center">iRacing.com Motorsport Simulations<br>
+        dallarap217 setup: dup<br>
+        track: lemans full</H2>
+        <H2><U>FRONT:</U></H2>
+        Brake bias: <U>54%</U><br><br>
+        <H2><U>Front:</U></H2>
+        Fuel level: <U>4.2 gal</U><br><br>"#,
+    )
+    .unwrap();
+
+    let err = setup_from_html(path, &config).unwrap_err();
+
+    match err {
+        Error::DuplicatePropGroup {
+            path: err_path,
+            group,
+            first,
+            second,
+        } => {
+            assert_eq!(err_path, path);
+            assert_eq!(group, "Front");
+            assert_eq!(first, "FRONT:");
+            assert_eq!(second, "Front:");
+        }
+        err => panic!("Unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn test_update_batch() {
+    use UpdateKind::*;
+
+    let mut setups = Setups::default();
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+
+    // Simulate copying many setups at once, with duplicate events for each file
+    let mut events = Vec::new();
+    for i in 0..5 {
+        let path = dir.path().join(format!("setup{}.htm", i));
+        std::fs::copy("./fixtures/skip_barber_centripetal.htm", &path)
+            .expect("Unable to copy file");
+
+        events.push(hotwatch::Event::Create(path.clone()));
+        events.push(hotwatch::Event::Write(path));
+    }
+
+    // A file that is created and removed within the batch is never added
+    let removed = dir.path().join("removed.htm");
+    events.push(hotwatch::Event::Create(removed.clone()));
+    events.push(hotwatch::Event::Remove(removed));
+
+    let result = setups.update_batch(&events, &mut config);
+    let track_name = "Centripetal Circuit".to_string();
+    let car_name = "Skip Barber Formula 2000".to_string();
+
+    assert_eq!(
+        &result,
+        &[
+            AddedSetup(track_name.clone(), car_name.clone(), 0),
+            AddedSetup(track_name.clone(), car_name.clone(), 1),
+            AddedSetup(track_name.clone(), car_name.clone(), 2),
+            AddedSetup(track_name.clone(), car_name.clone(), 3),
+            AddedSetup(track_name.clone(), car_name.clone(), 4),
+        ]
+    );
+    assert_eq!(setups.tracks().len(), 1);
+
+    let names: Vec<_> = setups.tracks()[&track_name][&car_name]
+        .iter()
+        .map(|info| info.name())
+        .collect();
+    assert_eq!(names, ["setup0", "setup1", "setup2", "setup3", "setup4"]);
+}
+
+#[test]
+fn test_settle_events() {
+    use hotwatch::Event::*;
+    use UpdateKind::*;
+
+    let mut setups = Setups::default();
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let path = dir.path().join("setup.htm");
+    let html = fs::read("./fixtures/skip_barber_centripetal.htm").expect("Unable to read file");
+    let mut sizes = HashMap::new();
+    let mut updates = Vec::new();
+
+    // The sim creates the export and writes it in a burst
+    fs::write(&path, &html[..html.len() / 2]).expect("Unable to write file");
+    let events = vec![
+        Create(path.clone()),
+        Write(path.clone()),
+        Write(path.clone()),
+    ];
+    let (events, unsettled) = settle_events(events, &mut sizes);
+    assert!(events.is_empty());
+    assert_eq!(unsettled, [path.clone()]);
+    updates.extend(setups.update_batch(&events, &mut config));
+
+    // The export is still growing in the next batch
+    fs::write(&path, &html).expect("Unable to write file");
+    let events = vec![Write(path.clone()), Write(path.clone())];
+    let (events, unsettled) = settle_events(events, &mut sizes);
+    assert!(events.is_empty());
+    assert_eq!(unsettled, [path.clone()]);
+    updates.extend(setups.update_batch(&events, &mut config));
+
+    // The export is loaded once its size stops changing
+    let (events, unsettled) = settle_events(vec![Write(path.clone())], &mut sizes);
+    assert!(unsettled.is_empty());
+    assert!(sizes.is_empty());
+    updates.extend(setups.update_batch(&events, &mut config));
+
+    let track_name = "Centripetal Circuit".to_string();
+    let car_name = "Skip Barber Formula 2000".to_string();
+    assert_eq!(updates, [AddedSetup(track_name, car_name, 0)]);
+
+    // Removing an export forgets its size, and other events pass through
+    let other = dir.path().join("other.htm");
+    fs::write(&other, &html).expect("Unable to write file");
+    let events = vec![
+        Create(other.clone()),
+        Remove(other.clone()),
+        Remove(path.clone()),
+        Write(dir.path().join("missing.htm")),
+    ];
+    let (events, unsettled) = settle_events(events, &mut sizes);
+    assert_eq!(unsettled, [other.clone()]);
+    assert!(sizes.is_empty());
+    assert_eq!(events.len(), 3);
+}
+
+#[test]
+fn test_incomplete_export() {
+    use UpdateKind::*;
+
+    let mut setups = Setups::default();
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let path = dir.path().join("growing.htm");
+    let html = fs::read("./fixtures/skip_barber_centripetal.htm").expect("Unable to read file");
+
+    // Simulate a file that is still being written by a cloud drive sync
+    fs::write(&path, "").expect("Unable to write file");
+    let event = hotwatch::Event::Create(path.clone());
+    assert_eq!(
+        setups.update(&event, &mut config),
+        [Incomplete(path.clone())]
+    );
+    assert!(matches!(
+        setup_from_html(&path, &config),
+        Err(Error::EmptyOrTruncated {
+            complete: false,
+            ..
+        })
+    ));
+
+    // The page header is complete, but property groups are missing
+    fs::write(&path, &html[..html.len() / 2]).expect("Unable to write file");
+    let event = hotwatch::Event::Write(path.clone());
+    assert_eq!(
+        setups.update(&event, &mut config),
+        [Incomplete(path.clone())]
+    );
+    assert!(setups.tracks().is_empty());
+
+    // The complete file is added
+    fs::write(&path, &html).expect("Unable to write file");
+    assert_eq!(
+        setups.update(&event, &mut config),
+        [AddedSetup(
+            "Centripetal Circuit".to_string(),
+            "Skip Barber Formula 2000".to_string(),
+            0
+        )]
+    );
+}
+
+#[test]
+fn test_open_setup() {
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    config.update_setups_path("./fixtures");
+    let (mut setups, _) = Setups::load(&config, |_, _| ());
+
+    // Open a setup outside of the setup exports path
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let path = dir.path().join("outside.htm");
+    std::fs::copy("./fixtures/skip_barber_centripetal.htm", &path).expect("Unable to copy file");
+
+    let found = setups.open(&path, &config).unwrap();
+    assert_eq!(
+        found,
+        ("Centripetal Circuit", "Skip Barber Formula 2000", 0)
+    );
+
+    let cars = &setups.tracks()["Centripetal Circuit"]["Skip Barber Formula 2000"];
+    assert_eq!(cars.len(), 2);
+    assert_eq!(cars[0].name(), "outside");
+    assert_eq!(cars[1].name(), "skip_barber_centripetal");
+
+    // Files that are not setup exports cannot be opened
+    let path = dir.path().join("empty.htm");
+    std::fs::write(&path, "").expect("Unable to write file");
+    assert!(setups.open(&path, &config).is_err());
+}
+
+#[test]
+fn test_empty_exports() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let path = dir.path().join("empty.htm");
+
+    // An empty file may still be written, so it is incomplete
+    std::fs::write(&path, "").expect("Unable to write file");
+    assert!(matches!(
+        setup_from_html(&path, &config),
+        Err(Error::EmptyOrTruncated {
+            complete: false,
+            ..
+        })
+    ));
+
+    // A whole document without content is not
+    for html in [
+        "<html></html>",
+        "<HTML>\r\n</HTML>\r\n",
+        "<html><head><title>Car Setup</title></head><body>\n\t<br>\n</body></html>",
+    ] {
+        std::fs::write(&path, html).expect("Unable to write file");
+        match setup_from_html(&path, &config) {
+            Err(Error::EmptyOrTruncated {
+                path: empty,
+                complete: true,
+            }) => assert_eq!(empty, path),
+            result => panic!("Expected an empty export, found {:?}", result),
+        }
+    }
+
+    let err = Error::EmptyOrTruncated {
+        path: path.clone(),
+        complete: true,
+    };
+    assert!(err.to_string().contains("has no content"));
+    let err = Error::EmptyOrTruncated {
+        path,
+        complete: false,
+    };
+    assert!(err.to_string().contains("is empty or incomplete"));
+}
+
+#[test]
+fn test_parse_error_excerpts() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+
+    // A page without a setup header, e.g. telemetry
+    let path = dir.path().join("telemetry.htm");
+    let html = format!(
+        "<html><body><h1>Lap\ttelemetry</h1>\n\n<p>{}</p></body></html>",
+        "Speed ".repeat(100),
+    );
+    std::fs::write(&path, html).expect("Unable to write file");
+    match setup_from_html(&path, &config) {
+        Err(Error::MissingHeader(excerpt)) => {
+            assert!(excerpt.starts_with("Lap telemetry Speed Speed"));
+            assert!(excerpt.ends_with('…'));
+            assert_eq!(excerpt.chars().count(), EXCERPT_LENGTH);
+        }
+        result => panic!("Expected a missing header, found {:?}", result),
+    }
+
+    // A setup header without a track
+    let path = dir.path().join("no_track.htm");
+    let html = r#"<h2 align="center">Setup
+skipbarberfw setup: baseline</h2>"#;
+    std::fs::write(&path, html).expect("Unable to write file");
+    match setup_from_html(&path, &config) {
+        Err(Error::MissingTrack(excerpt)) => {
+            assert_eq!(excerpt, "Setup skipbarberfw setup: baseline");
+        }
+        result => panic!("Expected a missing track, found {:?}", result),
+    }
+}
+
+#[test]
+fn test_setups_scope() {
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let league = dir.path().join("league");
+    let practice = dir.path().join("practice");
+    std::fs::create_dir(&league).expect("Unable to create dir");
+    std::fs::create_dir(&practice).expect("Unable to create dir");
+    std::fs::copy("./fixtures/baseline.htm", league.join("baseline.htm"))
+        .expect("Unable to copy file");
+    std::fs::copy(
+        "./fixtures/skip_barber_centripetal.htm",
+        practice.join("skip_barber_centripetal.htm"),
+    )
+    .expect("Unable to copy file");
+
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    config.update_setups_path(dir.path());
+    config.update_setups_scope("league");
+    let (mut setups, errors) = Setups::load(&config, |_, _| ());
+
+    assert!(errors.is_empty());
+    assert_eq!(setups.tracks().len(), 1);
+    assert!(setups.tracks().contains_key("Nürburgring Combined"));
+
+    // Setups created outside of the scope are ignored
+    let path = config
+        .get_setups_path()
+        .join("practice")
+        .join("skip_barber_centripetal.htm");
+    let event = hotwatch::Event::Create(path);
+    assert!(setups.update(&event, &mut config).is_empty());
+
+    // Setups moved out of the scope are removed
+    let from = config.get_setups_path().join("league").join("baseline.htm");
+    let to = config
+        .get_setups_path()
+        .join("practice")
+        .join("baseline.htm");
+    std::fs::rename(&from, &to).expect("Unable to move file");
+    let event = hotwatch::Event::Rename(from, to);
+    setups.update(&event, &mut config);
+    assert!(setups.tracks().is_empty());
+
+    // Clearing the scope restores the full library
+    config.update_setups_scope("");
+    let (setups, _) = Setups::load(&config, |_, _| ());
+    assert_eq!(setups.tracks().len(), 2);
+}
+
+#[test]
+fn test_excluded_paths() {
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let league = dir.path().join("league");
+    std::fs::create_dir(&league).expect("Unable to create dir");
+    std::fs::copy("./fixtures/baseline.htm", league.join("baseline.htm"))
+        .expect("Unable to copy file");
+    std::fs::copy(
+        "./fixtures/skip_barber_centripetal.htm",
+        dir.path().join("skip_barber_centripetal.htm"),
+    )
+    .expect("Unable to copy file");
+
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    config.update_setups_path(dir.path());
+    let (mut setups, errors) = Setups::load(&config, |_, _| ());
+    assert!(errors.is_empty());
+    assert_eq!(setups.tracks().len(), 2);
+
+    // Excluded setups are removed immediately
+    assert!(config.exclude_path(&config.get_setups_path().join("league")));
+    let result = setups.remove_excluded(&config);
+    assert_eq!(result.len(), 3);
+    assert!(matches!(result[0], UpdateKind::RemovedSetup(_, _, 0)));
+    assert!(matches!(result[1], UpdateKind::RemovedCar(_, _)));
+    assert!(matches!(result[2], UpdateKind::RemovedTrack(_)));
+    assert_eq!(setups.tracks().len(), 1);
+
+    // Setups created in excluded paths are ignored
+    let path = config.get_setups_path().join("league").join("baseline.htm");
+    let event = hotwatch::Event::Write(path);
+    assert!(setups.update(&event, &mut config).is_empty());
+
+    // Excluded paths are skipped when loading
+    let (setups, _) = Setups::load(&config, |_, _| ());
+    assert_eq!(setups.tracks().len(), 1);
+    assert!(!setups.tracks().contains_key("Nürburgring Combined"));
+
+    config.include_path(Path::new("league"));
+    let (setups, _) = Setups::load(&config, |_, _| ());
+    assert_eq!(setups.tracks().len(), 2);
+}
+
+#[test]
+fn test_count_exports() {
+    assert_eq!(count_exports(Path::new("./fixtures")), 7);
+    assert_eq!(count_exports(Path::new("./fixtures/missing")), 0);
+}
+
+#[test]
+fn test_remove_order() {
+    use UpdateKind::*;
+
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let old = dir.path().join("old");
+    std::fs::create_dir(&old).expect("Unable to create dir");
+    for name in [
+        "baseline.htm",
+        "mx5_charlotte_legends_oval.htm",
+        "skip_barber_centripetal.htm",
+    ] {
+        std::fs::copy(Path::new("./fixtures").join(name), old.join(name))
+            .expect("Unable to copy file");
+    }
+    std::fs::copy(
+        "./fixtures/skip_barber_centripetal.htm",
+        dir.path().join("skip_barber_b.htm"),
+    )
+    .expect("Unable to copy file");
+
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    config.update_setups_path(dir.path());
+    assert!(config.exclude_path(&config.get_setups_path().join("old")));
+
+    let expected = [
+        RemovedSetup(
+            "Nürburgring Combined".to_string(),
+            "Porsche 911 GT3 R".to_string(),
+            0,
+        ),
+        RemovedCar(
+            "Nürburgring Combined".to_string(),
+            "Porsche 911 GT3 R".to_string(),
+        ),
+        RemovedTrack("Nürburgring Combined".to_string()),
+        RemovedSetup(
+            "Charlotte Motor Speedway".to_string(),
+            "Global Mazda MX-5 Cup".to_string(),
+            0,
+        ),
+        RemovedCar(
+            "Charlotte Motor Speedway".to_string(),
+            "Global Mazda MX-5 Cup".to_string(),
+        ),
+        RemovedTrack("Charlotte Motor Speedway".to_string()),
+        RemovedSetup(
+            "Centripetal Circuit".to_string(),
+            "Skip Barber Formula 2000".to_string(),
+            1,
+        ),
+    ];
+
+    // Every tree has its own hash order, but updates are always emitted in the same order
+    for _ in 0..5 {
+        let mut loader_config = config.clone();
+        loader_config.include_path(Path::new("old"));
+        let (mut setups, errors) = Setups::load(&loader_config, |_, _| ());
+        assert!(errors.is_empty());
+
+        assert_eq!(setups.remove_excluded(&config), expected);
+        assert_eq!(setups.tracks().len(), 1);
+    }
+}
+
+#[test]
+fn test_duplicate_car_names() {
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let html = std::fs::read_to_string("./fixtures/skip_barber_centripetal.htm")
+        .expect("Unable to read file");
+    std::fs::write(dir.path().join("rt2000.htm"), &html).expect("Unable to write file");
+    std::fs::write(
+        dir.path().join("impostor.htm"),
+        html.replace("rt2000 setup", "impostor setup"),
+    )
+    .expect("Unable to write file");
+
+    // Map a second car ID to the name of an existing car
+    let config_path = dir.path().join("config.toml");
+    let toml = format!(
+        "[config]\nsetups_path = {:?}\n\n[cars]\nimpostor = \"Skip Barber Formula 2000\"\n",
+        dir.path().to_string_lossy(),
+    );
+    std::fs::write(&config_path, toml).expect("Unable to write file");
+    let mut config = Config::from_toml(&config_path, PhysicalSize::new(0, 0))
+        .expect("Unable to load config")
+        .expect("Config must exist");
+
+    let warnings = config.take_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].to_string().contains("Skip Barber Formula 2000"));
+
+    // The setups are not merged under one car
+    let (setups, errors) = Setups::load(&config, |_, _| ());
+    assert!(errors.is_empty());
+
+    let cars = &setups.tracks()["Centripetal Circuit"];
+    assert_eq!(cars.len(), 2);
+    assert_eq!(cars["Skip Barber Formula 2000 (rt2000)"].len(), 1);
+    assert_eq!(cars["Skip Barber Formula 2000 (impostor)"].len(), 1);
+}
+
+#[test]
+fn test_parse_log() {
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let mut config = Config::new(dir.path().join("config.toml"), PhysicalSize::new(0, 0));
+    let log_path = dir.path().join("parse.log");
+
+    // Logging is off by default
+    setup_from_html("./fixtures/skip_barber_centripetal.htm", &config).unwrap();
+    assert!(!log_path.exists());
+
+    config.set_parse_log(true);
+    setup_from_html("./fixtures/skip_barber_centripetal.htm", &config).unwrap();
+    setup_from_html("./fixtures/mx5_charlotte_legends_oval.htm", &config).unwrap();
+
+    let log = std::fs::read_to_string(&log_path).expect("Unable to read log");
+    let lines: Vec<_> = log.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(r#"file="skip_barber_centripetal.htm" car_id="rt2000""#));
+    assert!(lines[0].contains(r#"car="Skip Barber Formula 2000""#));
+    assert!(lines[0].contains(r#"track_id="skidpad""#));
+    assert!(lines[0].contains(r#"track="Centripetal Circuit""#));
+    assert!(lines[0].contains(r#"groups=["Front (1)""#));
+
+    // Folders are not logged
+    assert!(!log.contains("fixtures"));
+}
+
+#[test]
+fn test_group_by_corner() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let (_, _, setup, _) =
+        setup_from_html("./fixtures/iracing_lemans_default.htm", &config).unwrap();
+    let corners = group_by_corner(&setup);
+
+    // Tire groups are merged into the corner groups
+    let groups: Vec<_> = corners.keys().map(|key| key.as_str()).collect();
+    assert_eq!(
+        &groups[..6],
+        [
+            "Left Front",
+            "Left Rear",
+            "Right Front",
+            "Right Rear",
+            "Aero Settings",
+            "Aero Calculator",
+        ]
+    );
+    assert_eq!(corners.keys().len(), 14);
+
+    let left_front = corners.get("Left Front").unwrap();
+    let props: Vec<_> = left_front.keys().take(5).map(|key| key.as_str()).collect();
+    assert_eq!(
+        props,
+        [
+            "Starting pressure",
+            "Last hot pressure",
+            "Last temps O M I",
+            "Tread remaining",
+            "Corner weight",
+        ]
+    );
+    let temps: Vec<_> = left_front.get_all("Last temps O M I").collect();
+    assert_eq!(temps, ["178F", "182F", "187F"]);
+    assert_eq!(left_front.get("Camber"), Some(&"-2.8 deg".to_string()));
+
+    // Groups that don't name a corner are unchanged
+    assert_eq!(corners.get("Front"), setup.get("Front"));
+
+    // Colliding property names are prefixed with their original group name
+    let mut setup = Setup::default();
+    let tire = create_ordered_multimap(&[("Pressure", "20.0 psi")]);
+    let corner = create_ordered_multimap(&[("Pressure", "1.5 bar")]);
+    setup.insert("Left Front".to_string(), corner);
+    setup.insert("Left Front Tire".to_string(), tire);
+    let corners = group_by_corner(&setup);
+    let expected = create_ordered_multimap(&[
+        ("Pressure", "1.5 bar"),
+        ("Left Front Tire Pressure", "20.0 psi"),
+    ]);
+    assert_eq!(corners.get("Left Front"), Some(&expected));
+}
+
+#[test]
+fn test_diff_setups() {
+    use super::diff::{GroupDiff, PropDiff, SetupDiff};
+    use std::cmp::Ordering;
+
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let (_, _, baseline, _) =
+        setup_from_html("./fixtures/skip_barber_centripetal.htm", &config).unwrap();
+
+    // A setup does not differ from itself
+    assert_eq!(baseline.diff(&baseline), SetupDiff::default());
+
+    // Modify a copy of the baseline
+    let mut setup = baseline.clone();
+    let front = setup.get_mut("Front").unwrap();
+    *front.get_mut("Brake bias").unwrap() = "56%".to_string();
+    front.insert("Front wing".to_string(), "3".to_string());
+    let left_front = setup.get_mut("Left Front").unwrap();
+    left_front.remove("Caster");
+    *left_front.get_mut("Camber").unwrap() = "-1.2 deg".to_string();
+    setup.remove("Rear");
+    setup.insert(
+        "Aero".to_string(),
+        create_ordered_multimap(&[("Wing", "7")]),
+    );
+
+    let expected = SetupDiff {
+        added_groups: vec!["Aero".to_string()],
+        removed_groups: vec!["Rear".to_string()],
+        changed_groups: vec![
+            GroupDiff {
+                name: "Front".to_string(),
+                props: vec![
+                    PropDiff {
+                        name: "Brake bias".to_string(),
+                        change: Change::Changed {
+                            old: vec!["54%".to_string()],
+                            new: vec!["56%".to_string()],
+                            ordering: Ordering::Greater,
+                        },
+                    },
+                    PropDiff {
+                        name: "Front wing".to_string(),
+                        change: Change::Added(vec!["3".to_string()]),
+                    },
+                ],
+            },
+            GroupDiff {
+                name: "Left Front".to_string(),
+                props: vec![
+                    PropDiff {
+                        name: "Camber".to_string(),
+                        change: Change::Changed {
+                            old: vec!["-1.6 deg".to_string()],
+                            new: vec!["-1.2 deg".to_string()],
+                            ordering: Ordering::Greater,
+                        },
+                    },
+                    PropDiff {
+                        name: "Caster".to_string(),
+                        change: Change::Removed(vec!["+12.2 deg".to_string()]),
+                    },
+                ],
+            },
+        ],
+    };
+    assert_eq!(setup.diff(&baseline), expected);
+}
+
+#[test]
+fn test_diff_setups_multiple_values() {
+    use std::cmp::Ordering;
+
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let (_, _, baseline, _) =
+        setup_from_html("./fixtures/skip_barber_centripetal.htm", &config).unwrap();
+
+    // Change the last of three tire temperatures
+    let mut setup = baseline.clone();
+    let left_front = setup.get_mut("Left Front").unwrap();
+    left_front.remove("Last temps O M I");
+    for temp in ["119F", "119F", "117F"] {
+        left_front.append("Last temps O M I".to_string(), temp.to_string());
+    }
+
+    let diff = setup.diff(&baseline);
+    let prop = &diff.changed_groups[0].props[0];
+    assert_eq!(prop.name, "Last temps O M I");
+    assert_eq!(
+        prop.change,
+        Change::Changed {
+            old: vec!["119F".to_string(); 3],
+            new: vec!["119F".to_string(), "119F".to_string(), "117F".to_string()],
+            ordering: Ordering::Less,
+        }
+    );
+
+    // Localized exports have different group names
+    let (_, _, localized, _) =
+        setup_from_html("./fixtures/localized_okayama.htm", &config).unwrap();
+    let diff = localized.diff(&baseline);
+    assert!(diff.added_groups.contains(&"Vorne".to_string()));
+    assert!(diff.removed_groups.contains(&"Front".to_string()));
+}
+
+#[test]
+fn test_diff_setups_choices() {
+    use super::diff::SetupDiff;
+    use crate::value::compare_values;
+    use std::cmp::Ordering;
+
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let (_, _, porsche, _) = setup_from_html("./fixtures/baseline.htm", &config).unwrap();
+    let (_, _, dallara, _) =
+        setup_from_html("./fixtures/iracing_lemans_default.htm", &config).unwrap();
+
+    // The Porsche exports `Race 1` and the Dallara exports `Race1`
+    let porsche_page = porsche
+        .get("In-Car Dials")
+        .and_then(|props| props.get("Display page"))
+        .unwrap();
+    let dallara_page = dallara
+        .get("Front")
+        .and_then(|props| props.get("Display page"))
+        .unwrap();
+    assert_ne!(porsche_page, dallara_page);
+    assert_eq!(compare_values(porsche_page, dallara_page), Ordering::Equal);
+
+    // Spacing and case changes are not diffs
+    let mut setup = porsche.clone();
+    let dials = setup.get_mut("In-Car Dials").unwrap();
+    *dials.get_mut("Display page").unwrap() = dallara_page.to_string();
+    *dials.get_mut("Night LED strips").unwrap() = "blue".to_string();
+    assert_eq!(setup.diff(&porsche), SetupDiff::default());
+
+    // Other choices are
+    let dials = setup.get_mut("In-Car Dials").unwrap();
+    *dials.get_mut("Display page").unwrap() = "Race 2".to_string();
+    let diff = setup.diff(&porsche);
+    assert_eq!(diff.changed_groups[0].props[0].name, "Display page");
+}
+
+#[test]
+fn test_track_and_car_names() {
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    config.update_setups_path("./fixtures");
+    let (setups, errors) = Setups::load(&config, |_, _| ());
+    assert!(errors.is_empty());
+
+    assert_eq!(
+        setups.track_names(),
+        [
+            "Autódromo José Carlos Pace",
+            "Centripetal Circuit",
+            "Charlotte Motor Speedway",
+            "Circuit des 24 Heures du Mans - 24 Heures du Mans",
+            "Nürburgring Combined",
+            "Okayama International Circuit",
+            "Watkins Glen International",
+        ]
+    );
+    assert_eq!(
+        setups.track_car_names("Nürburgring Combined"),
+        ["Porsche 911 GT3 R"]
+    );
+    assert_eq!(
+        setups.track_car_names("Okayama International Circuit"),
+        ["Skip Barber Formula 2000"]
+    );
+    assert!(setups
+        .track_car_names("Daytona International Speedway")
+        .is_empty());
+
+    assert_eq!(
+        setups.car_names(),
+        [
+            "Dallara P217",
+            "Global Mazda MX-5 Cup",
+            "Mercedes-AMG W12 E Performance",
+            "Porsche 911 GT3 R",
+            "Skip Barber Formula 2000",
+        ]
+    );
+}
+
+#[test]
+fn test_car_setups() {
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let html =
+        fs::read_to_string("./fixtures/skip_barber_centripetal.htm").expect("Unable to read file");
+    let okayama = html.replace("track: skidpad", "track: okayama");
+    fs::write(dir.path().join("wet.htm"), &okayama).expect("Unable to write file");
+    fs::write(dir.path().join("dry.htm"), &okayama).expect("Unable to write file");
+    fs::write(dir.path().join("skidpad.htm"), &html).expect("Unable to write file");
+    fs::copy(
+        "./fixtures/mx5_charlotte_legends_oval.htm",
+        dir.path().join("mx5.htm"),
+    )
+    .expect("Unable to copy file");
+
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    config.update_setups_path(dir.path());
+    let (setups, errors) = Setups::load(&config, |_, _| ());
+    assert!(errors.is_empty());
+
+    assert_eq!(
+        setups.car_names(),
+        ["Global Mazda MX-5 Cup", "Skip Barber Formula 2000"]
+    );
+
+    // Setups are grouped by track, in track name order
+    let car_setups: Vec<_> = setups
+        .car_setups("Skip Barber Formula 2000")
+        .into_iter()
+        .map(|(track_name, info)| (track_name, info.name()))
+        .collect();
+    assert_eq!(
+        car_setups,
+        [
+            ("Centripetal Circuit", "skidpad"),
+            ("Okayama International Circuit", "dry"),
+            ("Okayama International Circuit", "wet"),
+        ]
+    );
+
+    assert!(setups.car_setups("Porsche 911 GT3 R").is_empty());
+}
+
+#[test]
+fn test_distribution() {
+    use super::distribution::Summary;
+
+    // Distinct numbers each get a bar, labeled as they first appear
+    let values = ["54%", "56%", "54.0%", "52%"];
+    let result = distribution(values).unwrap();
+    assert_eq!(
+        result.bars,
+        [
+            ("52%".to_string(), 1),
+            ("54%".to_string(), 2),
+            ("56%".to_string(), 1),
+        ]
+    );
+    assert_eq!(
+        result.summary,
+        Some(Summary {
+            count: 4,
+            min: 52.0,
+            max: 56.0,
+            median: 54.0,
+            unit: "%".to_string(),
+        })
+    );
+
+    // Many distinct numbers are grouped into ranges
+    let values: Vec<_> = (0..=24).map(|i| format!("{} clicks", i)).collect();
+    let result = distribution(values.iter().map(String::as_str)).unwrap();
+    assert_eq!(result.bars.len(), 12);
+    assert_eq!(result.bars[0], ("0 to 2 clicks".to_string(), 2));
+    assert_eq!(result.bars[11], ("22 to 24 clicks".to_string(), 3));
+    assert_eq!(
+        result.bars.iter().map(|(_, count)| count).sum::<usize>(),
+        25
+    );
+    assert_eq!(result.summary.unwrap().median, 12.0);
+
+    // Text and mixed units are counted as distinct strings, most frequent first
+    let result = distribution(["Soft", "Hard", "Soft", "Medium"]).unwrap();
+    assert_eq!(
+        result.bars,
+        [
+            ("Soft".to_string(), 2),
+            ("Hard".to_string(), 1),
+            ("Medium".to_string(), 1),
+        ]
+    );
+    assert_eq!(result.summary, None);
+    let result = distribution(["25.0 psi", "172 kPa"]).unwrap();
+    assert_eq!(result.bars.len(), 2);
+    assert_eq!(result.summary, None);
+
+    assert_eq!(distribution([]), None);
+    assert_eq!(with_unit(format_number(-0.0001), "in"), "0 in");
+    assert_eq!(with_unit(format_number(1.0 / 3.0), "\""), "0.333\"");
+}
+
+#[test]
+fn test_property_values() {
+    let dir = tempfile::tempdir().expect("Unable to create temp dir");
+    let html =
+        fs::read_to_string("./fixtures/skip_barber_centripetal.htm").expect("Unable to read file");
+    fs::write(dir.path().join("a.htm"), &html).expect("Unable to write file");
+    fs::write(dir.path().join("b.htm"), html.replace("54%", "56%")).expect("Unable to write file");
+
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    config.update_setups_path(dir.path());
+    let (setups, errors) = Setups::load(&config, |_, _| ());
+    assert!(errors.is_empty());
+
+    let car_name = "Skip Barber Formula 2000";
+    let mut values = setups.property_values(car_name, "Front", "Brake bias");
+    values.sort_unstable();
+    assert_eq!(values, ["54%", "56%"]);
+
+    // Every value of multi-value properties is included
+    let values = setups.property_values(car_name, "Left Front", "Last temps O M I");
+    assert_eq!(values.len(), 6);
+
+    assert!(setups
+        .property_values(car_name, "Front", "Missing")
+        .is_empty());
+    assert_eq!(
+        setups.group_property_names(car_name, "Front"),
+        ["Brake bias"]
+    );
+}
+
+#[test]
+fn test_align_gears() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let (_, _, setup, _) =
+        setup_from_html("./fixtures/iracing_lemans_default.htm", &config).unwrap();
+
+    // Gears are reordered, spelled differently, and the sixth gear is missing
+    let mut other = setup.clone();
+    let gear_ratios = create_ordered_multimap(&[
+        ("Speed in second", "110.0 mph"),
+        ("Speed in first", "85.0 mph"),
+        ("Gear stack", "Short"),
+        ("Speed in fifth", "180.0 mph"),
+        ("Speed in fourth", "155.0 mph"),
+        ("Speed in third", "130.0 mph"),
+    ]);
+    other.insert("Gear Ratios".to_string(), gear_ratios);
+
+    let aligned = align_gears(&[&setup, &other]);
+    assert_eq!(aligned[0].as_ref(), &setup);
+
+    let expected = create_ordered_multimap(&[
+        ("Speed in first", "85.0 mph"),
+        ("Speed in second", "110.0 mph"),
+        ("Speed in third", "130.0 mph"),
+        ("Speed in forth", "155.0 mph"),
+        ("Speed in fifth", "180.0 mph"),
+        ("Speed in sixth", "\u{2014}"),
+        ("Gear stack", "Short"),
+    ]);
+    assert_eq!(aligned[1].get("Gear Ratios"), Some(&expected));
+    assert_eq!(aligned[1].get("Front"), setup.get("Front"));
+
+    // Setups without gears are borrowed
+    let skip_barber = setup_from_html("./fixtures/skip_barber_centripetal.htm", &config)
+        .unwrap()
+        .2;
+    assert!(matches!(align_gears(&[&skip_barber])[0], Cow::Borrowed(_)));
+}
+
+#[test]
+fn test_setup_date() {
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let (_, _, _, exported_at) = setup_from_html("./fixtures/baseline.htm", &config).unwrap();
+    assert_eq!(exported_at.as_deref(), Some("2021-03-01 - 14:00"));
+
+    // Exports without a date fall back to the file modification time
+    let (_, _, _, exported_at) =
+        setup_from_html("./fixtures/skip_barber_centripetal.htm", &config).unwrap();
+    assert_eq!(exported_at, None);
+
+    config.update_setups_path("./fixtures");
+    let (setups, _) = Setups::load(&config, |_, _| ());
+    let tracks = setups.tracks();
+    let baseline = &tracks["Nürburgring Combined"]["Porsche 911 GT3 R"][0];
+    assert_eq!(
+        baseline.date(),
+        Some(&SetupDate::Exported("2021-03-01 - 14:00".to_string())),
+    );
+    let skip_barber = &tracks["Centripetal Circuit"]["Skip Barber Formula 2000"][0];
+    assert!(matches!(skip_barber.date(), Some(SetupDate::Modified(_))));
+
+    let time = UNIX_EPOCH + std::time::Duration::from_secs(951_868_740);
+    assert_eq!(format_time(time).as_deref(), Some("2000-02-29 - 23:59"));
+    assert_eq!(
+        format_time(UNIX_EPOCH - std::time::Duration::from_secs(1)),
+        None
+    );
+}
+
+#[test]
+fn test_clean_name() {
+    let clean_name = |name: &str| {
+        SetupInfo::new(Setup::default(), name.to_string(), "/tmp/setup.htm").clean_name()
+    };
+
+    assert_eq!(
+        clean_name("iracing_w12_baseline_glenboot"),
+        "Iracing W12 Baseline Glenboot",
+    );
+    assert_eq!(clean_name("baseline"), "Baseline");
+    assert_eq!(clean_name("Quali-Dry 2"), "Quali-Dry 2");
+}
+
+#[test]
+fn test_setup_from_clipboard() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let bytes = fs::read("./fixtures/baseline.htm").unwrap();
+    let html = encoding_rs::mem::decode_latin1(&bytes);
+
+    let (track_name, car_name, setup) = setup_from_clipboard(&html, &config).unwrap();
+    let (expected_track, expected_car, expected, _) =
+        setup_from_html("./fixtures/baseline.htm", &config).unwrap();
+    assert_eq!(track_name, expected_track);
+    assert_eq!(car_name, expected_car);
+    assert!(setup.keys().eq(expected.keys()));
+    assert!(setup
+        .values()
+        .zip(expected.values())
+        .all(|(props, expected)| props.iter().eq(expected.iter())));
+
+    assert!(matches!(
+        setup_from_clipboard("Not a setup export", &config),
+        Err(Error::MissingHeader(_)),
+    ));
+    assert!(matches!(
+        setup_from_clipboard("", &config),
+        Err(Error::EmptyOrTruncated { path, complete: true }) if path == Path::new(CLIPBOARD_PATH),
+    ));
+}
+
+/// Copied HTML is a fragment without the closing tags, which is not truncated.
+#[test]
+fn test_setup_from_clipboard_fragment() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let bytes = fs::read("./fixtures/baseline.htm").unwrap();
+    let html = encoding_rs::mem::decode_latin1(&bytes);
+    let fragment = &html[..html.rfind("</body>").unwrap()];
+    assert!(!fragment.contains("</html>"));
+
+    let (_, _, setup) = setup_from_clipboard(fragment, &config).unwrap();
+    let (_, _, expected) = setup_from_clipboard(&html, &config).unwrap();
+    assert!(setup.keys().eq(expected.keys()));
+    assert!(setup
+        .values()
+        .zip(expected.values())
+        .all(|(props, expected)| props.iter().eq(expected.iter())));
+}
+
+#[test]
+fn test_single_token_track_line() {
+    let config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    let bytes = fs::read("./fixtures/skip_barber_centripetal.htm").unwrap();
+    let html = encoding_rs::mem::decode_latin1(&bytes).replace("track: skidpad", "skidpad");
+
+    let (track_name, car_name, _) = setup_from_clipboard(&html, &config).unwrap();
+    assert_eq!(track_name, "Centripetal Circuit");
+    assert_eq!(car_name, "Skip Barber Formula 2000");
+}
+
+#[test]
+fn test_to_json() {
+    let mut config = Config::new("/tmp/some/path.toml", PhysicalSize::new(0, 0));
+    config.update_setups_path("./fixtures");
+    let (setups, errors) = Setups::load(&config, |_, _| ());
+    assert!(errors.is_empty());
+
+    // The tree survives a round trip through JSON text
+    let json = setups.to_json();
+    let reparsed: serde_json::Value = serde_json::from_str(&format!("{:#}", json)).unwrap();
+    assert_eq!(reparsed, json);
+
+    // Tracks and cars match the loaded tree
+    let tracks = json.as_object().unwrap();
+    assert_eq!(tracks.len(), setups.tracks().len());
+    for (track_name, cars) in setups.tracks() {
+        let json_cars = tracks[track_name].as_object().unwrap();
+        assert_eq!(json_cars.len(), cars.len());
+        for (car_name, infos) in cars {
+            assert_eq!(json_cars[car_name].as_array().unwrap().len(), infos.len());
+        }
+    }
+
+    let skip_barber = &json["Centripetal Circuit"]["Skip Barber Formula 2000"][0];
+    assert_eq!(skip_barber["name"], "skip_barber_centripetal");
+    assert!(skip_barber["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("skip_barber_centripetal.htm"));
+
+    // Groups and properties keep their export order, and repeated properties keep every value
+    let setup = skip_barber["setup"].as_object().unwrap();
+    assert_eq!(setup.keys().next().unwrap(), "Front");
+    let left_front = setup["Left Front"].as_object().unwrap();
+    let prop_names: Vec<_> = left_front.keys().take(4).collect();
+    assert_eq!(
+        prop_names,
+        [
+            "Cold pressure",
+            "Last hot pressure",
+            "Last temps O M I",
+            "Tread remaining"
+        ]
+    );
+    assert_eq!(
+        left_front["Tread remaining"],
+        serde_json::json!(["100%", "100%", "100%"])
+    );
+    assert_eq!(left_front["Camber"], serde_json::json!(["-1.6 deg"]));
+}
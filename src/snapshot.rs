@@ -0,0 +1,226 @@
+//! Offscreen rendering of egui content to PNG images.
+
+use crate::gpu::Gpu;
+use egui::CtxRef;
+use egui_wgpu_backend::{BackendError, RenderPass, ScreenDescriptor};
+use std::fs::File;
+use std::io::BufWriter;
+use std::num::NonZeroU32;
+use std::path::Path;
+use thiserror::Error;
+
+/// All textures rendered by egui use this format.
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+/// Snapshot errors.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    #[error("Rendering failed: {0}")]
+    Backend(BackendError),
+
+    #[error("Reading the rendered image failed: {0}")]
+    Map(#[from] wgpu::BufferAsyncError),
+
+    #[error("The image is too large to export: {0}x{1} pixels")]
+    TooLarge(u32, u32),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("PNG encoding error: {0}")]
+    Png(#[from] png::EncodingError),
+}
+
+/// An RGBA image with 8 bits per channel.
+pub(crate) struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// Render egui content to an image that is exactly large enough to contain it.
+///
+/// The content is laid out in a separate egui context with the given style and fonts, so the
+/// application GUI is not disturbed. Nothing is clipped to the window; the image only fails to
+/// render when it is larger than the GPU allows.
+pub(crate) fn render(
+    gpu: &Gpu,
+    style: std::sync::Arc<egui::Style>,
+    fonts: egui::FontDefinitions,
+    scale_factor: f32,
+    draw: impl FnOnce(&mut egui::Ui),
+) -> Result<Image, Error> {
+    let max_size = gpu.device.limits().max_texture_dimension_2d;
+
+    let mut egui_ctx = CtxRef::default();
+    egui_ctx.set_fonts(fonts);
+    egui_ctx.set_style(style.clone());
+
+    // Lay out the content in the largest area that can be rendered.
+    let max_points = max_size as f32 / scale_factor;
+    let raw_input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::Vec2::splat(max_points),
+        )),
+        pixels_per_point: Some(scale_factor),
+        ..egui::RawInput::default()
+    };
+    let mut content_rect = egui::Rect::NOTHING;
+    let (_, shapes) = egui_ctx.run(raw_input, |ctx| {
+        egui::Area::new("snapshot")
+            .fixed_pos(egui::Pos2::ZERO)
+            .interactable(false)
+            .show(ctx, |ui| {
+                let response = egui::Frame::none()
+                    .fill(style.visuals.window_fill())
+                    .margin(egui::Vec2::splat(8.0))
+                    .show(ui, draw);
+                content_rect = response.response.rect;
+            });
+    });
+    let paint_jobs = egui_ctx.tessellate(shapes);
+
+    let width = (content_rect.width() * scale_factor).ceil() as u32;
+    let height = (content_rect.height() * scale_factor).ceil() as u32;
+    if width == 0 || height == 0 || width > max_size || height > max_size {
+        return Err(Error::TooLarge(width, height));
+    }
+
+    // Render the content to a texture. The scissor rects are clamped to the texture size.
+    let size = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("snapshot_texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let screen_descriptor = ScreenDescriptor {
+        physical_width: width,
+        physical_height: height,
+        scale_factor,
+    };
+
+    // A separate render pass keeps the application font texture intact.
+    let mut rpass = RenderPass::new(&gpu.device, FORMAT, 1);
+    rpass.update_texture(&gpu.device, &gpu.queue, &egui_ctx.font_image());
+    rpass.update_user_textures(&gpu.device, &gpu.queue);
+    rpass.update_buffers(&gpu.device, &gpu.queue, &paint_jobs, &screen_descriptor);
+
+    let mut encoder = gpu
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("snapshot_command_encoder"),
+        });
+    let background = egui::Rgba::from(style.visuals.window_fill());
+    rpass
+        .execute(
+            &mut encoder,
+            &view,
+            &paint_jobs,
+            &screen_descriptor,
+            Some(wgpu::Color {
+                r: f64::from(background.r()),
+                g: f64::from(background.g()),
+                b: f64::from(background.b()),
+                a: 1.0,
+            }),
+        )
+        .map_err(Error::Backend)?;
+
+    // Copy the texture to a buffer that can be read by the CPU. Rows must be padded.
+    let row_size = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_row_size = (row_size + align - 1) / align * align;
+    let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("snapshot_buffer"),
+        size: u64::from(padded_row_size) * u64::from(height),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_row_size),
+                rows_per_image: None,
+            },
+        },
+        size,
+    );
+    gpu.queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let mapping = slice.map_async(wgpu::MapMode::Read);
+    gpu.device.poll(wgpu::Maintain::Wait);
+    pollster::block_on(mapping)?;
+
+    let pixels = unpad_bgra(&slice.get_mapped_range(), row_size, padded_row_size);
+    buffer.unmap();
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+impl Image {
+    /// Write the image to a PNG file.
+    pub(crate) fn save(&self, path: &Path) -> Result<(), Error> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)?;
+
+        Ok(())
+    }
+}
+
+/// Remove row padding and convert BGRA pixels to RGBA.
+fn unpad_bgra(data: &[u8], row_size: u32, padded_row_size: u32) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len());
+
+    for row in data.chunks(padded_row_size as usize) {
+        for pixel in row[..row_size as usize].chunks(4) {
+            output.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpad_bgra() {
+        let data = [
+            1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, //
+            9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0, 0,
+        ];
+
+        assert_eq!(
+            unpad_bgra(&data, 8, 12),
+            vec![3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16],
+        );
+    }
+}
@@ -1,5 +1,6 @@
 //! String extension traits.
 
+use crate::value::Value;
 use std::borrow::Cow;
 use std::cmp::Ordering;
 use unicode_segmentation::UnicodeSegmentation;
@@ -63,6 +64,42 @@ impl<'a> Capitalize<'a> for &'a str {
     }
 }
 
+/// An extension trait for strings that adds wildcard pattern matching.
+pub(crate) trait WildcardMatch {
+    /// Check if the whole string matches a pattern, ignoring case. `*` matches any text.
+    fn wildcard_match(&self, pattern: &str) -> bool;
+}
+
+impl WildcardMatch for str {
+    fn wildcard_match(&self, pattern: &str) -> bool {
+        let text = self.to_lowercase();
+        let pattern = pattern.to_lowercase();
+        let mut parts = pattern.split('*');
+
+        // The text must start with the first part and end with the last part
+        let first = parts.next().unwrap_or_default();
+        let mut rest = match text.strip_prefix(first) {
+            Some(rest) => rest,
+            None => return false,
+        };
+        let mut parts: Vec<_> = parts.collect();
+        let last = match parts.pop() {
+            Some(last) => last,
+            None => return rest.is_empty(),
+        };
+
+        // Middle parts are found in order, as early as possible
+        for part in parts {
+            match rest.find(part) {
+                Some(i) => rest = &rest[i + part.len()..],
+                None => return false,
+            }
+        }
+
+        rest.ends_with(last)
+    }
+}
+
 /// An extension trait for strings that adds "human sort" comparison methods.
 pub(crate) trait HumanCompare {
     fn human_compare(&self, other: &str) -> Ordering;
@@ -87,6 +124,14 @@ impl HumanCompare for &str {
 }
 
 fn human_compare(a: &str, b: &str) -> Ordering {
+    // Compare leading numbers by value first, so trailing units cannot misorder them.
+    if let (Value::Number(x), Value::Number(y)) = (Value::parse(a), Value::parse(b)) {
+        match x.as_f64().partial_cmp(&y.as_f64()) {
+            Some(Ordering::Equal) | None => (),
+            Some(ordering) => return ordering,
+        }
+    }
+
     if a.starts_with('-') && b.starts_with('-') {
         // Reverse parameter order when comparing negative numbers
         human_sort::compare(b, a)
@@ -127,6 +172,25 @@ mod tests {
         assert_eq!(s.clone().ellipsis(3), Cow::from("Th…"));
     }
 
+    /// Test the WildcardMatch trait.
+    #[test]
+    fn test_wildcard_match() {
+        assert!("baseline".wildcard_match("baseline"));
+        assert!("Baseline".wildcard_match("baseline"));
+        assert!(!"baseline_wet".wildcard_match("baseline"));
+        assert!("baseline_wet".wildcard_match("baseline*"));
+
+        assert!("iracing_default".wildcard_match("iracing_*default*"));
+        assert!("iRacing_Race_Default_2022".wildcard_match("iracing_*default*"));
+        assert!(!"my_default".wildcard_match("iracing_*default*"));
+        assert!(!"iracing_race".wildcard_match("iracing_*default*"));
+
+        // Parts cannot overlap
+        assert!(!"aba".wildcard_match("ab*ba"));
+        assert!("abba".wildcard_match("ab*ba"));
+        assert!("anything".wildcard_match("*"));
+    }
+
     /// Test the Capitalize trait.
     #[test]
     fn test_capitalize_words() {
@@ -161,9 +225,33 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Fractions are not yet supported"]
     fn test_human_compare_fractions() {
         assert_eq!("3/8".human_compare("1/2"), Ordering::Less);
         assert_eq!("5/8".human_compare("1/2"), Ordering::Greater);
     }
+
+    /// Test value formats found in the fixtures.
+    #[test]
+    fn test_human_compare_units() {
+        assert_eq!("9 psi".human_compare("10 psi"), Ordering::Less);
+        assert_eq!(r#"1.575""#.human_compare(r#"1.181""#), Ordering::Greater);
+        assert_eq!(r#"2.441""#.human_compare(r#"2.717""#), Ordering::Less);
+        assert_eq!("0.811 in".human_compare("0.8 in"), Ordering::Greater);
+        assert_eq!(r#"-2/32""#.human_compare(r#"+1/64""#), Ordering::Less);
+        assert_eq!(
+            "5 x 1/16 in.".human_compare("3 x 1/16 in."),
+            Ordering::Greater
+        );
+        assert_eq!(
+            "5 x 1/16 in.".human_compare("12 x 1/32 in."),
+            Ordering::Less
+        );
+        assert_eq!("-4.0 deg".human_compare("-3.4 deg"), Ordering::Less);
+        assert_eq!("-10 clicks".human_compare("-6 clicks"), Ordering::Less);
+        assert_eq!("50.0%".human_compare("49.5%"), Ordering::Greater);
+
+        // Ties fall back to human sort
+        assert_eq!("25.0 psi".human_compare("25.0 psi"), Ordering::Equal);
+        assert_eq!("1 (BMIG)".human_compare("1 (BMIS)"), Ordering::Less);
+    }
 }
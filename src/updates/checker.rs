@@ -0,0 +1,382 @@
+//! The update checker thread and its GitHub API client.
+
+use super::persist::{Error as PersistError, Persist};
+use super::UpdateFrequency;
+use crate::framework::UserEvent;
+use crate::timer::Timer;
+use log::error;
+use log::warn;
+use semver::Version;
+use serde::Deserialize;
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use thiserror::Error;
+use winit::event_loop::EventLoopProxy;
+
+const HTTP_TIMEOUT: u64 = 15;
+const STOP_TIMEOUT: Duration = Duration::from_millis(250);
+const RELEASES_URL: &str = "https://api.github.com/repos/parasyte/cartunes/releases/latest";
+const USER_AGENT: &str = concat!("cartunes/", env!("CARGO_PKG_VERSION"));
+
+/// All the ways in which update checking can fail.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// The update thread may panic.
+    #[error("Update thread panicked")]
+    ThreadPanic(Box<dyn Any + Send + 'static>),
+
+    /// Stopping the update thread may not succeed.
+    #[error("Unable to stop update thread")]
+    Stop,
+
+    /// Parsing or writing persistence may fail.
+    #[error("Persistence error: {0}")]
+    Persist(#[from] PersistError),
+}
+
+#[derive(Debug, Copy, Clone)]
+enum UpdateCheckerMessage {
+    Stop,
+    Ping,
+    Timeout,
+}
+
+/// Offers update checking functionality.
+pub(crate) struct UpdateChecker {
+    thread: JoinHandle<()>,
+    sender: SyncSender<UpdateCheckerMessage>,
+    stopped: Arc<AtomicBool>,
+
+    /// Disconnects when the thread exits, even if it panics.
+    running: Receiver<()>,
+}
+
+/// The thread container for update checking. This does all the actual work.
+struct UpdateCheckerThread {
+    event_loop_proxy: EventLoopProxy<UserEvent>,
+    sender: SyncSender<UpdateCheckerMessage>,
+    receiver: Option<Receiver<UpdateCheckerMessage>>,
+    stopped: Arc<AtomicBool>,
+    duration: Duration,
+    persist: Persist,
+}
+
+/// Parsed API response body.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReleaseBody {
+    name: String,
+    body: String,
+    html_url: String,
+}
+
+/// Update notification.
+#[derive(Debug)]
+pub(crate) struct UpdateNotification {
+    pub(crate) version: Version,
+    pub(crate) release_notes: String,
+    pub(crate) update_url: String,
+}
+
+impl UpdateFrequency {
+    /// Convert this frequency into a [`Duration`].
+    ///
+    /// Returns `None` when the frequency is `Never`.
+    fn into_duration(self) -> Option<Duration> {
+        const DAY: u64 = 60 * 60 * 24;
+        const WEEK: u64 = DAY * 7;
+
+        match self {
+            Self::Never => None,
+            Self::Daily => Some(Duration::from_secs(DAY)),
+            Self::Weekly => Some(Duration::from_secs(WEEK)),
+        }
+    }
+}
+
+/// Check the GitHub API periodically for a new version.
+impl UpdateChecker {
+    /// Create an update checker.
+    ///
+    /// Returns `None` when `freq` == `Never`.
+    pub(crate) fn new(
+        event_loop_proxy: EventLoopProxy<UserEvent>,
+        freq: UpdateFrequency,
+    ) -> Result<Option<Self>, Error> {
+        let duration = match freq.into_duration() {
+            None => return Ok(None),
+            Some(duration) => duration,
+        };
+        let (sender, receiver) = sync_channel(2);
+        let (running_sender, running) = sync_channel(0);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread = UpdateCheckerThread::new(
+            event_loop_proxy,
+            sender.clone(),
+            receiver,
+            stopped.clone(),
+            duration,
+        )?;
+        let thread = std::thread::spawn(move || {
+            let _running = running_sender;
+            thread.run();
+        });
+
+        Ok(Some(Self {
+            thread,
+            sender,
+            stopped,
+            running,
+        }))
+    }
+
+    /// Stop the update checker.
+    ///
+    /// When `blocking` is true, waits up to [`STOP_TIMEOUT`] for the thread to exit. A thread that
+    /// is still waiting on an HTTP request is detached; it discards the response and exits when the
+    /// request completes or times out.
+    pub(crate) fn stop(self, blocking: bool) -> Result<(), Error> {
+        self.stopped.store(true, Ordering::Release);
+
+        // The thread checks the flag when it wakes up, so a full queue does not need to block
+        match self.sender.try_send(UpdateCheckerMessage::Stop) {
+            Ok(()) | Err(TrySendError::Full(_)) => (),
+            Err(TrySendError::Disconnected(_)) => return Err(Error::Stop),
+        }
+
+        if blocking {
+            match self.running.recv_timeout(STOP_TIMEOUT) {
+                Err(RecvTimeoutError::Timeout) => {
+                    warn!("Update thread is busy, detaching it");
+                }
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                    self.thread.join().map_err(Error::ThreadPanic)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl UpdateCheckerThread {
+    /// Create a thread for the update checker.
+    fn new(
+        event_loop_proxy: EventLoopProxy<UserEvent>,
+        sender: SyncSender<UpdateCheckerMessage>,
+        receiver: Receiver<UpdateCheckerMessage>,
+        stopped: Arc<AtomicBool>,
+        duration: Duration,
+    ) -> Result<Self, Error> {
+        let persist = Persist::new()?;
+
+        Ok(Self {
+            event_loop_proxy,
+            sender,
+            receiver: Some(receiver),
+            stopped,
+            duration,
+            persist,
+        })
+    }
+
+    /// Periodically check for updates.
+    fn run(mut self) {
+        // Send update notification on startup if it has been persisted
+        self.send_update_notification();
+
+        // Perform initial update check
+        let mut duration = self.check();
+
+        // Create a timer to periodically ping our message loop
+        let mut _timer = Timer::new(
+            duration,
+            self.sender.clone(),
+            UpdateCheckerMessage::Ping,
+            UpdateCheckerMessage::Timeout,
+        );
+
+        for msg in self.receiver.take().expect("Missing receiver").iter() {
+            if self.is_stopped() {
+                break;
+            }
+
+            match msg {
+                UpdateCheckerMessage::Stop => break,
+                UpdateCheckerMessage::Ping => continue,
+                UpdateCheckerMessage::Timeout => {
+                    duration = self.check();
+
+                    // Update the timer
+                    _timer = Timer::new(
+                        duration,
+                        self.sender.clone(),
+                        UpdateCheckerMessage::Ping,
+                        UpdateCheckerMessage::Timeout,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check if the update checker has been asked to stop.
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+
+    /// Check for the latest version.
+    fn check(&mut self) -> Duration {
+        // Check last update time
+        match self.persist.last_check() {
+            Ok(last_check) => {
+                if last_check < self.duration {
+                    return self.duration - last_check;
+                }
+            }
+            Err(error) => {
+                error!("SystemTime error: {:?}", error);
+                return self.duration;
+            }
+        }
+
+        // Send API request
+        let agent = ureq::AgentBuilder::new()
+            .tls_connector(Arc::new(native_tls::TlsConnector::new().unwrap()))
+            .build();
+        let req = agent
+            .get(RELEASES_URL)
+            .timeout(Duration::from_secs(HTTP_TIMEOUT))
+            .set("Accept", "application/vnd.github.v3+json")
+            .set("User-Agent", USER_AGENT);
+
+        let res = req.call();
+
+        // The app may have closed while waiting for the response
+        if self.is_stopped() {
+            return self.duration;
+        }
+
+        let res = match res {
+            Ok(res) => res,
+            Err(error) => {
+                error!("HTTP request error: {:?}", error);
+                return self.duration;
+            }
+        };
+
+        // Parse the response
+        let body: ReleaseBody = match res.into_json() {
+            Ok(body) => body,
+            Err(error) => {
+                error!("HTTP response error: {:?}", error);
+                return self.duration;
+            }
+        };
+
+        // Parse the version in the response
+        let version = match Version::parse(&body.name) {
+            Ok(version) => version,
+            Err(error) => {
+                error!("SemVer parse error: {:?}", error);
+                return self.duration;
+            }
+        };
+
+        // Save the last update time
+        if let Err(error) = self.persist.update_last_check() {
+            error!("SystemTime error: {:?}", error);
+            return self.duration;
+        }
+
+        // Update persistence
+        self.persist.update_last_version(version);
+        self.persist
+            .update_release_notes(body.body.replace('\r', ""));
+        self.persist.update_url(body.html_url);
+
+        // Write persistence to the file system
+        if let Err(error) = self.persist.write_toml() {
+            error!("Persistence error: {:?}", error);
+            return self.duration;
+        }
+
+        // Send the update notification
+        self.send_update_notification();
+
+        self.duration
+    }
+
+    fn send_update_notification(&self) {
+        // Check last update version
+        if self.persist.last_version() > self.persist.current_version() {
+            // Notify user of the new update
+            self.event_loop_proxy
+                .send_event(UserEvent::UpdateAvailable(
+                    self.persist.get_update_notification(),
+                ))
+                .expect("Event loop must exist");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Create an update checker with a thread that ignores messages while it is busy.
+    fn busy_checker(busy: Duration) -> UpdateChecker {
+        let (sender, receiver) = sync_channel(2);
+        let (running_sender, running) = sync_channel(0);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+        let thread = std::thread::spawn(move || {
+            let _running = running_sender;
+
+            // Simulate a request in progress
+            std::thread::sleep(busy);
+
+            for msg in receiver.iter() {
+                if thread_stopped.load(Ordering::Acquire) {
+                    break;
+                }
+                if let UpdateCheckerMessage::Stop = msg {
+                    break;
+                }
+            }
+        });
+
+        // Fill the queue so sending `Stop` cannot succeed
+        sender.try_send(UpdateCheckerMessage::Ping).unwrap();
+        sender.try_send(UpdateCheckerMessage::Ping).unwrap();
+
+        UpdateChecker {
+            thread,
+            sender,
+            stopped,
+            running,
+        }
+    }
+
+    #[test]
+    fn test_stop_busy() {
+        let checker = busy_checker(Duration::from_secs(HTTP_TIMEOUT));
+        let start = Instant::now();
+        checker.stop(true).unwrap();
+        assert!(start.elapsed() < STOP_TIMEOUT * 4);
+
+        let checker = busy_checker(Duration::from_secs(HTTP_TIMEOUT));
+        let start = Instant::now();
+        checker.stop(false).unwrap();
+        assert!(start.elapsed() < STOP_TIMEOUT);
+    }
+
+    #[test]
+    fn test_stop_idle() {
+        let checker = busy_checker(Duration::ZERO);
+        checker.stop(true).unwrap();
+    }
+}
@@ -2,6 +2,7 @@
 
 use super::UpdateNotification;
 use crate::framework::cache_path;
+use crate::lockfile;
 use semver::Version;
 use std::fs;
 use std::path::PathBuf;
@@ -63,8 +64,14 @@ impl Persist {
         let mut doc_path = cache_path();
         doc_path.push("updates.toml");
 
+        Self::from_path(doc_path)
+    }
+
+    /// Load the persistence from a TOML file, or create defaults when it cannot be read.
+    fn from_path(doc_path: PathBuf) -> Result<Self, Error> {
         let doc: Document = match fs::read_to_string(&doc_path) {
-            Ok(data) => data.parse()?,
+            // Strip the UTF-8 byte order mark that some editors write
+            Ok(data) => data.strip_prefix('\u{feff}').unwrap_or(&data).parse()?,
             Err(_) => {
                 let mut doc = Document::new();
                 let last_version = Version::parse(env!("CARGO_PKG_VERSION"))?;
@@ -110,13 +117,14 @@ impl Persist {
     ///
     /// The Config remembers the original TOML path, and this method rewrites that file. The config
     /// file is created if it does not exist, along with all intermediate directories in the path.
+    /// Writes hold a lock file, like the config file.
     pub(crate) fn write_toml(&self) -> Result<(), Error> {
         let toml = self.doc.to_string();
         if let Some(parent) = self.doc_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        Ok(fs::write(&self.doc_path, toml)?)
+        Ok(lockfile::write(&self.doc_path, &toml)?)
     }
 
     pub(crate) fn last_check(&self) -> Result<Duration, SystemTimeError> {
@@ -166,3 +174,26 @@ impl Persist {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_order_mark() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("updates.toml");
+        let toml = concat!(
+            "\u{feff}last_check = 1.0\n",
+            "last_version = '1.2.3'\n",
+            "release_notes = 'Notes'\n",
+            "update_url = 'https://example.com/'\n",
+        );
+        fs::write(&path, toml).unwrap();
+
+        let persist = Persist::from_path(path).unwrap();
+        assert_eq!(persist.last_version(), &Version::new(1, 2, 3));
+        assert_eq!(persist.release_notes, "Notes");
+        assert_eq!(persist.update_url, "https://example.com/");
+    }
+}
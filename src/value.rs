@@ -0,0 +1,500 @@
+//! Typed representation of setup property values.
+//!
+//! Setup exports only contain strings, but most of them are numbers with a unit. E.g. `25.0 psi`,
+//! `-1.6 deg`, `-1/32"`, or `5 x 1/16 in.`. The parser in this module splits these strings into a
+//! sign, magnitude, and unit, and leaves anything else as free text.
+
+use crate::str_ext::HumanCompare;
+use std::cmp::Ordering;
+
+/// A property value parsed from a setup export.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value<'a> {
+    /// A number with an optional unit.
+    Number(Number<'a>),
+
+    /// Free text that does not start with a number.
+    Text(&'a str),
+}
+
+/// A number with an optional unit.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Number<'a> {
+    /// The sign of the number, as written in the export.
+    pub(crate) sign: Sign,
+
+    /// The absolute value of the number.
+    pub(crate) magnitude: f64,
+
+    /// Everything following the number, with surrounding whitespace removed. May be empty.
+    pub(crate) unit: &'a str,
+}
+
+/// The sign of a [`Number`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Sign {
+    /// No explicit sign.
+    Unsigned,
+
+    /// Explicit `+` sign.
+    Positive,
+
+    /// Explicit `-` sign.
+    Negative,
+}
+
+/// User's unit choice for spring perch offsets.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum PerchOffsetUnit {
+    /// Show values as they appear in the export. (default)
+    Original,
+
+    /// Decimal inches.
+    Inches,
+
+    /// Millimeters.
+    Millimeters,
+}
+
+impl<'a> Value<'a> {
+    /// Parse a property value.
+    ///
+    /// Supported number formats are decimals (`1.95 in`), fractions (`-1/32"`), and multiples of
+    /// fractions (`5 x 1/16 in.`). Anything that does not start with a number is returned as
+    /// [`Value::Text`].
+    pub(crate) fn parse(text: &'a str) -> Self {
+        let trimmed = text.trim();
+        let (sign, rest) = if let Some(rest) = trimmed.strip_prefix('+') {
+            (Sign::Positive, rest)
+        } else if let Some(rest) = trimmed.strip_prefix('-') {
+            (Sign::Negative, rest)
+        } else {
+            (Sign::Unsigned, trimmed)
+        };
+
+        let (magnitude, rest) = match parse_fraction(rest) {
+            Some(result) => result,
+            None => return Self::Text(text),
+        };
+
+        // Multiples of fractions, e.g. `5 x 1/16 in.`
+        let (magnitude, rest) = match rest.strip_prefix(" x ").and_then(parse_fraction) {
+            Some((multiplier, rest)) => (magnitude * multiplier, rest),
+            None => (magnitude, rest),
+        };
+
+        Self::Number(Number {
+            sign,
+            magnitude,
+            unit: rest.trim(),
+        })
+    }
+}
+
+impl<'a> Number<'a> {
+    /// Get the signed value of this number.
+    pub(crate) fn as_f64(&self) -> f64 {
+        match self.sign {
+            Sign::Negative => -self.magnitude,
+            Sign::Unsigned | Sign::Positive => self.magnitude,
+        }
+    }
+
+    /// Get the signed value of this number in inches.
+    ///
+    /// Returns `None` when the unit is not a length.
+    pub(crate) fn as_inches(&self) -> Option<f64> {
+        let factor = match self.unit {
+            "\"" | "in" | "in." => 1.0,
+            "mm" => 1.0 / 25.4,
+            "cm" => 1.0 / 2.54,
+            _ => return None,
+        };
+
+        Some(self.as_f64() * factor)
+    }
+
+    /// Compare two numbers by their signed values.
+    ///
+    /// Returns `None` when the units are not the same. Units are only used to confirm that the
+    /// numbers can be compared, so different spellings of a unit are the same, e.g. `in.` and `"`.
+    pub(crate) fn compare(&self, other: &Self) -> Option<Ordering> {
+        if is_same_unit(self.unit, other.unit) {
+            self.as_f64().partial_cmp(&other.as_f64())
+        } else {
+            None
+        }
+    }
+}
+
+/// Check if two units are the same, ignoring letter case and trailing dots.
+///
+/// Inches, pounds, and degrees have more than one spelling in setup exports.
+fn is_same_unit(a: &str, b: &str) -> bool {
+    let normalize = |unit: &str| {
+        let unit = unit.trim_end_matches('.').to_lowercase();
+        match unit.as_str() {
+            "\"" => "in".to_string(),
+            "lb" => "lbs".to_string(),
+            "\u{b0}" => "deg".to_string(),
+            _ => unit,
+        }
+    };
+
+    a == b || normalize(a) == normalize(b)
+}
+
+impl Default for PerchOffsetUnit {
+    fn default() -> Self {
+        Self::Original
+    }
+}
+
+impl std::fmt::Display for PerchOffsetUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Original => "As Exported",
+            Self::Inches => "Inches",
+            Self::Millimeters => "Millimeters",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl From<&str> for PerchOffsetUnit {
+    fn from(value: &str) -> Self {
+        match value {
+            "in" => Self::Inches,
+            "mm" => Self::Millimeters,
+            _ => Self::Original,
+        }
+    }
+}
+
+impl PerchOffsetUnit {
+    /// Get a string slice that is TOML-compatible for this `PerchOffsetUnit`.
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::Original => "original",
+            Self::Inches => "in",
+            Self::Millimeters => "mm",
+        }
+    }
+
+    /// Check if a property is a spring perch offset, e.g. `Spring perch offset`.
+    pub(crate) fn applies_to(prop_name: &str) -> bool {
+        prop_name.to_lowercase().ends_with("perch offset")
+    }
+
+    /// Convert a perch offset value to this unit.
+    ///
+    /// Both the decimal notation (`2.563"`) and the click notation (`5 x 1/16 in.`) are supported.
+    /// Returns `None` when the unit is `Original` or the value is not a length.
+    pub(crate) fn convert(&self, text: &str) -> Option<String> {
+        let inches = match Value::parse(text) {
+            Value::Number(number) => number.as_inches()?,
+            Value::Text(_) => return None,
+        };
+
+        match self {
+            Self::Original => None,
+            Self::Inches => Some(format!("{:.3} in", inches)),
+            Self::Millimeters => Some(format!("{:.1} mm", inches * 25.4)),
+        }
+    }
+}
+
+/// Compare two property values for diffing.
+///
+/// Numbers with the same unit are compared numerically, everything else uses human sort. Text
+/// values are choices from a list (e.g. `Display page` or `ARB size`), which some cars export
+/// with different spacing or letter case. Text that only differs in whitespace and case is equal.
+pub(crate) fn compare_values(a: &str, b: &str) -> Ordering {
+    match (Value::parse(a), Value::parse(b)) {
+        (Value::Number(x), Value::Number(y)) => x.compare(&y).unwrap_or_else(|| a.human_compare(b)),
+        (Value::Text(x), Value::Text(y)) if is_same_choice(x, y) => Ordering::Equal,
+        (Value::Text(x), Value::Text(y)) => x.human_compare(y),
+        _ => a.human_compare(b),
+    }
+}
+
+/// Check if two text values are the same choice, ignoring whitespace and letter case.
+///
+/// E.g. `Race1` and `Race 1` are the same `Display page`.
+fn is_same_choice(a: &str, b: &str) -> bool {
+    let normalize = |text: &str| {
+        text.chars()
+            .filter(|ch| !ch.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+
+    normalize(a) == normalize(b)
+}
+
+/// Compare lists of property values position by position, e.g. tire temperatures.
+///
+/// Values are matched by their position in the export, which is fixed by the property name, e.g.
+/// `Last temps O M I` on the left side and `Last temps I M O` on the right side. Values without a
+/// counterpart in `b` compare as greater.
+pub(crate) fn compare_positions<S: AsRef<str>>(a: &[S], b: &[S]) -> Vec<Ordering> {
+    a.iter()
+        .enumerate()
+        .map(|(i, x)| {
+            b.get(i).map_or(Ordering::Greater, |y| {
+                compare_values(x.as_ref(), y.as_ref())
+            })
+        })
+        .collect()
+}
+
+/// Parse an unsigned decimal number from the start of a string.
+///
+/// Returns the number and the remainder of the string.
+fn parse_decimal(text: &str) -> Option<(f64, &str)> {
+    let mut seen_dot = false;
+    let end = text
+        .char_indices()
+        .find(|&(i, ch)| {
+            if ch == '.' && !seen_dot && i > 0 {
+                seen_dot = true;
+                false
+            } else {
+                !ch.is_ascii_digit()
+            }
+        })
+        .map_or(text.len(), |(i, _)| i);
+
+    // A trailing dot belongs to the unit, e.g. `5. in`
+    let number = text[..end].trim_end_matches('.');
+    let value = number.parse().ok()?;
+
+    Some((value, &text[number.len()..]))
+}
+
+/// Parse an unsigned decimal number or fraction from the start of a string.
+///
+/// Returns the number and the remainder of the string.
+fn parse_fraction(text: &str) -> Option<(f64, &str)> {
+    let (numerator, rest) = parse_decimal(text)?;
+
+    match rest.strip_prefix('/').and_then(parse_decimal) {
+        Some((denominator, rest)) if denominator != 0.0 => Some((numerator / denominator, rest)),
+        _ => Some((numerator, rest)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(sign: Sign, magnitude: f64, unit: &str) -> Value<'_> {
+        Value::Number(Number {
+            sign,
+            magnitude,
+            unit,
+        })
+    }
+
+    #[test]
+    fn test_parse_decimals() {
+        assert_eq!(
+            Value::parse("25.0 psi"),
+            number(Sign::Unsigned, 25.0, "psi")
+        );
+        assert_eq!(Value::parse("-1.6 deg"), number(Sign::Negative, 1.6, "deg"));
+        assert_eq!(
+            Value::parse("+12.2 deg"),
+            number(Sign::Positive, 12.2, "deg")
+        );
+        assert_eq!(
+            Value::parse("1371 lbs/in"),
+            number(Sign::Unsigned, 1371.0, "lbs/in")
+        );
+        assert_eq!(Value::parse("0.75"), number(Sign::Unsigned, 0.75, ""));
+        assert_eq!(Value::parse("6"), number(Sign::Unsigned, 6.0, ""));
+    }
+
+    #[test]
+    fn test_parse_units_without_space() {
+        assert_eq!(Value::parse("119F"), number(Sign::Unsigned, 119.0, "F"));
+        assert_eq!(Value::parse("54%"), number(Sign::Unsigned, 54.0, "%"));
+        assert_eq!(
+            Value::parse(r#"2.563""#),
+            number(Sign::Unsigned, 2.563, r#"""#)
+        );
+    }
+
+    #[test]
+    fn test_parse_fractions() {
+        assert_eq!(
+            Value::parse(r#"-1/32""#),
+            number(Sign::Negative, 1.0 / 32.0, r#"""#)
+        );
+        assert_eq!(
+            Value::parse(r#"+2/16""#),
+            number(Sign::Positive, 2.0 / 16.0, r#"""#)
+        );
+        assert_eq!(
+            Value::parse(r#"-0/16""#),
+            number(Sign::Negative, 0.0, r#"""#)
+        );
+        assert_eq!(
+            Value::parse("5 x 1/16 in."),
+            number(Sign::Unsigned, 5.0 / 16.0, "in."),
+        );
+    }
+
+    #[test]
+    fn test_parse_trailing_text() {
+        assert_eq!(
+            Value::parse("57.0% front"),
+            number(Sign::Unsigned, 57.0, "% front")
+        );
+        assert_eq!(
+            Value::parse("1 (BMIG)"),
+            number(Sign::Unsigned, 1.0, "(BMIG)")
+        );
+        assert_eq!(
+            Value::parse("5.000 Turns"),
+            number(Sign::Unsigned, 5.0, "Turns")
+        );
+    }
+
+    #[test]
+    fn test_parse_text() {
+        assert_eq!(Value::parse("Race 1"), Value::Text("Race 1"));
+        assert_eq!(Value::parse("P2"), Value::Text("P2"));
+        assert_eq!(
+            Value::parse("Medium friction"),
+            Value::Text("Medium friction")
+        );
+        assert_eq!(Value::parse(""), Value::Text(""));
+        assert_eq!(Value::parse("-"), Value::Text("-"));
+        assert_eq!(Value::parse(".5"), Value::Text(".5"));
+    }
+
+    #[test]
+    fn test_compare_numbers() {
+        let parse = |text| match Value::parse(text) {
+            Value::Number(number) => number,
+            Value::Text(text) => panic!("Expected a number, found {:?}", text),
+        };
+        let a = parse("3/8");
+        let b = parse("1/2");
+        let c = parse("1/2 in");
+
+        assert_eq!(a.compare(&b), Some(Ordering::Less));
+        assert_eq!(b.compare(&a), Some(Ordering::Greater));
+        assert_eq!(b.compare(&b), Some(Ordering::Equal));
+        assert_eq!(b.compare(&c), None);
+    }
+
+    #[test]
+    fn test_compare_units() {
+        // Signs and magnitudes interact, regardless of how the strings sort
+        assert_eq!(compare_values("-2.7 deg", "-3.4 deg"), Ordering::Greater);
+        assert_eq!(compare_values("-3.4 deg", "+0.5 deg"), Ordering::Less);
+        assert_eq!(compare_values("9.5 in", "10.0 in"), Ordering::Less);
+        assert_eq!(compare_values("100 %", "99.5 %"), Ordering::Greater);
+        assert_eq!(compare_values("12 clicks", "9 clicks"), Ordering::Greater);
+
+        // Different spellings of the same unit
+        assert_eq!(compare_values("1.950 in.", r#"2.100""#), Ordering::Less);
+        assert_eq!(compare_values("25.0 PSI", "22.0 psi"), Ordering::Greater);
+        assert_eq!(compare_values("1200 lb", "1150 lbs"), Ordering::Greater);
+        assert_eq!(compare_values("-2.7\u{b0}", "-3.4 deg"), Ordering::Greater);
+        assert_eq!(compare_values("10 in", "10 in."), Ordering::Equal);
+
+        // Different units fall back to human sort
+        assert_eq!(
+            compare_values("10 mm", "9 in"),
+            "10 mm".human_compare("9 in")
+        );
+        assert_eq!(compare_values("5 kph", "5 mph"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_perch_offset_notations() {
+        // Click notation from the Skip Barber fixture
+        let skip_barber = "5 x 1/16 in.";
+        // Decimal notation from the MX-5 fixture
+        let mx5 = r#"2.563""#;
+
+        let inches = |text| match Value::parse(text) {
+            Value::Number(number) => number.as_inches(),
+            Value::Text(_) => None,
+        };
+        assert_eq!(inches(skip_barber), Some(5.0 / 16.0));
+        assert_eq!(inches(mx5), Some(2.563));
+        assert_eq!(inches("25.0 psi"), None);
+
+        let unit = PerchOffsetUnit::Inches;
+        assert_eq!(unit.convert(mx5), Some("2.563 in".to_string()));
+        assert_eq!(unit.convert("-1/8\""), Some("-0.125 in".to_string()));
+
+        let unit = PerchOffsetUnit::Millimeters;
+        assert_eq!(unit.convert(skip_barber), Some("7.9 mm".to_string()));
+        assert_eq!(unit.convert(mx5), Some("65.1 mm".to_string()));
+
+        assert_eq!(PerchOffsetUnit::Original.convert(mx5), None);
+        assert_eq!(PerchOffsetUnit::Inches.convert("Soft"), None);
+
+        assert!(PerchOffsetUnit::applies_to("Spring perch offset"));
+        assert!(PerchOffsetUnit::applies_to("Third perch offset"));
+        assert!(!PerchOffsetUnit::applies_to("Ride height"));
+    }
+
+    /// Test `compare_values()` with fractions, which human sort cannot compare.
+    #[test]
+    fn test_compare_values_fractions() {
+        assert_eq!(compare_values(r#"-1/32""#, r#"+1/64""#), Ordering::Less);
+        assert_eq!(compare_values(r#"+2/16""#, r#"+1/16""#), Ordering::Greater);
+        assert_eq!(compare_values("3/8", "1/2"), Ordering::Less);
+        assert_eq!(compare_values("1/2", "2/4"), Ordering::Equal);
+    }
+
+    /// Test `compare_values()` with mixed units and text.
+    #[test]
+    fn test_compare_values_mixed() {
+        assert_eq!(compare_values("25.0 psi", "25.5 psi"), Ordering::Less);
+        assert_eq!(
+            compare_values("119F, 119F, 120F", "119F, 119F, 119F"),
+            Ordering::Greater
+        );
+        assert_eq!(compare_values("Soft", "Medium"), Ordering::Greater);
+        assert_eq!(compare_values("Soft", "Soft"), Ordering::Equal);
+    }
+
+    /// Test `compare_values()` with choices that are exported with different spacing and case.
+    #[test]
+    fn test_compare_values_choices() {
+        assert_eq!(compare_values("Race1", "Race 1"), Ordering::Equal);
+        assert_eq!(compare_values(" Medium", "Medium"), Ordering::Equal);
+        assert_eq!(compare_values("blue", "Blue"), Ordering::Equal);
+        assert_eq!(compare_values("Race  1", "race 1 "), Ordering::Equal);
+
+        // Different choices are still ordered
+        assert_eq!(compare_values("Race1", "Race2"), Ordering::Less);
+        assert_eq!(compare_values("Race 10", "Race 2"), Ordering::Greater);
+        assert_eq!(compare_values("Soft", "Medium"), Ordering::Greater);
+    }
+
+    /// Test `compare_positions()` with tire temperatures.
+    #[test]
+    fn test_compare_positions() {
+        let a = ["119F", "121F", "120F"];
+        let b = ["119F", "119F", "122F"];
+        assert_eq!(
+            compare_positions(&a, &b),
+            [Ordering::Equal, Ordering::Greater, Ordering::Less]
+        );
+
+        // Missing positions
+        assert_eq!(
+            compare_positions(&a, &b[..1]),
+            [Ordering::Equal, Ordering::Greater, Ordering::Greater]
+        );
+        assert_eq!(compare_positions(&a[..1], &b), [Ordering::Equal]);
+    }
+}